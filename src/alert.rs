@@ -0,0 +1,238 @@
+//! # Alert Module
+//!
+//! Posts alerts about an unattended network to a webhook. The JSON payload carries a
+//! `text` field, so Slack incoming webhooks display it as is, next to the structured
+//! `event`, `network_id` and `node_id` fields for generic receivers.
+//!
+//! Alerts are raised when:
+//! - a node's container exits with a non-zero status other than a `docker stop` (crash),
+//! - a node's container is (re)started `restart_threshold` times while being watched,
+//! - the chain height stops advancing.
+
+use crate::docker::manager::ContainerState;
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    io::Result,
+};
+
+/// Exit code of a container stopped by `docker stop` (SIGTERM)
+const SIGTERM_EXIT_CODE: i32 = 143;
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertEvent {
+    NodeCrashed,
+    NodeRestarting,
+    ChainStalled,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Alert {
+    pub event: AlertEvent,
+    pub network_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    pub text: String,
+}
+
+impl Alert {
+    pub fn chain_stalled(network_id: &str, height: Option<u64>, seconds: u64) -> Self {
+        Alert {
+            event: AlertEvent::ChainStalled,
+            network_id: network_id.to_string(),
+            node_id: None,
+            text: format!(
+                "Chain height of network '{network_id}' has not advanced for {seconds}s \
+                (height: {})",
+                height.map_or("unknown".to_string(), |height| height.to_string())
+            ),
+        }
+    }
+}
+
+pub struct Webhook {
+    url: String,
+}
+
+impl Webhook {
+    pub fn new(url: &str) -> Self {
+        Webhook {
+            url: url.to_string(),
+        }
+    }
+
+    /// Posts `alert` as JSON to the webhook
+    pub fn send(&self, alert: &Alert) -> Result<()> {
+        info!("Posting '{:?}' alert to webhook", alert.event);
+        let response = crate::proxy::http_client()
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_string(alert)?)
+            .send()
+            .map_err(std::io::Error::other)?;
+
+        if !response.status().is_success() {
+            return Err(std::io::Error::other(format!(
+                "Webhook responded with status {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Posts `alert`, only logging failures so that watching goes on
+    pub fn notify(&self, alert: &Alert) {
+        if let Err(e) = self.send(alert) {
+            warn!("Failed to post alert to webhook: {e}");
+        }
+    }
+}
+
+/// Container state of a node at one poll
+pub struct NodeState {
+    pub node_id: String,
+    pub state: ContainerState,
+    pub exit_code: i32,
+}
+
+/// Tracks the nodes' container states across polls to detect crashes and restarts
+pub struct NodeWatch {
+    restart_threshold: u32,
+    states: HashMap<String, ContainerState>,
+    restarts: HashMap<String, u32>,
+    reported_restarting: HashSet<String>,
+}
+
+impl NodeWatch {
+    pub fn new(restart_threshold: u32) -> Self {
+        NodeWatch {
+            restart_threshold,
+            states: HashMap::new(),
+            restarts: HashMap::new(),
+            reported_restarting: HashSet::new(),
+        }
+    }
+
+    /// Records the nodes' current states and returns the alerts they raise
+    pub fn observe(&mut self, network_id: &str, nodes: Vec<NodeState>) -> Vec<Alert> {
+        let mut alerts = vec![];
+        for node in nodes {
+            let previous = self.states.insert(node.node_id.clone(), node.state.clone());
+            let Some(previous) = previous else {
+                continue;
+            };
+            if previous == node.state {
+                continue;
+            }
+
+            let crashed = matches!(node.state, ContainerState::Exited | ContainerState::Dead)
+                && ![0, SIGTERM_EXIT_CODE].contains(&node.exit_code);
+            if previous == ContainerState::Running && crashed {
+                alerts.push(Alert {
+                    event: AlertEvent::NodeCrashed,
+                    network_id: network_id.to_string(),
+                    node_id: Some(node.node_id.clone()),
+                    text: format!(
+                        "Node '{}' of network '{network_id}' exited with code {}",
+                        node.node_id, node.exit_code
+                    ),
+                });
+            }
+
+            let restarted = matches!(
+                node.state,
+                ContainerState::Running | ContainerState::Restarting
+            ) && !matches!(
+                previous,
+                ContainerState::Running | ContainerState::Restarting | ContainerState::Created
+            );
+            if restarted {
+                let restarts = self.restarts.entry(node.node_id.clone()).or_default();
+                *restarts += 1;
+                if *restarts >= self.restart_threshold
+                    && self.reported_restarting.insert(node.node_id.clone())
+                {
+                    alerts.push(Alert {
+                        event: AlertEvent::NodeRestarting,
+                        network_id: network_id.to_string(),
+                        node_id: Some(node.node_id.clone()),
+                        text: format!(
+                            "Node '{}' of network '{network_id}' restarted {restarts} times",
+                            node.node_id
+                        ),
+                    });
+                }
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(state: ContainerState, exit_code: i32) -> Vec<NodeState> {
+        vec![NodeState {
+            node_id: "bp".to_string(),
+            state,
+            exit_code,
+        }]
+    }
+
+    #[test]
+    fn test_node_watch_crash() {
+        let mut watch = NodeWatch::new(3);
+        assert!(watch
+            .observe("net", node(ContainerState::Running, 0))
+            .is_empty());
+
+        // stopped on purpose
+        assert!(watch
+            .observe("net", node(ContainerState::Exited, SIGTERM_EXIT_CODE))
+            .is_empty());
+        assert!(watch
+            .observe("net", node(ContainerState::Running, 0))
+            .is_empty());
+
+        let alerts = watch.observe("net", node(ContainerState::Exited, 137));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].event, AlertEvent::NodeCrashed);
+        assert_eq!(alerts[0].node_id.as_deref(), Some("bp"));
+    }
+
+    #[test]
+    fn test_node_watch_restarts() {
+        let mut watch = NodeWatch::new(2);
+        watch.observe("net", node(ContainerState::Running, 0));
+
+        watch.observe("net", node(ContainerState::Exited, 1));
+        assert!(watch
+            .observe("net", node(ContainerState::Running, 0))
+            .is_empty());
+        watch.observe("net", node(ContainerState::Exited, 1));
+        let alerts = watch.observe("net", node(ContainerState::Restarting, 1));
+        assert_eq!(
+            alerts.iter().map(|alert| &alert.event).collect::<Vec<_>>(),
+            vec![&AlertEvent::NodeRestarting]
+        );
+
+        // reported once
+        watch.observe("net", node(ContainerState::Exited, 1));
+        let alerts = watch.observe("net", node(ContainerState::Running, 0));
+        assert!(alerts.is_empty());
+    }
+
+    #[test]
+    fn test_alert_payload() {
+        let payload = serde_json::to_value(Alert::chain_stalled("net", Some(42), 600)).unwrap();
+        assert_eq!(payload["event"], "chain_stalled");
+        assert!(payload.get("node_id").is_none());
+        assert_eq!(
+            payload["text"],
+            "Chain height of network 'net' has not advanced for 600s (height: 42)"
+        );
+    }
+}
@@ -0,0 +1,4765 @@
+//! # API Module
+//!
+//! Programmatic counterpart of the `network` and `node` commands, for driving
+//! local networks from Rust (e.g. integration tests) instead of the CLI.
+//! Results are the same types the CLI prints, errors are [`MiniminaError`]s
+//! carrying the message the CLI would report.
+//!
+//! ```no_run
+//! use minimina::{api::CreateOptions, Network};
+//!
+//! let network = Network::new("default");
+//! network.create(&CreateOptions::default())?;
+//! network.start(&Default::default())?;
+//! let logs = network.node("mina-bp-1").logs()?;
+//! network.stop(&Default::default())?;
+//! # Ok::<(), minimina::error::MiniminaError>(())
+//! ```
+
+use crate::{
+    alert,
+    chaos::{self, ArchiveFault, PartitionGroups},
+    cli, config,
+    crash::{self, ExitState},
+    directory_manager::{self, DirectoryManager},
+    docker::{
+        builder::ImageBuilder,
+        compose::{ComposeProfile, DockerCompose},
+        health::DEFAULT_HEALTH_HOST_PORT,
+        manager::{stale_duplicates, AccountImport, ContainerInfo, ContainerState, DockerManager},
+        monitoring::{Monitoring, GRAFANA_PORT, PROMETHEUS_PORT},
+        postgres::{ArchiveDb, PostgresConfig, POSTGRES_PORT},
+        runtime::Runtime,
+    },
+    error::{MiniminaError, Result},
+    genesis_ledger::*,
+    graphql::{self, GraphQl},
+    k8s,
+    keys::{self, KeysManager, NodeKey, WalletFormat},
+    log_filter::{self, LogFilter, LogRecord},
+    named_accounts::{check_fundings, fund_named_accounts, Funding},
+    net_report,
+    output::{self, network, node},
+    ports::PortAllocator,
+    service::{
+        self, ContainerLimits, ResourceProfile, ServiceConfig, ServiceType,
+        DEFAULT_SNARK_WORKER_PROOF_LEVEL, UPTIME_SERVICE_PORT,
+    },
+    sizing, topology,
+    utils::{check_output, fetch_schema, sha256_file},
+};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{BufRead, BufReader, ErrorKind},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+// Hardcoded daemon image for default network
+const DEFAULT_DAEMON_DOCKER_IMAGE: &str =
+    "gcr.io/o1labs-192920/mina-daemon:2.0.0berkeley-rc1-1551e2f-bullseye-berkeley";
+
+// Hardcoded archive image for default network
+const DEFAULT_ARCHIVE_DOCKER_IMAGE: &str =
+    "gcr.io/o1labs-192920/mina-archive:2.0.0berkeley-rc1-1551e2f-bullseye";
+
+const IMAGE_COMMIT_HASH: &str = "1551e2f";
+
+// Timeout in seconds for waiting operations
+pub const TIMEOUT_IN_SECS: u16 = 180;
+
+// Exit code of a container killed by SIGKILL
+const KILLED_EXIT_CODE: i32 = 137;
+
+// Seconds flushed daemons are given to exit on their own unless a stop timeout is set,
+// the stop_grace_period of daemon containers
+const DAEMON_EXIT_TIMEOUT_SECS: u64 = 60;
+
+// Client ports of the default topology's nodes, the n-th seed or block
+// producer using `DEFAULT_PORT_STRIDE * n` above the first one
+const DEFAULT_SEED_PORT: u16 = 3100;
+const DEFAULT_BLOCK_PRODUCER_PORT: u16 = 4000;
+const DEFAULT_SNARK_COORDINATOR_PORT: u16 = 7000;
+const DEFAULT_PORT_STRIDE: u16 = 5;
+
+// Suffix of the archive database clone `node run-replayer --snapshot` replays
+const REPLAYER_CLONE_SUFFIX: &str = "_replayer";
+
+const DEFAULT_SNARK_COORDINATOR: &str = "mina-snark-coordinator";
+const DEFAULT_ARCHIVE: &str = "mina-archive";
+
+/// Options of [`Network::create`], see `network create --help`
+#[derive(Debug, Clone, Default)]
+pub struct CreateOptions {
+    pub topology: Option<PathBuf>,
+    pub genesis_ledger: Option<PathBuf>,
+    pub runtime: Runtime,
+    pub genesis_in: Option<chrono::Duration>,
+    pub genesis_timestamp: Option<chrono::DateTime<chrono::FixedOffset>>,
+    /// Genesis constants to override those of the genesis ledger with
+    pub preset: Option<Preset>,
+    pub verify_archive: bool,
+    /// Leave the archive databases to [`Network::init_archive`]
+    pub no_archive_bootstrap: bool,
+    /// Generate the default network's key pairs without docker
+    pub native_keys: bool,
+    /// Fail instead of overwriting an existing network
+    pub no_overwrite: bool,
+    /// Host ports to publish the services on, their default ports if free otherwise
+    pub port_range: Option<RangeInclusive<u16>>,
+    /// Size of the network created without a topology file
+    pub default_topology: DefaultTopology,
+    /// Create the network on the runtime or only render its kubernetes manifests
+    pub target: cli::Target,
+    /// Render the compose file with the image digests instead of their tags
+    pub pin_digests: bool,
+    /// Add the Prometheus and Grafana services of the monitoring stack
+    pub with_monitoring: bool,
+    /// Add a health probe sidecar to every daemon
+    pub with_health_probes: bool,
+    /// CPU limit of the daemon containers whose topology entry sets none
+    pub cpu_limit: Option<f64>,
+    /// Memory limit of the daemon containers whose topology entry sets none
+    pub memory_limit: Option<String>,
+    /// Resource profile of the daemon containers, below the limits above
+    pub profile: Option<ResourceProfile>,
+    /// Keep an existing network created from the same topology and genesis ledger
+    pub if_not_exists: bool,
+    /// Funded test accounts added to the default genesis ledger
+    pub extra_accounts: u16,
+    /// Named accounts of the user's registry funded in the genesis ledger
+    pub fund: Vec<Funding>,
+    /// Create the network even if it is estimated not to fit on the host
+    pub ignore_host_resources: bool,
+    /// Daemon image of the default network, the hardcoded one if not set
+    pub daemon_image: Option<String>,
+    /// Archive image of the default network, the hardcoded one if not set
+    pub archive_image: Option<String>,
+    /// Flags appended to the command of every daemon
+    pub extra_daemon_flags: Vec<String>,
+}
+
+impl CreateOptions {
+    /// Limits of the daemon containers whose topology entry doesn't set them: the
+    /// CPU and memory limits of the options, then those of the profile
+    fn resource_limits(&self) -> ContainerLimits {
+        let profile = self
+            .profile
+            .map(ResourceProfile::limits)
+            .unwrap_or_default();
+        ContainerLimits {
+            cpu_limit: self.cpu_limit.or(profile.cpu_limit),
+            memory_limit: self.memory_limit.clone().or(profile.memory_limit),
+            ..profile
+        }
+    }
+
+    fn daemon_image(&self) -> &str {
+        self.daemon_image
+            .as_deref()
+            .unwrap_or(DEFAULT_DAEMON_DOCKER_IMAGE)
+    }
+
+    fn archive_image(&self) -> &str {
+        self.archive_image
+            .as_deref()
+            .unwrap_or(DEFAULT_ARCHIVE_DOCKER_IMAGE)
+    }
+}
+
+/// Options of the command line, defaulting to the settings of the config file
+impl From<&cli::CreateNetworkArgs> for CreateOptions {
+    fn from(args: &cli::CreateNetworkArgs) -> Self {
+        let config = config::settings();
+        CreateOptions {
+            topology: args.topology.clone(),
+            genesis_ledger: args.genesis_ledger.clone(),
+            runtime: args.runtime,
+            genesis_in: args.genesis_in,
+            genesis_timestamp: args.genesis_timestamp,
+            preset: args.preset,
+            verify_archive: args.verify_archive,
+            no_archive_bootstrap: args.no_archive_bootstrap,
+            native_keys: args.native_keys,
+            no_overwrite: args.no_overwrite,
+            port_range: args.port_range.clone().or(config.port_range.clone()),
+            default_topology: DefaultTopology {
+                seeds: args.seeds,
+                block_producers: args.block_producers,
+                snark_workers: args.snark_workers,
+                archive: !args.no_archive,
+            },
+            target: args.target,
+            pin_digests: args.pin_digests,
+            with_monitoring: args.with_monitoring,
+            with_health_probes: args.with_health_probes,
+            cpu_limit: args.cpu_limit,
+            memory_limit: args.memory_limit.clone(),
+            profile: args.profile,
+            if_not_exists: args.if_not_exists,
+            extra_accounts: args.extra_accounts,
+            fund: args.fund.clone(),
+            ignore_host_resources: args.ignore_host_resources,
+            daemon_image: config.daemon_image.clone(),
+            archive_image: config.archive_image.clone(),
+            extra_daemon_flags: config.extra_daemon_flags.clone(),
+        }
+    }
+}
+
+/// Inputs a network was created from, recorded in its `fingerprint.json` so
+/// `network create --if-not-exists` can tell whether it would create the same network
+/// and `network start` whether the files were edited since
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    /// Topology file the network was created from
+    #[serde(default)]
+    pub topology: Option<PathBuf>,
+    pub topology_sha256: Option<String>,
+    /// Genesis ledger the network was created from
+    #[serde(default)]
+    pub genesis_ledger: Option<PathBuf>,
+    pub genesis_ledger_sha256: Option<String>,
+    /// Nodes of a network created without a topology file
+    pub default_topology: Option<DefaultTopology>,
+    /// Test accounts of the default genesis ledger
+    #[serde(default)]
+    pub extra_accounts: u16,
+    /// Named accounts funded in the genesis ledger
+    #[serde(default)]
+    pub fund: Vec<Funding>,
+}
+
+impl Fingerprint {
+    const FILE: &'static str = "fingerprint.json";
+
+    pub fn of(options: &CreateOptions) -> Result<Self> {
+        let sha256 = |path: &Option<PathBuf>| path.as_deref().map(sha256_file).transpose();
+        let absolute = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|path| fs::canonicalize(path).unwrap_or_else(|_| path.clone()))
+        };
+        Ok(Fingerprint {
+            topology: absolute(&options.topology),
+            topology_sha256: sha256(&options.topology)?,
+            genesis_ledger: absolute(&options.genesis_ledger),
+            genesis_ledger_sha256: sha256(&options.genesis_ledger)?,
+            default_topology: match options.topology {
+                Some(_) => None,
+                None => Some(options.default_topology.clone()),
+            },
+            extra_accounts: options.extra_accounts,
+            fund: options.fund.clone(),
+        })
+    }
+
+    /// Fingerprint of the network at `network_path`, `None` if it has none
+    pub fn load(network_path: &Path) -> Result<Option<Self>> {
+        match fs::read_to_string(network_path.join(Self::FILE)) {
+            Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Whether both were created from files with the same contents, wherever those are
+    pub fn same_inputs(&self, other: &Self) -> bool {
+        self.topology_sha256 == other.topology_sha256
+            && self.genesis_ledger_sha256 == other.genesis_ledger_sha256
+            && self.default_topology == other.default_topology
+            && self.extra_accounts == other.extra_accounts
+            && self.fund == other.fund
+    }
+
+    /// Input files whose contents changed since the network was created, those which
+    /// were removed since can't be compared and are left out
+    pub fn changed_inputs(&self) -> Result<Vec<PathBuf>> {
+        let mut changed = vec![];
+        for (path, sha256) in [
+            (&self.topology, &self.topology_sha256),
+            (&self.genesis_ledger, &self.genesis_ledger_sha256),
+        ] {
+            if let (Some(path), Some(sha256)) = (path, sha256) {
+                if path.exists() && &sha256_file(path)? != sha256 {
+                    changed.push(path.clone());
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    pub fn save(&self, network_path: &Path) -> Result<()> {
+        fs::write(
+            network_path.join(Self::FILE),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Nodes of the network generated when no topology file is given
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DefaultTopology {
+    pub seeds: u16,
+    pub block_producers: u16,
+    /// Workers of the snark coordinator, no coordinator if 0
+    pub snark_workers: u16,
+    pub archive: bool,
+}
+
+impl Default for DefaultTopology {
+    /// 1 seed, 2 bps, a snark coordinator with one worker and an archive
+    fn default() -> Self {
+        DefaultTopology {
+            seeds: 1,
+            block_producers: 2,
+            snark_workers: 1,
+            archive: true,
+        }
+    }
+}
+
+impl DefaultTopology {
+    /// Roles of the services of the network
+    fn service_types(&self) -> Vec<ServiceType> {
+        let mut service_types = vec![ServiceType::Seed; self.seeds as usize];
+        service_types.extend(vec![
+            ServiceType::BlockProducer;
+            self.block_producers as usize
+        ]);
+        if self.snark_workers > 0 {
+            service_types.push(ServiceType::SnarkCoordinator);
+            service_types.extend(vec![ServiceType::SnarkWorker; self.snark_workers as usize]);
+        }
+        if self.archive {
+            service_types.push(ServiceType::ArchiveNode);
+        }
+        service_types
+    }
+
+    fn seed_names(&self) -> Vec<String> {
+        (1..=self.seeds).map(|i| format!("mina-seed-{i}")).collect()
+    }
+
+    fn block_producer_names(&self) -> Vec<String> {
+        (1..=self.block_producers)
+            .map(|i| format!("mina-bp-{i}"))
+            .collect()
+    }
+
+    fn snark_worker_names(&self) -> Vec<String> {
+        (1..=self.snark_workers)
+            .map(|i| format!("mina-snark-worker-{i}"))
+            .collect()
+    }
+
+    /// Names of all services, which get key pairs and a genesis account
+    fn service_names(&self) -> Vec<String> {
+        let mut names = [self.seed_names(), self.block_producer_names()].concat();
+        if self.snark_workers > 0 {
+            names.push(DEFAULT_SNARK_COORDINATOR.to_string());
+            names.extend(self.snark_worker_names());
+        }
+        if self.archive {
+            names.push(DEFAULT_ARCHIVE.to_string());
+        }
+        names
+    }
+}
+
+/// Options of [`Network::start`], see `network start --help`
+#[derive(Debug, Clone, Default)]
+pub struct StartOptions {
+    pub stale_genesis_after: Option<u64>,
+    pub ignore_stale_genesis: bool,
+    pub fail_on_stale_genesis: bool,
+    /// Refresh a stale genesis timestamp, see [`Network::refresh_genesis`]
+    pub refresh_genesis: bool,
+    /// Print the output of `docker compose start`
+    pub verbose: bool,
+    /// Start the network even if its topology or genesis ledger changed since its creation
+    pub force: bool,
+    /// Wait this long for all nodes to report `SYNCED`
+    pub wait: Option<Duration>,
+    /// Only start the optional services of these profiles, all of them if `None`
+    pub profiles: Option<Vec<ComposeProfile>>,
+}
+
+impl From<&cli::StartNetworkArgs> for StartOptions {
+    fn from(args: &cli::StartNetworkArgs) -> Self {
+        StartOptions {
+            stale_genesis_after: args.stale_genesis_after,
+            ignore_stale_genesis: args.ignore_stale_genesis,
+            fail_on_stale_genesis: args.fail_on_stale_genesis,
+            refresh_genesis: args.refresh_genesis,
+            verbose: args.verbose,
+            force: args.force,
+            wait: args.wait.then(|| Duration::from_secs(args.wait_timeout)),
+            profiles: args.profiles.clone(),
+        }
+    }
+}
+
+/// Options of [`Network::stop`], see `network stop --help`
+#[derive(Debug, Clone, Default)]
+pub struct StopOptions {
+    /// Containers stopped at a time, all at once if `None`
+    pub concurrency: Option<usize>,
+    /// Seconds each batch is given to stop before its containers are killed,
+    /// their `stop_grace_period` if `None`
+    pub timeout: Option<u64>,
+    /// Shut daemons down with `mina client stop-daemon` first
+    pub flush: bool,
+}
+
+impl From<&cli::StopNetworkArgs> for StopOptions {
+    fn from(args: &cli::StopNetworkArgs) -> Self {
+        StopOptions {
+            concurrency: args.stop_concurrency.map(|n| n as usize),
+            timeout: args.stop_timeout,
+            flush: !args.no_flush,
+        }
+    }
+}
+
+/// Options of [`Network::archive_fault`], see `network archive-fault --help`
+#[derive(Debug, Clone)]
+pub struct ArchiveFaultOptions {
+    /// Archive to inject the fault into, the primary one if `None`
+    pub archive: Option<String>,
+    pub fault: ArchiveFault,
+    /// How long the fault lasts
+    pub duration: Duration,
+    /// Delay of each block write with [`ArchiveFault::WriteLatency`]
+    pub latency: Duration,
+    /// How long the archive is given to catch up once the fault is lifted
+    pub catch_up_timeout: Duration,
+}
+
+impl From<&cli::ArchiveFaultArgs> for ArchiveFaultOptions {
+    fn from(args: &cli::ArchiveFaultArgs) -> Self {
+        ArchiveFaultOptions {
+            archive: args.archive.clone(),
+            fault: args.fault,
+            duration: Duration::from_secs(args.duration),
+            latency: Duration::from_millis(args.latency),
+            catch_up_timeout: Duration::from_secs(args.catch_up_timeout),
+        }
+    }
+}
+
+/// Options of [`Node::start`], see `node start --help`
+#[derive(Debug, Clone, Default)]
+pub struct StartNodeOptions {
+    pub fresh_state: bool,
+    pub import_accounts: bool,
+    pub graphql_filtered_logs: bool,
+}
+
+impl From<&cli::StartNodeCommandArgs> for StartNodeOptions {
+    fn from(args: &cli::StartNodeCommandArgs) -> Self {
+        StartNodeOptions {
+            fresh_state: args.fresh_state,
+            import_accounts: args.import_accounts,
+            graphql_filtered_logs: args.graphql_filtered_logs,
+        }
+    }
+}
+
+/// Options of [`Node::restart`], see `node restart --help`
+#[derive(Debug, Clone, Default)]
+pub struct RestartNodeOptions {
+    pub fresh_state: bool,
+    pub import_accounts: bool,
+    pub flush: bool,
+}
+
+impl From<&cli::RestartNodeArgs> for RestartNodeOptions {
+    fn from(args: &cli::RestartNodeArgs) -> Self {
+        RestartNodeOptions {
+            fresh_state: args.fresh_state,
+            import_accounts: args.import_accounts,
+            flush: args.flush,
+        }
+    }
+}
+
+/// Options of [`Network::watch_height`], see `network watch-height --help`
+#[derive(Debug, Clone)]
+pub struct WatchHeightOptions {
+    /// Seconds without a new block after which the chain is stalled
+    pub stall_window: u64,
+    pub poll_interval: u64,
+    /// Seconds to watch for, until the chain stalls if None
+    pub duration: Option<u64>,
+    pub webhook_url: Option<String>,
+    pub restart_threshold: u32,
+}
+
+impl From<&cli::WatchHeightArgs> for WatchHeightOptions {
+    fn from(args: &cli::WatchHeightArgs) -> Self {
+        WatchHeightOptions {
+            stall_window: args.stall_window,
+            poll_interval: args.poll_interval,
+            duration: args.duration,
+            webhook_url: args.webhook_url.clone(),
+            restart_threshold: args.restart_threshold,
+        }
+    }
+}
+
+/// Options of [`Node::create_account`], see `node create-account --help`
+#[derive(Debug, Clone)]
+pub struct CreateAccountOptions {
+    pub account_name: String,
+    /// Genesis key pair funding the account, the node's own or the first one if None
+    pub funder: Option<String>,
+    /// Amount and fee of the funding payment in nanomina
+    pub amount: u64,
+    pub fee: u64,
+}
+
+impl From<&cli::CreateAccountArgs> for CreateAccountOptions {
+    fn from(args: &cli::CreateAccountArgs) -> Self {
+        CreateAccountOptions {
+            account_name: args.account_name.clone(),
+            funder: args.funder.clone(),
+            amount: args.amount,
+            fee: args.fee,
+        }
+    }
+}
+
+/// Options of [`Node::delegate`], see `node delegate --help`
+#[derive(Debug, Clone)]
+pub struct DelegateOptions {
+    pub from: String,
+    pub to: String,
+    /// Fee in nanomina
+    pub fee: u64,
+    /// Waits up to `wait_timeout` for the delegation to be included in a block
+    pub wait: bool,
+    pub wait_timeout: Duration,
+}
+
+impl From<&cli::DelegateArgs> for DelegateOptions {
+    fn from(args: &cli::DelegateArgs) -> Self {
+        DelegateOptions {
+            from: args.from.clone(),
+            to: args.to.clone(),
+            fee: args.fee,
+            wait: args.wait,
+            wait_timeout: Duration::from_secs(args.wait_timeout),
+        }
+    }
+}
+
+/// Options of [`Node::run_replayer`], see `node run-replayer --help`
+#[derive(Debug, Clone, Default)]
+pub struct ReplayerOptions {
+    pub start_slot_since_genesis: u64,
+    /// Replays a clone of the archive database instead of the live one
+    pub snapshot: bool,
+    pub target_state_hash: Option<String>,
+    /// File name in the network directory the replayed ledger is saved to
+    pub output_file: Option<String>,
+}
+
+impl From<&cli::ReplayerArgs> for ReplayerOptions {
+    fn from(args: &cli::ReplayerArgs) -> Self {
+        ReplayerOptions {
+            start_slot_since_genesis: args.start_slot_since_genesis,
+            snapshot: args.snapshot,
+            target_state_hash: args.target_state_hash.clone(),
+            output_file: args.output_file.clone(),
+        }
+    }
+}
+
+/// A local network, managed in the minimina directory
+#[derive(Clone)]
+pub struct Network {
+    directory_manager: DirectoryManager,
+    network_id: String,
+}
+
+impl Network {
+    /// Network `network_id` in the default minimina directory (`MINIMINA_HOME`, or the
+    /// config file's `base_directory`)
+    pub fn new(network_id: &str) -> Self {
+        Self::with_directory_manager(DirectoryManager::new(), network_id)
+    }
+
+    pub fn with_directory_manager(directory_manager: DirectoryManager, network_id: &str) -> Self {
+        Network {
+            directory_manager,
+            network_id: network_id.to_string(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.network_id
+    }
+
+    pub fn node(&self, node_id: &str) -> Node {
+        Node {
+            network: self.clone(),
+            node_id: node_id.to_string(),
+        }
+    }
+
+    pub fn exists(&self) -> bool {
+        self.directory_manager.network_path_exists(&self.network_id)
+    }
+
+    fn path(&self) -> PathBuf {
+        self.directory_manager.network_path(&self.network_id)
+    }
+
+    /// Compose file the network would be created with from the topology file at
+    /// `topology_path`, before images are built and host ports are assigned
+    pub fn render_compose(&self, topology_path: &Path) -> Result<String> {
+        let diagnostics = topology::validate(topology_path);
+        if !diagnostics.is_empty() {
+            let diagnostics: Vec<String> = diagnostics.iter().map(|d| format!("  {d}")).collect();
+            return failure(format!(
+                "Invalid topology file '{}':\n{}",
+                topology_path.display(),
+                diagnostics.join("\n")
+            ));
+        }
+        let topology = topology::Topology::new(topology_path)?;
+        let services = topology.services(&self.directory_manager.peer_list_file(&self.network_id));
+        Ok(DockerCompose::generate(&services, &self.path(), None))
+    }
+
+    /// Docker manager on the runtime the network was created with
+    fn docker(&self) -> DockerManager {
+        DockerManager::new(&self.path())
+    }
+
+    fn check_exists(&self) -> Result<()> {
+        if self.exists() {
+            Ok(())
+        } else {
+            Err(MiniminaError::NetworkNotFound {
+                network_id: self.network_id.clone(),
+                path: self.path(),
+            })
+        }
+    }
+
+    /// The networks in the directory of `directory_manager` with their config directories
+    pub fn list(directory_manager: &DirectoryManager) -> Result<network::List> {
+        let networks = match directory_manager.list_network_directories() {
+            Ok(networks) => networks,
+            Err(e) => return failure(format!("Failed to list networks: {e}")),
+        };
+        let mut list = network::List::new();
+        list.update(networks, &directory_manager.base_path.display().to_string());
+        Ok(list)
+    }
+
+    /// Creates the network, replacing an existing one with the same id
+    pub fn create(&self, options: &CreateOptions) -> Result<network::Create> {
+        let directory_manager = &self.directory_manager;
+        let network_id = self.network_id.as_str();
+        let fingerprint = Fingerprint::of(options)?;
+        if options.if_not_exists && self.exists() {
+            let recorded = Fingerprint::load(&self.path())?;
+            if !recorded.is_some_and(|recorded| recorded.same_inputs(&fingerprint)) {
+                return failure(format!(
+                    "Network '{network_id}' already exists and was not created from the same topology and genesis ledger."
+                ));
+            }
+            info!("Network '{network_id}' already exists, keeping it");
+            let info = directory_manager.get_network_info(network_id)?;
+            return Ok(serde_json::from_str(&info)?);
+        }
+        if options.no_overwrite && self.exists() {
+            return failure(format!(
+                "Network '{network_id}' already exists and overwriting it is disabled."
+            ));
+        }
+        // a broken topology is reported before anything is torn down or created
+        if let Some(topology_path) = &options.topology {
+            let diagnostics = topology::validate(topology_path);
+            if !diagnostics.is_empty() {
+                let diagnostics: Vec<String> =
+                    diagnostics.iter().map(|d| format!("  {d}")).collect();
+                return failure(format!(
+                    "Invalid topology file '{}':\n{}",
+                    topology_path.display(),
+                    diagnostics.join("\n")
+                ));
+            }
+        }
+        // as are named accounts funded twice
+        if let Err(e) = check_fundings(&options.fund) {
+            return failure(e.to_string());
+        }
+        if options.target == cli::Target::Docker {
+            check_host_resources(options, &self.path())?;
+        }
+        // an existing network is torn down on the runtime it was created with
+        check_setup_network(&self.docker(), directory_manager, network_id)?;
+        let docker = DockerManager::with_runtime(&self.path(), options.runtime);
+        let started = Instant::now();
+        let mut timings = network::Timings::default();
+
+        // key-pairs for block producers and libp2p keys for all services
+        // for default network (not topology based)
+        let mut bp_keys_opt: Option<HashMap<String, NodeKey>> = None;
+        let mut libp2p_keys_opt: Option<HashMap<String, NodeKey>> = None;
+
+        // consume the genesis ledger
+        handle_genesis_ledger(
+            options,
+            directory_manager,
+            network_id,
+            &mut bp_keys_opt,
+            &mut libp2p_keys_opt,
+            &mut timings,
+        )?;
+
+        // schedule genesis ahead of time
+        let scheduled_genesis = match (options.genesis_in, options.genesis_timestamp) {
+            (Some(delay), _) => Some(format_timestamp(&(chrono::Local::now() + delay))),
+            (None, Some(timestamp)) => Some(format_timestamp(&timestamp)),
+            (None, None) => None,
+        };
+        if let Some(timestamp) = scheduled_genesis {
+            info!("Scheduling genesis of network '{network_id}' at {timestamp}");
+            if let Err(e) = directory_manager.set_genesis_timestamp(network_id, &timestamp) {
+                return failure(format!("Failed to set genesis timestamp: {e}"));
+            }
+        }
+        if let Some(preset) = options.preset {
+            info!("Applying genesis constants preset {preset:?} to network '{network_id}'");
+            let genesis_ledger_path = directory_manager.genesis_ledger_path(network_id);
+            if let Err(e) = apply_preset(&genesis_ledger_path, preset) {
+                return failure(format!("Failed to apply genesis constants preset: {e}"));
+            }
+        }
+        if !options.fund.is_empty() {
+            let genesis_ledger_path = directory_manager.genesis_ledger_path(network_id);
+            match fund_named_accounts(
+                &DirectoryManager::home_path(),
+                &genesis_ledger_path,
+                &options.fund,
+            ) {
+                Ok(accounts) => {
+                    for (funding, account) in options.fund.iter().zip(accounts) {
+                        info!(
+                            "Funding named account '{}' ({}) in network '{network_id}'",
+                            funding.name, account.public_key
+                        );
+                    }
+                }
+                Err(e) => return failure(format!("Failed to fund named accounts: {e}")),
+            }
+        }
+
+        // build services from topology file
+        let mut services = handle_topology(
+            options,
+            directory_manager,
+            network_id,
+            bp_keys_opt,
+            libp2p_keys_opt,
+        )?;
+        let resource_limits = options.resource_limits();
+        for service in services.iter_mut() {
+            service.default_resource_limits(&resource_limits);
+            service.extend_daemon_flags(&options.extra_daemon_flags);
+        }
+
+        // build the images of services given as a git build
+        let builder = ImageBuilder::new(docker.runtime, &directory_manager.builds_path());
+        if let Err(e) = builder.build_services(&mut services, network_id) {
+            return failure(format!("Failed to build mina images from source: {e}"));
+        }
+
+        if options.with_monitoring && options.target == cli::Target::K8s {
+            return failure("The monitoring stack is only available for docker networks.".into());
+        }
+        if options.with_health_probes && options.target == cli::Target::K8s {
+            return failure("Health probe sidecars are only available for docker networks.".into());
+        }
+
+        // publish the services on host ports no other network uses
+        if options.target == cli::Target::Docker {
+            match assign_host_ports(
+                directory_manager,
+                network_id,
+                &mut services,
+                options.port_range.clone(),
+                options.with_monitoring,
+                options.with_health_probes,
+            ) {
+                Ok(Some(monitoring)) => {
+                    if let Err(e) = monitoring.save(&self.path()) {
+                        return failure(format!("Failed to set up the monitoring stack: {e}"));
+                    }
+                }
+                Ok(None) => (),
+                Err(e) => return failure(format!("Failed to assign host ports: {e}")),
+            }
+        }
+
+        // copy libp2p + network keys
+        if let Err(e) = directory_manager.copy_all_network_keys(network_id, &services) {
+            return failure(format!("Failed to copy keys with error: {e}"));
+        }
+
+        if options.target == cli::Target::K8s {
+            let created = render_k8s_network(
+                directory_manager,
+                network_id,
+                &services,
+                options,
+                started,
+                timings,
+            )?;
+            if let Err(e) = fingerprint.save(&self.path()) {
+                error!("Error generating fingerprint.json: {e}")
+            }
+            return Ok(created);
+        }
+
+        // generate docker compose
+        if let Err(e) = network::Timings::measure(&mut timings.compose_render_ms, || {
+            docker.compose_generate_file(&services)
+        }) {
+            return failure(format!(
+                "Failed to generate docker-compose.yaml with error: {e}"
+            ));
+        }
+
+        let created = create_network(
+            &docker,
+            directory_manager,
+            network_id,
+            &services,
+            options,
+            started,
+            timings,
+        )?;
+        if let Err(e) = fingerprint.save(&self.path()) {
+            error!("Error generating fingerprint.json: {e}")
+        }
+        Ok(created)
+    }
+
+    /// Starts all nodes of the network
+    pub fn start(&self, options: &StartOptions) -> Result<network::Start> {
+        let network_id = self.network_id.clone();
+        self.check_exists()?;
+
+        let genesis_staleness = if options.ignore_stale_genesis {
+            None
+        } else {
+            match self
+                .directory_manager
+                .genesis_staleness(&network_id, options.stale_genesis_after)
+            {
+                Ok(staleness) => Some(staleness),
+                Err(e) => {
+                    return failure(format!(
+                        "Failed to read genesis timestamp of network '{network_id}': {e}"
+                    ))
+                }
+            }
+        };
+
+        // the running network would not match the files it was created from
+        let changed = match Fingerprint::load(&self.path()) {
+            Ok(fingerprint) => fingerprint
+                .map(|fingerprint| fingerprint.changed_inputs())
+                .transpose()?
+                .unwrap_or_default(),
+            Err(e) => {
+                return failure(format!(
+                    "Failed to read fingerprint of network '{network_id}': {e}"
+                ))
+            }
+        };
+        if !changed.is_empty() {
+            let files: Vec<String> = changed.iter().map(|p| p.display().to_string()).collect();
+            let message = format!(
+                "{} changed since network '{network_id}' was created.",
+                files.join(", ")
+            );
+            if !options.force {
+                return failure(format!(
+                    "{message} Recreate it with 'network create' or pass --force to start it anyway."
+                ));
+            }
+            warn!("{message} Starting it anyway.");
+        }
+
+        let mut refreshed_genesis_timestamp = None;
+        if let Some(staleness) = genesis_staleness.as_ref().filter(|s| s.stale) {
+            let message = format!(
+                "Genesis timestamp '{}' may be outdated ({} minutes ago, threshold {} minutes).",
+                staleness.genesis_timestamp,
+                staleness.minutes_since_genesis,
+                staleness.stale_after_minutes
+            );
+            if options.fail_on_stale_genesis {
+                return failure(message);
+            }
+            if options.refresh_genesis {
+                info!("{message} Refreshing it.");
+                refreshed_genesis_timestamp = Some(self.refresh_genesis()?);
+            } else {
+                warn!("{message} In case network is unstable consider updating by running 'network create' again or pass --refresh-genesis.");
+            }
+        }
+
+        let docker = match &options.profiles {
+            Some(profiles) => self.docker().with_profiles(profiles),
+            None => self.docker(),
+        };
+        match docker.compose_start_all() {
+            Ok(output) => {
+                let compose_output = options.verbose.then(|| network::ComposeOutput {
+                    status: output.status.to_string(),
+                    stdout: String::from_utf8_lossy(&output.stdout).into(),
+                    stderr: String::from_utf8_lossy(&output.stderr).into(),
+                });
+                let sync_status = match options.wait {
+                    Some(timeout) => {
+                        let gql = GraphQl::new(self.directory_manager.clone());
+                        let mut endpoints = gql.get_endpoints(&network_id);
+                        // nodes of the profiles left out were not started
+                        if let Some(profiles) = &options.profiles {
+                            let services = self.directory_manager.get_services_info(&network_id)?;
+                            endpoints.retain(|node_id, _| {
+                                services
+                                    .iter()
+                                    .find(|service| &service.service_name == node_id)
+                                    .and_then(ComposeProfile::of)
+                                    .is_none_or(|profile| profiles.contains(&profile))
+                            });
+                        }
+                        Some(gql.wait_for_sync(&endpoints, timeout)?)
+                    }
+                    None => None,
+                };
+                Ok(network::Start {
+                    network_id,
+                    genesis_staleness,
+                    refreshed_genesis_timestamp,
+                    sync_status,
+                    compose_output,
+                })
+            }
+            Err(e) => failure(format!("Failed to start network '{network_id}': {e}")),
+        }
+    }
+
+    /// Sets the genesis timestamp of the network to now and removes its containers and
+    /// volumes, whose chain state and archive databases belong to the old genesis, then
+    /// recreates them like [`Network::recreate`]. Returns the new genesis timestamp.
+    pub fn refresh_genesis(&self) -> Result<String> {
+        let network_id = self.network_id.as_str();
+        self.check_exists()?;
+
+        let timestamp = current_timestamp();
+        self.directory_manager
+            .set_genesis_timestamp(network_id, &timestamp)
+            .map_err(|e| MiniminaError::Command(format!("Failed to set genesis timestamp: {e}")))?;
+
+        info!("Removing the containers and volumes of network '{network_id}'.");
+        let output = self.docker().compose_down(None, true, false)?;
+        if !output.status.success() {
+            return failure(format!(
+                "Failed to remove the containers of network '{network_id}': {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        self.recreate()?;
+        Ok(timestamp)
+    }
+
+    /// Creates and starts a network of a single daemon, seed and block producer at
+    /// once, without proofs and with a funded test account, and waits up to `wait`
+    /// for it to sync. Settings of the config file apply like for `network create`
+    pub fn quickstart(&self, wait: Duration) -> Result<network::Quickstart> {
+        let network_id = self.network_id.as_str();
+        let started = Instant::now();
+        let config = config::settings();
+        let default_topology = DefaultTopology {
+            seeds: 0,
+            block_producers: 1,
+            snark_workers: 0,
+            archive: false,
+        };
+        let mut extra_daemon_flags = config.extra_daemon_flags.clone();
+        extra_daemon_flags.extend(["-seed", "-proof-level", "none"].map(String::from));
+        let options = CreateOptions {
+            preset: Some(Preset::Quickstart),
+            port_range: config.port_range.clone(),
+            default_topology: default_topology.clone(),
+            extra_accounts: 1,
+            daemon_image: config.daemon_image.clone(),
+            archive_image: config.archive_image.clone(),
+            extra_daemon_flags,
+            ..Default::default()
+        };
+        let created = self.create(&options)?;
+        self.start(&StartOptions {
+            wait: Some(wait),
+            ..Default::default()
+        })?;
+
+        let genesis_ledger_path = self.directory_manager.genesis_ledger_path(network_id);
+        let account = ledger_accounts(&genesis_ledger_path)?
+            .into_iter()
+            .find_map(|account| {
+                Some(network::FundedAccount {
+                    private_key: account.sk?,
+                    public_key: account.pk,
+                    balance: account.balance,
+                })
+            })
+            .ok_or_else(|| {
+                MiniminaError::Command(format!(
+                    "Genesis ledger of network '{network_id}' has no funded test account."
+                ))
+            })?;
+        let node_id = default_topology.block_producer_names().remove(0);
+        Ok(network::Quickstart {
+            network_id: network_id.to_string(),
+            graphql_uri: created
+                .nodes
+                .get(&node_id)
+                .and_then(|node| node.graphql_uri.clone()),
+            node_id,
+            account,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Stops all nodes of the network in batches of `options.concurrency`,
+    /// reporting the containers which had to be killed
+    pub fn stop(&self, options: &StopOptions) -> Result<network::Stop> {
+        let network_id = self.network_id.clone();
+        self.check_exists()?;
+
+        let docker = self.docker();
+        let containers: Vec<String> = match docker.compose_ps(None) {
+            Ok(containers) => containers
+                .into_iter()
+                .filter(|c| {
+                    matches!(
+                        c.state,
+                        ContainerState::Running | ContainerState::Restarting
+                    )
+                })
+                .map(|c| c.name)
+                .collect(),
+            Err(e) => return failure(format!("Failed to stop network '{network_id}': {e}")),
+        };
+
+        let services = if options.flush {
+            match self.directory_manager.get_services_info(&network_id) {
+                Ok(services) => services,
+                Err(e) => return failure(format!("Failed to get services info: {e}")),
+            }
+        } else {
+            vec![]
+        };
+
+        let mut killed = vec![];
+        for batch in stop_batches(&containers, options.concurrency) {
+            let batch: Vec<&str> = batch.iter().map(String::as_str).collect();
+            let flushed: Vec<String> = services
+                .iter()
+                .filter(|s| batch.contains(&format!("{}-{network_id}", s.service_name).as_str()))
+                .filter(|service| stop_daemon(&docker, service, &network_id))
+                .map(|service| format!("{}-{network_id}", service.service_name))
+                .collect();
+            for container in &flushed {
+                wait_for_daemon_exit(&docker, container, options.timeout);
+            }
+            info!("Stopping {} in network '{network_id}'", batch.join(", "));
+            match docker.compose_stop_with_timeout(&batch, options.timeout) {
+                Ok(out) if out.status.success() => (),
+                Ok(out) => {
+                    return failure(format!(
+                        "Failed to stop network '{network_id}': {}",
+                        String::from_utf8_lossy(&out.stderr)
+                    ))
+                }
+                Err(e) => return failure(format!("Failed to stop network '{network_id}': {e}")),
+            }
+            killed.extend(
+                batch
+                    .into_iter()
+                    .filter(|container| was_killed(&docker, container))
+                    .map(String::from),
+            );
+        }
+
+        if !killed.is_empty() {
+            warn!(
+                "Containers not stopping in time were killed: {}",
+                killed.join(", ")
+            );
+        }
+        Ok(network::Stop { network_id, killed })
+    }
+
+    /// Logs of all containers of the network, `since` a timestamp or relative time
+    pub fn logs(&self, since: Option<&str>) -> Result<network::Logs> {
+        let network_id = self.network_id.clone();
+        self.check_exists()?;
+
+        match self.docker().compose_logs(since) {
+            Ok(out) if out.status.success() => Ok(network::Logs {
+                network_id,
+                logs: String::from_utf8_lossy(&out.stdout)
+                    .lines()
+                    .map(network::LogLine::parse)
+                    .collect(),
+            }),
+            Ok(out) => failure(format!(
+                "Failed to get logs of network '{network_id}': {}",
+                String::from_utf8_lossy(&out.stderr)
+            )),
+            Err(e) => failure(format!("Failed to get logs of network '{network_id}': {e}")),
+        }
+    }
+
+    /// Passes each new log line of the network's containers to `on_line` until
+    /// the containers stop, starting with those written `since`
+    pub fn follow_logs(
+        &self,
+        since: Option<&str>,
+        mut on_line: impl FnMut(&str) -> Result<()>,
+    ) -> Result<()> {
+        self.check_exists()?;
+
+        let mut child = self.docker().compose_logs_follow(since)?;
+        let stdout = child
+            .stdout
+            .take()
+            .expect("stdout of compose logs is piped");
+        for line in BufReader::new(stdout).lines() {
+            if let Err(e) = line
+                .map_err(MiniminaError::from)
+                .and_then(|line| on_line(&line))
+            {
+                let _ = child.kill();
+                return Err(e);
+            }
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            return failure(format!(
+                "Following the logs of network '{}' failed with {status}",
+                self.network_id
+            ));
+        }
+        Ok(())
+    }
+
+    /// Creates the database of the archive `archive`, or of all archives, and applies
+    /// its schema, dropping an existing database first if `reset`; optionally smoke
+    /// tests the archive afterwards. Postgres is left running if it was running.
+    pub fn init_archive(
+        &self,
+        archive: Option<&str>,
+        reset: bool,
+        verify: bool,
+    ) -> Result<network::InitArchive> {
+        let network_id = self.network_id.as_str();
+        self.check_exists()?;
+
+        let services = self.directory_manager.get_services_info(network_id)?;
+        let archives: Vec<&ServiceConfig> = match archive {
+            Some(name) => match ServiceConfig::get_archive(&services, name) {
+                Some(archive) => vec![archive],
+                None => {
+                    return failure(format!(
+                        "'{name}' is not an archive in network '{network_id}'."
+                    ))
+                }
+            },
+            None => ServiceConfig::get_archives(&services),
+        };
+        if archives.is_empty() {
+            return failure(format!("Network '{network_id}' has no archives."));
+        }
+
+        let docker = self.docker();
+        for archive in &archives {
+            let db = archive.archive_db(&services);
+            let postgres_name = db.service_name(network_id);
+            // the container is gone along with its data after `compose down -v`
+            check_output(docker.compose_create(Some(postgres_name.clone())))?;
+            let running = docker
+                .compose_ps(Some(ContainerState::Running))?
+                .iter()
+                .any(|container| container.name == postgres_name);
+
+            if reset {
+                check_output(docker.compose_start(vec![&postgres_name]))?;
+                docker.wait_for_state(
+                    &postgres_name,
+                    ContainerState::Running,
+                    Duration::from_secs(TIMEOUT_IN_SECS.into()),
+                )?;
+                let database = docker.postgres_config().for_archive(&db).database;
+                info!("Dropping archive database '{database}' in network '{network_id}'");
+                if let Err(e) = docker.drop_archive_database(&db, network_id, &database) {
+                    return failure(format!(
+                        "Failed to drop archive database '{database}' in network '{network_id}', \
+                         is its archive still running? {e}"
+                    ));
+                }
+            }
+
+            setup_archive_database(
+                &docker,
+                &self.directory_manager,
+                network_id,
+                archive,
+                &db,
+                None,
+                verify,
+            )?;
+            if running {
+                check_output(docker.compose_start(vec![&postgres_name]))?;
+            }
+        }
+        Ok(network::InitArchive {
+            network_id: network_id.to_string(),
+            archives: archives.iter().map(|a| a.service_name.clone()).collect(),
+        })
+    }
+
+    /// Reconciles the docker state of the network with its directory: re-renders
+    /// the compose file from `services.json`, removes stale duplicate containers
+    /// and containers of services the network doesn't define, and creates the
+    /// containers (and volumes) of services missing one
+    pub fn repair(&self) -> Result<network::Repair> {
+        let network_id = self.network_id.clone();
+        self.check_exists()?;
+        let docker = self.docker();
+
+        let services = self.directory_manager.get_services_info(&network_id)?;
+        let compose_file_rendered = docker.compose_refresh_file(&services)?;
+        if compose_file_rendered {
+            info!("Re-rendered the compose file of network '{network_id}' from its services.");
+        }
+
+        let containers = docker.compose_ps(None)?;
+        let removed_duplicates: Vec<String> = stale_duplicates(&containers)
+            .iter()
+            .map(|container| container.name.clone())
+            .collect();
+        let defined = docker.compose_services()?;
+        let (removed_unknown, created) = repair_plan(&defined, &containers);
+
+        for (removed, kind) in [
+            (&removed_duplicates, "stale"),
+            (&removed_unknown, "unknown"),
+        ] {
+            if !removed.is_empty() {
+                info!("Removing {kind} containers of network '{network_id}': {removed:?}");
+                let names: Vec<&str> = removed.iter().map(String::as_str).collect();
+                check_output(docker.remove_containers(&names))?;
+            }
+        }
+        for service in &created {
+            info!("Creating missing container of service '{service}' in network '{network_id}'.");
+            check_output(docker.compose_create(Some(service.clone())))?;
+        }
+
+        Ok(network::Repair {
+            network_id,
+            compose_file_rendered,
+            removed_duplicates,
+            removed_unknown,
+            created,
+        })
+    }
+
+    /// Recreates the docker state of the network from its directory, e.g. after its
+    /// containers and volumes were wiped: re-renders the compose file from
+    /// `services.json`, creates all containers and volumes and sets up the database
+    /// of every archive whose postgres volume was missing. Keys, the genesis ledger
+    /// and surviving volumes are left untouched.
+    pub fn recreate(&self) -> Result<network::Recreate> {
+        let network_id = self.network_id.as_str();
+        self.check_exists()?;
+        let docker = self.docker();
+
+        let services = match self.directory_manager.get_services_info(network_id) {
+            Ok(services) => services,
+            Err(e) => {
+                return failure(format!(
+                    "Failed to read the services of network '{network_id}', \
+                     it can't be recreated without its services.json: {e}"
+                ))
+            }
+        };
+        docker.compose_generate_file(&services)?;
+
+        // databases of volumes created now have to be set up again
+        let mut archives_initialized = vec![];
+        let mut archives_kept = vec![];
+        for archive in ServiceConfig::get_archives(&services) {
+            if docker.has_volume(&archive.archive_db(&services).volume())? {
+                archives_kept.push(archive);
+            } else {
+                archives_initialized.push(archive);
+            }
+        }
+
+        info!("Recreating the containers of network '{network_id}'.");
+        let output = docker.compose_create(None)?;
+        if !output.status.success() {
+            return failure(format!(
+                "Failed to recreate network '{network_id}' with 'docker compose create': {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        for archive in &archives_initialized {
+            info!(
+                "Setting up the database of archive '{}' in network '{network_id}'.",
+                archive.service_name
+            );
+            setup_archive_database(
+                &docker,
+                &self.directory_manager,
+                network_id,
+                archive,
+                &archive.archive_db(&services),
+                None,
+                false,
+            )?;
+        }
+        self.directory_manager
+            .save_network_info(network_id, &services, docker.runtime)?;
+
+        let names = |archives: &[&ServiceConfig]| {
+            archives
+                .iter()
+                .map(|archive| archive.service_name.clone())
+                .collect()
+        };
+        Ok(network::Recreate {
+            network_id: network_id.to_string(),
+            archives_initialized: names(&archives_initialized),
+            archives_kept: names(&archives_kept),
+        })
+    }
+
+    /// Variables pointing external tools at `node_id`, or at the first seed (or other
+    /// node with a GraphQL endpoint), and at the node's archive database or the
+    /// network's primary one
+    pub fn env(&self, node_id: Option<&str>) -> Result<network::Env> {
+        let network_id = self.network_id.clone();
+        self.check_exists()?;
+        let services = self.directory_manager.get_services_info(&network_id)?;
+
+        let node = match node_id {
+            Some(node_id) => services
+                .iter()
+                .find(|service| service.service_name == node_id),
+            None => {
+                let mut nodes: Vec<&ServiceConfig> = services
+                    .iter()
+                    .filter(|service| service.published_graphql_port().is_some())
+                    .collect();
+                nodes.sort_by_key(|service| {
+                    (
+                        service.service_type != ServiceType::Seed,
+                        &service.service_name,
+                    )
+                });
+                nodes.first().copied()
+            }
+        };
+        let Some(node) = node else {
+            return Err(match node_id {
+                Some(node_id) => self.node(node_id).not_found(),
+                None => MiniminaError::Command(format!(
+                    "Network '{network_id}' has no node with a GraphQL endpoint."
+                )),
+            });
+        };
+
+        let mut variables = BTreeMap::new();
+        variables.insert("NETWORK_DIR".to_string(), self.path().display().to_string());
+        variables.insert(
+            "NODE_CONTAINER".to_string(),
+            format!("{}-{network_id}", node.service_name),
+        );
+        if let Some(uri) = node.to_node_info().graphql_uri {
+            variables.insert("MINA_GRAPHQL_URL".to_string(), uri);
+        }
+
+        let archives = ServiceConfig::get_archives(&services);
+        let archive = archives
+            .iter()
+            .find(|archive| archive.service_name == node.service_name)
+            .or_else(|| {
+                archives
+                    .iter()
+                    .find(|archive| archive.archive_db(&services) == ArchiveDb::primary())
+            });
+        if let Some(archive) = archive {
+            let docker = self.docker();
+            let db = archive.archive_db(&services);
+            // published on a port of docker's choosing unless configured
+            let port = match archive.postgres_host_port {
+                Some(port) => Some(port),
+                None => docker
+                    .compose_port(&db.service_name(&network_id), POSTGRES_PORT)
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to get the host port of the archive database: {e}");
+                        None
+                    }),
+            };
+            match port {
+                Some(port) => {
+                    let uri = docker
+                        .postgres_config()
+                        .for_archive(&db)
+                        .uri_with_port("localhost", port);
+                    variables.insert("ARCHIVE_PG_URI".to_string(), uri);
+                }
+                None => warn!(
+                    "The archive database of '{}' is not running, ARCHIVE_PG_URI is not set.",
+                    archive.service_name
+                ),
+            }
+        }
+
+        Ok(network::Env {
+            network_id,
+            node_id: node.service_name.clone(),
+            variables,
+        })
+    }
+
+    /// Splits the nodes of the network into partitions that can't reach each other
+    pub fn partition(&self, groups: &PartitionGroups) -> Result<network::Partition> {
+        let network_id = self.network_id.clone();
+        self.check_exists()?;
+        let services = self.directory_manager.get_services_info(&network_id)?;
+
+        let state = chaos::partition(&self.docker(), &self.path(), &services, groups)?;
+        Ok(network::Partition {
+            network_id,
+            groups: state.partitions.iter().map(|p| p.nodes.clone()).collect(),
+            networks: state.partitions.into_iter().map(|p| p.network).collect(),
+        })
+    }
+
+    /// Reconnects the nodes of a partitioned network
+    pub fn heal(&self) -> Result<network::Heal> {
+        let network_id = self.network_id.clone();
+        self.check_exists()?;
+
+        let state = chaos::heal(&self.docker(), &self.path())?;
+        Ok(network::Heal {
+            network_id,
+            groups: state.partitions.into_iter().map(|p| p.nodes).collect(),
+        })
+    }
+
+    /// Injects a fault into the archive pipeline of the running network, lifts it and
+    /// audits the archive for missing blocks until it has caught up with the chain
+    pub fn archive_fault(&self, options: &ArchiveFaultOptions) -> Result<network::ArchiveFault> {
+        let network_id = self.network_id.clone();
+        self.check_exists()?;
+        let services = self.directory_manager.get_services_info(&network_id)?;
+        let archives = ServiceConfig::get_archives(&services);
+        let archive = match &options.archive {
+            Some(name) => match ServiceConfig::get_archive(&services, name) {
+                Some(archive) => archive,
+                None => {
+                    return failure(format!(
+                        "Node '{name}' is not an archive in network '{network_id}'."
+                    ))
+                }
+            },
+            None => match archives
+                .iter()
+                .find(|archive| archive.archive_db(&services) == ArchiveDb::primary())
+            {
+                Some(archive) => archive,
+                None => return failure(format!("Network '{network_id}' has no archive.")),
+            },
+        };
+        let db = archive.archive_db(&services);
+        let docker = self.docker();
+
+        let before = docker.archive_stats(&db, &network_id)?;
+        let started = Instant::now();
+        chaos::inject_archive_fault(
+            &docker,
+            archive,
+            &db,
+            &network_id,
+            options.fault,
+            options.duration,
+            options.latency,
+        )?;
+
+        let lifted = Instant::now();
+        let audit = chaos::wait_for_archive_catch_up(
+            &docker,
+            &db,
+            &network_id,
+            before.max_block_height,
+            options.catch_up_timeout,
+        )?;
+        let caught_up = audit.caught_up(before.max_block_height);
+        Ok(network::ArchiveFault {
+            network_id,
+            archive: archive.service_name.clone(),
+            fault: options.fault,
+            fault_duration_ms: lifted.duration_since(started).as_millis() as u64,
+            max_block_height_before: before.max_block_height,
+            max_block_height_after: audit.max_block_height,
+            missing_heights: audit.missing_heights,
+            caught_up,
+            catch_up_ms: caught_up.then(|| lifted.elapsed().as_millis() as u64),
+        })
+    }
+
+    /// The nodes of the network with their role, image, client port, GraphQL URL and
+    /// container state, sorted by node id
+    pub fn nodes(&self) -> Result<network::Nodes> {
+        self.check_exists()?;
+        let mut services = self.directory_manager.get_services_info(&self.network_id)?;
+        services.sort_by(|a, b| a.service_name.cmp(&b.service_name));
+        let containers = self.docker().compose_ps(None)?;
+        Ok(network::Nodes {
+            network_id: self.network_id.clone(),
+            nodes: services
+                .iter()
+                .map(|service| service.to_node_summary(&self.network_id, &containers))
+                .collect(),
+        })
+    }
+
+    /// Info of the network as created, with the state, IP addresses and published
+    /// ports of its containers merged in if they can be listed
+    pub fn info(&self) -> Result<network::Create> {
+        let mut info = self.created_info()?;
+        self.merge_containers(&mut info);
+        Ok(info)
+    }
+
+    /// Info of the network extended with the best chain height of each daemon and the
+    /// block statistics of each archive database, so archive lag is visible
+    pub fn live_info(&self) -> Result<network::LiveInfo> {
+        let network_id = self.network_id.as_str();
+        let info = self.info()?;
+        let gql = GraphQl::new(self.directory_manager.clone());
+        let heights = gql
+            .get_endpoints(network_id)
+            .into_iter()
+            .map(|(node_id, gql_ep)| (node_id, gql.block_height(&gql_ep)))
+            .collect();
+
+        let services = self.directory_manager.get_services_info(network_id)?;
+        let docker = self.docker();
+        let mut archives = HashMap::new();
+        for archive in ServiceConfig::get_archives(&services) {
+            match docker.archive_stats(&archive.archive_db(&services), network_id) {
+                Ok(stats) => {
+                    archives.insert(archive.service_name.clone(), stats);
+                }
+                Err(e) => warn!(
+                    "Failed to query the archive database of '{}' in network '{network_id}': {e}",
+                    archive.service_name
+                ),
+            }
+        }
+
+        Ok(network::LiveInfo {
+            info,
+            heights,
+            archives,
+        })
+    }
+
+    /// Dependency graph of the network's services
+    pub fn graph(&self) -> Result<network::Graph> {
+        let network_id = self.network_id.as_str();
+        self.check_exists()?;
+        match self.directory_manager.get_service_graph(network_id) {
+            Ok(services) => Ok(network::Graph {
+                network_id: network_id.to_string(),
+                services,
+            }),
+            Err(e) => failure(format!(
+                "Failed to get the service graph of network '{network_id}': {e}"
+            )),
+        }
+    }
+
+    /// Info of the network as saved when it was created
+    fn created_info(&self) -> Result<network::Create> {
+        let network_id = self.network_id.as_str();
+        self.check_exists()?;
+        match self.directory_manager.get_network_info(network_id) {
+            Ok(json_data) => Ok(serde_json::from_str(&json_data)?),
+            Err(e) => failure(format!(
+                "Failed to get info for network '{network_id}' with error: {e}"
+            )),
+        }
+    }
+
+    /// Merges the state, IP addresses and published ports of the network's containers
+    /// into its info, which is left as created if they can't be listed
+    fn merge_containers(&self, info: &mut network::Create) {
+        let network_id = self.network_id.as_str();
+        let docker = self.docker();
+        let containers = match docker.compose_ps(None) {
+            Ok(containers) => containers,
+            Err(e) => {
+                warn!("Failed to list the containers of network '{network_id}': {e}");
+                return;
+            }
+        };
+        let services = match self.directory_manager.get_services_info(network_id) {
+            Ok(services) => services,
+            Err(e) => {
+                warn!("Failed to get the services of network '{network_id}': {e}");
+                return;
+            }
+        };
+        let names: Vec<&str> = containers.iter().map(|c| c.name.as_str()).collect();
+        let ip_addresses = docker.inspect_ip_addresses(&names).unwrap_or_else(|e| {
+            warn!("Failed to get the IP addresses of network '{network_id}': {e}");
+            HashMap::new()
+        });
+        info.merge_containers(&services, &containers, &ip_addresses);
+    }
+
+    /// Status of the network from `docker compose ls` and `docker compose ps`
+    pub fn status(&self) -> Result<network::Status> {
+        let network_id = self.network_id.as_str();
+        self.check_exists()?;
+        let docker = self.docker();
+        let ls_out = match docker.compose_ls() {
+            Ok(out) => out,
+            Err(e) => {
+                return failure(format!(
+                    "Failed to get status from docker compose ls for network '{network_id}': {e}."
+                ))
+            }
+        };
+        let ps_out = match docker.compose_ps(None) {
+            Ok(out) => out,
+            Err(e) => {
+                return failure(format!(
+                    "Failed to get status from docker compose ps for network '{network_id}': {e}."
+                ))
+            }
+        };
+
+        let mut status = network::Status::new(network_id);
+        status.update_from_compose_ls(ls_out, &docker.compose_path.to_string_lossy());
+        status.update_from_compose_ps(ps_out);
+        status.network_dir = self.path().display().to_string();
+        Ok(status)
+    }
+
+    /// Passes the status of the network to `on_status` every `interval` until it
+    /// fails. Failures to get the status are logged without stopping, e.g. while
+    /// the network is being created
+    pub fn watch_status(
+        &self,
+        interval: Duration,
+        mut on_status: impl FnMut(&network::Status) -> Result<()>,
+    ) -> Result<()> {
+        self.check_exists()?;
+        loop {
+            match self.status() {
+                Ok(status) => on_status(&status)?,
+                Err(e) => warn!("{e}"),
+            }
+            std::thread::sleep(interval);
+        }
+    }
+
+    /// Current global slot and epoch of the network from the slot constants of its
+    /// genesis ledger
+    pub fn slots(&self) -> Result<network::Slots> {
+        let network_id = self.network_id.clone();
+        self.check_exists()?;
+        let genesis_ledger_path = self.directory_manager.genesis_ledger_path(&network_id);
+        let calendar = match SlotCalendar::from_genesis_ledger(&genesis_ledger_path) {
+            Ok(calendar) => calendar,
+            Err(e) => {
+                return failure(format!(
+                    "Failed to read slot constants for network '{network_id}': {e}"
+                ))
+            }
+        };
+        let position = calendar.position_at(chrono::Local::now());
+        Ok(network::Slots {
+            network_id,
+            genesis_timestamp: calendar.genesis_timestamp.to_rfc3339(),
+            slots_per_epoch: calendar.slots_per_epoch,
+            slot_duration_ms: calendar.slot_duration_ms,
+            started: position.started,
+            global_slot: position.global_slot,
+            epoch: position.epoch,
+            slot_in_epoch: position.slot_in_epoch,
+            ms_until_next_slot: position.ms_until_next_slot,
+            ms_until_next_epoch: position.ms_until_next_epoch,
+        })
+    }
+
+    /// Bytes received and transmitted by each running node over `interval`
+    pub fn net_report(&self, interval: Duration) -> Result<network::NetReport> {
+        let network_id = self.network_id.as_str();
+        self.check_exists()?;
+        match net_report::sample(&self.docker(), network_id, interval) {
+            Ok(report) => {
+                if report.nodes.is_empty() {
+                    warn!("No running nodes found in network '{network_id}'.");
+                }
+                Ok(report)
+            }
+            Err(e) => failure(format!(
+                "Failed to report network traffic of network '{network_id}': {e}"
+            )),
+        }
+    }
+
+    /// o1js `Mina.Network` configuration of the network, paying fees from the key of
+    /// `fee_payer` or of the first block producer
+    pub fn o1js_config(
+        &self,
+        fee_payer: Option<&str>,
+        archive_endpoint: Option<String>,
+    ) -> Result<network::O1jsConfig> {
+        let info = self.created_info()?;
+        network::O1jsConfig::from_network_info(&info, fee_payer, archive_endpoint).or_else(|e| {
+            failure(format!(
+                "Failed to build o1js config for network '{}': {e}",
+                self.network_id
+            ))
+        })
+    }
+
+    /// The genesis accounts joined with the stored key files and the running nodes' wallets
+    pub fn accounts(&self) -> Result<network::Accounts> {
+        let network_id = self.network_id.as_str();
+        self.check_exists()?;
+        let directory_manager = &self.directory_manager;
+
+        let genesis_ledger_path = directory_manager.genesis_ledger_path(network_id);
+        let ledger_accounts = match ledger_accounts(&genesis_ledger_path) {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                return failure(format!(
+                    "Failed to read genesis accounts of network '{network_id}': {e}"
+                ))
+            }
+        };
+        let key_files = directory_manager.stored_public_keys(network_id)?;
+        let services = directory_manager.get_services_info(network_id)?;
+
+        let running: Vec<String> = self
+            .docker()
+            .compose_ps(Some(ContainerState::Running))?
+            .into_iter()
+            .map(|container| container.name)
+            .collect();
+        let gql = GraphQl::new(directory_manager.clone());
+        let mut wallets: Vec<(String, Vec<String>)> = vec![];
+        for (node_id, gql_ep) in gql.get_endpoints(network_id) {
+            if !running.contains(&format!("{node_id}-{network_id}")) {
+                continue;
+            }
+            match gql.owned_wallets(&gql_ep) {
+                Ok(public_keys) => wallets.push((node_id, public_keys)),
+                Err(e) => warn!("Failed to get wallets of node '{node_id}': {e}"),
+            }
+        }
+        wallets.sort();
+
+        let accounts = ledger_accounts
+            .into_iter()
+            .map(|account| {
+                let key_file = key_files.get(&account.pk).cloned();
+                let node = key_file
+                    .as_ref()
+                    .and_then(|key_file| {
+                        key_file.strip_prefix(&format!("{}/", directory_manager::NETWORK_KEYPAIRS))
+                    })
+                    .filter(|name| services.iter().any(|service| service.service_name == *name))
+                    .map(String::from);
+                let imported_into = wallets
+                    .iter()
+                    .filter(|(_, public_keys)| public_keys.contains(&account.pk))
+                    .map(|(node_id, _)| node_id.clone())
+                    .collect();
+                network::Account {
+                    public_key: account.pk,
+                    balance: account.balance,
+                    delegate: account.delegate,
+                    key_file,
+                    node,
+                    imported_into,
+                }
+            })
+            .collect();
+
+        Ok(network::Accounts {
+            network_id: network_id.into(),
+            accounts,
+        })
+    }
+
+    /// The key pair of `node_id`, or of the first block producer, in the import format of
+    /// `format` with the GraphQL endpoint the node is published on
+    pub fn export_key(
+        &self,
+        node_id: Option<&str>,
+        format: WalletFormat,
+    ) -> Result<output::keys::Export> {
+        self.check_exists()?;
+        let mut services = self.directory_manager.get_services_info(&self.network_id)?;
+        services.sort_by(|a, b| a.service_name.cmp(&b.service_name));
+        let service = match node_id {
+            Some(node_id) => services
+                .iter()
+                .find(|service| service.service_name == node_id)
+                .ok_or_else(|| self.node(node_id).not_found())?,
+            None => services
+                .iter()
+                .find(|service| {
+                    service.service_type == ServiceType::BlockProducer
+                        && service.private_key.is_some()
+                })
+                .ok_or_else(|| {
+                    MiniminaError::Command(
+                        "Network has no block producer with a private key.".to_string(),
+                    )
+                })?,
+        };
+        let Some(private_key) = service.private_key.clone() else {
+            return failure(format!(
+                "Node '{}' has no private key.",
+                service.service_name
+            ));
+        };
+
+        // the live port if the node runs, as it may have been remapped
+        let containers = self.docker().compose_ps(None).unwrap_or_else(|e| {
+            warn!(
+                "Failed to list containers of network '{}': {e}",
+                self.network_id
+            );
+            vec![]
+        });
+        Ok(output::keys::Export {
+            network_id: self.network_id.clone(),
+            node_id: service.service_name.clone(),
+            format,
+            public_key: keys::public_key_of(&private_key)?,
+            private_key,
+            graphql_uri: service
+                .to_node_summary(&self.network_id, &containers)
+                .graphql_uri,
+        })
+    }
+
+    /// Queries the chain height of every node of the running network and reports those
+    /// more than `max_lag` blocks behind the best one, with the resource limits of
+    /// their containers
+    pub fn lag_report(&self, max_lag: u64) -> Result<network::LagReport> {
+        let network_id = self.network_id.clone();
+        self.check_exists()?;
+        let services = self.directory_manager.get_services_info(&network_id)?;
+        let gql = GraphQl::new(self.directory_manager.clone());
+        let heights: BTreeMap<String, Option<u64>> = gql
+            .get_endpoints(&network_id)
+            .iter()
+            .map(|(node_id, gql_ep)| (node_id.clone(), gql.block_height(gql_ep)))
+            .collect();
+        if heights.is_empty() {
+            return failure(format!(
+                "No graphql endpoints found for network '{network_id}'."
+            ));
+        }
+
+        let best_height = heights.values().flatten().max().copied();
+        let behind = nodes_behind(&heights, max_lag);
+        let nodes = heights
+            .into_iter()
+            .map(|(node_id, height)| {
+                let limits = services
+                    .iter()
+                    .find(|service| service.service_name == node_id)
+                    .and_then(|service| service.limits.clone());
+                let lag = height.zip(best_height).map(|(height, best)| best - height);
+                (
+                    node_id,
+                    network::NodeLag {
+                        height,
+                        lag,
+                        limits,
+                    },
+                )
+            })
+            .collect();
+        Ok(network::LagReport {
+            network_id,
+            best_height,
+            max_lag,
+            nodes,
+            behind,
+        })
+    }
+
+    /// Polls the chain height of every node of the network, alerting on restarting
+    /// and dead containers, until the height hasn't advanced for `stall_window`
+    /// seconds or `duration` elapsed, and returns the last report, `stalled` in the
+    /// former case
+    pub fn watch_height(&self, options: &WatchHeightOptions) -> Result<network::WatchHeight> {
+        let network_id = self.network_id.as_str();
+        self.check_exists()?;
+        let gql = GraphQl::new(self.directory_manager.clone());
+        let endpoints = gql.get_endpoints(network_id);
+        if endpoints.is_empty() {
+            return failure(format!(
+                "No graphql endpoints found for network '{network_id}'."
+            ));
+        }
+
+        let webhook = options.webhook_url.as_deref().map(alert::Webhook::new);
+        let docker = self.docker();
+        let mut node_watch = alert::NodeWatch::new(options.restart_threshold);
+
+        let started = Instant::now();
+        let mut last_advance = Instant::now();
+        let mut best_height: Option<u64> = None;
+
+        loop {
+            match docker.compose_ps(None) {
+                Ok(containers) => {
+                    let nodes = containers
+                        .into_iter()
+                        .map(|container| alert::NodeState {
+                            node_id: container
+                                .service
+                                .strip_suffix(&format!("-{network_id}"))
+                                .unwrap_or(&container.service)
+                                .to_string(),
+                            state: container.state,
+                            exit_code: container.exit_code,
+                        })
+                        .collect();
+                    for alert in node_watch.observe(network_id, nodes) {
+                        warn!("{}", alert.text);
+                        if let Some(webhook) = &webhook {
+                            webhook.notify(&alert);
+                        }
+                    }
+                }
+                Err(e) => warn!("Failed to get container states of network '{network_id}': {e}"),
+            }
+
+            let heights: HashMap<String, Option<u64>> = endpoints
+                .iter()
+                .map(|(node_id, gql_ep)| (node_id.clone(), gql.block_height(gql_ep)))
+                .collect();
+
+            let height = heights.values().flatten().max().copied();
+            if height > best_height {
+                best_height = height;
+                last_advance = Instant::now();
+            }
+
+            let seconds_since_advance = last_advance.elapsed().as_secs();
+            let report = network::WatchHeight {
+                network_id: network_id.to_string(),
+                stalled: seconds_since_advance >= options.stall_window,
+                height: best_height,
+                seconds_since_advance,
+                heights,
+            };
+            info!(
+                "Network '{network_id}' height: {best_height:?}, unchanged for {seconds_since_advance}s"
+            );
+
+            if report.stalled {
+                if let Some(webhook) = &webhook {
+                    webhook.notify(&alert::Alert::chain_stalled(
+                        network_id,
+                        best_height,
+                        seconds_since_advance,
+                    ));
+                }
+                return Ok(report);
+            }
+            if options
+                .duration
+                .is_some_and(|duration| started.elapsed().as_secs() >= duration)
+            {
+                return Ok(report);
+            }
+
+            std::thread::sleep(Duration::from_secs(options.poll_interval));
+        }
+    }
+
+    /// Exports the network directory to the archive `output`, with a dump of its
+    /// archive databases if `archive_data`
+    pub fn export(&self, output: &Path, archive_data: bool) -> Result<network::Export> {
+        let directory_manager = &self.directory_manager;
+        let network_id = self.network_id.as_str();
+        self.check_exists()?;
+        let mut dump_paths = vec![];
+
+        if archive_data {
+            let services = directory_manager.get_services_info(network_id)?;
+            let archives = ServiceConfig::get_archives(&services);
+            if archives.is_empty() {
+                return failure(format!(
+                    "Network '{network_id}' has no archive node to export data from."
+                ));
+            }
+
+            let docker = self.docker();
+            for archive in archives {
+                let db = archive.archive_db(&services);
+                let dump_path = directory_manager.archive_dump_path(network_id, &db);
+                match docker.compose_dump_archive_data(&db, network_id) {
+                    Ok(out) if out.status.success() => fs::write(&dump_path, out.stdout)?,
+                    Ok(out) => {
+                        return failure(format!(
+                            "Failed to dump archive data of '{}' in network '{network_id}': {}",
+                            archive.service_name,
+                            String::from_utf8_lossy(&out.stderr)
+                        ))
+                    }
+                    Err(e) => {
+                        return failure(format!(
+                        "Error while dumping archive data of '{}' in network '{network_id}': {e}",
+                        archive.service_name
+                    ))
+                    }
+                }
+                dump_paths.push(dump_path);
+            }
+        }
+
+        let result = directory_manager.export_network(network_id, output);
+        for dump_path in dump_paths {
+            fs::remove_file(&dump_path)?;
+        }
+
+        match result {
+            Ok(()) => Ok(network::Export {
+                network_id: network_id.to_string(),
+                output: output.display().to_string(),
+                archive_data,
+            }),
+            Err(e) => failure(format!("Failed to export network '{network_id}': {e}")),
+        }
+    }
+
+    /// Imports a network exported with [`Network::export`] into the minimina directory
+    /// of `directory_manager`, registers it with docker compose and restores its
+    /// archive databases if the export contains their data
+    pub fn import(directory_manager: DirectoryManager, input: &Path) -> Result<network::Import> {
+        let network_id = match directory_manager.import_network(input) {
+            Ok(network_id) => network_id,
+            Err(e) => {
+                return failure(format!(
+                    "Failed to import network from '{}': {e}",
+                    input.display()
+                ))
+            }
+        };
+
+        let network = Network::with_directory_manager(directory_manager, &network_id);
+        let directory_manager = &network.directory_manager;
+        let docker = network.docker();
+        let services = directory_manager.get_services_info(&network_id)?;
+
+        // compose file contains absolute paths of the exporting machine
+        if let Err(e) = docker.compose_generate_file(&services) {
+            return failure(format!(
+                "Failed to generate docker-compose.yaml with error: {e}"
+            ));
+        }
+
+        match docker.compose_create(None) {
+            Ok(out) if out.status.success() => {
+                info!("Successfully created docker-compose for network '{network_id}'!")
+            }
+            Ok(out) => {
+                return failure(format!(
+                    "Failed to create network '{network_id}' with 'docker compose create': {}",
+                    String::from_utf8_lossy(&out.stderr)
+                ))
+            }
+            Err(e) => {
+                return failure(format!(
+                    "Failed to register network '{network_id}' with 'docker compose create': {e}"
+                ))
+            }
+        }
+
+        let mut archive_data = false;
+        for archive in ServiceConfig::get_archives(&services) {
+            let db = archive.archive_db(&services);
+            let dump_path = directory_manager.archive_dump_path(&network_id, &db);
+            let dump = dump_path.exists().then_some(dump_path.as_path());
+            setup_archive_database(
+                &docker,
+                directory_manager,
+                &network_id,
+                archive,
+                &db,
+                dump,
+                false,
+            )?;
+            if dump.is_some() {
+                fs::remove_file(&dump_path)?;
+                archive_data = true;
+            }
+        }
+
+        Ok(network::Import {
+            network_dir: network.path().display().to_string(),
+            network_id,
+            archive_data,
+        })
+    }
+
+    /// Packs the network directory together with its container volumes into the
+    /// archive `output`, pausing the running containers while the volumes are exported
+    pub fn snapshot(&self, output: &Path) -> Result<network::Snapshot> {
+        let directory_manager = &self.directory_manager;
+        let network_id = self.network_id.as_str();
+        self.check_exists()?;
+        let docker = self.docker();
+        let services = directory_manager.get_services_info(network_id)?;
+        let volumes = docker.state_volumes(&services);
+
+        let paused = !docker.compose_ps(Some(ContainerState::Running))?.is_empty();
+        if paused {
+            if let Err(e) = check_output(docker.compose_pause(vec![])) {
+                return failure(format!("Failed to pause network '{network_id}': {e}"));
+            }
+        }
+
+        let volumes_path = directory_manager.snapshot_volumes_path(network_id);
+        fs::create_dir_all(&volumes_path)?;
+        let result = volumes
+            .iter()
+            .try_for_each(|(volume, image)| {
+                info!("Exporting volume '{volume}' of network '{network_id}'");
+                check_output(docker.export_volume(volume, image, &volumes_path))
+            })
+            .and_then(|_| directory_manager.export_network(network_id, output));
+        fs::remove_dir_all(&volumes_path)?;
+
+        if paused {
+            if let Err(e) = check_output(docker.compose_unpause(vec![])) {
+                return failure(format!("Failed to unpause network '{network_id}': {e}"));
+            }
+        }
+
+        match result {
+            Ok(()) => Ok(network::Snapshot {
+                network_id: network_id.to_string(),
+                output: output.display().to_string(),
+                volumes: volumes.into_iter().map(|(volume, _)| volume).collect(),
+            }),
+            Err(e) => failure(format!("Failed to snapshot network '{network_id}': {e}")),
+        }
+    }
+
+    /// Restores a snapshot taken with [`Network::snapshot`] as this network:
+    /// registers it with docker compose and fills the volumes of the created containers
+    pub fn restore(&self, input: &Path) -> Result<network::Restore> {
+        let directory_manager = &self.directory_manager;
+        let network_id = self.network_id.as_str();
+        let snapshot_network_id = match directory_manager.restore_network(input, network_id) {
+            Ok(snapshot_network_id) => snapshot_network_id,
+            Err(e) => {
+                return failure(format!(
+                    "Failed to restore network from '{}': {e}",
+                    input.display()
+                ))
+            }
+        };
+
+        let docker = self.docker();
+        let services = directory_manager.get_services_info(network_id)?;
+        directory_manager.save_network_info(network_id, &services, docker.runtime)?;
+
+        if let Err(e) = docker.compose_generate_file(&services) {
+            return failure(format!(
+                "Failed to generate docker-compose.yaml with error: {e}"
+            ));
+        }
+        if let Err(e) = check_output(docker.compose_create(None)) {
+            return failure(format!(
+                "Failed to create network '{network_id}' with 'docker compose create': {e}"
+            ));
+        }
+
+        let volumes_path = directory_manager.snapshot_volumes_path(network_id);
+        let mut volumes = vec![];
+        for (volume, image) in docker.state_volumes(&services) {
+            if !volumes_path.join(format!("{volume}.tar.gz")).exists() {
+                warn!("Snapshot has no data for volume '{volume}'");
+                continue;
+            }
+            info!("Restoring volume '{volume}' of network '{network_id}'");
+            if let Err(e) = check_output(docker.import_volume(&volume, &image, &volumes_path)) {
+                return failure(format!("Failed to restore volume '{volume}': {e}"));
+            }
+            volumes.push(volume);
+        }
+        if volumes_path.exists() {
+            fs::remove_dir_all(&volumes_path)?;
+        }
+
+        Ok(network::Restore {
+            network_id: network_id.to_string(),
+            snapshot_network_id,
+            network_dir: self.path().display().to_string(),
+            volumes,
+        })
+    }
+
+    /// What [`Network::delete`] destroys, or overwriting the network with
+    /// [`Network::create`] if not `remove_volumes`: its containers and directory.
+    /// Images are only listed if `remove_images`
+    pub fn deletion_plan(
+        &self,
+        remove_volumes: bool,
+        remove_images: bool,
+    ) -> Result<network::DeletionPlan> {
+        self.check_exists()?;
+        let docker = self.docker();
+        let containers = docker.compose_ps(None)?;
+
+        let volumes = if remove_volumes {
+            docker.project_volumes()?
+        } else {
+            vec![]
+        };
+        let mut images: Vec<String> = if remove_images {
+            containers.iter().map(|c| c.image.clone()).collect()
+        } else {
+            vec![]
+        };
+        images.sort();
+        images.dedup();
+        Ok(network::DeletionPlan {
+            network_id: self.network_id.clone(),
+            directory: self.path().display().to_string(),
+            containers: containers.into_iter().map(|c| c.name).collect(),
+            volumes,
+            images,
+        })
+    }
+
+    /// Removes the containers and volumes of the network and its directory, and
+    /// its images if `remove_images` (other networks may share them)
+    pub fn delete(&self, remove_images: bool) -> Result<network::Delete> {
+        let network_id = self.network_id.clone();
+        self.check_exists()?;
+
+        if let Err(e) = self.docker().compose_down(None, true, remove_images) {
+            return failure(format!("Failed to delete network '{network_id}': {e}"));
+        }
+        chaos::remove_partition_networks(&self.docker(), &self.path());
+        match self.directory_manager.delete_network_directory(&network_id) {
+            Ok(_) => Ok(network::Delete { network_id }),
+            Err(e) => failure(format!(
+                "Failed to delete network directory for '{network_id}': {e}"
+            )),
+        }
+    }
+}
+
+/// A node of a [`Network`]
+#[derive(Clone)]
+pub struct Node {
+    network: Network,
+    node_id: String,
+}
+
+impl Node {
+    pub fn id(&self) -> &str {
+        &self.node_id
+    }
+
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    fn container(&self) -> String {
+        format!("{}-{}", self.node_id, self.network.network_id)
+    }
+
+    fn not_found(&self) -> MiniminaError {
+        MiniminaError::NodeNotFound {
+            node_id: self.node_id.clone(),
+            network_id: self.network.network_id.clone(),
+        }
+    }
+
+    /// Docker info of the node's container
+    pub fn ps(&self) -> Result<ContainerInfo> {
+        match self
+            .network
+            .docker()
+            .compose_ps_service(&self.container())?
+        {
+            Some(container) => Ok(container),
+            None => Err(self.not_found()),
+        }
+    }
+
+    /// Starts the node, optionally with fresh state and imported genesis accounts
+    pub fn start(&self, options: &StartNodeOptions) -> Result<node::Start> {
+        let node_id = self.node_id.clone();
+        let network_id = self.network.network_id.clone();
+        let directory_manager = &self.network.directory_manager;
+        let container = self.container();
+        let docker = self.network.docker();
+        let start_error =
+            |error: String| failure(format!("Failed to start node '{node_id}': {error}"));
+        self.network.check_exists()?;
+
+        match docker.compose_ps_service(&container)? {
+            Some(node) => match node.state {
+                ContainerState::Running => {
+                    warn!("Node '{node_id}' is already running in network '{network_id}'.")
+                }
+                ContainerState::Created => {
+                    info!("Starting node '{node_id}' in network '{network_id}' for the first time.")
+                }
+                container_state => {
+                    info!("Node '{node_id}' is {container_state} in network '{network_id}'.")
+                }
+            },
+            None => return Err(self.not_found()),
+        };
+
+        if options.fresh_state {
+            info!("Starting node '{node_id}' in network '{network_id}' with fresh state.");
+            docker.compose_down(Some(container.clone()), true, false)?;
+            docker.compose_create(Some(container.clone()))?;
+        }
+
+        if options.import_accounts {
+            warn!("Importing accounts for node '{node_id}' in network '{network_id}'. This can take a moment...");
+            let accounts = import_all_accounts(&docker, directory_manager, &node_id, &network_id)?;
+            if let Some(failed) = accounts.failed.first() {
+                return failure(format!(
+                    "Failed to import account from file '{}' \
+                    for node '{node_id}' on network '{network_id}': {}",
+                    failed.file, failed.error
+                ));
+            }
+        }
+
+        match docker.compose_start(vec![&container]) {
+            Ok(out) if out.status.success() => {
+                if options.graphql_filtered_logs {
+                    warn!("Waiting for graphql server to be operational so I can request filtered logs. This can take a moment...");
+                    let gql = GraphQl::new(directory_manager.clone());
+                    if let Some(gql_ep) = gql.get_endpoint(&node_id, &network_id) {
+                        gql.wait_for_server(&gql_ep)?;
+                        gql.request_filtered_logs(&gql_ep)?;
+                    }
+                }
+                Ok(node::Start {
+                    node_id,
+                    network_id,
+                })
+            }
+            Ok(out) => start_error(String::from_utf8_lossy(&out.stderr).to_string()),
+            Err(e) => start_error(e.to_string()),
+        }
+    }
+
+    /// Stops the node, shutting its daemon down with `mina client stop-daemon`
+    /// first if `flush`
+    pub fn stop(&self, flush: bool) -> Result<node::Stop> {
+        let node_id = self.node_id.clone();
+        let network_id = self.network.network_id.clone();
+        let docker = self.network.docker();
+        let stop_error =
+            |error: String| failure(format!("Failed to stop node '{node_id}': {error}"));
+        self.network.check_exists()?;
+        self.ps()?;
+
+        if flush {
+            let services = match self
+                .network
+                .directory_manager
+                .get_services_info(&network_id)
+            {
+                Ok(services) => services,
+                Err(e) => return stop_error(format!("Failed to get services info: {e}")),
+            };
+            let flushed = services
+                .iter()
+                .find(|s| s.service_name == node_id)
+                .is_some_and(|service| stop_daemon(&docker, service, &network_id));
+            if flushed {
+                wait_for_daemon_exit(&docker, &self.container(), None);
+            }
+        }
+
+        match docker.compose_stop(vec![&self.container()]) {
+            Ok(out) if out.status.success() => Ok(node::Stop {
+                node_id,
+                network_id,
+            }),
+            Ok(out) => stop_error(String::from_utf8_lossy(&out.stderr).to_string()),
+            Err(e) => stop_error(e.to_string()),
+        }
+    }
+
+    /// Stops the node, recreates its container, dropping its volumes if
+    /// `options.fresh_state`, and starts it again, optionally with imported genesis
+    /// accounts, then waits for its GraphQL server
+    pub fn restart(&self, options: &RestartNodeOptions) -> Result<node::Restart> {
+        let node_id = self.node_id.clone();
+        let network_id = self.network.network_id.clone();
+        let directory_manager = &self.network.directory_manager;
+        let container = self.container();
+        let docker = self.network.docker();
+        let started = Instant::now();
+        let restart_error =
+            |error: String| failure(format!("Failed to restart node '{node_id}': {error}"));
+        self.network.check_exists()?;
+        self.ps()?;
+        self.stop(options.flush)?;
+
+        info!("Recreating node '{node_id}' in network '{network_id}'.");
+        for out in [
+            docker.compose_down(Some(container.clone()), options.fresh_state, false)?,
+            docker.compose_create(Some(container.clone()))?,
+        ] {
+            if !out.status.success() {
+                return restart_error(String::from_utf8_lossy(&out.stderr).to_string());
+            }
+        }
+
+        let mut imported_accounts = vec![];
+        if options.import_accounts {
+            warn!("Importing accounts for node '{node_id}' in network '{network_id}'. This can take a moment...");
+            let accounts = import_all_accounts(&docker, directory_manager, &node_id, &network_id)?;
+            if let Some(failed) = accounts.failed.first() {
+                return failure(format!(
+                    "Failed to import account from file '{}' \
+                    for node '{node_id}' on network '{network_id}': {}",
+                    failed.file, failed.error
+                ));
+            }
+            imported_accounts = [accounts.imported, accounts.skipped].concat();
+        }
+
+        match docker.compose_start(vec![&container]) {
+            Ok(out) if out.status.success() => (),
+            Ok(out) => return restart_error(String::from_utf8_lossy(&out.stderr).to_string()),
+            Err(e) => return restart_error(e.to_string()),
+        }
+
+        let gql = GraphQl::new(directory_manager.clone());
+        if let Some(gql_ep) = gql.get_endpoint(&node_id, &network_id) {
+            gql.wait_for_server(&gql_ep)?;
+        }
+
+        Ok(node::Restart {
+            network_id,
+            node_id,
+            fresh_state: options.fresh_state,
+            imported_accounts,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Imports the genesis accounts of the network into the node's wallet
+    pub fn import_accounts(&self) -> Result<node::ImportAccounts> {
+        let network = &self.network;
+        network.check_exists()?;
+        import_all_accounts(
+            &network.docker(),
+            &network.directory_manager,
+            &self.node_id,
+            &network.network_id,
+        )
+    }
+
+    /// Runs `command` in the node's container, its failure is reported in the result
+    pub fn exec(&self, command: &[String]) -> Result<node::Exec> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        self.network.check_exists()?;
+
+        let args: Vec<&str> = command.iter().map(String::as_str).collect();
+        match self.network.docker().exec(&self.container(), &args) {
+            Ok(output) => Ok(node::Exec {
+                command: command.to_vec(),
+                stdout: String::from_utf8_lossy(&output.stdout).into(),
+                stderr: String::from_utf8_lossy(&output.stderr).into(),
+                exit_code: output.status.code(),
+                network_id: network_id.into(),
+                node_id: node_id.into(),
+            }),
+            Err(e) => failure(format!(
+                "Failed to run {command:?} on '{node_id}' in '{network_id}': {e}"
+            )),
+        }
+    }
+
+    /// Runs `mina client` with `args` against the node's daemon, on its client port
+    pub fn client(&self, args: &[String]) -> Result<node::Exec> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        self.network.check_exists()?;
+
+        let services = self
+            .network
+            .directory_manager
+            .get_services_info(network_id)?;
+        let Some(service) = services.iter().find(|s| s.service_name == node_id) else {
+            return Err(self.not_found());
+        };
+        match service.client_command(args) {
+            Some(command) => self.exec(&command),
+            None => failure(format!("Node '{node_id}' does not run a mina daemon.")),
+        }
+    }
+
+    /// GraphQL client of the network and the endpoint of the node
+    fn graphql(&self) -> Result<(GraphQl, String)> {
+        let gql = GraphQl::new(self.network.directory_manager.clone());
+        match gql.get_endpoint(&self.node_id, &self.network.network_id) {
+            Some(gql_ep) => Ok((gql, gql_ep)),
+            None => failure(format!(
+                "Node '{}' has no graphql endpoint in network '{}'.",
+                self.node_id, self.network.network_id
+            )),
+        }
+    }
+
+    /// Sends the GraphQL `query` to the node
+    pub fn query(&self, query: &str) -> Result<node::Query> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        self.network.check_exists()?;
+        let (gql, gql_ep) = self.graphql()?;
+
+        match gql.query(&gql_ep, query) {
+            Ok(response) => Ok(node::Query {
+                response,
+                network_id: network_id.into(),
+                node_id: node_id.into(),
+            }),
+            Err(e) => failure(format!(
+                "Failed to query node '{node_id}' in network '{network_id}': {e}"
+            )),
+        }
+    }
+
+    /// Generates a key pair in the network's accounts directory, imports it into the
+    /// node's wallet and funds it from a genesis account. The key files of an account
+    /// which couldn't be created are removed, so it can be retried under the same name
+    pub fn create_account(&self, options: &CreateAccountOptions) -> Result<node::CreateAccount> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        let directory_manager = &self.network.directory_manager;
+        let account_name = options.account_name.as_str();
+        self.network.check_exists()?;
+
+        let services = directory_manager.get_services_info(network_id)?;
+        let Some(docker_image) = services
+            .iter()
+            .find(|service| service.service_name == node_id)
+            .and_then(|service| service.docker_image.as_deref())
+        else {
+            return failure(format!(
+                "Node '{node_id}' with a docker image does not exist in network '{network_id}'."
+            ));
+        };
+        let (gql, gql_ep) = self.graphql()?;
+
+        let mut keypair_files = directory_manager.get_network_keypair_files(network_id)?;
+        keypair_files.sort();
+        let funder = match &options.funder {
+            Some(funder) if keypair_files.contains(funder) => funder.clone(),
+            Some(funder) => {
+                return failure(format!(
+                    "Genesis key pair '{funder}' does not exist in network '{network_id}'."
+                ))
+            }
+            None => match keypair_files
+                .iter()
+                .find(|file| *file == node_id)
+                .or(keypair_files.first())
+            {
+                Some(funder) => funder.clone(),
+                None => {
+                    return failure(format!(
+                        "Network '{network_id}' has no genesis key pairs to fund the account."
+                    ))
+                }
+            },
+        };
+
+        let network_path = self.network.path();
+        let account_key_path = network_path
+            .join(directory_manager::ACCOUNTS)
+            .join(account_name);
+        if account_key_path.exists() {
+            return failure(format!(
+                "Account '{account_name}' already exists in network '{network_id}'."
+            ));
+        }
+
+        let keys_manager = KeysManager::new(
+            &network_path,
+            docker_image,
+            Runtime::of_network(&network_path),
+        );
+        let result = keys_manager
+            .generate_account_key_pair(account_name)
+            .and_then(|key| {
+                let private_key = keys_manager.dump_private_key(&key.key_path_docker)?;
+                info!("Importing account '{account_name}' into node '{node_id}'");
+                gql.import_account(&gql_ep, &key.key_path_docker, keys::MINA_PRIVKEY_PASS)?;
+
+                let funder_key_path = format!(
+                    "/local-network/{}/{funder}",
+                    directory_manager::NETWORK_KEYPAIRS
+                );
+                let funder_public_key = gql.import_and_unlock_account(
+                    &gql_ep,
+                    &funder_key_path,
+                    keys::MINA_PRIVKEY_PASS,
+                )?;
+
+                info!("Funding account '{account_name}' from genesis account '{funder}'");
+                let payment_hash = gql.send_payment(
+                    &gql_ep,
+                    &funder_public_key,
+                    &key.key_string,
+                    options.amount,
+                    options.fee,
+                )?;
+                Ok((key, private_key, payment_hash))
+            });
+
+        match result {
+            Ok((key, private_key, payment_hash)) => Ok(node::CreateAccount {
+                public_key: key.key_string,
+                private_key,
+                key_file: account_key_path.display().to_string(),
+                funder,
+                amount: options.amount,
+                payment_hash,
+                network_id: network_id.into(),
+                node_id: node_id.into(),
+            }),
+            Err(e) => {
+                for path in [
+                    account_key_path.clone(),
+                    account_key_path.with_extension("pub"),
+                ] {
+                    if let Err(e) = fs::remove_file(&path) {
+                        if e.kind() != ErrorKind::NotFound {
+                            warn!("Failed to remove '{}': {e}", path.display());
+                        }
+                    }
+                }
+                failure(format!(
+                    "Failed to create account '{account_name}' on node '{node_id}' in network '{network_id}': {e}"
+                ))
+            }
+        }
+    }
+
+    /// Sends a stake delegation through the node from an account whose key file is
+    /// stored in the network directory
+    pub fn delegate(&self, options: &DelegateOptions) -> Result<node::Delegate> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        let (from, to) = (options.from.as_str(), options.to.as_str());
+        self.network.check_exists()?;
+        let (gql, gql_ep) = self.graphql()?;
+
+        let Some(key_file) = self
+            .network
+            .directory_manager
+            .find_key_file(network_id, from)?
+        else {
+            return failure(format!(
+                "No key file for '{from}' is stored in network '{network_id}'."
+            ));
+        };
+
+        let result = gql
+            .import_and_unlock_account(
+                &gql_ep,
+                &format!("/local-network/{key_file}"),
+                keys::MINA_PRIVKEY_PASS,
+            )
+            .and_then(|_| gql.send_delegation(&gql_ep, from, to, options.fee))
+            .and_then(|(id, hash)| {
+                info!("Sent delegation '{hash}' from '{from}' to '{to}'");
+                if !options.wait {
+                    return Ok((hash, None));
+                }
+                gql.wait_for_inclusion(&gql_ep, &id, options.wait_timeout)?;
+                Ok((hash, Some(graphql::INCLUDED_STATUS.to_string())))
+            });
+
+        match result {
+            Ok((delegation_hash, status)) => Ok(node::Delegate {
+                from: from.into(),
+                to: to.into(),
+                delegation_hash,
+                status,
+                network_id: network_id.into(),
+                node_id: node_id.into(),
+            }),
+            Err(e) => failure(format!(
+                "Failed to delegate from '{from}' to '{to}' on node '{node_id}' in network '{network_id}': {e}"
+            )),
+        }
+    }
+
+    /// Runs the replayer on the archive database of the archive node from slot
+    /// `start_slot_since_genesis`, on a clone of the database if `options.snapshot`
+    pub fn run_replayer(&self, options: &ReplayerOptions) -> Result<node::ReplayerLogs> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        let start_slot = options.start_slot_since_genesis;
+        self.network.check_exists()?;
+        let network_path = self.network.path();
+        let docker = self.network.docker();
+        let services = self
+            .network
+            .directory_manager
+            .get_services_info(network_id)?;
+        // the ledger is written by the archive service, which mounts the
+        // network directory but nothing beside it
+        if let Some(output_file) = &options.output_file {
+            if Path::new(output_file).file_name() != Some(output_file.as_ref()) {
+                return failure(format!(
+                    "Replayer output file '{output_file}' should be a file name in the network directory."
+                ));
+            }
+        }
+
+        let Some(archive_db) = ServiceConfig::get_archive(&services, node_id)
+            .map(|archive| archive.archive_db(&services))
+        else {
+            return failure(format!(
+                "Node '{node_id}' is not an archive node in '{network_id}' network."
+            ));
+        };
+
+        if let Err(e) = set_slot_since_genesis(&network_path, start_slot) {
+            return failure(format!(
+                "Failed to set slot since genesis to '{start_slot}' for node '{node_id}' on network '{network_id}': {e}"
+            ));
+        }
+        if let Err(e) =
+            set_replayer_target_state_hash(&network_path, options.target_state_hash.as_deref())
+        {
+            return failure(format!(
+                "Failed to set target state hash of the replayer for node '{node_id}' on network '{network_id}': {e}"
+            ));
+        }
+
+        let archive_service_id = format!("{node_id}-service");
+        let postgres = if options.snapshot {
+            match snapshot_archive_database(&docker, &archive_service_id, &archive_db, network_id)
+            {
+                Ok(postgres) => postgres,
+                Err(e) => {
+                    return failure(format!(
+                        "Failed to clone the archive database of node '{node_id}' on network '{network_id}': {e}"
+                    ))
+                }
+            }
+        } else {
+            docker.postgres_config().for_archive(&archive_db)
+        };
+
+        let result = docker.compose_run_replayer(
+            &archive_service_id,
+            &archive_db,
+            &postgres,
+            network_id,
+            options.output_file.as_deref(),
+        );
+        if options.snapshot {
+            if let Err(e) =
+                docker.drop_archive_database(&archive_db, network_id, &postgres.database)
+            {
+                warn!(
+                    "Failed to drop archive database clone '{}': {e}",
+                    postgres.database
+                );
+            }
+        }
+
+        match result {
+            Ok(output) if output.status.success() => {
+                info!(
+                    "Successfully ran replayer for node '{node_id}' on network '{network_id}' \
+                     and start_slot_since_genesis '{start_slot}'"
+                );
+                Ok(node::ReplayerLogs {
+                    logs: String::from_utf8_lossy(&output.stdout).into(),
+                    network_id: network_id.into(),
+                    node_id: node_id.into(),
+                    output_file: options
+                        .output_file
+                        .as_ref()
+                        .map(|output_file| network_path.join(output_file)),
+                })
+            }
+            Ok(output) => failure(format!(
+                "Failed to run replayer for node '{node_id}' on network '{network_id}' \
+                 and start_slot_since_genesis '{start_slot}': {}",
+                String::from_utf8_lossy(&output.stderr)
+            )),
+            Err(e) => failure(format!(
+                "Error while running replayer for node '{node_id}' on network '{network_id}' \
+                 and start_slot_since_genesis '{start_slot}': {e}"
+            )),
+        }
+    }
+
+    /// Streams the records of the node's logs matching `filters` to `on_record` as
+    /// they are read, following new ones if `follow`
+    pub fn log_records(
+        &self,
+        filters: &[LogFilter],
+        follow: bool,
+        mut on_record: impl FnMut(&LogRecord) -> Result<()>,
+    ) -> Result<()> {
+        self.network.check_exists()?;
+        let mut child = self
+            .network
+            .docker()
+            .compose_service_logs(&self.container(), follow)?;
+        let stdout = child
+            .stdout
+            .take()
+            .expect("stdout of compose logs is piped");
+        for line in BufReader::new(stdout).lines() {
+            let result = line.map_err(MiniminaError::from).and_then(|line| {
+                let record = LogRecord::parse(&network::LogLine::parse(&line).message);
+                if log_filter::matches_all(filters, &record) {
+                    on_record(&record)?;
+                }
+                Ok(())
+            });
+            if let Err(e) = result {
+                let _ = child.kill();
+                return Err(e);
+            }
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            return failure(format!(
+                "Reading the logs of '{}' on '{}' failed with {status}",
+                self.node_id, self.network.network_id
+            ));
+        }
+        Ok(())
+    }
+
+    /// Dumps the node's archive database to `output_dir` every `interval`, as
+    /// `<node id>-<UTC timestamp>.sql` files of which only the latest `retain` are kept,
+    /// reporting each dump to `on_dump`. Runs until writing a dump or `on_dump` fails, a
+    /// failed `pg_dump`, e.g. while postgres restarts, is retried at the next interval.
+    pub fn dump_archive_data_every(
+        &self,
+        output_dir: &Path,
+        interval: Duration,
+        retain: Option<usize>,
+        mut on_dump: impl FnMut(&node::ArchiveDataFile) -> Result<()>,
+    ) -> Result<()> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        let archive_db = self.archive_db()?;
+        fs::create_dir_all(output_dir)?;
+
+        let docker = self.network.docker();
+        loop {
+            let started = Instant::now();
+            let timestamp = chrono::Utc::now().format(ARCHIVE_DUMP_TIMESTAMP);
+            let path = output_dir.join(format!("{node_id}-{timestamp}.sql"));
+            match docker.compose_dump_archive_data(&archive_db, network_id) {
+                Ok(output) if output.status.success() => {
+                    // only complete dumps get the name rotation and restores look for
+                    let partial = path.with_extension("sql.partial");
+                    fs::write(&partial, &output.stdout)?;
+                    fs::rename(&partial, &path)?;
+                    for removed in rotate_archive_dumps(output_dir, node_id, retain)? {
+                        info!("Removed archive dump '{}'", removed.display());
+                    }
+                    on_dump(&node::ArchiveDataFile {
+                        dump_file: path.display().to_string(),
+                        network_id: network_id.into(),
+                        node_id: node_id.into(),
+                    })?;
+                }
+                Ok(output) => error!(
+                    "Failed to dump archive data for node '{node_id}', network '{network_id}': {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                Err(e) => error!(
+                    "Error while dumping archive data for node '{node_id}', network '{network_id}': {e}"
+                ),
+            }
+            std::thread::sleep(interval.saturating_sub(started.elapsed()));
+        }
+    }
+
+    /// Archive database of the node, which must be an archive node
+    fn archive_db(&self) -> Result<ArchiveDb> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        self.network.check_exists()?;
+        let services = self
+            .network
+            .directory_manager
+            .get_services_info(network_id)?;
+        match ServiceConfig::get_archive(&services, node_id) {
+            Some(archive) => Ok(archive.archive_db(&services)),
+            None => failure(format!(
+                "Node '{node_id}' is not an archive node in '{network_id}' network."
+            )),
+        }
+    }
+
+    /// `pg_dump` of the node's archive database
+    pub fn dump_archive_data(&self) -> Result<node::ArchiveData> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        let archive_db = self.archive_db()?;
+        match self
+            .network
+            .docker()
+            .compose_dump_archive_data(&archive_db, network_id)
+        {
+            Ok(output) if output.status.success() => {
+                info!("Successfully dumped archive data for node '{node_id}', network '{network_id}'");
+                Ok(node::ArchiveData {
+                    data: String::from_utf8_lossy(&output.stdout).into(),
+                    network_id: network_id.into(),
+                    node_id: node_id.into(),
+                })
+            }
+            Ok(output) => failure(format!(
+                "Failed to dump archive data for node '{node_id}', network '{network_id}': {}",
+                String::from_utf8_lossy(&output.stderr)
+            )),
+            Err(e) => failure(format!(
+                "Error while dumping archive data for node '{node_id}', network_id '{network_id}': {e}"
+            )),
+        }
+    }
+
+    /// Writes a `pg_dump` of the node's archive database to `path`
+    pub fn dump_archive_data_to(&self, path: &Path) -> Result<node::ArchiveDataFile> {
+        let dump = self.dump_archive_data()?;
+        if let Err(e) = fs::write(path, &dump.data) {
+            return failure(format!(
+                "Failed to write archive data of node '{}' to '{}': {e}",
+                dump.node_id,
+                path.display()
+            ));
+        }
+        Ok(node::ArchiveDataFile {
+            dump_file: path.display().to_string(),
+            network_id: dump.network_id,
+            node_id: dump.node_id,
+        })
+    }
+
+    /// Replaces the node's archive database by the `pg_dump` file `input`, stopping
+    /// the archive service meanwhile so it reconnects to the new data
+    pub fn restore_archive_data(&self, input: &Path) -> Result<node::ArchiveDataFile> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        self.network.check_exists()?;
+        if !input.is_file() {
+            return failure(format!(
+                "Archive dump '{}' does not exist.",
+                input.display()
+            ));
+        }
+        let archive_db = self.archive_db()?;
+
+        let docker = self.network.docker();
+        let archive_service = format!("{node_id}-service-{network_id}");
+        let restore = || -> Result<()> {
+            check_output(docker.compose_stop(vec![&archive_service]))?;
+            let result = docker
+                .recreate_archive_database(&archive_db, network_id)
+                .and_then(|()| docker.restore_archive_data(&archive_db, network_id, input));
+            check_output(docker.compose_start(vec![&archive_service]))?;
+            Ok(result?)
+        };
+        if let Err(e) = restore() {
+            return failure(format!(
+                "Failed to restore archive data of node '{node_id}' on network '{network_id}': {e}"
+            ));
+        }
+
+        info!("Successfully restored archive data for node '{node_id}', network '{network_id}'");
+        Ok(node::ArchiveDataFile {
+            dump_file: input.display().to_string(),
+            network_id: network_id.into(),
+            node_id: node_id.into(),
+        })
+    }
+
+    /// Precomputed blocks the node has written to its config directory
+    pub fn dump_precomputed_blocks(&self) -> Result<node::PrecomputedBlocks> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        self.network.check_exists()?;
+        match self
+            .network
+            .docker()
+            .compose_dump_precomputed_blocks(node_id, network_id)
+        {
+            Ok(output) if output.status.success() => {
+                info!("Successfully dumped precomputed blocks for '{node_id}' on '{network_id}'");
+                Ok(node::PrecomputedBlocks {
+                    blocks: String::from_utf8_lossy(&output.stdout).into(),
+                    network_id: network_id.into(),
+                    node_id: node_id.into(),
+                })
+            }
+            Ok(output) => failure(format!(
+                "Failed to dump precomputed blocks for '{node_id}' on '{network_id}': {}",
+                String::from_utf8_lossy(&output.stderr)
+            )),
+            Err(e) => failure(format!(
+                "Failed to dump precomputed blocks for '{node_id}' on '{network_id}': {e}"
+            )),
+        }
+    }
+
+    /// Collects the exit state, logs and crash files of the node's container into a
+    /// bundle in the network directory
+    pub fn collect_crash(&self) -> Result<node::CrashReport> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        self.network.check_exists()?;
+        let bundle = match crash::collect(
+            &self.network.docker(),
+            &self.network.directory_manager,
+            network_id,
+            node_id,
+        ) {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                return failure(format!(
+                    "Failed to collect crash bundle for '{node_id}' on '{network_id}': {e}"
+                ))
+            }
+        };
+        if bundle.state.status == "running" {
+            warn!("Node '{node_id}' is still running in network '{network_id}'.");
+        }
+        Ok(node::CrashReport {
+            bundle: bundle.path.display().to_string(),
+            status: bundle.state.status,
+            exit_code: bundle.state.exit_code,
+            oom_killed: bundle.state.oom_killed,
+            network_id: network_id.into(),
+            node_id: node_id.into(),
+        })
+    }
+
+    /// Saves a tarball of the log files in the node's config directory to the
+    /// network directory
+    pub fn internal_logs(&self) -> Result<node::InternalLogs> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        self.network.check_exists()?;
+        let output = match self
+            .network
+            .docker()
+            .compose_tar_internal_logs(node_id, network_id)
+        {
+            Ok(output) => output,
+            Err(e) => {
+                return failure(format!(
+                    "Error while getting internal logs for '{node_id}' on '{network_id}': {e}"
+                ))
+            }
+        };
+        if !output.status.success() {
+            return failure(format!(
+                "Failed to get internal logs for '{node_id}' on '{network_id}': {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        let logs_file = self
+            .network
+            .directory_manager
+            .internal_logs_path(network_id, node_id);
+        if let Some(parent) = logs_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&logs_file, output.stdout)?;
+        info!("Saved internal logs for '{node_id}' on '{network_id}' to {logs_file:?}");
+        Ok(node::InternalLogs {
+            logs_file: logs_file.display().to_string(),
+            network_id: network_id.into(),
+            node_id: node_id.into(),
+        })
+    }
+
+    /// Container logs of the node
+    pub fn logs(&self) -> Result<node::Logs> {
+        let node_id = self.node_id.as_str();
+        let network_id = self.network.network_id.as_str();
+        self.network.check_exists()?;
+        let services = match self.network.directory_manager.get_services_info(network_id) {
+            Ok(services) => services,
+            Err(e) => return failure(format!("Failed to get services info: {e}")),
+        };
+
+        let output = match self.network.docker().run_docker_logs(node_id, network_id) {
+            Ok(output) => output,
+            Err(e) => return failure(format!("Error while running 'docker logs {node_id}': {e}")),
+        };
+        if !output.status.success() {
+            return failure(format!(
+                "Failed to get logs for '{node_id}' on '{network_id}': {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        info!("Successfully got logs for '{node_id}' on '{network_id}'");
+        // uptime service logs to stderr
+        let out = if is_node_uptime_service(services, node_id) {
+            &output.stderr
+        } else {
+            &output.stdout
+        };
+        Ok(node::Logs {
+            logs: String::from_utf8_lossy(out).into(),
+            network_id: network_id.into(),
+            node_id: node_id.into(),
+        })
+    }
+}
+
+/// Splits `containers` into the batches stopped together
+fn stop_batches(
+    containers: &[String],
+    concurrency: Option<usize>,
+) -> std::slice::Chunks<'_, String> {
+    containers.chunks(concurrency.unwrap_or(containers.len()).max(1))
+}
+
+/// Asks the daemon of `service` to flush its state and shut down, so stopping its
+/// container does not kill it midway, returns whether it accepted; services without
+/// a daemon are skipped
+fn stop_daemon(docker: &DockerManager, service: &ServiceConfig, network_id: &str) -> bool {
+    let client_port = match (&service.service_type, service.client_port) {
+        (ServiceType::ArchiveService | ServiceType::UptimeServiceBackend, _) | (_, None) => {
+            return false
+        }
+        (_, Some(client_port)) => client_port,
+    };
+    let node_id = &service.service_name;
+    info!("Stopping the daemon of '{node_id}' in network '{network_id}'");
+    match docker.compose_stop_daemon(node_id, network_id, client_port) {
+        Ok(out) if out.status.success() => true,
+        Ok(out) => {
+            warn!(
+                "Failed to stop the daemon of '{node_id}' in network '{network_id}': {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            );
+            false
+        }
+        Err(e) => {
+            warn!("Failed to stop the daemon of '{node_id}' in network '{network_id}': {e}");
+            false
+        }
+    }
+}
+
+/// Waits up to `timeout` seconds for the container of a daemon asked to shut down to
+/// exit, as stopping it while the daemon still flushes would kill it midway
+fn wait_for_daemon_exit(docker: &DockerManager, container: &str, timeout: Option<u64>) {
+    let timeout = Duration::from_secs(timeout.unwrap_or(DAEMON_EXIT_TIMEOUT_SECS));
+    if let Err(e) = docker.wait_for_state(container, ContainerState::Exited, timeout) {
+        warn!("The daemon of '{container}' did not shut down on its own: {e}");
+    }
+}
+
+/// Whether the stopped `container` exited because it was killed
+fn was_killed(docker: &DockerManager, container: &str) -> bool {
+    docker
+        .inspect_state(container)
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| serde_json::from_slice::<ExitState>(&out.stdout).ok())
+        .is_some_and(|state| state.exit_code == KILLED_EXIT_CODE)
+}
+
+/// UTC timestamp in the file names of `node dump-archive-data --interval`, sorting
+/// chronologically
+const ARCHIVE_DUMP_TIMESTAMP: &str = "%Y%m%dT%H%M%SZ";
+
+/// Removes all but the latest `retain` timestamped archive dumps of `node_id` in `dir`,
+/// returning the removed files. Other files in `dir` are left alone.
+fn rotate_archive_dumps(dir: &Path, node_id: &str, retain: Option<usize>) -> Result<Vec<PathBuf>> {
+    let Some(retain) = retain else {
+        return Ok(vec![]);
+    };
+    let prefix = format!("{node_id}-");
+    let mut dumps: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&prefix)?.strip_suffix(".sql"))
+                .is_some_and(|timestamp| {
+                    chrono::NaiveDateTime::parse_from_str(timestamp, ARCHIVE_DUMP_TIMESTAMP).is_ok()
+                })
+        })
+        .collect();
+    dumps.sort();
+    let removed = dumps.len().saturating_sub(retain);
+    dumps.truncate(removed);
+    for dump in &dumps {
+        fs::remove_file(dump)?;
+    }
+    Ok(dumps)
+}
+
+/// Clones the archive database of `db` for the replayer, stopping the archive
+/// service `archive_service_id` meanwhile, and returns the credentials of the clone
+fn snapshot_archive_database(
+    docker: &DockerManager,
+    archive_service_id: &str,
+    db: &ArchiveDb,
+    network_id: &str,
+) -> Result<PostgresConfig> {
+    let postgres = docker.postgres_config().for_archive(db);
+    let clone = PostgresConfig {
+        database: format!("{}{REPLAYER_CLONE_SUFFIX}", postgres.database),
+        ..postgres
+    };
+
+    let archive_service = format!("{archive_service_id}-{network_id}");
+    check_output(docker.compose_stop(vec![&archive_service]))?;
+    let result = docker.clone_archive_database(db, network_id, &clone.database);
+    check_output(docker.compose_start(vec![&archive_service]))?;
+    result?;
+    Ok(clone)
+}
+
+fn failure<T>(error_message: String) -> Result<T> {
+    Err(MiniminaError::Command(error_message))
+}
+
+fn create_network(
+    docker: &DockerManager,
+    directory_manager: &DirectoryManager,
+    network_id: &str,
+    services: &[ServiceConfig],
+    options: &CreateOptions,
+    started: Instant,
+    mut timings: network::Timings,
+) -> Result<network::Create> {
+    // pull separately from create to time it, create pulls whatever is still missing
+    match network::Timings::measure(&mut timings.image_pull_ms, || docker.compose_pull()) {
+        Ok(output) if !output.status.success() => warn!(
+            "Failed to pull images for network '{network_id}': {}",
+            String::from_utf8_lossy(&output.stderr)
+        ),
+        Err(e) => warn!("Failed to pull images for network '{network_id}': {e}"),
+        Ok(_) => (),
+    }
+
+    // record what the images resolve to now, their tags may move later on
+    let mut services = services.to_vec();
+    resolve_image_digests(docker, &mut services, options.pin_digests);
+    if options.pin_digests {
+        if let Err(e) = docker.compose_generate_file(&services) {
+            return failure(format!(
+                "Failed to pin the image digests of network '{network_id}': {e}"
+            ));
+        }
+    }
+    let services = services.as_slice();
+
+    match network::Timings::measure(&mut timings.container_create_ms, || {
+        docker.compose_create(None)
+    }) {
+        Ok(output) => {
+            if !output.status.success() {
+                let error_message = format!(
+                    "Failed to create network '{network_id}' with 'docker compose create': {}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                return failure(error_message);
+            }
+            info!("Successfully created docker-compose for network '{network_id}'!");
+
+            // if we have archive node we need to:
+            //  - create input file for replayer (for run-replayer command)
+            //  - create database and apply schema scripts
+            let archives = ServiceConfig::get_archives(services);
+            if !archives.is_empty() && options.no_archive_bootstrap {
+                info!("Skipping the archive database bootstrap of network '{network_id}'.");
+                default::LedgerGenerator::generate_replayer_input(
+                    &directory_manager.network_path(network_id),
+                )?;
+            } else if !archives.is_empty() {
+                network::Timings::measure(&mut timings.schema_apply_ms, || {
+                    archives.iter().try_for_each(|archive| {
+                        let db = archive.archive_db(services);
+                        setup_archive_database(
+                            docker,
+                            directory_manager,
+                            network_id,
+                            archive,
+                            &db,
+                            None,
+                            options.verify_archive,
+                        )
+                    })
+                })?;
+            }
+
+            // generate network.json and services.json
+            if let Err(e) =
+                directory_manager.save_network_info(network_id, services, docker.runtime)
+            {
+                error!("Error generating network.json: {e}")
+            }
+
+            if let Err(e) = directory_manager.save_services_info(network_id, services) {
+                error!("Error generating services.json: {e}")
+            }
+
+            timings.total_ms = started.elapsed().as_millis() as u64;
+            let monitoring = Monitoring::load(&directory_manager.network_path(network_id))
+                .ok()
+                .flatten()
+                .map(|monitoring| network::Monitoring {
+                    grafana_url: monitoring.grafana_url(),
+                    prometheus_url: monitoring.prometheus_url(),
+                });
+            Ok(network::Create {
+                timings: Some(timings),
+                monitoring,
+                ..output::generate_network_info(services, network_id, docker.runtime)
+            })
+        }
+        Err(e) => {
+            let error_message = format!(
+                "Failed to register network '{network_id}' with 'docker compose create': {e}"
+            );
+            failure(error_message)
+        }
+    }
+}
+
+/// Records the digests the images of `services` resolve to, replacing the images
+/// with their digests if `pin`. Images without one, e.g. built from source, are kept
+fn resolve_image_digests(docker: &DockerManager, services: &mut [ServiceConfig], pin: bool) {
+    let mut digests: HashMap<String, Option<String>> = HashMap::new();
+    let mut resolve = |image: &mut Option<String>, digest: &mut Option<String>| {
+        let Some(tag) = image.clone() else {
+            return;
+        };
+        *digest = digests
+            .entry(tag.clone())
+            .or_insert_with(|| match docker.image_digest(&tag) {
+                Ok(digest) => digest,
+                Err(e) => {
+                    warn!("Failed to resolve the digest of image '{tag}': {e}");
+                    None
+                }
+            })
+            .clone();
+        if pin && digest.is_some() {
+            *image = digest.clone();
+        }
+    };
+
+    for service in services.iter_mut() {
+        resolve(&mut service.docker_image, &mut service.docker_image_digest);
+        resolve(
+            &mut service.archive_docker_image,
+            &mut service.archive_docker_image_digest,
+        );
+    }
+}
+
+/// Renders the kubernetes manifests of a freshly set up network instead of creating
+/// it on the runtime, their archive databases initialized from the schema scripts
+fn render_k8s_network(
+    directory_manager: &DirectoryManager,
+    network_id: &str,
+    services: &[ServiceConfig],
+    options: &CreateOptions,
+    started: Instant,
+    mut timings: network::Timings,
+) -> Result<network::Create> {
+    let network_path = directory_manager.network_path(network_id);
+    let mut schemas = HashMap::new();
+    if !options.no_archive_bootstrap {
+        for archive in ServiceConfig::get_archives(services) {
+            let scripts = archive
+                .archive_schema_files
+                .iter()
+                .flatten()
+                .map(|script| {
+                    fetch_verified_schema(
+                        script,
+                        archive.archive_schema_sha256.as_ref(),
+                        &network_path,
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?;
+            schemas.insert(archive.service_name.clone(), scripts);
+        }
+    }
+
+    match network::Timings::measure(&mut timings.compose_render_ms, || {
+        k8s::write_manifests(
+            services,
+            &network_path,
+            &PostgresConfig::from_env(),
+            &schemas,
+        )
+    }) {
+        Ok(path) => info!(
+            "Rendered kubernetes manifests of network '{network_id}' to {}",
+            path.display()
+        ),
+        Err(e) => {
+            return failure(format!(
+                "Failed to render kubernetes manifests of network '{network_id}': {e}"
+            ))
+        }
+    }
+
+    if let Err(e) = directory_manager.save_network_info(network_id, services, options.runtime) {
+        error!("Error generating network.json: {e}")
+    }
+    if let Err(e) = directory_manager.save_services_info(network_id, services) {
+        error!("Error generating services.json: {e}")
+    }
+
+    timings.total_ms = started.elapsed().as_millis() as u64;
+    Ok(network::Create {
+        timings: Some(timings),
+        ..output::generate_network_info(services, network_id, options.runtime)
+    })
+}
+
+/// Creates the database `db` of an archive of a freshly created network and fills it
+/// from `archive_dump` if given, otherwise from the archive's schema scripts,
+/// optionally verifying the archive works with it afterwards
+/// Estimates the resources of the network to create and refuses to create it if it
+/// doesn't fit on the host, unless `ignore_host_resources`
+fn check_host_resources(options: &CreateOptions, network_path: &Path) -> Result<()> {
+    let service_types = match &options.topology {
+        Some(topology_path) => topology::Topology::new(topology_path)?
+            .services(Path::new(""))
+            .into_iter()
+            .map(|service| service.service_type)
+            .collect(),
+        None => options.default_topology.service_types(),
+    };
+    let required = sizing::Requirements::of(&service_types);
+    let host = sizing::HostResources::query(options.runtime, network_path);
+    let assessment = sizing::assess(&required, &host);
+    for warning in &assessment.warnings {
+        warn!("The network {warning}, it may be slow on this host.");
+    }
+    if assessment.shortfalls.is_empty() {
+        return Ok(());
+    }
+    let shortfalls = assessment.shortfalls.join("; ");
+    if options.ignore_host_resources {
+        warn!("The network {shortfalls}, creating it anyway.");
+        return Ok(());
+    }
+    failure(format!(
+        "The network {shortfalls}. Shrink the topology or pass --ignore-host-resources to create it anyway."
+    ))
+}
+
+/// Nodes of `heights` more than `max_lag` blocks behind the best height, or whose
+/// height could not be queried
+fn nodes_behind(heights: &BTreeMap<String, Option<u64>>, max_lag: u64) -> Vec<String> {
+    let best_height = heights
+        .values()
+        .flatten()
+        .max()
+        .copied()
+        .unwrap_or_default();
+    heights
+        .iter()
+        .filter(|(_, height)| height.is_none_or(|height| best_height - height > max_lag))
+        .map(|(node_id, _)| node_id.clone())
+        .collect()
+}
+
+pub fn setup_archive_database(
+    docker: &DockerManager,
+    directory_manager: &DirectoryManager,
+    network_id: &str,
+    archive: &ServiceConfig,
+    db: &ArchiveDb,
+    archive_dump: Option<&Path>,
+    verify: bool,
+) -> Result<()> {
+    // generate input file for mina-replayer
+    default::LedgerGenerator::generate_replayer_input(&directory_manager.network_path(network_id))?;
+
+    // start postgres container
+    let postgres_name = db.service_name(network_id);
+    let error_message = format!("Failed to start postgres container in network '{network_id}'.");
+
+    match docker.compose_start(vec![&postgres_name]) {
+        Ok(out) => {
+            if out.status.success() {
+                info!("Successfully started postgres container in network '{network_id}'!");
+            } else {
+                return failure(format!(
+                    "{}: {}",
+                    error_message,
+                    String::from_utf8_lossy(&out.stderr)
+                ));
+            }
+        }
+        Err(e) => return failure(format!("{error_message}: {e}")),
+    };
+
+    // make sure postgres is accepting connections
+    if let Err(e) = docker.wait_for_state(
+        &postgres_name,
+        ContainerState::Running,
+        Duration::from_secs(TIMEOUT_IN_SECS.into()),
+    ) {
+        return failure(format!(
+            "Failed to start postgres in network '{network_id}': {e}"
+        ));
+    }
+
+    // create database
+    let postgres = docker.postgres_config().for_archive(db);
+    let cmd = ["createdb", "-U", &postgres.user, &postgres.database];
+    if let Err(e) = check_output(docker.exec(&postgres_name, &cmd)) {
+        docker.compose_stop(vec![&postgres_name])?;
+        return failure(format!(
+            "Failed to create archive database '{}' in network '{network_id}': {e}",
+            postgres.database
+        ));
+    }
+
+    match archive_dump {
+        Some(dump) => {
+            if let Err(e) = docker.restore_archive_data(db, network_id, dump) {
+                return failure(format!("Failed to restore archive database: {e}"));
+            }
+        }
+        None => {
+            // apply schema scripts
+            let scripts = archive.archive_schema_files.as_ref().unwrap();
+            apply_schema_scripts(
+                docker.clone(),
+                &postgres_name,
+                &postgres,
+                scripts,
+                archive.archive_schema_sha256.as_ref(),
+                &directory_manager.network_path(network_id),
+            )?;
+        }
+    }
+
+    if verify {
+        if let Err(e) = verify_archive(docker, network_id, archive, db) {
+            docker.compose_stop(vec![&postgres_name])?;
+            return failure(format!(
+                "Failed to verify archive '{}' in network '{network_id}': {e}",
+                archive.service_name
+            ));
+        }
+        info!("Verified archive '{}'", archive.service_name);
+    }
+
+    // stop postgres
+    docker.compose_stop(vec![&postgres_name])?;
+    Ok(())
+}
+
+/// Smoke tests the archive pipeline on its freshly set up database `db`: a query of
+/// the blocks table and a replayer pass from the archive image over the empty chain
+fn verify_archive(
+    docker: &DockerManager,
+    network_id: &str,
+    archive: &ServiceConfig,
+    db: &ArchiveDb,
+) -> Result<()> {
+    docker
+        .archive_stats(db, network_id)
+        .map_err(|e| MiniminaError::Command(format!("archive query failed: {e}")))?;
+
+    let out = docker.compose_run_replayer_once(&archive.archive_server(), db, network_id)?;
+    if !out.status.success() {
+        return Err(MiniminaError::Command(format!(
+            "replayer failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn wait_for_daemon(
+    docker: &DockerManager,
+    node_id: &str,
+    network_id: &str,
+    client_port: u16,
+) -> Result<()> {
+    let mut retries = 0;
+    let mut daemon_running = false;
+    info!("Waiting for daemon to start for node '{node_id}' on network '{network_id}'...");
+    while !daemon_running && retries < TIMEOUT_IN_SECS {
+        let out = docker.compose_client_status(node_id, network_id, client_port)?;
+        if out.status.success() {
+            daemon_running = true;
+        } else {
+            retries += 1;
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+    }
+    if !daemon_running {
+        return failure(format!(
+            "Failed to start daemon for node '{node_id}' on network '{network_id}' within {TIMEOUT_IN_SECS}s",
+        ));
+    }
+    Ok(())
+}
+
+/// Applies provided schema `scripts` to the postgres db, `postgres_name`
+fn apply_schema_scripts(
+    docker: DockerManager,
+    postgres_name: &str,
+    postgres: &PostgresConfig,
+    scripts: &Vec<String>,
+    checksums: Option<&HashMap<String, String>>,
+    network_path: &Path,
+) -> Result<()> {
+    // copy scripts first, keeping the verified files to apply
+    let mut file_paths = vec![];
+    for script in scripts {
+        let file_path = fetch_verified_schema(script, checksums, network_path)?;
+        let file_name = file_path.file_name().unwrap().to_str().unwrap();
+        let docker_file_path = Path::new("/tmp").join(file_path.file_name().unwrap());
+
+        info!("Copying schema script: {}", file_name);
+        docker.cp(postgres_name, &file_path, &docker_file_path)?;
+        file_paths.push(file_path);
+    }
+
+    // then apply scripts 1 by 1
+    for file_path in file_paths {
+        let file_name = file_path.file_name().unwrap().to_str().unwrap();
+        let docker_file_path = Path::new("/tmp").join(file_path.file_name().unwrap());
+        let cmd = [
+            "psql",
+            "-U",
+            &postgres.user,
+            "-d",
+            &postgres.database,
+            "-f",
+            docker_file_path.to_str().unwrap(),
+        ];
+
+        info!("Applying schema script: {}", file_name);
+        docker.exec(postgres_name, &cmd)?;
+    }
+
+    Ok(())
+}
+
+/// Fetches the schema script `script` to the network directory, checking it against
+/// its sha256 in `checksums` if there is one
+fn fetch_verified_schema(
+    script: &str,
+    checksums: Option<&HashMap<String, String>>,
+    network_path: &Path,
+) -> Result<PathBuf> {
+    let file_path = fetch_schema(script, network_path.to_path_buf())?;
+    if let Some(expected) = checksums.and_then(|checksums| checksums.get(script)) {
+        let actual = sha256_file(&file_path)?;
+        if &actual != expected {
+            return failure(format!(
+                "Checksum mismatch for schema script '{script}': expected sha256 {expected}, got {actual}"
+            ));
+        }
+    }
+    Ok(file_path)
+}
+
+/// Publishes the services on host ports which no other network records and which
+/// are free on the host, their default ports if possible. Host ports given in the
+/// topology are kept, failing if another network records them
+fn assign_host_ports(
+    directory_manager: &DirectoryManager,
+    network_id: &str,
+    services: &mut [ServiceConfig],
+    port_range: Option<RangeInclusive<u16>>,
+    with_monitoring: bool,
+    with_health_probes: bool,
+) -> Result<Option<Monitoring>> {
+    let mut taken: HashMap<u16, String> = HashMap::new();
+    for other in directory_manager.list_network_directories()? {
+        if other == network_id {
+            continue;
+        }
+        // networks being created or deleted may have no services yet
+        for service in directory_manager
+            .get_services_info(&other)
+            .unwrap_or_default()
+        {
+            for port in service.host_ports() {
+                taken.insert(port, other.clone());
+            }
+        }
+        if let Ok(Some(monitoring)) = Monitoring::load(&directory_manager.network_path(&other)) {
+            taken.insert(monitoring.prometheus_host_port, other.clone());
+            taken.insert(monitoring.grafana_host_port, other.clone());
+        }
+    }
+
+    let mut allocator = PortAllocator::new(port_range, taken.keys().copied());
+    for service in services.iter() {
+        let explicit = [
+            service.graphql_host_port,
+            service.limited_graphql_host_port,
+            service.archive_host_port,
+            service.postgres_host_port,
+            service.rosetta_host_port,
+            service.health_host_port,
+            service.uptime_host_port,
+        ];
+        for port in explicit.into_iter().flatten() {
+            if let Some(other) = taken.get(&port) {
+                return failure(format!(
+                    "Host port {port} of '{}' is already used by network '{other}'",
+                    service.service_name
+                ));
+            }
+            allocator.reserve(port);
+        }
+    }
+
+    for service in services.iter_mut() {
+        let Some(client_port) = service.client_port else {
+            continue;
+        };
+        if service.graphql_host_port.is_none() && service.published_graphql_port().is_some() {
+            service.graphql_host_port = Some(allocator.allocate(client_port + 1)?);
+        }
+        if service.limited_graphql_host_port.is_none()
+            && service.published_limited_graphql_port().is_some()
+        {
+            service.limited_graphql_host_port = Some(allocator.allocate(client_port + 5)?);
+        }
+    }
+    for service in services.iter_mut() {
+        if service.service_type == ServiceType::ArchiveService
+            && service.archive_host_port.is_none()
+        {
+            if let Some(archive_port) = service.archive_port {
+                service.archive_host_port = Some(allocator.allocate(archive_port)?);
+            }
+        }
+        if with_health_probes
+            && service.health_host_port.is_none()
+            && service.daemon_port().is_some()
+        {
+            service.health_host_port = Some(allocator.allocate(DEFAULT_HEALTH_HOST_PORT)?);
+        }
+        if service.service_type == ServiceType::Rosetta && service.rosetta_host_port.is_none() {
+            if let Some(rosetta_port) = service.rosetta_port {
+                service.rosetta_host_port = Some(allocator.allocate(rosetta_port)?);
+            }
+        }
+        if service.service_type == ServiceType::UptimeServiceBackend
+            && service.uptime_host_port.is_none()
+        {
+            service.uptime_host_port = Some(allocator.allocate(UPTIME_SERVICE_PORT)?);
+        }
+    }
+    if !with_monitoring {
+        return Ok(None);
+    }
+    Ok(Some(Monitoring {
+        prometheus_host_port: allocator.allocate(PROMETHEUS_PORT)?,
+        grafana_host_port: allocator.allocate(GRAFANA_PORT)?,
+    }))
+}
+
+/// Containers to remove because the compose file doesn't define their service,
+/// and services of the compose file without a container
+fn repair_plan(defined: &[String], containers: &[ContainerInfo]) -> (Vec<String>, Vec<String>) {
+    let unknown = containers
+        .iter()
+        .filter(|container| !defined.contains(&container.service))
+        .map(|container| container.name.clone())
+        .collect();
+    let missing = defined
+        .iter()
+        .filter(|service| !containers.iter().any(|c| &c.service == *service))
+        .cloned()
+        .collect();
+    (unknown, missing)
+}
+
+/// Generates a genesis ledger for the default network, funding every service
+/// of the default topology
+fn generate_default_genesis_ledger(
+    bp_keys_opt: &mut Option<HashMap<String, NodeKey>>,
+    libp2p_keys_opt: &mut Option<HashMap<String, NodeKey>>,
+    network_path: &Path,
+    docker_image: &str,
+    options: &CreateOptions,
+    timings: &mut network::Timings,
+) -> Result<()> {
+    info!("Genesis ledger not provided. Generating default genesis ledger.");
+
+    // set default services to generate keys for
+    let service_names = options.default_topology.service_names();
+    let all_services: Vec<&str> = service_names.iter().map(String::as_str).collect();
+
+    // generate key-pairs for default services
+    let keys_manager = KeysManager::new(network_path, docker_image, options.runtime)
+        .with_native_keys(options.native_keys);
+    network::Timings::measure(&mut timings.key_generation_ms, || -> Result<()> {
+        *bp_keys_opt = Some(
+            keys_manager
+                .generate_bp_key_pairs(&all_services)
+                .map_err(|e| {
+                    MiniminaError::Command(format!(
+                        "Failed to generate key pairs for mina services: {e}"
+                    ))
+                })?,
+        );
+        *libp2p_keys_opt = Some(
+            keys_manager
+                .generate_libp2p_key_pairs(&all_services)
+                .map_err(|e| {
+                    MiniminaError::Command(format!(
+                        "Failed to generate libp2p key pairs for mina services: {e}"
+                    ))
+                })?,
+        );
+        Ok(())
+    })?;
+
+    // generate the key pairs of the test accounts, their private keys go into the ledger
+    let test_accounts = match network::Timings::measure(&mut timings.key_generation_ms, || {
+        (1..=options.extra_accounts)
+            .map(|i| {
+                let key = keys_manager.generate_bp_key_pair(&format!("test-account-{i}"))?;
+                Ok(default::TestAccount {
+                    private_key: keys_manager.dump_private_key(&key.key_path_docker)?,
+                    public_key: key.key_string,
+                })
+            })
+            .collect::<Result<Vec<_>>>()
+    }) {
+        Ok(test_accounts) => test_accounts,
+        Err(e) => return failure(format!("Failed to generate test accounts: {e}")),
+    };
+
+    // generate default genesis ledger
+    if let Err(e) = network::Timings::measure(&mut timings.genesis_ledger_ms, || {
+        default::LedgerGenerator::generate(
+            network_path,
+            bp_keys_opt.as_ref().unwrap(),
+            &test_accounts,
+        )
+    }) {
+        return failure(format!(
+            "Failed to generate the default genesis ledger: {e}"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Generates the services of the default topology: seeds peering with each other,
+/// block producers, a snark coordinator with its workers and an archive node,
+/// all peering with the seeds
+fn generate_default_topology(
+    topology: &DefaultTopology,
+    bp_keys: &HashMap<String, NodeKey>,
+    libp2p_keys: &HashMap<String, NodeKey>,
+    docker_image: &str,
+    docker_image_archive: &str,
+    network_id: &str,
+) -> Vec<service::ServiceConfig> {
+    let peer_id = |name: &str| -> String {
+        libp2p_keys[name]
+            .key_string
+            .split(',')
+            .next_back()
+            .unwrap()
+            .to_string()
+    };
+    let client_port = |base: u16, index: usize| base + DEFAULT_PORT_STRIDE * index as u16;
+
+    let seed_names = topology.seed_names();
+    let peers: Vec<String> = seed_names
+        .iter()
+        .enumerate()
+        .map(|(i, seed_name)| {
+            ServiceConfig::generate_peer(
+                seed_name,
+                network_id,
+                &peer_id(seed_name),
+                client_port(DEFAULT_SEED_PORT, i) + 2,
+            )
+        })
+        .collect();
+
+    let mut services = vec![];
+    for (i, seed_name) in seed_names.iter().enumerate() {
+        let other_seeds: Vec<String> = peers
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, peer)| peer.clone())
+            .collect();
+        services.push(ServiceConfig {
+            service_type: ServiceType::Seed,
+            service_name: seed_name.clone(),
+            docker_image: Some(docker_image.into()),
+            client_port: Some(client_port(DEFAULT_SEED_PORT, i)),
+            libp2p_keypair: Some(libp2p_keys[seed_name].key_string.clone()),
+            libp2p_peerid: Some(peer_id(seed_name)),
+            peers: (!other_seeds.is_empty()).then_some(other_seeds),
+            ..Default::default()
+        });
+    }
+
+    for (i, bp_name) in topology.block_producer_names().iter().enumerate() {
+        services.push(ServiceConfig {
+            service_type: ServiceType::BlockProducer,
+            service_name: bp_name.clone(),
+            docker_image: Some(docker_image.into()),
+            client_port: Some(client_port(DEFAULT_BLOCK_PRODUCER_PORT, i)),
+            public_key: Some(bp_keys[bp_name].key_string.clone()),
+            public_key_path: Some(bp_keys[bp_name].key_path_docker.clone()),
+            libp2p_keypair: Some(libp2p_keys[bp_name].key_string.clone()),
+            peers: Some(peers.clone()),
+            ..Default::default()
+        });
+    }
+
+    if topology.snark_workers > 0 {
+        services.push(ServiceConfig {
+            service_type: ServiceType::SnarkCoordinator,
+            service_name: DEFAULT_SNARK_COORDINATOR.to_string(),
+            docker_image: Some(docker_image.into()),
+            client_port: Some(DEFAULT_SNARK_COORDINATOR_PORT),
+            public_key: Some(bp_keys[DEFAULT_SNARK_COORDINATOR].key_string.clone()),
+            libp2p_keypair: Some(libp2p_keys[DEFAULT_SNARK_COORDINATOR].key_string.clone()),
+            peers: Some(peers.clone()),
+            snark_coordinator_fees: Some("0.001".into()),
+            snark_worker_proof_level: Some(DEFAULT_SNARK_WORKER_PROOF_LEVEL.into()),
+            worker_nodes: Some(topology.snark_workers),
+            ..Default::default()
+        });
+
+        for snark_worker_name in topology.snark_worker_names() {
+            services.push(ServiceConfig {
+                service_type: ServiceType::SnarkWorker,
+                service_name: snark_worker_name,
+                docker_image: Some(docker_image.into()),
+                snark_coordinator_port: Some(DEFAULT_SNARK_COORDINATOR_PORT),
+                snark_worker_proof_level: Some(DEFAULT_SNARK_WORKER_PROOF_LEVEL.into()),
+                snark_coordinator_host: Some(DEFAULT_SNARK_COORDINATOR.to_string()),
+                ..Default::default()
+            });
+        }
+    }
+
+    if topology.archive {
+        services.push(ServiceConfig {
+            service_type: ServiceType::ArchiveNode,
+            service_name: DEFAULT_ARCHIVE.to_string(),
+            docker_image: Some(docker_image.into()),
+            client_port: Some(5005),
+            public_key: Some(bp_keys[DEFAULT_ARCHIVE].key_string.clone()),
+            public_key_path: Some(bp_keys[DEFAULT_ARCHIVE].key_path_docker.clone()),
+            libp2p_keypair: Some(libp2p_keys[DEFAULT_ARCHIVE].key_string.clone()),
+            peers: Some(peers),
+            archive_docker_image: Some(docker_image_archive.into()),
+            archive_schema_files: Some(vec![
+                format!("https://raw.githubusercontent.com/MinaProtocol/mina/{IMAGE_COMMIT_HASH}/src/app/archive/zkapp_tables.sql"),
+                format!("https://raw.githubusercontent.com/MinaProtocol/mina/{IMAGE_COMMIT_HASH}/src/app/archive/create_schema.sql"),
+            ]),
+            archive_port: Some(3086),
+            ..Default::default()
+        });
+    }
+    services
+}
+
+/// If the network exists, its directory is deleted, corresponding docker
+/// images are removed, and it is created anew.
+/// If the network doesn't exist, the directory structure is created.
+fn check_setup_network(
+    docker: &DockerManager,
+    directory_manager: &DirectoryManager,
+    network_id: &str,
+) -> Result<()> {
+    if directory_manager.network_path_exists(network_id) {
+        warn!("Network '{network_id}' already exists. Overwriting!");
+        docker.compose_down(None, false, false)?;
+        directory_manager.delete_network_directory(network_id)?;
+    }
+
+    // create directory structure for network
+    info!("Creating network '{network_id}'.");
+    if let Err(e) = directory_manager.generate_dir_structure(network_id) {
+        return failure(format!(
+            "Failed to set up network directory structure for '{network_id}' with error: {e}"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Handles `network_id`'s genesis ledger
+///
+/// If no genesis ledger is provided, a default ledger will be generated
+fn handle_genesis_ledger(
+    options: &CreateOptions,
+    directory_manager: &DirectoryManager,
+    network_id: &str,
+    bp_keys_opt: &mut Option<HashMap<String, NodeKey>>,
+    libp2p_keys_opt: &mut Option<HashMap<String, NodeKey>>,
+    timings: &mut network::Timings,
+) -> Result<()> {
+    let network_path = directory_manager.network_path(network_id);
+
+    match &options.genesis_ledger {
+        Some(genesis_ledger_path) => {
+            if options.topology.is_none() {
+                directory_manager.delete_network_directory(network_id)?;
+                return failure(
+                    "Must provide a topology file with a genesis ledger, keys will be incompatible otherwise.".to_string(),
+                );
+            }
+
+            info!(
+                "Copying genesis ledger from '{}' to network directory.",
+                genesis_ledger_path.display()
+            );
+
+            network::Timings::measure(&mut timings.genesis_ledger_ms, || -> Result<()> {
+                directory_manager.copy_genesis_ledger(network_id, genesis_ledger_path)?;
+                directory_manager.overwrite_genesis_timestamp(network_id, genesis_ledger_path)?;
+                Ok(())
+            })
+        }
+        None => generate_default_genesis_ledger(
+            bp_keys_opt,
+            libp2p_keys_opt,
+            &network_path,
+            options.daemon_image(),
+            options,
+            timings,
+        ),
+    }
+}
+
+/// Creates the list of docker service configs from the topology file at `topology_path`
+/// using the seed nodes as the list of network peers (at least 1 seed node must be declared)
+///
+/// Logs and error and exits with code 1 if the topology file can't be parsed
+fn create_services(
+    directory_manager: &DirectoryManager,
+    topology_path: &Path,
+    network_id: &str,
+) -> Result<Vec<ServiceConfig>> {
+    match topology::Topology::new(topology_path) {
+        Ok(topology) => {
+            let peer_list_file = directory_manager.peer_list_file(network_id);
+            let services = topology.services(&peer_list_file);
+            let peers: Vec<&ServiceConfig> = ServiceConfig::get_seeds(&services);
+            directory_manager.create_peer_list_file(network_id, &peers)?;
+
+            if let Some(uptime_service_backend) =
+                ServiceConfig::get_uptime_service_backend(&services)
+            {
+                match directory_manager
+                    .copy_uptime_service_config(network_id, uptime_service_backend)
+                {
+                    Ok(_) => info!("Successfully copied uptime service config."),
+                    Err(e) => {
+                        let error_message = format!("Failed to copy uptime service config: {e}");
+                        return failure(error_message);
+                    }
+                }
+            }
+
+            if peers.is_empty() {
+                return failure(
+                    "There are no seed nodes declared in this network. You must include seed nodes."
+                        .to_string(),
+                );
+            }
+
+            Ok(services)
+        }
+        Err(err) => failure(format!(
+            "Error occured while parsing the topology file:\n\
+             path: {}\n\
+             error: {err}",
+            topology_path.display()
+        )),
+    }
+}
+
+/// Creates service configs for the nodes specified in the topology file of the given `options`
+fn handle_topology(
+    options: &CreateOptions,
+    directory_manager: &DirectoryManager,
+    network_id: &str,
+    bp_keys: Option<HashMap<String, NodeKey>>,
+    libp2p_keys: Option<HashMap<String, NodeKey>>,
+) -> Result<Vec<ServiceConfig>> {
+    match &options.topology {
+        Some(topology_path) => {
+            if options.genesis_ledger.is_none() {
+                directory_manager.delete_network_directory(network_id)?;
+                return failure(
+                    "Must provide a genesis ledger with a topology file, \
+                     keys will be incompatible otherwise."
+                        .to_string(),
+                );
+            }
+
+            info!(
+                "Generating docker-compose based on provided topology '{}'.",
+                topology_path.display()
+            );
+
+            let network_topology_path = directory_manager.topology_file_path(network_id);
+            std::fs::copy(topology_path, network_topology_path)?;
+            create_services(directory_manager, topology_path, network_id)
+        }
+        None => {
+            info!("Topology not provided. Generating docker-compose based on default topology.");
+
+            if let (Some(bp_keys), Some(libp2p_keys)) = (&bp_keys.as_ref(), &libp2p_keys.as_ref()) {
+                Ok(generate_default_topology(
+                    &options.default_topology,
+                    bp_keys,
+                    libp2p_keys,
+                    options.daemon_image(),
+                    options.archive_image(),
+                    network_id,
+                ))
+            } else {
+                let err = "Failed to generate docker-compose.yaml. Keys not generated.";
+                error!("{err}");
+                failure(err.to_string())
+            }
+        }
+    }
+}
+
+fn is_node_uptime_service(services: Vec<ServiceConfig>, node_id: &str) -> bool {
+    if let Some(uptime) = ServiceConfig::get_uptime_service_backend(&services) {
+        if uptime.service_name == node_id {
+            return true;
+        }
+    }
+    false
+}
+
+/// Imports the genesis accounts of `network_id` into the wallet of `node_id` in a
+/// single container, skipping those imported before and carrying on past accounts
+/// which fail to import
+fn import_all_accounts(
+    docker: &DockerManager,
+    directory_manager: &DirectoryManager,
+    node_id: &str,
+    network_id: &str,
+) -> Result<node::ImportAccounts> {
+    let mut result = node::ImportAccounts {
+        imported: vec![],
+        skipped: vec![],
+        failed: vec![],
+        network_id: network_id.into(),
+        node_id: node_id.into(),
+    };
+    let account_files = directory_manager.get_network_keypair_files(network_id)?;
+    for (account_file, import) in
+        docker.compose_import_accounts(node_id, network_id, &account_files)?
+    {
+        match import {
+            AccountImport::Imported => {
+                info!(
+                    "Successfully imported account from file '{account_file}' \
+                    for node '{node_id}' on network '{network_id}'",
+                );
+                result.imported.push(account_file);
+            }
+            AccountImport::Skipped => {
+                info!(
+                    "Account from file '{account_file}' was already imported \
+                    for node '{node_id}' on network '{network_id}'",
+                );
+                result.skipped.push(account_file);
+            }
+            AccountImport::Failed(error) => {
+                warn!(
+                    "Failed to import account from file '{account_file}' \
+                    for node '{node_id}' on network '{network_id}': {error}",
+                );
+                result.failed.push(node::FailedImport {
+                    file: account_file,
+                    error,
+                });
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stop_batches() {
+        let containers: Vec<String> = (1..=5).map(|i| format!("mina-bp-{i}-net")).collect();
+
+        let batches: Vec<_> = stop_batches(&containers, Some(2)).collect();
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[2], ["mina-bp-5-net".to_string()]);
+
+        assert_eq!(stop_batches(&containers, None).count(), 1);
+        assert_eq!(stop_batches(&[], None).count(), 0);
+    }
+
+    fn default_keys(topology: &DefaultTopology) -> HashMap<String, NodeKey> {
+        topology
+            .service_names()
+            .into_iter()
+            .map(|name| {
+                let key = NodeKey {
+                    key_string: format!("key,pub,peer-{name}"),
+                    key_path_docker: format!("/local-network/network-keypairs/{name}"),
+                };
+                (name, key)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_generate_default_topology() {
+        let topology = DefaultTopology::default();
+        let keys = default_keys(&topology);
+        let services =
+            generate_default_topology(&topology, &keys, &keys, "daemon", "archive", "net");
+
+        let names: Vec<&str> = services.iter().map(|s| s.service_name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "mina-seed-1",
+                "mina-bp-1",
+                "mina-bp-2",
+                "mina-snark-coordinator",
+                "mina-snark-worker-1",
+                "mina-archive"
+            ]
+        );
+        assert_eq!(services[2].client_port, Some(4005));
+        assert_eq!(
+            services[1].peers,
+            Some(vec![
+                "/dns4/mina-seed-1-net/tcp/3102/p2p/peer-mina-seed-1".to_string()
+            ])
+        );
+        assert_eq!(services[0].peers, None);
+    }
+
+    #[test]
+    fn test_generate_scaled_default_topology() {
+        let topology = DefaultTopology {
+            seeds: 2,
+            block_producers: 4,
+            snark_workers: 0,
+            archive: false,
+        };
+        let keys = default_keys(&topology);
+        let services =
+            generate_default_topology(&topology, &keys, &keys, "daemon", "archive", "net");
+
+        assert_eq!(services.len(), 6);
+        assert_eq!(services[1].client_port, Some(3105));
+        assert_eq!(
+            services[0].peers,
+            Some(vec![
+                "/dns4/mina-seed-2-net/tcp/3107/p2p/peer-mina-seed-2".to_string()
+            ])
+        );
+        assert_eq!(services[5].service_name, "mina-bp-4");
+        assert_eq!(services[5].client_port, Some(4015));
+        assert_eq!(services[5].peers.as_ref().unwrap().len(), 2);
+        assert!(services
+            .iter()
+            .all(|s| s.service_type == ServiceType::Seed
+                || s.service_type == ServiceType::BlockProducer));
+    }
+
+    #[test]
+    fn test_repair_plan() {
+        let container = |name: &str, service: &str| ContainerInfo {
+            id: name.to_string(),
+            name: name.to_string(),
+            image: "daemon-image".to_string(),
+            command: "daemon".to_string(),
+            created_at: String::new(),
+            state: ContainerState::Exited,
+            status: String::new(),
+            health: String::new(),
+            exit_code: 0,
+            labels: String::new(),
+            service: service.to_string(),
+            publishers: None,
+        };
+        let defined = ["mina-seed-1-net".to_string(), "mina-bp-1-net".to_string()];
+        let containers = [
+            container("mina-seed-1-net", "mina-seed-1-net"),
+            container("mina-bp-9-net", "mina-bp-9-net"),
+        ];
+
+        let (unknown, missing) = repair_plan(&defined, &containers);
+        assert_eq!(unknown, ["mina-bp-9-net"]);
+        assert_eq!(missing, ["mina-bp-1-net"]);
+    }
+
+    #[test]
+    fn test_assign_host_ports() {
+        let tempdir = tempdir::TempDir::new("test_assign_host_ports").unwrap();
+        let directory_manager = DirectoryManager::_new_with_base_path(tempdir.path().into());
+        let seed = ServiceConfig {
+            service_type: ServiceType::Seed,
+            service_name: "mina-seed-1".to_string(),
+            client_port: Some(3100),
+            ..Default::default()
+        };
+        directory_manager.create_network_directory("first").unwrap();
+        directory_manager
+            .save_services_info("first", std::slice::from_ref(&seed))
+            .unwrap();
+
+        let mut services = vec![seed.clone()];
+        let monitoring = assign_host_ports(
+            &directory_manager,
+            "second",
+            &mut services,
+            Some(40000..=40100),
+            false,
+            false,
+        )
+        .unwrap();
+        assert_eq!(monitoring, None);
+        let port = services[0].graphql_host_port.unwrap();
+        assert!((40000..=40100).contains(&port));
+        assert_eq!(services[0].host_ports(), [port]);
+
+        // health probe sidecars get a port of their own
+        let mut services = vec![seed.clone()];
+        assign_host_ports(
+            &directory_manager,
+            "second",
+            &mut services,
+            Some(40000..=40100),
+            false,
+            true,
+        )
+        .unwrap();
+        let health_port = services[0].health_host_port.unwrap();
+        assert!((40000..=40100).contains(&health_port));
+        assert_ne!(Some(health_port), services[0].graphql_host_port);
+
+        // the monitoring ports of other networks are taken too
+        let first = Monitoring {
+            prometheus_host_port: 40050,
+            grafana_host_port: 40051,
+        };
+        first
+            .save(&directory_manager.network_path("first"))
+            .unwrap();
+        let mut services = vec![seed.clone()];
+        let monitoring = assign_host_ports(
+            &directory_manager,
+            "second",
+            &mut services,
+            Some(40050..=40100),
+            true,
+            false,
+        )
+        .unwrap()
+        .unwrap();
+        let ports = [
+            services[0].graphql_host_port.unwrap(),
+            monitoring.prometheus_host_port,
+            monitoring.grafana_host_port,
+        ];
+        assert!(ports.iter().all(|port| *port > 40051));
+        assert_ne!(ports[1], ports[2]);
+
+        let mut services = vec![ServiceConfig {
+            graphql_host_port: Some(3101),
+            ..seed
+        }];
+        let error = assign_host_ports(
+            &directory_manager,
+            "second",
+            &mut services,
+            None,
+            false,
+            false,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("already used by network 'first'"));
+    }
+
+    #[test]
+    fn test_missing_network_and_node() {
+        let tempdir = tempdir::TempDir::new("test_missing_network_and_node").unwrap();
+        let directory_manager = DirectoryManager::_new_with_base_path(tempdir.path().into());
+        let network = Network::with_directory_manager(directory_manager.clone(), "net");
+        let node = network.node("mina-bp-1");
+        for result in [
+            node.query("{ version }").map(drop),
+            node.start(&Default::default()).map(drop),
+            node.stop(false).map(drop),
+            node.restart(&Default::default()).map(drop),
+            node.internal_logs().map(drop),
+            node.collect_crash().map(drop),
+            node.dump_archive_data().map(drop),
+            network.info().map(drop),
+            network.status().map(drop),
+            network.slots().map(drop),
+            network.accounts().map(drop),
+        ] {
+            assert!(matches!(
+                result,
+                Err(MiniminaError::NetworkNotFound { network_id, .. }) if network_id == "net"
+            ));
+        }
+
+        directory_manager.create_network_directory("net").unwrap();
+        directory_manager.save_services_info("net", &[]).unwrap();
+        assert!(matches!(
+            network.node("mina-bp-1").client(&[]),
+            Err(MiniminaError::NodeNotFound { node_id, .. }) if node_id == "mina-bp-1"
+        ));
+    }
+
+    #[test]
+    fn test_archive_data_of_non_archive_node() {
+        let tempdir = tempdir::TempDir::new("test_archive_data_of_non_archive_node").unwrap();
+        let directory_manager = DirectoryManager::_new_with_base_path(tempdir.path().into());
+        directory_manager.create_network_directory("net").unwrap();
+        directory_manager.save_services_info("net", &[]).unwrap();
+        let dump = tempdir.path().join("dump.sql");
+        fs::write(&dump, "").unwrap();
+
+        let node = Network::with_directory_manager(directory_manager, "net").node("mina-bp-1");
+        for result in [
+            node.dump_archive_data().map(drop),
+            node.restore_archive_data(&dump).map(drop),
+        ] {
+            assert!(matches!(
+                result,
+                Err(MiniminaError::Command(e))
+                    if e == "Node 'mina-bp-1' is not an archive node in 'net' network."
+            ));
+        }
+        assert!(matches!(
+            node.restore_archive_data(&tempdir.path().join("missing.sql")),
+            Err(MiniminaError::Command(e)) if e.starts_with("Archive dump")
+        ));
+    }
+
+    #[test]
+    fn test_list_networks() {
+        let tempdir = tempdir::TempDir::new("test_list_networks").unwrap();
+        let directory_manager = DirectoryManager::_new_with_base_path(tempdir.path().into());
+        assert!(Network::list(&directory_manager)
+            .unwrap()
+            .networks
+            .is_empty());
+
+        directory_manager.create_network_directory("net").unwrap();
+        let list = Network::list(&directory_manager).unwrap();
+        assert_eq!(list.networks.len(), 1);
+        assert_eq!(list.networks[0].network_id, "net");
+        assert_eq!(
+            list.networks[0].config_dir,
+            tempdir.path().join("net").display().to_string()
+        );
+    }
+
+    #[test]
+    fn test_create_if_not_exists() {
+        let tempdir = tempdir::TempDir::new("test_create_if_not_exists").unwrap();
+        let directory_manager = DirectoryManager::_new_with_base_path(tempdir.path().into());
+        directory_manager.create_network_directory("net").unwrap();
+        directory_manager
+            .save_network_info("net", &[], Runtime::Docker)
+            .unwrap();
+        let network = Network::with_directory_manager(directory_manager, "net");
+        let options = CreateOptions {
+            if_not_exists: true,
+            ..Default::default()
+        };
+        Fingerprint::of(&options)
+            .unwrap()
+            .save(&network.path())
+            .unwrap();
+
+        let info = network.create(&options).unwrap();
+        assert_eq!(info.network_id, "net");
+        assert!(info.nodes.is_empty());
+
+        let bigger = CreateOptions {
+            default_topology: DefaultTopology {
+                block_producers: 3,
+                ..Default::default()
+            },
+            ..options
+        };
+        assert!(network.create(&bigger).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint_changed_inputs() {
+        let tempdir = tempdir::TempDir::new("test_fingerprint_changed_inputs").unwrap();
+        let topology = tempdir.path().join("topology.json");
+        let genesis_ledger = tempdir.path().join("genesis_ledger.json");
+        fs::write(&topology, "{}").unwrap();
+        fs::write(&genesis_ledger, "{}").unwrap();
+        let options = CreateOptions {
+            topology: Some(topology.clone()),
+            genesis_ledger: Some(genesis_ledger.clone()),
+            ..Default::default()
+        };
+        let fingerprint = Fingerprint::of(&options).unwrap();
+        assert!(fingerprint.changed_inputs().unwrap().is_empty());
+
+        fs::write(&topology, "{\"seed\": {}}").unwrap();
+        fs::remove_file(&genesis_ledger).unwrap();
+        assert_eq!(
+            fingerprint.changed_inputs().unwrap(),
+            vec![fs::canonicalize(tempdir.path())
+                .unwrap()
+                .join("topology.json")]
+        );
+        assert!(!fingerprint.same_inputs(&Fingerprint::of(&CreateOptions::default()).unwrap()));
+    }
+
+    const COMPOSE_GOLDEN_DIR: &str = "./tests/data/golden/compose";
+
+    /// Representative networks with their golden file name
+    fn compose_cases(network_path: &Path) -> Vec<(&'static str, Vec<ServiceConfig>)> {
+        let default = |topology: DefaultTopology| {
+            let keys = default_keys(&topology);
+            generate_default_topology(
+                &topology,
+                &keys,
+                &keys,
+                DEFAULT_DAEMON_DOCKER_IMAGE,
+                DEFAULT_ARCHIVE_DOCKER_IMAGE,
+                "golden",
+            )
+        };
+        let from_file = |path: &str| {
+            topology::Topology::new(Path::new(path))
+                .unwrap()
+                .services(&network_path.join("peer_list_file.txt"))
+        };
+        vec![
+            ("default", default(DefaultTopology::default())),
+            (
+                "archive_only",
+                default(DefaultTopology {
+                    snark_workers: 0,
+                    ..Default::default()
+                }),
+            ),
+            (
+                "workers",
+                default(DefaultTopology {
+                    snark_workers: 3,
+                    archive: false,
+                    ..Default::default()
+                }),
+            ),
+            (
+                "large",
+                from_file("./tests/data/large_network/topology.json"),
+            ),
+            (
+                "uptime_service",
+                from_file("./tests/data/uptime_service_network/topology.json"),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_compose_matches_golden_files() {
+        let update = std::env::var("UPDATE_GOLDEN").is_ok();
+        let network_path = Path::new("/minimina/golden");
+
+        for (name, services) in compose_cases(network_path) {
+            let compose = DockerCompose::generate(&services, network_path, None);
+            let golden_file = PathBuf::from(COMPOSE_GOLDEN_DIR).join(format!("{name}.yaml"));
+
+            if update {
+                fs::create_dir_all(COMPOSE_GOLDEN_DIR).unwrap();
+                fs::write(&golden_file, &compose).unwrap();
+                continue;
+            }
+
+            let expected = fs::read_to_string(&golden_file)
+                .unwrap_or_else(|_| panic!("Missing golden file {}", golden_file.display()));
+            assert_eq!(expected, compose, "golden file '{name}' differs");
+        }
+    }
+
+    #[test]
+    fn test_render_compose() {
+        let tempdir = tempdir::TempDir::new("test_render_compose").unwrap();
+        let directory_manager = DirectoryManager::_new_with_base_path(tempdir.path().into());
+        let network = Network::with_directory_manager(directory_manager, "net");
+
+        let compose = network
+            .render_compose(Path::new("./tests/data/small_network/topology.json"))
+            .unwrap();
+        assert!(compose.contains("container_name: seed-0-net"));
+        // rendering leaves no network behind
+        assert!(!network.exists());
+    }
+
+    #[test]
+    fn test_resource_limits() {
+        let options = CreateOptions {
+            profile: Some(ResourceProfile::Constrained),
+            cpu_limit: Some(2.0),
+            ..Default::default()
+        };
+        let limits = options.resource_limits();
+        assert_eq!(limits.cpu_limit, Some(2.0));
+        assert_eq!(limits.memory_limit, Some("4g".to_string()));
+        assert_eq!(limits.io_weight, Some(100));
+
+        let limits = CreateOptions::default().resource_limits();
+        assert_eq!(limits, ContainerLimits::default());
+    }
+
+    #[test]
+    fn test_nodes_behind() {
+        let heights = BTreeMap::from([
+            ("mina-bp-1".to_string(), Some(20)),
+            ("mina-bp-2".to_string(), Some(18)),
+            ("mina-bp-3".to_string(), Some(17)),
+            ("mina-seed-1".to_string(), None),
+        ]);
+        assert_eq!(
+            nodes_behind(&heights, 2),
+            vec!["mina-bp-3".to_string(), "mina-seed-1".to_string()]
+        );
+        assert_eq!(nodes_behind(&heights, 3), vec!["mina-seed-1".to_string()]);
+    }
+
+    #[test]
+    fn test_rotate_archive_dumps() {
+        let tempdir = tempdir::TempDir::new("test_rotate_archive_dumps").unwrap();
+        let dir = tempdir.path();
+        for name in [
+            "mina-archive-20240102T100000Z.sql",
+            "mina-archive-20240102T090000Z.sql",
+            "mina-archive-20240102T110000Z.sql",
+            "mina-archive-20240102T120000Z.sql.partial",
+            "mina-archive-2-20240102T080000Z.sql",
+            "mina-archive-backup.sql",
+        ] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        assert!(rotate_archive_dumps(dir, "mina-archive", None)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            rotate_archive_dumps(dir, "mina-archive", Some(2)).unwrap(),
+            vec![dir.join("mina-archive-20240102T090000Z.sql")]
+        );
+        assert!(dir.join("mina-archive-20240102T100000Z.sql").exists());
+        assert!(dir.join("mina-archive-2-20240102T080000Z.sql").exists());
+        assert!(dir.join("mina-archive-backup.sql").exists());
+        assert!(dir
+            .join("mina-archive-20240102T120000Z.sql.partial")
+            .exists());
+    }
+}
@@ -0,0 +1,514 @@
+//! # Chaos Module
+//!
+//! Splits the peer graph of a network into partitions and heals it again, to test
+//! fork resolution locally.
+//!
+//! Every partition is a docker network of its own, named `<network>_partition_<n>`.
+//! The daemons of a group are connected to the network of their partition and
+//! disconnected from the compose project's default network, so they only resolve and
+//! reach each other. Containers of the other services (postgres, archive services,
+//! the uptime service backend) are connected to every partition, so archive nodes
+//! keep reaching their database whichever group they are in. Daemons depending on
+//! each other in the network's service graph, e.g. snark workers and their
+//! coordinator, have to be in the same group.
+//!
+//! The partitions are recorded in the network's `partition.json` until the network
+//! is healed: its daemons reconnected to the default network and the partition
+//! networks removed.
+//!
+//! Faults are also injected into the archive pipeline, for a while: its postgres
+//! stopped, its archive server paused or each block write delayed. The archive is
+//! then audited for missing blocks until it has caught up with the chain again.
+
+use crate::{
+    docker::{
+        manager::{ContainerState, DockerManager},
+        postgres::ArchiveDb,
+    },
+    service::{graph::Graph, ServiceConfig, ServiceType},
+    utils::check_output,
+};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs,
+    io::{Error, Result},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+const PARTITION_FILE: &str = "partition.json";
+
+/// Interval between the missing-blocks audits of an archive catching up
+const AUDIT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Time postgres is given to accept connections again after being restarted
+const POSTGRES_START_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Groups of node ids given as `a,b|c,d`, each group a partition
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionGroups(pub Vec<Vec<String>>);
+
+/// Parses `a,b|c,d` into the groups `[a, b]` and `[c, d]`
+pub fn parse_groups(groups: &str) -> std::result::Result<PartitionGroups, String> {
+    let groups: Vec<Vec<String>> = groups
+        .split('|')
+        .map(|group| {
+            group
+                .split(',')
+                .map(|node| node.trim().to_string())
+                .collect()
+        })
+        .collect();
+    if groups.len() < 2 {
+        return Err("expected at least two groups separated by '|', e.g. 'a,b|c,d'".to_string());
+    }
+    if groups.iter().flatten().any(String::is_empty) {
+        return Err("node ids of a group are separated by ',' and can't be empty".to_string());
+    }
+    Ok(PartitionGroups(groups))
+}
+
+/// Partitions of a network, as recorded in its `partition.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartitionState {
+    pub partitions: Vec<Partition>,
+    /// Containers of services other than daemons, connected to every partition
+    pub shared: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Partition {
+    /// Docker network of the partition
+    pub network: String,
+    /// Node ids of the group
+    pub nodes: Vec<String>,
+    /// Containers of the group's nodes
+    pub containers: Vec<String>,
+}
+
+impl PartitionState {
+    /// Partitions of the daemons of `services` into `groups`, every daemon has to be in
+    /// exactly one group. `containers` are the containers of the network
+    pub fn plan(
+        network_id: &str,
+        services: &[ServiceConfig],
+        groups: &PartitionGroups,
+        containers: &[String],
+    ) -> Result<Self> {
+        let daemons: Vec<&str> = services
+            .iter()
+            .filter(|service| is_daemon(service))
+            .map(|service| service.service_name.as_str())
+            .collect();
+
+        let mut grouped = HashSet::new();
+        for node in groups.0.iter().flatten() {
+            if !daemons.contains(&node.as_str()) {
+                return Err(Error::other(format!(
+                    "'{node}' is not a node of network '{network_id}', nodes: {daemons:?}"
+                )));
+            }
+            if !grouped.insert(node.as_str()) {
+                return Err(Error::other(format!("'{node}' is in more than one group")));
+            }
+        }
+        let ungrouped: Vec<&str> = daemons
+            .iter()
+            .filter(|daemon| !grouped.contains(*daemon))
+            .copied()
+            .collect();
+        if !ungrouped.is_empty() {
+            return Err(Error::other(format!(
+                "Every node has to be in a group, missing: {ungrouped:?}"
+            )));
+        }
+        // e.g. a snark worker can't be cut off from its coordinator
+        let group = |node: &str| groups.0.iter().position(|g| g.iter().any(|n| n == node));
+        let graph = Graph::of(services);
+        for node in groups.0.iter().flatten() {
+            for dependency in graph.service_dependencies(node) {
+                if group(dependency).is_some_and(|other| Some(other) != group(node)) {
+                    return Err(Error::other(format!(
+                        "'{node}' depends on '{dependency}', they have to be in the same group"
+                    )));
+                }
+            }
+        }
+
+        let partitions: Vec<Partition> = groups
+            .0
+            .iter()
+            .enumerate()
+            .map(|(index, nodes)| Partition {
+                network: format!("{network_id}_partition_{}", index + 1),
+                nodes: nodes.clone(),
+                containers: nodes
+                    .iter()
+                    .map(|node| format!("{node}-{network_id}"))
+                    .collect(),
+            })
+            .collect();
+        let shared = containers
+            .iter()
+            .filter(|container| {
+                !partitions
+                    .iter()
+                    .any(|partition| partition.containers.contains(container))
+            })
+            .cloned()
+            .collect();
+        Ok(PartitionState { partitions, shared })
+    }
+
+    fn path(network_path: &Path) -> PathBuf {
+        network_path.join(PARTITION_FILE)
+    }
+
+    /// Partitions of the network at `network_path`, `None` if it is not partitioned
+    pub fn load(network_path: &Path) -> Result<Option<Self>> {
+        match fs::read_to_string(Self::path(network_path)) {
+            Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&self, network_path: &Path) -> Result<()> {
+        fs::write(
+            Self::path(network_path),
+            serde_json::to_string_pretty(self)?,
+        )
+    }
+
+    fn remove(network_path: &Path) -> Result<()> {
+        fs::remove_file(Self::path(network_path))
+    }
+}
+
+/// Daemons take part in the peer graph, the other services are reached by them
+fn is_daemon(service: &ServiceConfig) -> bool {
+    !matches!(
+        service.service_type,
+        ServiceType::ArchiveService | ServiceType::UptimeServiceBackend | ServiceType::Rosetta
+    )
+}
+
+/// Partitions the daemons of the network at `network_path` into `groups`
+pub fn partition(
+    docker: &DockerManager,
+    network_path: &Path,
+    services: &[ServiceConfig],
+    groups: &PartitionGroups,
+) -> Result<PartitionState> {
+    let network_id = network_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .expect("Failed to get network id from network path");
+    if PartitionState::load(network_path)?.is_some() {
+        return Err(Error::other(format!(
+            "Network '{network_id}' is already partitioned, heal it first."
+        )));
+    }
+
+    let containers: Vec<String> = docker
+        .compose_ps(None)?
+        .into_iter()
+        .map(|container| container.name)
+        .collect();
+    let state = PartitionState::plan(network_id, services, groups, &containers)?;
+    // recorded first, so a partition failing halfway can be healed
+    state.save(network_path)?;
+
+    for partition in &state.partitions {
+        info!(
+            "Creating partition '{}' of nodes {:?}",
+            partition.network, partition.nodes
+        );
+        check_output(docker.network_create(&partition.network))?;
+        for container in partition.containers.iter().chain(&state.shared) {
+            check_output(docker.network_connect(&partition.network, container))?;
+        }
+    }
+    let default_network = docker.default_network();
+    for container in state.partitions.iter().flat_map(|p| &p.containers) {
+        check_output(docker.network_disconnect(&default_network, container))?;
+    }
+    Ok(state)
+}
+
+/// Reconnects the daemons of the partitioned network at `network_path` to the default
+/// network and removes the partition networks, going on past the steps that fail
+pub fn heal(docker: &DockerManager, network_path: &Path) -> Result<PartitionState> {
+    let Some(state) = PartitionState::load(network_path)? else {
+        return Err(Error::other("The network is not partitioned."));
+    };
+
+    let default_network = docker.default_network();
+    for container in state.partitions.iter().flat_map(|p| &p.containers) {
+        if let Err(e) = check_output(docker.network_connect(&default_network, container)) {
+            warn!("Failed to reconnect '{container}' to '{default_network}': {e}");
+        }
+    }
+    for partition in &state.partitions {
+        for container in partition.containers.iter().chain(&state.shared) {
+            if let Err(e) = check_output(docker.network_disconnect(&partition.network, container)) {
+                warn!(
+                    "Failed to disconnect '{container}' from '{}': {e}",
+                    partition.network
+                );
+            }
+        }
+        remove_network(docker, &partition.network);
+    }
+    PartitionState::remove(network_path)?;
+    Ok(state)
+}
+
+/// Removes the partition networks left of a network being deleted
+pub fn remove_partition_networks(docker: &DockerManager, network_path: &Path) {
+    if let Ok(Some(state)) = PartitionState::load(network_path) {
+        for partition in &state.partitions {
+            remove_network(docker, &partition.network);
+        }
+    }
+}
+
+fn remove_network(docker: &DockerManager, network: &str) {
+    if let Err(e) = check_output(docker.network_remove(network)) {
+        warn!("Failed to remove partition network '{network}': {e}");
+    }
+}
+
+/// Fault injected into the archive pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFault {
+    /// Stop the archive's postgres
+    StopPostgres,
+    /// Pause the archive server, so it stops writing the blocks of its daemon
+    PauseArchive,
+    /// Delay every block written to the archive database
+    WriteLatency,
+}
+
+/// Injects `fault` into the pipeline of `archive`, whose database is `db`, and lifts
+/// it after `duration`. With [`ArchiveFault::WriteLatency`] each block write is
+/// delayed by `latency`
+pub fn inject_archive_fault(
+    docker: &DockerManager,
+    archive: &ServiceConfig,
+    db: &ArchiveDb,
+    network_id: &str,
+    fault: ArchiveFault,
+    duration: Duration,
+    latency: Duration,
+) -> Result<()> {
+    let postgres = db.service_name(network_id);
+    let archive_server = format!("{}-{network_id}", archive.archive_server());
+
+    info!(
+        "Injecting {fault:?} into archive '{}' for {}s",
+        archive.service_name,
+        duration.as_secs()
+    );
+    match fault {
+        ArchiveFault::StopPostgres => check_output(docker.compose_stop(vec![&postgres]))?,
+        ArchiveFault::PauseArchive => check_output(docker.compose_pause(vec![&archive_server]))?,
+        ArchiveFault::WriteLatency => {
+            docker.set_archive_write_latency(db, network_id, Some(latency))?
+        }
+    }
+
+    thread::sleep(duration);
+
+    info!("Lifting {fault:?} from archive '{}'", archive.service_name);
+    match fault {
+        ArchiveFault::StopPostgres => {
+            check_output(docker.compose_start(vec![&postgres]))?;
+            docker.wait_for_state(&postgres, ContainerState::Running, POSTGRES_START_TIMEOUT)
+        }
+        ArchiveFault::PauseArchive => check_output(docker.compose_unpause(vec![&archive_server])),
+        ArchiveFault::WriteLatency => docker.set_archive_write_latency(db, network_id, None),
+    }
+}
+
+/// Last missing-blocks audit of an archive catching up after a fault
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveAudit {
+    /// Height of the highest block in the archive database
+    pub max_block_height: Option<u64>,
+    /// Heights without a block between the lowest and highest block
+    pub missing_heights: Vec<u64>,
+}
+
+impl ArchiveAudit {
+    /// Whether no block is missing and one above `height` was written, i.e. the
+    /// archive writes the blocks of the chain again
+    pub fn caught_up(&self, height: Option<u64>) -> bool {
+        self.missing_heights.is_empty() && self.max_block_height > height
+    }
+}
+
+/// Audits the archive database of `db` for missing blocks until it has caught up
+/// with the chain since it was at `height`, or `timeout` passed. Audits failing,
+/// e.g. while postgres is recovering, are retried
+pub fn wait_for_archive_catch_up(
+    docker: &DockerManager,
+    db: &ArchiveDb,
+    network_id: &str,
+    height: Option<u64>,
+    timeout: Duration,
+) -> Result<ArchiveAudit> {
+    let started = Instant::now();
+    let mut last = Err(Error::other("archive not audited"));
+    loop {
+        let audit = docker.archive_stats(db, network_id).and_then(|stats| {
+            Ok(ArchiveAudit {
+                max_block_height: stats.max_block_height,
+                missing_heights: docker.archive_missing_heights(db, network_id)?,
+            })
+        });
+        match audit {
+            Ok(audit) => {
+                debug!("Archive audit: {audit:?}");
+                if audit.caught_up(height) {
+                    return Ok(audit);
+                }
+                last = Ok(audit);
+            }
+            Err(e) => {
+                debug!("Archive audit failed: {e}");
+                if last.is_err() {
+                    last = Err(e);
+                }
+            }
+        }
+        if started.elapsed() >= timeout {
+            return last;
+        }
+        thread::sleep(AUDIT_POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    fn services() -> Vec<ServiceConfig> {
+        [
+            ("seed", ServiceType::Seed),
+            ("bp-1", ServiceType::BlockProducer),
+            ("bp-2", ServiceType::BlockProducer),
+            ("archive", ServiceType::ArchiveNode),
+        ]
+        .into_iter()
+        .map(|(name, service_type)| ServiceConfig {
+            service_name: name.to_string(),
+            service_type,
+            ..Default::default()
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_parse_groups() {
+        assert_eq!(
+            parse_groups("a, b|c").unwrap(),
+            PartitionGroups(vec![
+                vec!["a".to_string(), "b".to_string()],
+                vec!["c".to_string()]
+            ])
+        );
+        assert!(parse_groups("a,b").is_err());
+        assert!(parse_groups("a,|c").is_err());
+        assert!(parse_groups("a||c").is_err());
+    }
+
+    #[test]
+    fn test_plan() {
+        let containers: Vec<String> = [
+            "seed-net",
+            "bp-1-net",
+            "bp-2-net",
+            "archive-net",
+            "archive-service-net",
+            "postgres-net",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        let groups = parse_groups("seed,bp-1|bp-2,archive").unwrap();
+
+        let state = PartitionState::plan("net", &services(), &groups, &containers).unwrap();
+        assert_eq!(state.partitions.len(), 2);
+        assert_eq!(state.partitions[0].network, "net_partition_1");
+        assert_eq!(state.partitions[0].containers, vec!["seed-net", "bp-1-net"]);
+        assert_eq!(state.partitions[1].nodes, vec!["bp-2", "archive"]);
+        assert_eq!(state.shared, vec!["archive-service-net", "postgres-net"]);
+
+        // every node in exactly one group
+        let groups = parse_groups("seed,bp-1|bp-2").unwrap();
+        assert!(PartitionState::plan("net", &services(), &groups, &containers).is_err());
+        let groups = parse_groups("seed,bp-1|bp-1,bp-2,archive").unwrap();
+        assert!(PartitionState::plan("net", &services(), &groups, &containers).is_err());
+        let groups = parse_groups("seed,bp-1|bp-2,archive,bp-3").unwrap();
+        assert!(PartitionState::plan("net", &services(), &groups, &containers).is_err());
+    }
+
+    #[test]
+    fn test_state_file() {
+        let tempdir = TempDir::new("test_partition_state").unwrap();
+        assert_eq!(PartitionState::load(tempdir.path()).unwrap(), None);
+
+        let groups = parse_groups("seed,bp-1|bp-2,archive").unwrap();
+        let state = PartitionState::plan("net", &services(), &groups, &[]).unwrap();
+        state.save(tempdir.path()).unwrap();
+        assert_eq!(PartitionState::load(tempdir.path()).unwrap(), Some(state));
+
+        PartitionState::remove(tempdir.path()).unwrap();
+        assert_eq!(PartitionState::load(tempdir.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_plan_keeps_dependencies_together() {
+        let mut services = services();
+        services.push(ServiceConfig {
+            service_name: "coordinator".to_string(),
+            service_type: ServiceType::SnarkCoordinator,
+            ..Default::default()
+        });
+        services.push(ServiceConfig {
+            service_name: "worker".to_string(),
+            service_type: ServiceType::SnarkWorker,
+            snark_coordinator_host: Some("coordinator".to_string()),
+            ..Default::default()
+        });
+
+        let groups = parse_groups("seed,bp-1,coordinator|bp-2,archive,worker").unwrap();
+        let error = PartitionState::plan("net", &services, &groups, &[]).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("'worker' depends on 'coordinator'"));
+
+        let groups = parse_groups("seed,bp-1|bp-2,archive,coordinator,worker").unwrap();
+        assert!(PartitionState::plan("net", &services, &groups, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_archive_audit_caught_up() {
+        let audit = |max_block_height, missing_heights: &[u64]| ArchiveAudit {
+            max_block_height,
+            missing_heights: missing_heights.to_vec(),
+        };
+        assert!(audit(Some(12), &[]).caught_up(Some(10)));
+        assert!(audit(Some(3), &[]).caught_up(None));
+        // gaps left by the fault
+        assert!(!audit(Some(12), &[11]).caught_up(Some(10)));
+        // nothing written since the fault
+        assert!(!audit(Some(10), &[]).caught_up(Some(10)));
+        assert!(!audit(None, &[]).caught_up(None));
+    }
+}
@@ -1,5 +1,6 @@
 //! # `minimina` Command-Line Interface (CLI)
 
+use crate::docker::runtime::Runtime;
 use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser)]
@@ -23,6 +24,126 @@ pub enum Command {
     /// Manage a single node
     #[clap(subcommand)]
     Node(NodeCommand),
+
+    /// Check topology files
+    #[clap(subcommand)]
+    Topology(TopologyCommand),
+
+    /// Run a minimina command and append it to a session file
+    Record(RecordArgs),
+
+    /// Run the commands of a session file again
+    Replay(ReplayArgs),
+
+    /// Inspect what minimina generates without creating a network
+    #[clap(subcommand)]
+    Debug(DebugCommand),
+
+    /// Export the keys of a local network's nodes
+    #[clap(subcommand)]
+    Keys(KeysCommand),
+
+    /// Create and start a network of a single daemon without proofs and with a funded
+    /// account, printing its GraphQL endpoint once it synced
+    Quickstart(QuickstartArgs),
+}
+
+#[derive(Args)]
+pub struct QuickstartArgs {
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Overwrite an existing network without asking for confirmation
+    #[clap(short = 'y', long, default_value_t = false)]
+    pub yes: bool,
+
+    /// Seconds to wait for the daemon to sync before failing
+    #[clap(long, default_value_t = 300)]
+    pub wait_timeout: u64,
+
+    /// Specify log level
+    #[clap(short = 'l', long, default_value = "warn")]
+    pub log_level: String,
+}
+
+#[derive(Subcommand)]
+pub enum KeysCommand {
+    /// Print a node's key pair in the import format of a browser wallet, with the
+    /// GraphQL endpoint to add as the wallet's network
+    Export(KeysExportArgs),
+}
+
+#[derive(Args)]
+pub struct KeysExportArgs {
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Node whose key is exported (defaults to the first block producer)
+    #[clap(short = 'i', long)]
+    pub node_id: Option<String>,
+
+    /// Import format of the wallet
+    #[clap(short, long, value_enum, default_value = "auro")]
+    pub format: crate::keys::WalletFormat,
+}
+
+#[derive(Subcommand)]
+pub enum DebugCommand {
+    /// Print the docker compose file a topology file renders to
+    Render(RenderArgs),
+}
+
+#[derive(Args)]
+pub struct RenderArgs {
+    /// Path to the (JSON) topology file
+    pub topology: std::path::PathBuf,
+
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+}
+
+#[derive(Args)]
+pub struct RecordArgs {
+    /// Session file the command is appended to, created if missing
+    #[clap(short, long)]
+    pub out: std::path::PathBuf,
+
+    /// minimina command to run, e.g. `-- network create -n test`
+    #[clap(last = true, required = true)]
+    pub args: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// Session file recorded with `minimina record`
+    pub session: std::path::PathBuf,
+
+    /// Network to replay the session on, replacing the recorded `--network-id`s
+    #[clap(short, long)]
+    pub network_id: Option<String>,
+
+    /// Wait between commands as long as when they were recorded
+    #[clap(long)]
+    pub keep_timing: bool,
+
+    /// Specify log level
+    #[clap(short = 'l', long, default_value = "info")]
+    pub log_level: String,
+}
+
+#[derive(Subcommand)]
+pub enum TopologyCommand {
+    /// Validate a topology file without creating a network
+    Validate(ValidateTopologyArgs),
+}
+
+#[derive(Args)]
+pub struct ValidateTopologyArgs {
+    /// Path to the (JSON) topology file
+    pub topology: std::path::PathBuf,
 }
 
 #[derive(Subcommand)]
@@ -30,17 +151,177 @@ pub enum NetworkCommand {
     /// Create a local network
     Create(CreateNetworkArgs),
     /// Delete a local network
-    Delete(NetworkId),
+    Delete(DeleteNetworkArgs),
     /// List local networks
     List,
     /// Get status of a local network
-    Status(NetworkId),
+    Status(StatusNetworkArgs),
     /// Get details of a local network
-    Info(NetworkId),
+    Info(InfoNetworkArgs),
     /// Start a local network
     Start(StartNetworkArgs),
     /// Stop a local network
-    Stop(NetworkId),
+    Stop(StopNetworkArgs),
+    /// Show the current slot and epoch of a local network
+    Slots(NetworkId),
+    /// Watch chain height and fail if it stops advancing
+    WatchHeight(WatchHeightArgs),
+    /// Export a local network to a tarball
+    Export(ExportNetworkArgs),
+    /// Import a local network from a tarball created by `network export`
+    Import(ImportNetworkArgs),
+    /// Checkpoint a local network, including its container volumes, to a tarball
+    Snapshot(SnapshotNetworkArgs),
+    /// Restore a snapshot created by `network snapshot` as a new local network
+    Restore(RestoreNetworkArgs),
+    /// Print the o1js `Mina.Network` config and fee payer key of a local network
+    O1jsConfig(O1jsConfigArgs),
+    /// List the genesis accounts of a local network and where their keys are
+    Accounts(NetworkId),
+    /// List the nodes of a local network with their role, image, client port, GraphQL
+    /// URL and container state
+    Nodes(NetworkId),
+    /// Report the network traffic of each running node over an interval
+    NetReport(NetReportArgs),
+    /// Print the logs of all containers of a local network, interleaved
+    Logs(NetworkLogsArgs),
+    /// Create the archive databases and apply their schema, e.g. for networks created
+    /// with `--no-archive-bootstrap` or after wiping postgres data
+    InitArchive(InitArchiveArgs),
+    /// Reconcile the containers of a local network with its directory: re-render the
+    /// compose file, remove stale and unknown containers and create missing ones
+    Repair(NetworkId),
+    /// Recreate the docker state of a local network from its directory, e.g. after its
+    /// containers and volumes were wiped, keeping its keys and ledger
+    Recreate(NetworkId),
+    /// Split the nodes of a local network into partitions that can't reach each other
+    Partition(PartitionNetworkArgs),
+    /// Reconnect the partitions of a local network split by `network partition`
+    Heal(NetworkId),
+    /// Inject a fault into the archive pipeline of a running local network, then audit
+    /// the archive for missing blocks until it has caught up
+    ArchiveFault(ArchiveFaultArgs),
+    /// Report the nodes of a running local network whose chain height fell behind the
+    /// best one, e.g. on a network created with `--profile constrained`
+    LagReport(LagReportArgs),
+    /// Print shell exports pointing external tools at a local network, e.g.
+    /// `eval $(minimina network env -n default)`
+    Env(EnvNetworkArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct PartitionNetworkArgs {
+    /// Network identifier
+    #[clap(short, long, default_value = "default")]
+    pub network_id: String,
+
+    /// Node ids of each partition, nodes separated by ',' and partitions by '|',
+    /// e.g. "mina-seed-1,mina-bp-1|mina-bp-2,mina-archive"
+    #[clap(short, long, value_parser = crate::chaos::parse_groups)]
+    pub groups: crate::chaos::PartitionGroups,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ArchiveFaultArgs {
+    /// Network identifier
+    #[clap(short, long, default_value = "default")]
+    pub network_id: String,
+
+    /// Archive node or service to inject the fault into (defaults to the primary archive)
+    #[clap(short, long)]
+    pub archive: Option<String>,
+
+    /// Fault to inject
+    #[clap(short, long, value_enum)]
+    pub fault: crate::chaos::ArchiveFault,
+
+    /// Seconds the fault lasts
+    #[clap(short, long, default_value_t = 60)]
+    pub duration: u64,
+
+    /// Milliseconds each block write is delayed by with `--fault write-latency`
+    #[clap(long, default_value_t = 500)]
+    pub latency: u64,
+
+    /// Seconds the archive is given to catch up once the fault is lifted
+    #[clap(long, default_value_t = 300)]
+    pub catch_up_timeout: u64,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct LagReportArgs {
+    /// Network identifier
+    #[clap(short, long, default_value = "default")]
+    pub network_id: String,
+
+    /// Blocks a node may be behind the best chain height before it is reported
+    #[clap(short, long, default_value_t = 2)]
+    pub max_lag: u64,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct EnvNetworkArgs {
+    /// Network identifier
+    #[clap(short, long, default_value = "default")]
+    pub network_id: String,
+
+    /// Node the variables point at (defaults to the first seed)
+    #[clap(short = 'i', long)]
+    pub node: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct StatusNetworkArgs {
+    /// Network identifier
+    #[clap(short, long, default_value = "default")]
+    pub network_id: String,
+
+    /// Keep refreshing the status every this many seconds (2 if not given) until interrupted
+    #[clap(short = 'w', long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "2",
+        value_parser = clap::value_parser!(u64).range(1..))]
+    pub watch: Option<u64>,
+
+    /// Output of each refresh in watch mode
+    #[clap(long, value_enum, default_value_t = WatchFormat::Json, requires = "watch")]
+    pub format: WatchFormat,
+}
+
+/// What `network create` deploys the network's services to
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum Target {
+    /// A docker compose project on the container runtime
+    #[default]
+    Docker,
+    /// Kubernetes manifests, applied with `kubectl apply -f`
+    K8s,
+}
+
+/// Output of `network status --watch`
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum WatchFormat {
+    /// One JSON status per line
+    Json,
+    /// A table redrawn in place
+    Table,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DeleteNetworkArgs {
+    /// Network identifier
+    #[clap(short, long, default_value = "default")]
+    pub network_id: String,
+
+    /// Delete without asking for confirmation
+    #[clap(short = 'y', long, default_value_t = false)]
+    pub yes: bool,
+
+    /// Print the containers, volumes, images and directory that would be deleted and exit
+    #[clap(long, default_value_t = false)]
+    pub dry_run: bool,
+
+    /// Also remove the images of the network's services, which other networks may use
+    #[clap(long, default_value_t = false)]
+    pub remove_images: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -64,6 +345,129 @@ pub struct CreateNetworkArgs {
     #[clap(flatten)]
     pub network_id: NetworkId,
 
+    /// Container runtime the network runs on
+    #[clap(long, value_enum, default_value_t = Runtime::Docker)]
+    pub runtime: Runtime,
+
+    /// Create the network on the container runtime, or only render its kubernetes
+    /// manifests to the network directory
+    #[clap(long, value_enum, default_value_t = Target::Docker)]
+    pub target: Target,
+
+    /// Schedule genesis this far in the future, e.g. `10m` or `1h30m`
+    #[clap(long, value_parser = crate::genesis_ledger::parse_delay)]
+    pub genesis_in: Option<chrono::Duration>,
+
+    /// Schedule genesis at this (RFC 3339) timestamp
+    #[clap(long, conflicts_with = "genesis_in", value_parser = crate::genesis_ledger::parse_genesis_timestamp)]
+    pub genesis_timestamp: Option<chrono::DateTime<chrono::FixedOffset>>,
+
+    /// Override the genesis constants of the genesis ledger with a preset
+    #[clap(long, value_enum)]
+    pub preset: Option<crate::genesis_ledger::Preset>,
+
+    /// Smoke test each archive after applying its schema: query its database and
+    /// run a replayer pass over the empty chain
+    #[clap(long, default_value_t = false)]
+    pub verify_archive: bool,
+
+    /// Define the archives without starting postgres, creating their databases and
+    /// applying their schema, e.g. to point them at an already provisioned database
+    /// (see `network init-archive`)
+    #[clap(long, default_value_t = false, conflicts_with = "verify_archive")]
+    pub no_archive_bootstrap: bool,
+
+    /// Generate the default network's key pairs natively instead of in docker containers
+    #[clap(long, default_value_t = false)]
+    pub native_keys: bool,
+
+    /// Fail if the network already exists instead of overwriting it
+    #[clap(long, default_value_t = false)]
+    pub no_overwrite: bool,
+
+    /// Publish the services on free host ports of this range, e.g. `20000-20999`,
+    /// instead of their default ports (or the next free ones)
+    #[clap(long, value_name = "START-END", value_parser = crate::ports::parse_port_range)]
+    pub port_range: Option<std::ops::RangeInclusive<u16>>,
+
+    /// Keep an existing network created from the same topology and genesis ledger,
+    /// printing its info, instead of recreating it; fail if it was created from others
+    #[clap(long, default_value_t = false, conflicts_with_all = ["no_overwrite", "yes"])]
+    pub if_not_exists: bool,
+
+    /// Overwrite an existing network without asking for confirmation
+    #[clap(
+        short = 'y',
+        long,
+        default_value_t = false,
+        conflicts_with = "no_overwrite"
+    )]
+    pub yes: bool,
+
+    /// Run the services on the digests their images resolve to now instead of their
+    /// (possibly moving) tags, so recreated containers keep the same images
+    #[clap(long, default_value_t = false)]
+    pub pin_digests: bool,
+
+    /// Add Prometheus scraping the metrics of every node and Grafana with the Mina
+    /// dashboards to the network
+    #[clap(long, default_value_t = false)]
+    pub with_monitoring: bool,
+
+    /// Add a sidecar per daemon serving `/healthz` on a published port, healthy
+    /// while the daemon is synced
+    #[clap(long, default_value_t = false)]
+    pub with_health_probes: bool,
+
+    /// CPUs each daemon container may use, unless its topology entry sets `cpu_limit`
+    #[clap(long, value_name = "CPUS")]
+    pub cpu_limit: Option<f64>,
+
+    /// Memory each daemon container may use, e.g. `2g`, unless its topology entry
+    /// sets `memory_limit`
+    #[clap(long, value_name = "SIZE")]
+    pub memory_limit: Option<String>,
+
+    /// Resource limits of every daemon simulating their hardware, e.g. `constrained`
+    /// for low-end community machines. `--cpu-limit`, `--memory-limit` and the
+    /// topology's `limits` take precedence
+    #[clap(long, value_enum)]
+    pub profile: Option<crate::service::ResourceProfile>,
+
+    /// Create the network even if its estimated memory and disk exceed those of the
+    /// host
+    #[clap(long, default_value_t = false)]
+    pub ignore_host_resources: bool,
+
+    /// Add this many funded test accounts to the default genesis ledger, their keys in
+    /// `network-keypairs` and their private keys in the ledger, e.g. to deploy zkApps
+    #[clap(long, default_value_t = 0, conflicts_with = "genesis_ledger")]
+    pub extra_accounts: u16,
+
+    /// Fund a named account of `~/.minimina/accounts.toml` in the genesis ledger, e.g.
+    /// `alice=1000`, generating its key pair the first time the name is used
+    #[clap(long, value_name = "NAME=AMOUNT", value_parser = crate::named_accounts::parse_funding)]
+    pub fund: Vec<crate::named_accounts::Funding>,
+
+    /// Number of seed nodes of the default network
+    #[clap(long, default_value_t = 1, conflicts_with = "topology",
+        value_parser = clap::value_parser!(u16).range(1..=100))]
+    pub seeds: u16,
+
+    /// Number of block producers of the default network
+    #[clap(long, default_value_t = 2, conflicts_with = "topology",
+        value_parser = clap::value_parser!(u16).range(1..=100))]
+    pub block_producers: u16,
+
+    /// Number of snark workers of the default network, 0 for no snark coordinator
+    #[clap(long, default_value_t = 1, conflicts_with = "topology",
+        value_parser = clap::value_parser!(u16).range(0..=100))]
+    pub snark_workers: u16,
+
+    /// Leave the archive node out of the default network
+    #[clap(long, default_value_t = false, conflicts_with = "topology")]
+    pub no_archive: bool,
+
     /// Specify log level
     #[clap(short = 'l', long, default_value = "warn")]
     pub log_level: String,
@@ -75,29 +479,295 @@ pub struct StartNetworkArgs {
     #[clap(flatten)]
     pub network_id: NetworkId,
 
-    /// Network identifier
+    /// Report the status and output of `docker compose start`
     #[clap(short = 'v', long, default_value_t = false)]
     pub verbose: bool,
 
+    /// Minutes after genesis the network is considered stale (defaults to `k / 2 * 3`)
+    #[clap(long)]
+    pub stale_genesis_after: Option<u64>,
+
+    /// Don't check whether the genesis timestamp is stale
+    #[clap(long, default_value_t = false)]
+    pub ignore_stale_genesis: bool,
+
+    /// Fail instead of warning when the genesis timestamp is stale
+    #[clap(long, default_value_t = false, conflicts_with = "ignore_stale_genesis")]
+    pub fail_on_stale_genesis: bool,
+
+    /// Set a stale genesis timestamp to now instead of warning, recreating the
+    /// containers and volumes of the network so its nodes start from the new genesis
+    #[clap(long, default_value_t = false,
+        conflicts_with_all = ["ignore_stale_genesis", "fail_on_stale_genesis"])]
+    pub refresh_genesis: bool,
+
+    /// Start the network even if its topology file or genesis ledger changed since it
+    /// was created, only warning about it
+    #[clap(long, default_value_t = false)]
+    pub force: bool,
+
+    /// Only start the optional services of these compose profiles, e.g.
+    /// `archive,monitoring`, or none of them if no profile is given
+    #[clap(long, value_enum, value_delimiter = ',', num_args = 0..)]
+    pub profiles: Option<Vec<crate::docker::compose::ComposeProfile>>,
+
+    /// Wait for all nodes to report `SYNCED` over GraphQL before returning
+    #[clap(long, default_value_t = false)]
+    pub wait: bool,
+
+    /// Seconds to wait for the nodes to sync before failing
+    #[clap(long, default_value_t = 900, requires = "wait")]
+    pub wait_timeout: u64,
+
+    /// Specify log level
+    #[clap(short = 'l', long, default_value = "warn")]
+    pub log_level: String,
+}
+
+#[derive(Args, Clone)]
+pub struct WatchHeightArgs {
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Seconds the chain height may stay unchanged before it is considered stalled
+    #[clap(short = 'w', long, default_value_t = 600)]
+    pub stall_window: u64,
+
+    /// Seconds between polls of the nodes' chain height
+    #[clap(short = 'p', long, default_value_t = 10)]
+    pub poll_interval: u64,
+
+    /// Stop watching successfully after this many seconds (watch forever if omitted)
+    #[clap(short = 'd', long)]
+    pub duration: Option<u64>,
+
+    /// Webhook (e.g. Slack) receiving a JSON alert when a node crashes, restarts
+    /// repeatedly or the chain stalls
+    #[clap(long)]
+    pub webhook_url: Option<String>,
+
+    /// Restarts of a node while watching after which an alert is posted
+    #[clap(long, default_value_t = 3)]
+    pub restart_threshold: u32,
+
+    /// Specify log level
+    #[clap(short = 'l', long, default_value = "warn")]
+    pub log_level: String,
+}
+
+#[derive(Args, Clone)]
+pub struct ExportNetworkArgs {
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Path of the tarball to write
+    #[clap(short = 'o', long)]
+    pub output: std::path::PathBuf,
+
+    /// Include a `pg_dump` of the archive database (the network must be running)
+    #[clap(short = 'a', long, default_value_t = false)]
+    pub archive_data: bool,
+
+    /// Specify log level
+    #[clap(short = 'l', long, default_value = "warn")]
+    pub log_level: String,
+}
+
+#[derive(Args, Clone)]
+pub struct ImportNetworkArgs {
+    /// Path of the tarball to import
+    #[clap(short = 'i', long)]
+    pub input: std::path::PathBuf,
+
+    /// Specify log level
+    #[clap(short = 'l', long, default_value = "warn")]
+    pub log_level: String,
+}
+
+#[derive(Args, Clone)]
+pub struct StopNetworkArgs {
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Stop at most this many containers at a time (all at once by default)
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub stop_concurrency: Option<u64>,
+
+    /// Seconds each batch of containers is given to stop before being killed
+    /// (defaults to the services' stop_grace_period)
+    #[clap(long, visible_alias = "timeout")]
+    pub stop_timeout: Option<u64>,
+
+    /// Stop the containers right away instead of asking their daemons to flush their
+    /// state and shut down with `mina client stop-daemon` first
+    #[clap(long, default_value_t = false)]
+    pub no_flush: bool,
+
+    /// Daemons are flushed by default, kept for compatibility
+    #[clap(
+        long,
+        default_value_t = false,
+        hide = true,
+        conflicts_with = "no_flush"
+    )]
+    pub flush: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct InfoNetworkArgs {
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Add the best chain height of each daemon and block statistics of each
+    /// archive database (the network must be running)
+    #[clap(long, default_value_t = false)]
+    pub live: bool,
+
+    /// Print the dependency graph of the network's services instead
+    #[clap(long, default_value_t = false, conflicts_with = "live")]
+    pub graph: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct SnapshotNetworkArgs {
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Path of the tarball to write
+    #[clap(short = 'o', long)]
+    pub output: std::path::PathBuf,
+
+    /// Specify log level
+    #[clap(short = 'l', long, default_value = "warn")]
+    pub log_level: String,
+}
+
+#[derive(Args, Clone)]
+pub struct RestoreNetworkArgs {
+    /// Identifier of the restored network
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Path of the snapshot to restore
+    #[clap(short = 'i', long)]
+    pub input: std::path::PathBuf,
+
+    /// Specify log level
+    #[clap(short = 'l', long, default_value = "warn")]
+    pub log_level: String,
+}
+
+#[derive(Args, Clone)]
+pub struct InitArchiveArgs {
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Archive node or service to initialize (all archives by default)
+    #[clap(short = 'a', long)]
+    pub archive: Option<String>,
+
+    /// Drop existing archive databases first, their archives must be stopped
+    #[clap(long, default_value_t = false)]
+    pub reset: bool,
+
+    /// Smoke test each archive after applying its schema
+    #[clap(long, default_value_t = false)]
+    pub verify_archive: bool,
+
     /// Specify log level
     #[clap(short = 'l', long, default_value = "warn")]
     pub log_level: String,
 }
 
+#[derive(Args, Clone)]
+pub struct O1jsConfigArgs {
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Node whose account pays the fees (defaults to the first block producer)
+    #[clap(short = 'f', long)]
+    pub fee_payer: Option<String>,
+
+    /// Archive node API endpoint to include, as minimina does not run one
+    #[clap(short = 'a', long)]
+    pub archive_endpoint: Option<String>,
+
+    /// Print a JavaScript snippet instead of JSON
+    #[clap(short = 'r', long, default_value_t = false)]
+    pub raw_output: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct NetworkLogsArgs {
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Keep printing new log lines as they are written, one JSON object per line
+    #[clap(short = 'f', long, default_value_t = false)]
+    pub follow: bool,
+
+    /// Only logs since a timestamp (e.g. `2024-01-02T13:23:37Z`) or a relative time (e.g. `10m`)
+    #[clap(long)]
+    pub since: Option<String>,
+
+    /// Raw output (`docker compose logs` lines, not wrapped in JSON)
+    #[clap(short = 'r', long, default_value_t = false)]
+    pub raw_output: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct NetReportArgs {
+    /// Network identifier
+    #[clap(flatten)]
+    pub network_id: NetworkId,
+
+    /// Seconds between the two samples of the nodes' traffic counters
+    #[clap(short = 'i', long, default_value_t = 10)]
+    pub interval: u64,
+}
+
 #[derive(Subcommand)]
 pub enum NodeCommand {
     /// Start a node
     Start(StartNodeCommandArgs),
     /// Stop a node
-    Stop(NodeCommandArgs),
+    Stop(StopNodeArgs),
+    /// Stop a node, recreate its container and start it again, waiting for its GraphQL server
+    Restart(RestartNodeArgs),
     /// Dump the node's logs to stdout
-    Logs(NodeCommandArgs),
+    Logs(LogsArgs),
     /// Dump the node's precomputed blocks to stdout
     DumpPrecomputedBlocks(NodeCommandArgs),
     /// Dump an archive node's data
-    DumpArchiveData(NodeCommandArgs),
+    DumpArchiveData(DumpArchiveDataArgs),
+    /// Replace an archive node's data by a dump, e.g. one of another network
+    RestoreArchiveData(RestoreArchiveDataArgs),
     /// Run the replayer on an archive node's db
     RunReplayer(ReplayerArgs),
+    /// Bundle the exit state, last logs and crash files of a dead node
+    CollectCrash(NodeCommandArgs),
+    /// Create a new account, import it into the node's wallet and fund it from a genesis account
+    CreateAccount(CreateAccountArgs),
+    /// Delegate the stake of a locally stored account through the node
+    Delegate(DelegateArgs),
+    /// Send a GraphQL query to a node and print its JSON response
+    Query(QueryArgs),
+    /// Import the genesis accounts of the network into the node's wallet
+    ImportAccounts(NodeCommandArgs),
+    /// Get the docker info of the node's container
+    Ps(NodeCommandArgs),
+    /// Run a command in the node's container, e.g. `node exec -i mina-bp-1 -- mina client status`
+    Exec(ExecArgs),
+    /// Run `mina client` against the node's daemon, e.g. `node client -i mina-bp-1 -- status`
+    Client(ClientArgs),
 }
 
 #[derive(Args, Debug)]
@@ -126,6 +796,17 @@ pub struct NodeCommandArgs {
     pub raw_output: bool,
 }
 
+#[derive(Args, Debug)]
+pub struct StopNodeArgs {
+    /// Ask the daemon to flush its state and shut down with
+    /// `mina client stop-daemon` before stopping it
+    #[clap(long, default_value_t = false)]
+    pub flush: bool,
+
+    #[clap(flatten)]
+    pub node_args: NodeCommandArgs,
+}
+
 #[derive(Args, Debug)]
 pub struct StartNodeCommandArgs {
     /// Start node with fresh state
@@ -145,18 +826,191 @@ pub struct StartNodeCommandArgs {
 }
 
 #[derive(Args, Debug)]
-pub struct ReplayerArgs {
-    /// Global slot since genesis
-    #[clap(short = 's', long)]
-    pub start_slot_since_genesis: u64,
+pub struct RestartNodeArgs {
+    /// Drop the node's volumes, e.g. its config directory, when recreating it
+    #[clap(short = 'f', long, default_value_t = false)]
+    pub fresh_state: bool,
+
+    /// Import genesis accounts from network-keypairs before starting the node
+    #[clap(short = 'a', long, default_value_t = false)]
+    pub import_accounts: bool,
+
+    /// Ask the daemon to flush its state and shut down with
+    /// `mina client stop-daemon` before stopping it
+    #[clap(long, default_value_t = false)]
+    pub flush: bool,
 
     #[clap(flatten)]
     pub node_args: NodeCommandArgs,
 }
 
-pub trait DefaultLogLevel {
-    fn log_level(&self) -> &str;
-}
+#[derive(Args, Debug)]
+pub struct LogsArgs {
+    /// Fetch the daemon's log files (mina.log, best tip, verifier and prover logs)
+    /// from the config directory into the network directory instead
+    #[clap(long, default_value_t = false, conflicts_with_all = ["filters", "json_lines", "follow"])]
+    pub internal: bool,
+
+    /// Only print the JSON log lines whose field matches, e.g. `level=Error` or
+    /// `module=Gossip`; repeat for several fields, or several values of one field
+    #[clap(long = "filter", value_name = "FIELD=VALUE", value_parser = crate::log_filter::parse_log_filter)]
+    pub filters: Vec<crate::log_filter::LogFilter>,
+
+    /// Print each log line as a JSON record (timestamp, level, module, location,
+    /// message and metadata) on its own line, as it is read
+    #[clap(long, default_value_t = false)]
+    pub json_lines: bool,
+
+    /// Keep printing new log lines as they are written
+    #[clap(short = 'f', long, default_value_t = false)]
+    pub follow: bool,
+
+    #[clap(flatten)]
+    pub node_args: NodeCommandArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct DumpArchiveDataArgs {
+    /// Write the dump to this file instead of stdout
+    #[clap(short = 'o', long, conflicts_with = "interval")]
+    pub output: Option<std::path::PathBuf>,
+
+    /// Keep dumping every interval, e.g. `10m` or `1h`, to timestamped files in
+    /// `--output-dir` until interrupted
+    #[clap(long, value_parser = crate::genesis_ledger::parse_delay, requires = "output_dir")]
+    pub interval: Option<chrono::Duration>,
+
+    /// Directory the dumps of `--interval` are written to
+    #[clap(long, requires = "interval")]
+    pub output_dir: Option<std::path::PathBuf>,
+
+    /// Number of dumps of `--interval` to keep, removing the oldest ones (all are kept
+    /// if not set)
+    #[clap(long, requires = "interval",
+        value_parser = clap::value_parser!(u32).range(1..))]
+    pub retain: Option<u32>,
+
+    #[clap(flatten)]
+    pub node_args: NodeCommandArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct RestoreArchiveDataArgs {
+    /// `pg_dump` file of an archive database, e.g. written by `node dump-archive-data --output`
+    #[clap(long)]
+    pub input: std::path::PathBuf,
+
+    #[clap(flatten)]
+    pub node_args: NodeCommandArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayerArgs {
+    /// Global slot since genesis
+    #[clap(short = 's', long)]
+    pub start_slot_since_genesis: u64,
+
+    /// Replay a clone of the archive database instead of the live one, so a long
+    /// replay does not contend with the archive writer, which is stopped while cloning
+    #[clap(long, default_value_t = false)]
+    pub snapshot: bool,
+
+    /// State hash to replay up to, whose epoch ledgers the replayer outputs
+    #[clap(long)]
+    pub target_state_hash: Option<String>,
+
+    /// Name of the file in the network directory the replayed ledger is saved to
+    /// instead of being discarded
+    #[clap(long)]
+    pub output_file: Option<String>,
+
+    #[clap(flatten)]
+    pub node_args: NodeCommandArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct CreateAccountArgs {
+    /// Name of the account's key file in the network's accounts directory,
+    /// made of letters, digits, '-' and '_'
+    #[clap(long, value_parser = crate::utils::parse_account_name)]
+    pub account_name: String,
+
+    /// Genesis key pair (in network-keypairs) funding the account,
+    /// defaults to the node's own key or the first genesis key pair
+    #[clap(short = 'f', long)]
+    pub funder: Option<String>,
+
+    /// Amount of MINA sent to the account
+    #[clap(short = 'a', long, default_value = "1000", value_parser = crate::utils::parse_mina_amount)]
+    pub amount: u64,
+
+    /// Fee of the funding payment in MINA
+    #[clap(long, default_value = "0.1", value_parser = crate::utils::parse_mina_amount)]
+    pub fee: u64,
+
+    #[clap(flatten)]
+    pub node_args: NodeCommandArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct DelegateArgs {
+    /// Public key of the delegating account, whose key file is stored in the network directory
+    #[clap(long)]
+    pub from: String,
+
+    /// Public key of the block producer receiving the stake
+    #[clap(long)]
+    pub to: String,
+
+    /// Fee of the delegation in MINA
+    #[clap(long, default_value = "0.1", value_parser = crate::utils::parse_mina_amount)]
+    pub fee: u64,
+
+    /// Wait for the delegation to be included in the node's best chain
+    #[clap(short = 'w', long, default_value_t = false)]
+    pub wait: bool,
+
+    /// Seconds to wait for the inclusion
+    #[clap(long, default_value_t = 600)]
+    pub wait_timeout: u64,
+
+    #[clap(flatten)]
+    pub node_args: NodeCommandArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct QueryArgs {
+    /// GraphQL query, e.g. '{ syncStatus }'
+    #[clap(short = 'q', long)]
+    pub query: String,
+
+    #[clap(flatten)]
+    pub node_args: NodeCommandArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ExecArgs {
+    /// Command and its arguments, after `--`
+    #[clap(last = true, required = true)]
+    pub command: Vec<String>,
+
+    #[clap(flatten)]
+    pub node_args: NodeCommandArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ClientArgs {
+    /// Arguments of `mina client`, after `--`; `-daemon-port` is added unless given
+    #[clap(last = true, required = true)]
+    pub args: Vec<String>,
+
+    #[clap(flatten)]
+    pub node_args: NodeCommandArgs,
+}
+
+pub trait DefaultLogLevel {
+    fn log_level(&self) -> &str;
+}
 
 trait LogLevel {
     fn log_level(&self) -> &str;
@@ -201,31 +1055,131 @@ macro_rules! node_id {
 }
 
 log_level!(StartNetworkArgs);
+log_level!(ReplayArgs);
 log_level!(CreateNetworkArgs);
 log_level!(NodeCommandArgs);
+log_level!(WatchHeightArgs);
+log_level!(ExportNetworkArgs);
+log_level!(ImportNetworkArgs);
+log_level!(SnapshotNetworkArgs);
+log_level!(RestoreNetworkArgs);
+log_level!(InitArchiveArgs);
+log_level!(QuickstartArgs);
 
 network_id!(StartNetworkArgs);
+network_id!(StopNetworkArgs);
 network_id!(CreateNetworkArgs);
 network_id!(NodeCommandArgs);
+network_id!(WatchHeightArgs);
+network_id!(ExportNetworkArgs);
+network_id!(InfoNetworkArgs);
+network_id!(SnapshotNetworkArgs);
+network_id!(RestoreNetworkArgs);
+network_id!(O1jsConfigArgs);
+network_id!(NetReportArgs);
+network_id!(NetworkLogsArgs);
+network_id!(InitArchiveArgs);
+network_id!(KeysExportArgs);
+network_id!(QuickstartArgs);
 
 node_id!(NodeCommandArgs);
 
+impl Command {
+    /// Existing network the command operates on
+    pub fn network_id(&self) -> Option<&str> {
+        match self {
+            Command::Network(cmd) => match cmd {
+                NetworkCommand::Delete(args) => Some(&args.network_id),
+                NetworkCommand::Status(args) => Some(&args.network_id),
+                NetworkCommand::Slots(args)
+                | NetworkCommand::Accounts(args)
+                | NetworkCommand::Nodes(args)
+                | NetworkCommand::Repair(args)
+                | NetworkCommand::Recreate(args)
+                | NetworkCommand::Heal(args) => Some(&args.network_id),
+                NetworkCommand::Partition(args) => Some(&args.network_id),
+                NetworkCommand::ArchiveFault(args) => Some(&args.network_id),
+                NetworkCommand::LagReport(args) => Some(&args.network_id),
+                NetworkCommand::Env(args) => Some(&args.network_id),
+                NetworkCommand::Start(args) => Some(args.network_id()),
+                NetworkCommand::Stop(args) => Some(args.network_id()),
+                NetworkCommand::Info(args) => Some(args.network_id()),
+                NetworkCommand::WatchHeight(args) => Some(args.network_id()),
+                NetworkCommand::Export(args) => Some(args.network_id()),
+                NetworkCommand::Snapshot(args) => Some(args.network_id()),
+                NetworkCommand::O1jsConfig(args) => Some(args.network_id()),
+                NetworkCommand::NetReport(args) => Some(args.network_id()),
+                NetworkCommand::Logs(args) => Some(args.network_id()),
+                NetworkCommand::InitArchive(args) => Some(args.network_id()),
+                NetworkCommand::Create(_)
+                | NetworkCommand::List
+                | NetworkCommand::Import(_)
+                | NetworkCommand::Restore(_) => None,
+            },
+            Command::Node(cmd) => Some(match cmd {
+                NodeCommand::DumpPrecomputedBlocks(args)
+                | NodeCommand::CollectCrash(args)
+                | NodeCommand::ImportAccounts(args)
+                | NodeCommand::Ps(args) => args.network_id(),
+                NodeCommand::Stop(args) => args.node_args.network_id(),
+                NodeCommand::Restart(args) => args.node_args.network_id(),
+                NodeCommand::Logs(args) => args.node_args.network_id(),
+                NodeCommand::Start(args) => args.node_args.network_id(),
+                NodeCommand::DumpArchiveData(args) => args.node_args.network_id(),
+                NodeCommand::RestoreArchiveData(args) => args.node_args.network_id(),
+                NodeCommand::RunReplayer(args) => args.node_args.network_id(),
+                NodeCommand::CreateAccount(args) => args.node_args.network_id(),
+                NodeCommand::Delegate(args) => args.node_args.network_id(),
+                NodeCommand::Query(args) => args.node_args.network_id(),
+                NodeCommand::Exec(args) => args.node_args.network_id(),
+                NodeCommand::Client(args) => args.node_args.network_id(),
+            }),
+            Command::Keys(KeysCommand::Export(args)) => Some(args.network_id()),
+            Command::Quickstart(args) => Some(args.network_id()),
+            Command::Topology(_) | Command::Record(_) | Command::Replay(_) | Command::Debug(_) => {
+                None
+            }
+        }
+    }
+}
+
 impl DefaultLogLevel for Command {
     fn log_level(&self) -> &str {
         match self {
             Command::Network(cmd) => match cmd {
                 NetworkCommand::Create(args) => args.log_level(),
                 NetworkCommand::Start(args) => args.log_level(),
+                NetworkCommand::WatchHeight(args) => args.log_level(),
+                NetworkCommand::Export(args) => args.log_level(),
+                NetworkCommand::Import(args) => args.log_level(),
+                NetworkCommand::Snapshot(args) => args.log_level(),
+                NetworkCommand::Restore(args) => args.log_level(),
+                NetworkCommand::InitArchive(args) => args.log_level(),
                 _ => "warn",
             },
             Command::Node(cmd) => match cmd {
-                NodeCommand::DumpArchiveData(args)
-                | NodeCommand::DumpPrecomputedBlocks(args)
-                | NodeCommand::Logs(args)
-                | NodeCommand::Stop(args) => args.log_level(),
+                NodeCommand::DumpPrecomputedBlocks(args)
+                | NodeCommand::CollectCrash(args)
+                | NodeCommand::ImportAccounts(args)
+                | NodeCommand::Ps(args) => args.log_level(),
+                NodeCommand::Stop(args) => args.node_args.log_level(),
+                NodeCommand::Restart(args) => args.node_args.log_level(),
+                NodeCommand::Logs(args) => args.node_args.log_level(),
                 NodeCommand::Start(args) => args.node_args.log_level(),
+                NodeCommand::DumpArchiveData(args) => args.node_args.log_level(),
+                NodeCommand::RestoreArchiveData(args) => args.node_args.log_level(),
                 NodeCommand::RunReplayer(args) => args.node_args.log_level(),
+                NodeCommand::CreateAccount(args) => args.node_args.log_level(),
+                NodeCommand::Delegate(args) => args.node_args.log_level(),
+                NodeCommand::Query(args) => args.node_args.log_level(),
+                NodeCommand::Exec(args) => args.node_args.log_level(),
+                NodeCommand::Client(args) => args.node_args.log_level(),
             },
+            Command::Replay(args) => args.log_level(),
+            Command::Quickstart(args) => args.log_level(),
+            Command::Topology(_) | Command::Record(_) | Command::Debug(_) | Command::Keys(_) => {
+                "warn"
+            }
         }
     }
 }
@@ -246,12 +1200,15 @@ mod tests {
             "/path/to/dir",
             "--network-id",
             "test",
+            "--verify-archive",
         ];
 
         let cli = Cli::parse_from(args);
 
         match cli.command {
             Command::Network(NetworkCommand::Create(args)) => {
+                assert!(args.verify_archive);
+                assert!(!args.no_archive_bootstrap);
                 assert_eq!(
                     args.topology,
                     Some(std::path::PathBuf::from("/path/to/file"))
@@ -266,6 +1223,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_network_create_scheduled_genesis() {
+        let args = vec!["minimina", "network", "create", "--genesis-in", "1h30m"];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert_eq!(args.genesis_in, Some(chrono::Duration::minutes(90)));
+                assert_eq!(args.genesis_timestamp, None);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec![
+            "minimina",
+            "network",
+            "create",
+            "--genesis-in",
+            "10m",
+            "--genesis-timestamp",
+            "2030-01-01T00:00:00Z",
+        ];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
     #[test]
     fn test_network_delete_command() {
         let args = vec!["minimina", "network", "delete", "--network-id", "test"];
@@ -301,9 +1284,66 @@ mod tests {
         match cli.command {
             Command::Network(NetworkCommand::Start(args)) => {
                 assert_eq!(args.network_id(), "test");
+                assert_eq!(args.stale_genesis_after, None);
+                assert!(!args.ignore_stale_genesis);
+                assert!(!args.fail_on_stale_genesis);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_start_stale_genesis_flags() {
+        let args = vec![
+            "minimina",
+            "network",
+            "start",
+            "--stale-genesis-after",
+            "60",
+            "--fail-on-stale-genesis",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Network(NetworkCommand::Start(args)) => {
+                assert_eq!(args.stale_genesis_after, Some(60));
+                assert!(args.fail_on_stale_genesis);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec![
+            "minimina",
+            "network",
+            "start",
+            "--ignore-stale-genesis",
+            "--fail-on-stale-genesis",
+        ];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_network_start_refresh_genesis() {
+        let cli = Cli::parse_from(["minimina", "network", "start", "--refresh-genesis"]);
+        match cli.command {
+            Command::Network(NetworkCommand::Start(args)) => {
+                assert!(args.refresh_genesis);
+                assert!(!args.fail_on_stale_genesis);
             }
             _ => panic!("Unexpected command parsed"),
         }
+
+        for conflicting in ["--ignore-stale-genesis", "--fail-on-stale-genesis"] {
+            let args = [
+                "minimina",
+                "network",
+                "start",
+                "--refresh-genesis",
+                conflicting,
+            ];
+            assert!(Cli::try_parse_from(args).is_err());
+        }
     }
 
     #[test]
@@ -314,109 +1354,1467 @@ mod tests {
 
         match cli.command {
             Command::Network(NetworkCommand::Stop(args)) => {
-                assert_eq!(args.network_id, "test");
+                assert_eq!(args.network_id(), "test");
+                assert_eq!(args.stop_concurrency, None);
+                assert_eq!(args.stop_timeout, None);
+                assert!(!args.no_flush);
             }
             _ => panic!("Unexpected command parsed"),
         }
     }
 
     #[test]
-    fn test_node_start_command() {
-        let args = vec!["minimina", "node", "start", "--node-id", "test"];
+    fn test_network_slots_command() {
+        let args = vec!["minimina", "network", "slots", "--network-id", "test"];
 
         let cli = Cli::parse_from(args);
 
         match cli.command {
-            Command::Node(NodeCommand::Start(args)) => {
-                assert_eq!(args.node_args.node_id(), "test");
-                assert_eq!(args.node_args.network_id(), "default");
-                assert!(!args.fresh_state);
+            Command::Network(NetworkCommand::Slots(args)) => {
+                assert_eq!(args.network_id, "test");
             }
             _ => panic!("Unexpected command parsed"),
         }
     }
 
     #[test]
-    fn test_node_start_fresh_state() {
+    fn test_network_watch_height_command() {
         let args = vec![
             "minimina",
-            "node",
-            "start",
-            "--node-id",
+            "network",
+            "watch-height",
+            "--network-id",
             "test",
-            "--fresh-state",
+            "--stall-window",
+            "120",
+            "--duration",
+            "3600",
+            "--webhook-url",
+            "https://hooks.example.com/minimina",
         ];
 
         let cli = Cli::parse_from(args);
 
         match cli.command {
-            Command::Node(NodeCommand::Start(args)) => {
-                assert_eq!(args.node_args.node_id(), "test");
-                assert_eq!(args.node_args.network_id(), "default");
-                assert!(args.fresh_state);
+            Command::Network(NetworkCommand::WatchHeight(args)) => {
+                assert_eq!(args.network_id(), "test");
+                assert_eq!(args.stall_window, 120);
+                assert_eq!(args.poll_interval, 10);
+                assert_eq!(args.duration, Some(3600));
+                assert_eq!(
+                    args.webhook_url.as_deref(),
+                    Some("https://hooks.example.com/minimina")
+                );
+                assert_eq!(args.restart_threshold, 3);
             }
             _ => panic!("Unexpected command parsed"),
         }
     }
 
     #[test]
-    fn test_node_stop_command() {
+    fn test_network_export_command() {
         let args = vec![
             "minimina",
-            "node",
-            "stop",
-            "--node-id",
-            "test",
+            "network",
+            "export",
             "--network-id",
-            "banana",
+            "test",
+            "--output",
+            "/tmp/test.tar.gz",
+            "--archive-data",
         ];
 
         let cli = Cli::parse_from(args);
 
         match cli.command {
-            Command::Node(NodeCommand::Stop(args)) => {
-                assert_eq!(args.node_id(), "test");
-                assert_eq!(args.network_id(), "banana");
+            Command::Network(NetworkCommand::Export(args)) => {
+                assert_eq!(args.network_id(), "test");
+                assert_eq!(args.output, std::path::PathBuf::from("/tmp/test.tar.gz"));
+                assert!(args.archive_data);
             }
             _ => panic!("Unexpected command parsed"),
         }
     }
 
     #[test]
-    fn test_node_logs_command() {
-        let args = vec!["minimina", "node", "logs", "--node-id", "test"];
+    fn test_network_import_command() {
+        let args = vec!["minimina", "network", "import", "--input", "test.tar.gz"];
 
         let cli = Cli::parse_from(args);
 
         match cli.command {
-            Command::Node(NodeCommand::Logs(args)) => {
-                assert_eq!(args.node_id(), "test");
-                assert_eq!(args.network_id(), "default");
+            Command::Network(NetworkCommand::Import(args)) => {
+                assert_eq!(args.input, std::path::PathBuf::from("test.tar.gz"));
             }
             _ => panic!("Unexpected command parsed"),
         }
     }
 
     #[test]
-    fn test_node_dump_precomputed_blocks() {
+    fn test_network_create_runtime() {
+        let cli = Cli::parse_from(["minimina", "network", "create", "--runtime", "podman"]);
+
+        match cli.command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert_eq!(args.runtime, Runtime::Podman);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let cli = Cli::parse_from(["minimina", "network", "create"]);
+        match &cli.command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert_eq!(args.runtime, Runtime::Docker);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+        assert_eq!(cli.command.network_id(), None);
+
+        let cli = Cli::parse_from(["minimina", "node", "stop", "-n", "net", "-i", "bp"]);
+        assert_eq!(cli.command.network_id(), Some("net"));
+    }
+
+    #[test]
+    fn test_network_snapshot_command() {
         let args = vec![
             "minimina",
-            "node",
-            "dump-precomputed-blocks",
-            "--node-id",
-            "test_node",
+            "network",
+            "snapshot",
+            "-n",
+            "my-net",
+            "-o",
+            "snap.tar.gz",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Network(NetworkCommand::Snapshot(args)) => {
+                assert_eq!(args.network_id(), "my-net");
+                assert_eq!(args.output, std::path::PathBuf::from("snap.tar.gz"));
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_restore_command() {
+        let args = vec![
+            "minimina",
+            "network",
+            "restore",
             "--network-id",
-            "test_network",
+            "my-copy",
+            "--input",
+            "snap.tar.gz",
         ];
 
         let cli = Cli::parse_from(args);
 
         match cli.command {
-            Command::Node(NodeCommand::DumpPrecomputedBlocks(args)) => {
-                assert_eq!(args.node_id(), "test_node");
-                assert_eq!(args.network_id(), "test_network");
+            Command::Network(NetworkCommand::Restore(args)) => {
+                assert_eq!(args.network_id(), "my-copy");
+                assert_eq!(args.input, std::path::PathBuf::from("snap.tar.gz"));
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_o1js_config_command() {
+        let args = vec![
+            "minimina",
+            "network",
+            "o1js-config",
+            "--fee-payer",
+            "mina-bp-2",
+            "--raw-output",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Network(NetworkCommand::O1jsConfig(args)) => {
+                assert_eq!(args.network_id(), "default");
+                assert_eq!(args.fee_payer.as_deref(), Some("mina-bp-2"));
+                assert_eq!(args.archive_endpoint, None);
+                assert!(args.raw_output);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_accounts_command() {
+        let args = vec!["minimina", "network", "accounts", "--network-id", "test"];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Network(NetworkCommand::Accounts(args)) => {
+                assert_eq!(args.network_id, "test");
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_net_report_command() {
+        let args = vec!["minimina", "network", "net-report", "--interval", "30"];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Network(NetworkCommand::NetReport(args)) => {
+                assert_eq!(args.network_id(), "default");
+                assert_eq!(args.interval, 30);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_start_command() {
+        let args = vec!["minimina", "node", "start", "--node-id", "test"];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::Start(args)) => {
+                assert_eq!(args.node_args.node_id(), "test");
+                assert_eq!(args.node_args.network_id(), "default");
+                assert!(!args.fresh_state);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_start_fresh_state() {
+        let args = vec![
+            "minimina",
+            "node",
+            "start",
+            "--node-id",
+            "test",
+            "--fresh-state",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::Start(args)) => {
+                assert_eq!(args.node_args.node_id(), "test");
+                assert_eq!(args.node_args.network_id(), "default");
+                assert!(args.fresh_state);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_stop_command() {
+        let args = vec![
+            "minimina",
+            "node",
+            "stop",
+            "--node-id",
+            "test",
+            "--network-id",
+            "banana",
+            "--flush",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::Stop(args)) => {
+                assert_eq!(args.node_args.node_id(), "test");
+                assert_eq!(args.node_args.network_id(), "banana");
+                assert!(args.flush);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_logs_command() {
+        let args = vec!["minimina", "node", "logs", "--node-id", "test"];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::Logs(args)) => {
+                assert_eq!(args.node_args.node_id(), "test");
+                assert_eq!(args.node_args.network_id(), "default");
+                assert!(!args.internal);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_logs_internal_command() {
+        let args = vec![
+            "minimina",
+            "node",
+            "logs",
+            "--node-id",
+            "test",
+            "--internal",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::Logs(args)) => {
+                assert_eq!(args.node_args.node_id(), "test");
+                assert!(args.internal);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_logs_filter_command() {
+        let cli = Cli::parse_from([
+            "minimina",
+            "node",
+            "logs",
+            "-i",
+            "test",
+            "--filter",
+            "level=Error",
+            "--filter",
+            "module=Gossip",
+            "--json-lines",
+        ]);
+        match cli.command {
+            Command::Node(NodeCommand::Logs(args)) => {
+                assert_eq!(args.filters.len(), 2);
+                assert_eq!(args.filters[1].field, "module");
+                assert_eq!(args.filters[1].value, "Gossip");
+                assert!(args.json_lines);
+                assert!(!args.follow);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        assert!(Cli::try_parse_from([
+            "minimina", "node", "logs", "-i", "test", "--filter", "level"
+        ])
+        .is_err());
+        assert!(Cli::try_parse_from([
+            "minimina",
+            "node",
+            "logs",
+            "-i",
+            "test",
+            "--internal",
+            "--json-lines"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_node_collect_crash_command() {
+        let args = vec![
+            "minimina",
+            "node",
+            "collect-crash",
+            "--node-id",
+            "test_node",
+            "--network-id",
+            "test_network",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::CollectCrash(args)) => {
+                assert_eq!(args.node_id(), "test_node");
+                assert_eq!(args.network_id(), "test_network");
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_dump_precomputed_blocks() {
+        let args = vec![
+            "minimina",
+            "node",
+            "dump-precomputed-blocks",
+            "--node-id",
+            "test_node",
+            "--network-id",
+            "test_network",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::DumpPrecomputedBlocks(args)) => {
+                assert_eq!(args.node_id(), "test_node");
+                assert_eq!(args.network_id(), "test_network");
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_create_account_command() {
+        let args = vec![
+            "minimina",
+            "node",
+            "create-account",
+            "--node-id",
+            "mina-bp-1",
+            "--account-name",
+            "alice",
+            "--amount",
+            "2.5",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::CreateAccount(args)) => {
+                assert_eq!(args.node_args.node_id(), "mina-bp-1");
+                assert_eq!(args.account_name, "alice");
+                assert_eq!(args.funder, None);
+                assert_eq!(args.amount, 2_500_000_000);
+                assert_eq!(args.fee, 100_000_000);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_create_account_rejects_path_names() {
+        let args = vec![
+            "minimina",
+            "node",
+            "create-account",
+            "--node-id",
+            "mina-bp-1",
+            "--account-name",
+            "../network-keypairs/mina-bp-1",
+        ];
+
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_node_delegate_command() {
+        let args = vec![
+            "minimina",
+            "node",
+            "delegate",
+            "--node-id",
+            "mina-bp-1",
+            "--from",
+            "B62qalice",
+            "--to",
+            "B62qbp",
+            "--wait",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::Delegate(args)) => {
+                assert_eq!(args.node_args.node_id(), "mina-bp-1");
+                assert_eq!(args.from, "B62qalice");
+                assert_eq!(args.to, "B62qbp");
+                assert_eq!(args.fee, 100_000_000);
+                assert!(args.wait);
+                assert_eq!(args.wait_timeout, 600);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_query_command() {
+        let args = vec![
+            "minimina",
+            "node",
+            "query",
+            "-n",
+            "test",
+            "-i",
+            "mina-bp-1",
+            "-q",
+            "{ syncStatus }",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::Query(args)) => {
+                assert_eq!(args.node_args.network_id(), "test");
+                assert_eq!(args.node_args.node_id(), "mina-bp-1");
+                assert_eq!(args.query, "{ syncStatus }");
+                assert!(!args.node_args.raw_output);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_run_replayer_on_snapshot() {
+        let args = vec![
+            "minimina",
+            "node",
+            "run-replayer",
+            "-i",
+            "archive",
+            "-s",
+            "120",
+            "--snapshot",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::RunReplayer(args)) => {
+                assert_eq!(args.node_args.node_id(), "archive");
+                assert_eq!(args.start_slot_since_genesis, 120);
+                assert!(args.snapshot);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_stop_in_batches() {
+        let args = vec![
+            "minimina",
+            "network",
+            "stop",
+            "--network-id",
+            "test",
+            "--stop-concurrency",
+            "4",
+            "--stop-timeout",
+            "30",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Network(NetworkCommand::Stop(args)) => {
+                assert_eq!(args.stop_concurrency, Some(4));
+                assert_eq!(args.stop_timeout, Some(30));
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec![
+            "minimina",
+            "network",
+            "stop",
+            "--network-id",
+            "test",
+            "--stop-concurrency",
+            "0",
+        ];
+        assert!(Cli::try_parse_from(args).is_err());
+
+        let args = vec![
+            "minimina",
+            "network",
+            "stop",
+            "--timeout",
+            "90",
+            "--no-flush",
+        ];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Stop(args)) => {
+                assert_eq!(args.stop_timeout, Some(90));
+                assert!(args.no_flush);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_import_accounts_command() {
+        let args = vec![
+            "minimina",
+            "node",
+            "import-accounts",
+            "-n",
+            "test",
+            "-i",
+            "mina-bp-1",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Node(NodeCommand::ImportAccounts(args)) => {
+                assert_eq!(args.network_id(), "test");
+                assert_eq!(args.node_id(), "mina-bp-1");
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_logs_command() {
+        let args = vec![
+            "minimina", "network", "logs", "-n", "test", "--follow", "--since", "10m",
+        ];
+
+        let cli = Cli::parse_from(args);
+
+        match cli.command {
+            Command::Network(NetworkCommand::Logs(args)) => {
+                assert_eq!(args.network_id(), "test");
+                assert!(args.follow);
+                assert_eq!(args.since.as_deref(), Some("10m"));
+                assert!(!args.raw_output);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_create_without_archive_bootstrap() {
+        let args = vec!["minimina", "network", "create", "--no-archive-bootstrap"];
+
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert!(args.no_archive_bootstrap);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec![
+            "minimina",
+            "network",
+            "create",
+            "--no-archive-bootstrap",
+            "--verify-archive",
+        ];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_network_init_archive_command() {
+        let args = vec![
+            "minimina",
+            "network",
+            "init-archive",
+            "-n",
+            "test",
+            "--archive",
+            "mina-archive",
+            "--reset",
+        ];
+
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::InitArchive(args)) => {
+                assert_eq!(args.network_id(), "test");
+                assert_eq!(args.archive.as_deref(), Some("mina-archive"));
+                assert!(args.reset);
+                assert!(!args.verify_archive);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_create_with_native_keys() {
+        let args = vec!["minimina", "network", "create", "--native-keys"];
+
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert!(args.native_keys);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_ps_command() {
+        let args = vec![
+            "minimina",
+            "node",
+            "ps",
+            "-n",
+            "test",
+            "-i",
+            "mina-bp-1",
+            "-r",
+        ];
+
+        match Cli::parse_from(args).command {
+            Command::Node(NodeCommand::Ps(args)) => {
+                assert_eq!(args.network_id(), "test");
+                assert_eq!(args.node_id(), "mina-bp-1");
+                assert!(args.raw_output);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_create_default_topology_size() {
+        let args = vec![
+            "minimina",
+            "network",
+            "create",
+            "--seeds",
+            "2",
+            "--block-producers",
+            "5",
+            "--snark-workers",
+            "0",
+            "--no-archive",
+        ];
+
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert_eq!(args.seeds, 2);
+                assert_eq!(args.block_producers, 5);
+                assert_eq!(args.snark_workers, 0);
+                assert!(args.no_archive);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec![
+            "minimina",
+            "network",
+            "create",
+            "-t",
+            "topology.json",
+            "--seeds",
+            "2",
+        ];
+        assert!(Cli::try_parse_from(args).is_err());
+        let args = vec!["minimina", "network", "create", "--block-producers", "0"];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_network_repair_command() {
+        let args = vec!["minimina", "network", "repair", "-n", "test"];
+
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Repair(args)) => {
+                assert_eq!(args.network_id, "test");
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_recreate_command() {
+        let args = vec!["minimina", "network", "recreate", "-n", "test"];
+
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Recreate(args)) => {
+                assert_eq!(args.network_id, "test");
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_destructive_commands_confirmation() {
+        let args = vec![
+            "minimina",
+            "network",
+            "delete",
+            "-n",
+            "test",
+            "-y",
+            "--dry-run",
+        ];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Delete(args)) => {
+                assert_eq!(args.network_id, "test");
+                assert!(args.yes);
+                assert!(args.dry_run);
+                assert!(!args.remove_images);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec!["minimina", "network", "create", "--no-overwrite"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert!(args.no_overwrite);
+                assert!(!args.yes);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec!["minimina", "network", "create", "--no-overwrite", "--yes"];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_network_status_watch() {
+        let args = vec!["minimina", "network", "status", "-n", "test"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Status(args)) => {
+                assert_eq!(args.network_id, "test");
+                assert_eq!(args.watch, None);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec![
+            "minimina", "network", "status", "--watch", "--format", "table",
+        ];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Status(args)) => {
+                assert_eq!(args.watch, Some(2));
+                assert_eq!(args.format, WatchFormat::Table);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec!["minimina", "network", "status", "-w", "5"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Status(args)) => assert_eq!(args.watch, Some(5)),
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec!["minimina", "network", "status", "--format", "table"];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_network_create_port_range() {
+        let args = vec![
+            "minimina",
+            "network",
+            "create",
+            "--port-range",
+            "20000-20999",
+        ];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert_eq!(args.port_range, Some(20000..=20999));
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec![
+            "minimina",
+            "network",
+            "create",
+            "--port-range",
+            "20999-20000",
+        ];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_network_delete_remove_images() {
+        let args = vec!["minimina", "network", "delete", "--remove-images"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Delete(args)) => assert!(args.remove_images),
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_create_target() {
+        let args = vec!["minimina", "network", "create", "--target", "k8s"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Create(args)) => assert_eq!(args.target, Target::K8s),
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec!["minimina", "network", "create"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert_eq!(args.target, Target::Docker)
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_create_pin_digests() {
+        let args = vec!["minimina", "network", "create", "--pin-digests"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Create(args)) => assert!(args.pin_digests),
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_create_with_monitoring() {
+        let args = vec!["minimina", "network", "create", "--with-monitoring"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Create(args)) => assert!(args.with_monitoring),
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_exec_command() {
+        let args = vec![
+            "minimina", "node", "exec", "-n", "net", "-i", "node", "-r", "--", "mina", "client",
+            "status",
+        ];
+        match Cli::parse_from(args).command {
+            Command::Node(NodeCommand::Exec(args)) => {
+                assert_eq!(args.command, vec!["mina", "client", "status"]);
+                assert_eq!(args.node_args.network_id(), "net");
+                assert_eq!(args.node_args.node_id(), "node");
+                assert!(args.node_args.raw_output);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec!["minimina", "node", "exec", "-i", "node"];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_network_partition_and_heal() {
+        let args = vec![
+            "minimina",
+            "network",
+            "partition",
+            "-n",
+            "net",
+            "--groups",
+            "a,b|c",
+        ];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Partition(args)) => {
+                assert_eq!(args.network_id, "net");
+                assert_eq!(args.groups.0.len(), 2);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec!["minimina", "network", "partition", "--groups", "a,b"];
+        assert!(Cli::try_parse_from(args).is_err());
+
+        let args = vec!["minimina", "network", "heal", "-n", "net"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Heal(args)) => assert_eq!(args.network_id, "net"),
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_env_command() {
+        let args = vec!["minimina", "network", "env", "-n", "net", "--node", "bp"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Env(args)) => {
+                assert_eq!(args.network_id, "net");
+                assert_eq!(args.node.as_deref(), Some("bp"));
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec!["minimina", "network", "env"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Env(args)) => assert_eq!(args.node, None),
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_client_command() {
+        let args = vec![
+            "minimina", "node", "client", "-n", "net", "-i", "node", "--", "status",
+        ];
+        match Cli::parse_from(args).command {
+            Command::Node(NodeCommand::Client(args)) => {
+                assert_eq!(args.args, vec!["status"]);
+                assert_eq!(args.node_args.node_id(), "node");
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec!["minimina", "node", "client", "-i", "node"];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_network_create_preset() {
+        let args = vec!["minimina", "network", "create", "--preset", "fast-epochs"];
+        match Cli::parse_from(args).command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert_eq!(args.preset, Some(crate::genesis_ledger::Preset::FastEpochs))
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let args = vec!["minimina", "network", "create", "--preset", "slow"];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_topology_validate_command() {
+        let cli = Cli::parse_from(["minimina", "topology", "validate", "/path/to/topology.json"]);
+
+        match &cli.command {
+            Command::Topology(TopologyCommand::Validate(args)) => {
+                assert_eq!(
+                    args.topology,
+                    std::path::PathBuf::from("/path/to/topology.json")
+                );
+                assert!(cli.command.network_id().is_none());
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay_commands() {
+        let cli = Cli::parse_from([
+            "minimina",
+            "record",
+            "--out",
+            "session.json",
+            "--",
+            "network",
+            "create",
+            "-n",
+            "test",
+        ]);
+        match cli.command {
+            Command::Record(args) => {
+                assert_eq!(args.out, std::path::PathBuf::from("session.json"));
+                assert_eq!(args.args, vec!["network", "create", "-n", "test"]);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let cli = Cli::parse_from([
+            "minimina",
+            "replay",
+            "session.json",
+            "--network-id",
+            "replay",
+            "--keep-timing",
+        ]);
+        match cli.command {
+            Command::Replay(args) => {
+                assert_eq!(args.session, std::path::PathBuf::from("session.json"));
+                assert_eq!(args.network_id.as_deref(), Some("replay"));
+                assert!(args.keep_timing);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_create_with_health_probes() {
+        let cli = Cli::parse_from(["minimina", "network", "create", "--with-health-probes"]);
+        match cli.command {
+            Command::Network(NetworkCommand::Create(args)) => assert!(args.with_health_probes),
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_create_resource_limits() {
+        let cli = Cli::parse_from([
+            "minimina",
+            "network",
+            "create",
+            "--cpu-limit",
+            "1.5",
+            "--memory-limit",
+            "2g",
+        ]);
+        match cli.command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert_eq!(args.cpu_limit, Some(1.5));
+                assert_eq!(args.memory_limit, Some("2g".to_string()));
+                assert_eq!(args.profile, None);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let cli = Cli::parse_from(["minimina", "network", "create", "--profile", "constrained"]);
+        match cli.command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                assert_eq!(
+                    args.profile,
+                    Some(crate::service::ResourceProfile::Constrained)
+                );
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_create_if_not_exists() {
+        let cli = Cli::parse_from(["minimina", "network", "create", "--if-not-exists"]);
+        match cli.command {
+            Command::Network(NetworkCommand::Create(args)) => assert!(args.if_not_exists),
+            _ => panic!("Unexpected command parsed"),
+        }
+        assert!(
+            Cli::try_parse_from(["minimina", "network", "create", "--if-not-exists", "-y"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_network_start_force() {
+        let cli = Cli::parse_from(["minimina", "network", "start", "--force"]);
+        match cli.command {
+            Command::Network(NetworkCommand::Start(args)) => assert!(args.force),
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_restart() {
+        let cli = Cli::parse_from([
+            "minimina",
+            "node",
+            "restart",
+            "-i",
+            "mina-bp-1",
+            "--fresh-state",
+            "-a",
+        ]);
+        match cli.command {
+            Command::Node(NodeCommand::Restart(args)) => {
+                assert!(args.fresh_state);
+                assert!(args.import_accounts);
+                assert!(!args.flush);
+                assert_eq!(args.node_args.node_id(), "mina-bp-1");
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_debug_render() {
+        let cli = Cli::parse_from(["minimina", "debug", "render", "topology.json", "-n", "net"]);
+        match cli.command {
+            Command::Debug(DebugCommand::Render(args)) => {
+                assert_eq!(args.topology, std::path::PathBuf::from("topology.json"));
+                assert_eq!(args.network_id.network_id, "net");
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_start_wait() {
+        let cli = Cli::parse_from(["minimina", "network", "start", "--wait"]);
+        match cli.command {
+            Command::Network(NetworkCommand::Start(args)) => {
+                assert!(args.wait);
+                assert_eq!(args.wait_timeout, 900);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let cli = Cli::parse_from([
+            "minimina",
+            "network",
+            "start",
+            "--wait",
+            "--wait-timeout",
+            "60",
+        ]);
+        match cli.command {
+            Command::Network(NetworkCommand::Start(args)) => assert_eq!(args.wait_timeout, 60),
+            _ => panic!("Unexpected command parsed"),
+        }
+        assert!(
+            Cli::try_parse_from(["minimina", "network", "start", "--wait-timeout", "60"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_network_info_graph() {
+        let cli = Cli::parse_from(["minimina", "network", "info", "--graph"]);
+        match cli.command {
+            Command::Network(NetworkCommand::Info(args)) => assert!(args.graph),
+            _ => panic!("Unexpected command parsed"),
+        }
+        assert!(Cli::try_parse_from(["minimina", "network", "info", "--graph", "--live"]).is_err());
+    }
+
+    #[test]
+    fn test_network_create_extra_accounts() {
+        let cli = Cli::parse_from(["minimina", "network", "create", "--extra-accounts", "5"]);
+        match cli.command {
+            Command::Network(NetworkCommand::Create(args)) => assert_eq!(args.extra_accounts, 5),
+            _ => panic!("Unexpected command parsed"),
+        }
+        assert!(Cli::try_parse_from([
+            "minimina",
+            "network",
+            "create",
+            "--extra-accounts",
+            "5",
+            "-g",
+            "ledger.json"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_node_archive_data_files() {
+        let cli = Cli::parse_from([
+            "minimina",
+            "node",
+            "dump-archive-data",
+            "-i",
+            "mina-archive",
+            "-o",
+            "archive.sql",
+        ]);
+        match cli.command {
+            Command::Node(NodeCommand::DumpArchiveData(args)) => {
+                assert_eq!(args.output, Some(std::path::PathBuf::from("archive.sql")));
+                assert_eq!(args.node_args.node_id(), "mina-archive");
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let cli = Cli::parse_from([
+            "minimina",
+            "node",
+            "restore-archive-data",
+            "-n",
+            "other",
+            "-i",
+            "mina-archive",
+            "--input",
+            "archive.sql",
+        ]);
+        match cli.command {
+            Command::Node(NodeCommand::RestoreArchiveData(args)) => {
+                assert_eq!(args.input, std::path::PathBuf::from("archive.sql"));
+                assert_eq!(args.node_args.network_id(), "other");
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_archive_fault() {
+        let cli = Cli::parse_from([
+            "minimina",
+            "network",
+            "archive-fault",
+            "-n",
+            "net",
+            "--fault",
+            "write-latency",
+            "--latency",
+            "2000",
+        ]);
+        match cli.command {
+            Command::Network(NetworkCommand::ArchiveFault(args)) => {
+                assert_eq!(args.network_id, "net");
+                assert_eq!(args.fault, crate::chaos::ArchiveFault::WriteLatency);
+                assert_eq!(args.archive, None);
+                assert_eq!(args.duration, 60);
+                assert_eq!(args.latency, 2000);
+                assert_eq!(args.catch_up_timeout, 300);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_lag_report() {
+        let cli = Cli::parse_from(["minimina", "network", "lag-report", "-n", "test"]);
+        match cli.command {
+            Command::Network(NetworkCommand::LagReport(args)) => {
+                assert_eq!(args.network_id, "test");
+                assert_eq!(args.max_lag, 2);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_create_ignore_host_resources() {
+        let cli = Cli::parse_from(["minimina", "network", "create", "--ignore-host-resources"]);
+        match cli.command {
+            Command::Network(NetworkCommand::Create(args)) => assert!(args.ignore_host_resources),
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_nodes_command() {
+        let cli = Cli::parse_from(["minimina", "network", "nodes", "--network-id", "test"]);
+        match cli.command {
+            Command::Network(NetworkCommand::Nodes(args)) => assert_eq!(args.network_id, "test"),
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_keys_export_command() {
+        let cli = Cli::parse_from([
+            "minimina",
+            "keys",
+            "export",
+            "-n",
+            "test",
+            "-i",
+            "mina-bp-2",
+        ]);
+        match cli.command {
+            Command::Keys(KeysCommand::Export(args)) => {
+                assert_eq!(args.network_id(), "test");
+                assert_eq!(args.node_id.as_deref(), Some("mina-bp-2"));
+                assert_eq!(args.format, crate::keys::WalletFormat::Auro);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let cli = Cli::parse_from(["minimina", "keys", "export", "-n", "test"]);
+        assert_eq!(cli.command.network_id(), Some("test"));
+        assert!(Cli::try_parse_from(["minimina", "keys", "export", "--format", "pem"]).is_err());
+    }
+
+    #[test]
+    fn test_node_dump_archive_data_interval() {
+        let cli = Cli::parse_from([
+            "minimina",
+            "node",
+            "dump-archive-data",
+            "-i",
+            "mina-archive",
+            "--interval",
+            "10m",
+            "--retain",
+            "12",
+            "--output-dir",
+            "dumps/",
+        ]);
+        match cli.command {
+            Command::Node(NodeCommand::DumpArchiveData(args)) => {
+                assert_eq!(args.interval, Some(chrono::Duration::minutes(10)));
+                assert_eq!(args.retain, Some(12));
+                assert_eq!(args.output_dir, Some(std::path::PathBuf::from("dumps/")));
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+
+        let dump = |args: &[&str]| {
+            let base = [
+                "minimina",
+                "node",
+                "dump-archive-data",
+                "-i",
+                "mina-archive",
+            ];
+            Cli::try_parse_from(base.iter().chain(args))
+        };
+        assert!(dump(&["--interval", "10m"]).is_err());
+        assert!(dump(&["--output-dir", "dumps/"]).is_err());
+        assert!(dump(&["--interval", "10m", "--output-dir", "dumps/", "-o", "a.sql"]).is_err());
+        assert!(dump(&[
+            "--interval",
+            "10m",
+            "--output-dir",
+            "dumps/",
+            "--retain",
+            "0"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_network_start_profiles() {
+        use crate::docker::compose::ComposeProfile;
+
+        let profiles = |args: &[&str]| {
+            let base = ["minimina", "network", "start", "-n", "test"];
+            match Cli::parse_from(base.iter().chain(args)).command {
+                Command::Network(NetworkCommand::Start(args)) => args.profiles,
+                _ => panic!("Unexpected command parsed"),
+            }
+        };
+        assert_eq!(profiles(&[]), None);
+        assert_eq!(profiles(&["--profiles"]), Some(vec![]));
+        assert_eq!(
+            profiles(&["--profiles", "archive,monitoring"]),
+            Some(vec![ComposeProfile::Archive, ComposeProfile::Monitoring])
+        );
+        assert!(
+            Cli::try_parse_from(["minimina", "network", "start", "--profiles", "k8s"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_quickstart_command() {
+        let cli = Cli::parse_from(["minimina", "quickstart", "-n", "dev", "-y"]);
+        assert_eq!(cli.command.network_id(), Some("dev"));
+        assert_eq!(cli.command.log_level(), "warn");
+        match cli.command {
+            Command::Quickstart(args) => {
+                assert!(args.yes);
+                assert_eq!(args.wait_timeout, 300);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_node_run_replayer_target_and_output() {
+        let cli = Cli::parse_from([
+            "minimina",
+            "node",
+            "run-replayer",
+            "-i",
+            "archive",
+            "-s",
+            "0",
+            "--target-state-hash",
+            "3NKtarget",
+            "--output-file",
+            "ledger.json",
+        ]);
+        match cli.command {
+            Command::Node(NodeCommand::RunReplayer(args)) => {
+                assert_eq!(args.target_state_hash.as_deref(), Some("3NKtarget"));
+                assert_eq!(args.output_file.as_deref(), Some("ledger.json"));
+                assert!(!args.snapshot);
+            }
+            _ => panic!("Unexpected command parsed"),
+        }
+    }
+
+    #[test]
+    fn test_network_create_fund_named_accounts() {
+        let cli = Cli::parse_from([
+            "minimina",
+            "network",
+            "create",
+            "--fund",
+            "alice=1000",
+            "--fund",
+            "faucet=0.5",
+        ]);
+        match cli.command {
+            Command::Network(NetworkCommand::Create(args)) => {
+                let names: Vec<&str> = args.fund.iter().map(|f| f.name.as_str()).collect();
+                assert_eq!(names, ["alice", "faucet"]);
+                assert_eq!(args.fund[1].amount, 500_000_000);
             }
             _ => panic!("Unexpected command parsed"),
         }
+        assert!(Cli::try_parse_from(["minimina", "network", "create", "--fund", "alice"]).is_err());
     }
 }
@@ -0,0 +1,177 @@
+//! # Config Module
+//!
+//! User settings of minimina, read from `~/.minimina/config.toml`:
+//!
+//! ```toml
+//! docker_binary = "nerdctl"
+//! daemon_image = "minaprotocol/mina-daemon:3.0.0-bullseye-devnet"
+//! archive_image = "minaprotocol/mina-archive:3.0.0-bullseye"
+//! base_directory = "/data/minimina"
+//! log_level = "info"
+//! port_range = "20000-20999"
+//! extra_daemon_flags = ["-log-level", "Info"]
+//!
+//! [proxy]
+//! https_proxy = "http://proxy:3128"
+//! no_proxy = "localhost,127.0.0.1"
+//! ```
+//!
+//! Every setting is optional; the file itself does not need to exist. Without a
+//! `config.toml` the same settings are read from a `config.json`, the format of
+//! earlier versions.
+
+use crate::{directory_manager::DirectoryManager, ports::parse_port_range, proxy::ProxyConfig};
+use log::warn;
+use serde::{Deserialize, Deserializer};
+use std::{
+    fs,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+const CONFIG_FILE: &str = "config.toml";
+/// Config file of earlier versions, read if there is no `config.toml`
+const LEGACY_CONFIG_FILE: &str = "config.json";
+/// Overrides the docker binary of the config file
+const DOCKER_BINARY_ENV: &str = "MINIMINA_DOCKER";
+const DEFAULT_DOCKER_BINARY: &str = "docker";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    /// Name or path of the docker compatible CLI, e.g. `nerdctl` or `/opt/docker/bin/docker`
+    pub docker_binary: Option<String>,
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+    /// Daemon image of networks created without a topology file
+    pub daemon_image: Option<String>,
+    /// Archive image of networks created without a topology file
+    pub archive_image: Option<String>,
+    /// Directory the networks are kept in instead of `~/.minimina`
+    pub base_directory: Option<PathBuf>,
+    /// Log level of the commands run without `--log-level`
+    pub log_level: Option<String>,
+    /// Host ports of networks created without `--port-range`, e.g. `"20000-20999"`
+    #[serde(default, deserialize_with = "deserialize_port_range")]
+    pub port_range: Option<RangeInclusive<u16>>,
+    /// Flags appended to the command of every daemon of the networks created
+    #[serde(default)]
+    pub extra_daemon_flags: Vec<String>,
+}
+
+fn deserialize_port_range<'de, D>(deserializer: D) -> Result<Option<RangeInclusive<u16>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|range| parse_port_range(&range).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+impl Config {
+    /// Settings of the config file in the minimina home
+    pub fn load() -> Self {
+        Self::load_from(&DirectoryManager::home_path())
+    }
+
+    pub fn load_from(base_path: &Path) -> Self {
+        let parsed = match fs::read_to_string(base_path.join(CONFIG_FILE)) {
+            Ok(contents) => toml::from_str(&contents).map_err(|e| (CONFIG_FILE, e.to_string())),
+            Err(_) => match fs::read_to_string(base_path.join(LEGACY_CONFIG_FILE)) {
+                Ok(contents) => {
+                    serde_json::from_str(&contents).map_err(|e| (LEGACY_CONFIG_FILE, e.to_string()))
+                }
+                Err(_) => Ok(Self::default()),
+            },
+        };
+        parsed.unwrap_or_else(|(file, e)| {
+            warn!("Ignoring invalid {file}: {e}");
+            Self::default()
+        })
+    }
+
+    fn resolve_docker_binary(&self, env: Option<String>) -> String {
+        env.filter(|binary| !binary.is_empty())
+            .or_else(|| self.docker_binary.clone())
+            .unwrap_or_else(|| DEFAULT_DOCKER_BINARY.to_string())
+    }
+}
+
+/// Settings of the config file in the minimina home, read once
+pub fn settings() -> &'static Config {
+    static SETTINGS: OnceLock<Config> = OnceLock::new();
+    SETTINGS.get_or_init(Config::load)
+}
+
+/// Docker compatible CLI all container commands are run with: `$MINIMINA_DOCKER`,
+/// the config file's `docker_binary` or `docker`
+pub fn docker_binary() -> &'static str {
+    static DOCKER_BINARY: OnceLock<String> = OnceLock::new();
+    DOCKER_BINARY
+        .get_or_init(|| settings().resolve_docker_binary(std::env::var(DOCKER_BINARY_ENV).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_resolve_docker_binary() {
+        let tempdir = TempDir::new("test_resolve_docker_binary").unwrap();
+        let config = || Config::load_from(tempdir.path());
+        assert_eq!(config().resolve_docker_binary(None), "docker");
+
+        fs::write(
+            tempdir.path().join(LEGACY_CONFIG_FILE),
+            r#"{"docker_binary": "nerdctl"}"#,
+        )
+        .unwrap();
+        assert_eq!(config().resolve_docker_binary(None), "nerdctl");
+        assert_eq!(
+            config().resolve_docker_binary(Some("/usr/local/bin/docker".to_string())),
+            "/usr/local/bin/docker"
+        );
+    }
+
+    #[test]
+    fn test_load_toml_config() {
+        let tempdir = TempDir::new("test_load_toml_config").unwrap();
+        fs::write(
+            tempdir.path().join(LEGACY_CONFIG_FILE),
+            r#"{"docker_binary": "nerdctl"}"#,
+        )
+        .unwrap();
+        fs::write(
+            tempdir.path().join(CONFIG_FILE),
+            r#"
+daemon_image = "mina-daemon:custom"
+base_directory = "/data/minimina"
+log_level = "info"
+port_range = "20000-20999"
+extra_daemon_flags = ["-log-level", "Info"]
+
+[proxy]
+https_proxy = "http://proxy:3128"
+"#,
+        )
+        .unwrap();
+
+        // the toml file takes the place of the legacy one
+        let config = Config::load_from(tempdir.path());
+        assert_eq!(config.docker_binary, None);
+        assert_eq!(config.daemon_image.as_deref(), Some("mina-daemon:custom"));
+        assert_eq!(config.archive_image, None);
+        assert_eq!(config.base_directory, Some(PathBuf::from("/data/minimina")));
+        assert_eq!(config.log_level.as_deref(), Some("info"));
+        assert_eq!(config.port_range, Some(20000..=20999));
+        assert_eq!(config.extra_daemon_flags, vec!["-log-level", "Info"]);
+        assert_eq!(
+            config.proxy.https_proxy.as_deref(),
+            Some("http://proxy:3128")
+        );
+
+        fs::write(tempdir.path().join(CONFIG_FILE), r#"port_range = "20000""#).unwrap();
+        assert_eq!(Config::load_from(tempdir.path()).port_range, None);
+    }
+}
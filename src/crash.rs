@@ -0,0 +1,256 @@
+//! # Crash Module
+//!
+//! Collects what is left of a dead daemon container into a single bundle,
+//! `crashes/{node_id}-{timestamp}.tar.gz` in the network directory, containing:
+//! - `state.json`: the container's `docker inspect` state (exit code, OOM kill, error).
+//! - `docker.log`: the last lines of the container's logs.
+//! - `oom.log`: kernel OOM killer messages from `dmesg`, if readable.
+//! - `config-directory/`: core dumps and crash reports left in the daemon's config directory.
+
+use crate::{
+    directory_manager::DirectoryManager, docker::compose::CONFIG_DIRECTORY,
+    docker::manager::DockerManager, utils::run_command,
+};
+use log::{info, warn};
+use serde::Deserialize;
+use std::{
+    fs,
+    io::{Error, ErrorKind, Result},
+    path::{Path, PathBuf},
+};
+
+/// Number of log lines kept in a crash bundle
+const LOG_TAIL_LINES: u32 = 1000;
+
+/// Files in the config directory worth keeping after a crash
+const CRASH_FILE_PATTERNS: &[&str] = &["core*", "*crash*", "*backtrace*"];
+
+/// Exit details of a container, as reported by `docker inspect`
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct ExitState {
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "ExitCode")]
+    pub exit_code: i32,
+    #[serde(rename = "OOMKilled")]
+    pub oom_killed: bool,
+    #[serde(rename = "Error")]
+    pub error: String,
+    #[serde(rename = "FinishedAt")]
+    pub finished_at: String,
+}
+
+pub struct CrashBundle {
+    pub path: PathBuf,
+    pub state: ExitState,
+}
+
+/// Collects a crash bundle for `node_id`
+pub fn collect(
+    docker: &DockerManager,
+    directory_manager: &DirectoryManager,
+    network_id: &str,
+    node_id: &str,
+) -> Result<CrashBundle> {
+    let container = format!("{node_id}-{network_id}");
+    let output = docker.inspect_state(&container)?;
+    if !output.status.success() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "Cannot inspect container '{container}': {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ));
+    }
+    let state: ExitState = serde_json::from_slice(&output.stdout)?;
+
+    let crashes_path = directory_manager.crashes_path(network_id);
+    let incident = format!("{node_id}-{}", chrono::Local::now().format("%Y%m%dT%H%M%S"));
+    let staging = crashes_path.join(&incident);
+    fs::create_dir_all(&staging)?;
+    fs::write(staging.join("state.json"), &output.stdout)?;
+
+    // daemons log to both stdout and stderr
+    let logs = docker.logs_tail(&container, LOG_TAIL_LINES)?;
+    fs::write(
+        staging.join("docker.log"),
+        [logs.stdout.as_slice(), logs.stderr.as_slice()].concat(),
+    )?;
+
+    if let Some(oom_messages) = oom_messages() {
+        fs::write(staging.join("oom.log"), oom_messages)?;
+    }
+
+    let running = state.status == "running";
+    if let Err(e) = copy_crash_files(docker, &container, running, &staging) {
+        warn!("Failed to copy crash files from '{container}': {e}");
+    }
+
+    let path = crashes_path.join(format!("{incident}.tar.gz"));
+    let out = run_command(
+        "tar",
+        &[
+            "-czf",
+            path.to_str().unwrap(),
+            "-C",
+            crashes_path.to_str().unwrap(),
+            &incident,
+        ],
+    )?;
+    if !out.status.success() {
+        return Err(Error::other(format!(
+            "Failed to bundle crash files: {}",
+            String::from_utf8_lossy(&out.stderr)
+        )));
+    }
+    fs::remove_dir_all(&staging)?;
+
+    info!("Collected crash bundle for '{container}' at {path:?}");
+    Ok(CrashBundle { path, state })
+}
+
+/// Kernel OOM killer lines from `dmesg`, `None` if there are none or `dmesg` is not readable
+fn oom_messages() -> Option<String> {
+    let out = run_command("dmesg", &[]).ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let messages = filter_oom_lines(&String::from_utf8_lossy(&out.stdout));
+    (!messages.is_empty()).then_some(messages)
+}
+
+fn filter_oom_lines(dmesg: &str) -> String {
+    dmesg
+        .lines()
+        .filter(|line| {
+            let line = line.to_lowercase();
+            line.contains("out of memory")
+                || line.contains("oom-kill")
+                || line.contains("oom_reaper")
+        })
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+/// Copies the files matching `CRASH_FILE_PATTERNS` out of the container's config
+/// directory, listing them with `find` in the container if it is `running` or in a
+/// one-off container of its service otherwise, so the ledger and frontier databases
+/// next to them are never copied
+fn copy_crash_files(
+    docker: &DockerManager,
+    container: &str,
+    running: bool,
+    staging: &Path,
+) -> Result<()> {
+    let config_directory = format!("/{CONFIG_DIRECTORY}");
+    let find = find_command(&config_directory, CRASH_FILE_PATTERNS);
+    let find: Vec<&str> = find.iter().map(String::as_str).collect();
+    let out = if running {
+        docker.exec(container, &find)?
+    } else {
+        docker.compose_run_command(container, &find)?
+    };
+    if !out.status.success() {
+        return Err(Error::other(
+            String::from_utf8_lossy(&out.stderr).trim().to_string(),
+        ));
+    }
+
+    for file in String::from_utf8_lossy(&out.stdout).lines() {
+        let Some(relative) = file.strip_prefix(&config_directory) else {
+            continue;
+        };
+        let dest = staging
+            .join(CONFIG_DIRECTORY)
+            .join(relative.trim_start_matches('/'));
+        fs::create_dir_all(dest.parent().unwrap())?;
+        let out = docker.cp_from(container, file, &dest)?;
+        if !out.status.success() {
+            warn!(
+                "Failed to copy crash file '{file}' from '{container}': {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `find` command listing the files under `dir` whose name matches any of `patterns`
+fn find_command(dir: &str, patterns: &[&str]) -> Vec<String> {
+    let mut command = vec![
+        "find".to_string(),
+        dir.to_string(),
+        "-type".into(),
+        "f".into(),
+        "(".into(),
+    ];
+    for (i, pattern) in patterns.iter().enumerate() {
+        if i > 0 {
+            command.push("-o".into());
+        }
+        command.push("-name".into());
+        command.push(pattern.to_string());
+    }
+    command.push(")".into());
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_exit_state() {
+        let state: ExitState = serde_json::from_str(
+            r#"{"Status":"exited","Running":false,"Paused":false,"Restarting":false,
+                "OOMKilled":true,"Dead":false,"Pid":0,"ExitCode":137,"Error":"",
+                "StartedAt":"2024-01-01T00:00:00Z","FinishedAt":"2024-01-01T01:00:00Z"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            state,
+            ExitState {
+                status: "exited".into(),
+                exit_code: 137,
+                oom_killed: true,
+                error: "".into(),
+                finished_at: "2024-01-01T01:00:00Z".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_filter_oom_lines() {
+        let dmesg = "[1.0] eth0: link up\n\
+            [2.0] mina invoked oom-killer: gfp_mask=0x100cca\n\
+            [2.1] Out of memory: Killed process 42 (mina)\n";
+
+        assert_eq!(
+            filter_oom_lines(dmesg),
+            "[2.0] mina invoked oom-killer: gfp_mask=0x100cca\n\
+             [2.1] Out of memory: Killed process 42 (mina)\n"
+        );
+    }
+
+    #[test]
+    fn test_find_command() {
+        assert_eq!(
+            find_command("/root/.mina-config", &["core*", "*crash*"]),
+            [
+                "find",
+                "/root/.mina-config",
+                "-type",
+                "f",
+                "(",
+                "-name",
+                "core*",
+                "-o",
+                "-name",
+                "*crash*",
+                ")"
+            ]
+        );
+    }
+}
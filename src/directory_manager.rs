@@ -8,24 +8,57 @@
 //! - `genesis_ledger.json`: Contains the genesis ledger for the network.
 //! - `docker-compose.yml`: Contains the docker compose file for the network.
 //! - `network.json`: Contains the network topology representation in JSON format.
+//! - `services.json`: The service configs of the network and their dependency graph.
+//! - `fingerprint.json`: Hashes of the topology and genesis ledger the network was created from.
 //! - `peer_list_file.txt`: Contains the list of libp2p peers for the network.
+//! - `logs`: Daemon log files fetched with `node logs --internal`.
+//! - `crashes`: Crash bundles collected with `node collect-crash`.
+//! - `archive_dump.sql`: Archive database dump, only present while exporting/importing a network.
+//! - `volumes`: Docker volume tarballs, only present while snapshotting/restoring a network.
 
+use crate::config;
+use crate::docker::{postgres::ArchiveDb, runtime::Runtime};
 use crate::genesis_ledger::GENESIS_LEDGER_JSON;
 use crate::output;
-use crate::service::ServiceConfig;
+use crate::service::{graph::Graph, ServiceConfig};
+use crate::utils::run_command;
 use dirs::home_dir;
 use log::{debug, info};
+use serde::{Deserialize, Serialize};
 use std::env;
 use std::os::unix::fs::PermissionsExt;
 use std::{
+    collections::HashMap,
     fs,
-    io::Result,
+    io::{ErrorKind, Result},
     path::{Path, PathBuf},
 };
+use tempdir::TempDir;
 
 pub const NETWORK_KEYPAIRS: &str = "network-keypairs";
-const LIBP2P_KEYPAIRS: &str = "libp2p-keypairs";
+/// Key pairs of accounts created with `node create-account`, not part of the genesis ledger
+pub const ACCOUNTS: &str = "accounts";
+pub const LIBP2P_KEYPAIRS: &str = "libp2p-keypairs";
 const MINIMINA_HOME: &str = "MINIMINA_HOME";
+pub const ARCHIVE_DUMP_SQL: &str = "archive_dump.sql";
+/// `mina client` settings of each daemon, by node
+pub const CLIENTS: &str = "clients";
+/// Hidden so it is not listed as a network
+const BUILDS: &str = ".builds";
+/// Volume tarballs of a network snapshot, only present while snapshotting/restoring
+const SNAPSHOT_VOLUMES: &str = "volumes";
+
+/// Contents of a network's `services.json`
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ServicesFile {
+    Graph {
+        services: Vec<ServiceConfig>,
+        graph: Graph,
+    },
+    /// Services of networks created before the graph was recorded
+    Services(Vec<ServiceConfig>),
+}
 
 #[derive(Clone)]
 pub struct DirectoryManager {
@@ -33,20 +66,36 @@ pub struct DirectoryManager {
     pub subdirectories: [&'static str; 2],
 }
 
+impl Default for DirectoryManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DirectoryManager {
+    /// Manager of the config file's `base_directory`, the minimina home if not set
     pub fn new() -> Self {
-        let mut base_path = if let Ok(env_path) = env::var(MINIMINA_HOME) {
-            PathBuf::from(env_path)
-        } else {
-            home_dir().expect("Home directory not found")
+        let base_path = match &config::settings().base_directory {
+            Some(base_directory) => base_directory.clone(),
+            None => Self::home_path(),
         };
-        base_path.push(".minimina");
         DirectoryManager {
             base_path,
             subdirectories: Self::subdirectories(),
         }
     }
 
+    /// `.minimina` in `$MINIMINA_HOME` or the home directory, holding the config file
+    pub fn home_path() -> PathBuf {
+        let mut home_path = if let Ok(env_path) = env::var(MINIMINA_HOME) {
+            PathBuf::from(env_path)
+        } else {
+            home_dir().expect("Home directory not found")
+        };
+        home_path.push(".minimina");
+        home_path
+    }
+
     // for testing purposes
     pub fn _new_with_base_path(base_path: PathBuf) -> Self {
         DirectoryManager {
@@ -65,6 +114,11 @@ impl DirectoryManager {
         network_path.join(network_id)
     }
 
+    /// Directory Mina sources are checked out to for building images
+    pub fn builds_path(&self) -> PathBuf {
+        self.base_path.join(BUILDS)
+    }
+
     // list of all subdirectories that needs to be created for the network
     fn subdirectories() -> [&'static str; 2] {
         [NETWORK_KEYPAIRS, LIBP2P_KEYPAIRS]
@@ -109,12 +163,34 @@ impl DirectoryManager {
         fs::remove_dir_all(network_path)
     }
 
+    /// Creates the base directory unless it exists, tolerating concurrent invocations creating it
+    pub fn ensure_base_path(&self) -> Result<()> {
+        match fs::create_dir_all(&self.base_path) {
+            Err(e) if e.kind() != ErrorKind::AlreadyExists => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Lists the networks in the base directory, which need not exist yet.
+    /// Hidden directories, e.g. of in-progress restores, are skipped.
     pub fn list_network_directories(&self) -> Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.base_path) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+
         let mut networks = vec![];
-        for entry in fs::read_dir(&self.base_path)? {
+        for entry in entries {
             let entry = entry?;
-            if entry.file_type()?.is_dir() {
-                if let Some(network_id) = entry.file_name().to_str() {
+            // another invocation may delete the entry while listing
+            let is_dir = match entry.file_type() {
+                Ok(file_type) => file_type.is_dir(),
+                Err(e) if e.kind() == ErrorKind::NotFound => false,
+                Err(e) => return Err(e),
+            };
+            if let Some(network_id) = entry.file_name().to_str() {
+                if is_dir && !network_id.starts_with('.') {
                     networks.push(network_id.to_string());
                 }
             }
@@ -126,6 +202,31 @@ impl DirectoryManager {
         self.get_files_in_network_subdir(network_id, NETWORK_KEYPAIRS, Some(".pub"))
     }
 
+    /// Finds the locally stored key file of `public_key` among the genesis and created
+    /// accounts, returning its path relative to the network directory
+    pub fn find_key_file(&self, network_id: &str, public_key: &str) -> Result<Option<String>> {
+        Ok(self.stored_public_keys(network_id)?.remove(public_key))
+    }
+
+    /// Maps the public keys of all locally stored key files to their path
+    /// relative to the network directory
+    pub fn stored_public_keys(&self, network_id: &str) -> Result<HashMap<String, String>> {
+        let mut public_keys = HashMap::new();
+        for subdir in [NETWORK_KEYPAIRS, ACCOUNTS] {
+            let path = self.network_path(network_id).join(subdir);
+            if !path.exists() {
+                continue;
+            }
+            for file in self.get_files_in_network_subdir(network_id, subdir, None)? {
+                if let Some(key_file) = file.strip_suffix(".pub") {
+                    let public_key = fs::read_to_string(path.join(&file))?.trim().to_string();
+                    public_keys.insert(public_key, format!("{subdir}/{key_file}"));
+                }
+            }
+        }
+        Ok(public_keys)
+    }
+
     pub fn _get_libp2p_keypair_files(&self, network_id: &str) -> Result<Vec<String>> {
         self.get_files_in_network_subdir(network_id, LIBP2P_KEYPAIRS, Some(".peerid"))
     }
@@ -221,11 +322,26 @@ impl DirectoryManager {
                     .file_name()
                     .expect("Failed to extract filename from source path"),
             );
-            debug!(
-                "Copying uptime service backend from {:?} app config to {:?}",
-                uptime_service_backend_app_config, dest_path
-            );
-            fs::copy(uptime_service_backend_app_config, dest_path)?;
+            match &service.uptime_app_config {
+                Some(app_config) => {
+                    debug!(
+                        "Writing uptime service backend app config {:?} with {:?} to {:?}",
+                        uptime_service_backend_app_config, app_config, dest_path
+                    );
+                    let base = serde_json::from_str(&fs::read_to_string(
+                        uptime_service_backend_app_config,
+                    )?)?;
+                    let rendered = app_config.apply(base).map_err(std::io::Error::other)?;
+                    fs::write(dest_path, serde_json::to_string_pretty(&rendered)?)?;
+                }
+                None => {
+                    debug!(
+                        "Copying uptime service backend from {:?} app config to {:?}",
+                        uptime_service_backend_app_config, dest_path
+                    );
+                    fs::copy(uptime_service_backend_app_config, dest_path)?;
+                }
+            }
         }
         if let Some(uptime_service_backend_minasheets) = &service.uptime_service_backend_minasheets
         {
@@ -281,46 +397,55 @@ impl DirectoryManager {
         Ok(())
     }
 
-    /// Checks whether the genesis timestamp is too far in the past.
-    pub fn check_genesis_timestamp(&self, network_id: &str) -> Result<()> {
-        use chrono::{prelude::*, Duration};
+    /// Computes how long ago genesis was and whether that is longer than
+    /// `stale_after_minutes`, which defaults to `k / 2 * 3` minutes
+    pub fn genesis_staleness(
+        &self,
+        network_id: &str,
+        stale_after_minutes: Option<u64>,
+    ) -> Result<output::network::GenesisStaleness> {
+        use crate::genesis_ledger::parse_genesis_timestamp;
+        use chrono::prelude::*;
 
         let network_path = self.network_path(network_id);
         let genesis_ledger_path = network_path.join(GENESIS_LEDGER_JSON);
         let contents = fs::read_to_string(genesis_ledger_path)?;
         let json: serde_json::Value = serde_json::from_str(&contents)?;
-        let genesis = json
-            .get("genesis")
-            .expect("'genesis' field should be present in genesis ledger");
-        let genesis_timestamp = DateTime::parse_from_rfc3339(
+        let genesis = json.get("genesis").ok_or_else(|| {
+            invalid_genesis_ledger("'genesis' field should be present in genesis ledger")
+        })?;
+        let genesis_timestamp = parse_genesis_timestamp(
             genesis
                 .get("genesis_state_timestamp")
-                .expect("'genesis_state_timestamp' should be a field in 'genesis' object")
-                .as_str()
-                .unwrap(),
-        )
-        .unwrap();
+                .and_then(serde_json::Value::as_str)
+                .ok_or_else(|| {
+                    invalid_genesis_ledger(
+                        "'genesis_state_timestamp' should be a string field in 'genesis' object",
+                    )
+                })?,
+        )?;
 
         // use k genesis parameter to calculate cutoff time
         // in case k is not present, use default value of 20
         let k = match genesis.get("k") {
-            Some(k) => k.to_string().parse::<u32>().unwrap(),
-            None => 20_u32,
+            Some(k) => k
+                .as_u64()
+                .ok_or_else(|| invalid_genesis_ledger("'k' should be a positive integer"))?,
+            None => 20_u64,
         };
-        let cutoff = Local::now()
-            .checked_sub_signed(Duration::minutes((k / 2 * 3) as i64))
-            .unwrap();
+        let stale_after_minutes = stale_after_minutes.unwrap_or(k / 2 * 3);
+        let seconds_since_genesis = Local::now()
+            .signed_duration_since(genesis_timestamp)
+            .num_seconds();
 
         // if we're outside of the first half of the first transition frontier,
-        // we throw an error
-        if cutoff > genesis_timestamp {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("Genesis timestamp '{genesis_timestamp}' may be outdated."),
-            ));
-        }
-
-        Ok(())
+        // genesis is considered stale
+        Ok(output::network::GenesisStaleness {
+            genesis_timestamp: genesis_timestamp.to_rfc3339(),
+            stale_after_minutes,
+            minutes_since_genesis: seconds_since_genesis / 60,
+            stale: seconds_since_genesis > (stale_after_minutes * 60) as i64,
+        })
     }
 
     /// Copies the genesis ledger at `genesis_ledger_path` to the network directory
@@ -335,17 +460,18 @@ impl DirectoryManager {
         genesis_ledger_path: &Path,
     ) -> Result<()> {
         use crate::genesis_ledger::current_timestamp;
-        use fs::{read_to_string, write};
 
-        let contents = read_to_string(genesis_ledger_path)?;
-        let mut ledger: serde_json::Value = serde_json::from_str(&contents)?;
-        let genesis = ledger.get_mut("genesis").unwrap();
-        let timestamp = genesis.get_mut("genesis_state_timestamp").unwrap();
-
-        *timestamp = serde_json::Value::String(current_timestamp());
+        write_genesis_timestamp(
+            genesis_ledger_path,
+            &self.genesis_ledger_path(network_id),
+            &current_timestamp(),
+        )
+    }
 
-        let contents = serde_json::to_string_pretty(&ledger)?;
-        write(self.genesis_ledger_path(network_id), contents)
+    /// Sets the genesis timestamp of the network's genesis ledger
+    pub fn set_genesis_timestamp(&self, network_id: &str, timestamp: &str) -> Result<()> {
+        let genesis_ledger_path = self.genesis_ledger_path(network_id);
+        write_genesis_timestamp(&genesis_ledger_path, &genesis_ledger_path, timestamp)
     }
 
     /// Returns the genesis ledger path for the given network
@@ -358,10 +484,39 @@ impl DirectoryManager {
         self.network_path(network_id).join("network.json")
     }
 
-    pub fn save_network_info(&self, network_id: &str, services: &[ServiceConfig]) -> Result<()> {
+    pub fn save_network_info(
+        &self,
+        network_id: &str,
+        services: &[ServiceConfig],
+        runtime: Runtime,
+    ) -> Result<()> {
         let network_file_path = self.network_file_path(network_id);
-        let contents = format!("{}", output::generate_network_info(services, network_id));
-        fs::write(network_file_path, contents)
+        let contents = format!(
+            "{}",
+            output::generate_network_info(services, network_id, runtime)
+        );
+        fs::write(network_file_path, contents)?;
+        self.save_client_configs(network_id, services)
+    }
+
+    /// Writes the `mina client` settings of every daemon to `clients/<node>.json`
+    fn save_client_configs(&self, network_id: &str, services: &[ServiceConfig]) -> Result<()> {
+        let clients_path = self.network_path(network_id).join(CLIENTS);
+        // nodes removed since are not left behind
+        if clients_path.exists() {
+            fs::remove_dir_all(&clients_path)?;
+        }
+        fs::create_dir_all(&clients_path)?;
+        for config in services
+            .iter()
+            .filter_map(|service| service.to_client_config(network_id))
+        {
+            fs::write(
+                clients_path.join(format!("{}.json", config.node_id)),
+                config.to_string(),
+            )?;
+        }
+        Ok(())
     }
 
     pub fn get_network_info(&self, network_id: &str) -> Result<String> {
@@ -374,23 +529,227 @@ impl DirectoryManager {
         self.network_path(network_id).join("services.json")
     }
 
+    /// Records the services of the network with their dependency graph
     pub fn save_services_info(&self, network_id: &str, services: &[ServiceConfig]) -> Result<()> {
         let services_file_path = self.services_file_path(network_id);
-        let contents = serde_json::to_string_pretty(services)?;
+        let contents = serde_json::to_string_pretty(&ServicesFile::Graph {
+            services: services.to_vec(),
+            graph: Graph::of(services),
+        })?;
         fs::write(services_file_path, contents)
     }
 
-    pub fn get_services_info(&self, network_id: &str) -> Result<Vec<ServiceConfig>> {
+    fn read_services_file(&self, network_id: &str) -> Result<ServicesFile> {
         let services_file_path = self.services_file_path(network_id);
         let contents = fs::read_to_string(services_file_path)?;
-        let services: Vec<ServiceConfig> = serde_json::from_str(&contents)?;
-        Ok(services)
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn get_services_info(&self, network_id: &str) -> Result<Vec<ServiceConfig>> {
+        Ok(match self.read_services_file(network_id)? {
+            ServicesFile::Graph { services, .. } | ServicesFile::Services(services) => services,
+        })
+    }
+
+    /// Dependency graph of the services of the network, computed from them for
+    /// networks whose `services.json` doesn't record it
+    pub fn get_service_graph(&self, network_id: &str) -> Result<Graph> {
+        Ok(match self.read_services_file(network_id)? {
+            ServicesFile::Graph { graph, .. } => graph,
+            ServicesFile::Services(services) => Graph::of(&services),
+        })
     }
 
     /// Returns the topology file path for the given network
     pub fn topology_file_path(&self, network_id: &str) -> PathBuf {
         self.network_path(network_id).join("topology.json")
     }
+
+    /// Returns the path the database of `db` is dumped to when exporting the network
+    pub fn archive_dump_path(&self, network_id: &str, db: &ArchiveDb) -> PathBuf {
+        let file_name = match db.archive() {
+            Some(archive) => format!("archive_dump-{archive}.sql"),
+            None => ARCHIVE_DUMP_SQL.to_string(),
+        };
+        self.network_path(network_id).join(file_name)
+    }
+
+    /// Returns the path the node's internal logs tarball is saved to
+    pub fn internal_logs_path(&self, network_id: &str, node_id: &str) -> PathBuf {
+        self.network_path(network_id)
+            .join("logs")
+            .join(format!("{node_id}-internal-logs.tar.gz"))
+    }
+
+    /// Returns the directory crash bundles of the given network are saved to
+    pub fn crashes_path(&self, network_id: &str) -> PathBuf {
+        self.network_path(network_id).join("crashes")
+    }
+
+    /// Packs the network directory into a gzipped tarball at `output`
+    pub fn export_network(&self, network_id: &str, output: &Path) -> Result<()> {
+        let output = std::path::absolute(output)?;
+        let out = run_command(
+            "tar",
+            &[
+                "-czf",
+                output.to_str().unwrap(),
+                "-C",
+                self.base_path.to_str().unwrap(),
+                network_id,
+            ],
+        )?;
+        check_tar_output(&out)
+    }
+
+    /// Unpacks a tarball created by `export_network` into the base directory
+    /// and returns the id of the imported network
+    pub fn import_network(&self, input: &Path) -> Result<String> {
+        let input = input.to_str().unwrap();
+        let network_id = tarball_network_id(input)?;
+        self.check_network_absent(&network_id)?;
+
+        self.ensure_base_path()?;
+        let out = run_command(
+            "tar",
+            &["-xzf", input, "-C", self.base_path.to_str().unwrap()],
+        )?;
+        check_tar_output(&out)?;
+        Ok(network_id)
+    }
+
+    /// Returns the directory volume tarballs are kept in while snapshotting/restoring
+    pub fn snapshot_volumes_path(&self, network_id: &str) -> PathBuf {
+        self.network_path(network_id).join(SNAPSHOT_VOLUMES)
+    }
+
+    /// Unpacks a snapshot taken of any network as `network_id`, rewriting the
+    /// hostnames and paths of the snapshotted network, and returns its id
+    pub fn restore_network(&self, input: &Path, network_id: &str) -> Result<String> {
+        let input = input.to_str().unwrap();
+        let snapshot_id = tarball_network_id(input)?;
+        self.check_network_absent(network_id)?;
+
+        self.ensure_base_path()?;
+        let unpack_dir = TempDir::new_in(&self.base_path, ".restore")?;
+        let out = run_command(
+            "tar",
+            &["-xzf", input, "-C", unpack_dir.path().to_str().unwrap()],
+        )?;
+        check_tar_output(&out)?;
+        fs::rename(
+            unpack_dir.path().join(&snapshot_id),
+            self.network_path(network_id),
+        )?;
+
+        if snapshot_id != network_id {
+            // volume tarballs are named after the volumes of the snapshotted network
+            let volumes_path = self.snapshot_volumes_path(network_id);
+            if volumes_path.exists() {
+                let suffix = format!("-{snapshot_id}.tar.gz");
+                for entry in fs::read_dir(&volumes_path)? {
+                    let name = entry?.file_name().to_string_lossy().to_string();
+                    if let Some(volume) = name.strip_suffix(&suffix) {
+                        fs::rename(
+                            volumes_path.join(&name),
+                            volumes_path.join(format!("{volume}-{network_id}.tar.gz")),
+                        )?;
+                    }
+                }
+            }
+            for path in [
+                self.services_file_path(network_id),
+                self.peer_list_file(network_id),
+            ] {
+                if path.exists() {
+                    let contents = fs::read_to_string(&path)?;
+                    fs::write(
+                        &path,
+                        rename_network_references(&contents, &snapshot_id, network_id),
+                    )?;
+                }
+            }
+        }
+        Ok(snapshot_id)
+    }
+
+    fn check_network_absent(&self, network_id: &str) -> Result<()> {
+        if self.network_path_exists(network_id) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("Network '{network_id}' already exists."),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Id of the network packed in a tarball created by `export_network`
+fn tarball_network_id(input: &str) -> Result<String> {
+    let out = run_command("tar", &["-tzf", input])?;
+    check_tar_output(&out)?;
+    let entries = String::from_utf8_lossy(&out.stdout);
+    network_id_from_entries(entries.lines())
+}
+
+/// Replaces the container hostnames (`{service}-{old}`) and network directory
+/// paths (`.../{old}/...`) of network `old` with those of network `new`
+fn rename_network_references(contents: &str, old: &str, new: &str) -> String {
+    contents
+        .replace(&format!("-{old}/tcp/"), &format!("-{new}/tcp/"))
+        .replace(&format!("/{old}/"), &format!("/{new}/"))
+}
+
+fn invalid_genesis_ledger(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Writes the genesis ledger at `source` to `dest` with its genesis timestamp set to `timestamp`
+fn write_genesis_timestamp(source: &Path, dest: &Path, timestamp: &str) -> Result<()> {
+    let contents = fs::read_to_string(source)?;
+    let mut ledger: serde_json::Value = serde_json::from_str(&contents)?;
+    let genesis = ledger
+        .get_mut("genesis")
+        .and_then(serde_json::Value::as_object_mut)
+        .ok_or_else(|| {
+            invalid_genesis_ledger("'genesis' field should be present in genesis ledger")
+        })?;
+    genesis.insert(
+        "genesis_state_timestamp".to_string(),
+        serde_json::Value::String(timestamp.to_string()),
+    );
+
+    fs::write(dest, serde_json::to_string_pretty(&ledger)?)
+}
+
+fn check_tar_output(out: &std::process::Output) -> Result<()> {
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "tar failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        )))
+    }
+}
+
+/// All entries of an exported network live under a single `{network_id}/` directory
+fn network_id_from_entries<'a>(mut entries: impl Iterator<Item = &'a str>) -> Result<String> {
+    let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string());
+    let network_id = entries
+        .next()
+        .and_then(|entry| entry.split('/').next())
+        .filter(|id| !id.is_empty() && *id != "." && *id != "..")
+        .ok_or_else(|| invalid("Archive does not contain a network directory."))?
+        .to_string();
+
+    let prefix = format!("{network_id}/");
+    if entries.any(|entry| !entry.starts_with(&prefix) || entry.split('/').any(|c| c == "..")) {
+        return Err(invalid(&format!(
+            "Archive contains entries outside of network directory '{network_id}'."
+        )));
+    }
+    Ok(network_id)
 }
 
 fn set_key_file_permissions(file: &Path) -> Result<()> {
@@ -472,6 +831,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_list_networks_without_base_dir() {
+        let tempdir = TempDir::new("test_list_networks_without_base_dir")
+            .expect("Cannot create temporary directory");
+        let dir_manager = DirectoryManager::_new_with_base_path(tempdir.path().join(".minimina"));
+        assert!(dir_manager.list_network_directories().unwrap().is_empty());
+
+        dir_manager.ensure_base_path().unwrap();
+        dir_manager.ensure_base_path().unwrap();
+        fs::create_dir(dir_manager.base_path.join(".restore1234")).unwrap();
+        dir_manager.create_network_directory("test").unwrap();
+        assert_eq!(
+            dir_manager.list_network_directories().unwrap(),
+            vec!["test"]
+        );
+    }
+
     #[test]
     fn test_chmod_network_subdirectories() {
         let tempdir = TempDir::new("test_chmod_network_subdirectories")
@@ -521,7 +897,7 @@ mod tests {
     }
 
     #[test]
-    fn test_check_genesis_timestamp() -> Result<()> {
+    fn test_genesis_staleness() -> Result<()> {
         use chrono::{prelude::*, Duration};
         let tempdir = TempDir::new("test_network_subdirectories_paths")
             .expect("Cannot create temporary directory");
@@ -566,11 +942,16 @@ mod tests {
 
         // genesis ledger is too old so the timestamp will be overwritten
         fs::write(genesis_ledger_path.clone(), old_genesis.clone())?;
-        assert!(dir_manager.check_genesis_timestamp(network_id).is_err());
+        assert!(dir_manager.genesis_staleness(network_id, None)?.stale);
 
         // genesis ledger is recent enough so the timestamp will not be overwritten
         fs::write(genesis_ledger_path.clone(), recent_genesis.clone())?;
-        assert!(dir_manager.check_genesis_timestamp(network_id).is_ok());
+        assert!(!dir_manager.genesis_staleness(network_id, None)?.stale);
+
+        // the threshold can be overridden
+        let staleness = dir_manager.genesis_staleness(network_id, Some(k as u64 / 2 * 3 - 5))?;
+        assert!(staleness.stale);
+        assert_eq!(staleness.minutes_since_genesis, k / 2 * 3 - 1);
 
         dir_manager.delete_network_directory(network_id)?;
 
@@ -614,6 +995,37 @@ mod tests {
         assert!(!files.contains(&file2.to_string()));
     }
 
+    #[test]
+    fn test_find_key_file() {
+        let tempdir =
+            TempDir::new("test_find_key_file").expect("Cannot create temporary directory");
+        let network_id = "test_network";
+        let dir_manager = DirectoryManager::_new_with_base_path(tempdir.path().into());
+        dir_manager.generate_dir_structure(network_id).unwrap();
+
+        let network_path = dir_manager.network_path(network_id);
+        fs::write(
+            network_path.join(NETWORK_KEYPAIRS).join("bp.pub"),
+            "B62qbp\n",
+        )
+        .unwrap();
+        assert_eq!(
+            dir_manager.find_key_file(network_id, "B62qalice").unwrap(),
+            None
+        );
+
+        fs::create_dir_all(network_path.join(ACCOUNTS)).unwrap();
+        fs::write(network_path.join(ACCOUNTS).join("alice.pub"), "B62qalice\n").unwrap();
+        assert_eq!(
+            dir_manager.find_key_file(network_id, "B62qbp").unwrap(),
+            Some("network-keypairs/bp".to_string())
+        );
+        assert_eq!(
+            dir_manager.find_key_file(network_id, "B62qalice").unwrap(),
+            Some("accounts/alice".to_string())
+        );
+    }
+
     #[test]
     fn test_save_network_info() {
         let tempdir =
@@ -637,13 +1049,29 @@ mod tests {
 
         // Save the network info
         dir_manager
-            .save_network_info(network_id, &services)
+            .save_network_info(network_id, &services, Runtime::Podman)
             .unwrap();
 
         // Check that the network info is saved
         let network_info = dir_manager.get_network_info(network_id).unwrap();
         assert!(network_info.contains("test_service1"));
+        assert_eq!(
+            Runtime::of_network(&dir_manager.network_path(network_id)),
+            Runtime::Podman
+        );
         assert!(network_info.contains("test_service2"));
+        let client: output::node::ClientConfig = serde_json::from_str(
+            &fs::read_to_string(
+                dir_manager
+                    .network_path(network_id)
+                    .join(CLIENTS)
+                    .join("test_service1.json"),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(client.container, "test_service1-test_network");
+        assert_eq!(client.daemon_port, 3100);
 
         // Clean up
         dir_manager.delete_network_directory(network_id).unwrap();
@@ -723,4 +1151,182 @@ mod tests {
             .exists());
         dir_manager.delete_network_directory(network_id).unwrap();
     }
+
+    #[test]
+    fn test_copy_uptime_service_config_with_app_config() {
+        use crate::service::{UptimeAppConfig, UptimeStorage};
+
+        let tempdir = TempDir::new("test_copy_uptime_service_config_with_app_config")
+            .expect("Cannot create temporary directory");
+        let network_id = "test_network";
+        let dir_manager = DirectoryManager::_new_with_base_path(tempdir.path().into());
+        let mut service = ServiceConfig {
+            service_name: "uptime".to_string(),
+            service_type: crate::service::ServiceType::UptimeServiceBackend,
+            uptime_service_backend_app_config: Some(PathBuf::from(
+                "./tests/data/uptime_service_network/uptime_service_config_test/app_config.json",
+            )),
+            uptime_app_config: Some(UptimeAppConfig {
+                network_name: Some("soak".to_string()),
+                delegation_whitelist_disabled: Some(true),
+                storage: Some(UptimeStorage::Filesystem),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        dir_manager.create_network_directory(network_id).unwrap();
+        dir_manager
+            .copy_uptime_service_config(network_id, &service)
+            .unwrap();
+
+        let app_config_path = dir_manager
+            .network_path(network_id)
+            .join("uptime_service_config")
+            .join("app_config.json");
+        let app_config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&app_config_path).unwrap()).unwrap();
+        assert_eq!(app_config["network_name"], "soak");
+        assert_eq!(app_config["delegation_whitelist_disabled"], true);
+        assert_eq!(app_config["filesystem"]["path"], "/uptime-storage");
+        assert_eq!(app_config["delegation_whitelist_column"], "E");
+        assert!(app_config.get("aws").is_none());
+        assert!(app_config.get("verify_signature_disabled").is_none());
+
+        // a storage needs its section in the app config file
+        fs::remove_file(&app_config_path).unwrap();
+        service.uptime_app_config = Some(UptimeAppConfig {
+            storage: Some(UptimeStorage::AwsKeyspaces),
+            ..Default::default()
+        });
+        assert!(dir_manager
+            .copy_uptime_service_config(network_id, &service)
+            .is_err());
+    }
+
+    #[test]
+    fn test_export_and_import_network() {
+        let tempdir = TempDir::new("test_export_and_import_network")
+            .expect("Cannot create temporary directory");
+        let source = DirectoryManager::_new_with_base_path(tempdir.path().join("source"));
+        let target = DirectoryManager::_new_with_base_path(tempdir.path().join("target"));
+        let network_id = "test_network";
+        let archive = tempdir.path().join("test_network.tar.gz");
+
+        source.generate_dir_structure(network_id).unwrap();
+        fs::write(
+            source.archive_dump_path(network_id, &ArchiveDb::primary()),
+            "-- dump",
+        )
+        .unwrap();
+        source.export_network(network_id, &archive).unwrap();
+
+        assert_eq!(target.import_network(&archive).unwrap(), network_id);
+        assert_eq!(
+            fs::read_to_string(target.archive_dump_path(network_id, &ArchiveDb::primary()))
+                .unwrap(),
+            "-- dump"
+        );
+        assert!(target
+            .network_path(network_id)
+            .join(NETWORK_KEYPAIRS)
+            .exists());
+
+        // importing the same network twice fails
+        let err = target.import_network(&archive).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    }
+
+    #[test]
+    fn test_restore_network_as_new_id() {
+        let tempdir = TempDir::new("test_restore_network_as_new_id")
+            .expect("Cannot create temporary directory");
+        let dir_manager = DirectoryManager::_new_with_base_path(tempdir.path().join("networks"));
+        let snapshot = tempdir.path().join("snap.tar.gz");
+
+        dir_manager.generate_dir_structure("old-net").unwrap();
+        let peer = "/dns4/mina-seed-1-old-net/tcp/3102/p2p/peerid\n";
+        fs::write(dir_manager.peer_list_file("old-net"), peer).unwrap();
+        let volumes_path = dir_manager.snapshot_volumes_path("old-net");
+        fs::create_dir_all(&volumes_path).unwrap();
+        fs::write(volumes_path.join("mina-seed-1-old-net.tar.gz"), "").unwrap();
+        fs::write(volumes_path.join("postgres-data.tar.gz"), "").unwrap();
+        dir_manager.export_network("old-net", &snapshot).unwrap();
+
+        assert_eq!(
+            dir_manager.restore_network(&snapshot, "new-net").unwrap(),
+            "old-net"
+        );
+        assert_eq!(
+            fs::read_to_string(dir_manager.peer_list_file("new-net")).unwrap(),
+            "/dns4/mina-seed-1-new-net/tcp/3102/p2p/peerid\n"
+        );
+        let volumes_path = dir_manager.snapshot_volumes_path("new-net");
+        assert!(volumes_path.join("mina-seed-1-new-net.tar.gz").exists());
+        assert!(volumes_path.join("postgres-data.tar.gz").exists());
+        // only the restored network and the original one are left behind
+        assert_eq!(dir_manager.list_network_directories().unwrap().len(), 2);
+
+        let err = dir_manager
+            .restore_network(&snapshot, "new-net")
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    }
+
+    #[test]
+    fn test_rename_network_references() {
+        let services = r#"{"peer_list_file": "/home/u/.minimina/net/peer_list_file.txt",
+            "peers": ["/dns4/mina-seed-1-net/tcp/3102/p2p/id"], "service_name": "net"}"#;
+        assert_eq!(
+            rename_network_references(services, "net", "copy"),
+            r#"{"peer_list_file": "/home/u/.minimina/copy/peer_list_file.txt",
+            "peers": ["/dns4/mina-seed-1-copy/tcp/3102/p2p/id"], "service_name": "net"}"#
+        );
+    }
+
+    #[test]
+    fn test_network_id_from_entries() {
+        let entries = ["net/", "net/services.json", "net/network-keypairs/bp.json"];
+        assert_eq!(network_id_from_entries(entries.into_iter()).unwrap(), "net");
+
+        let entries = ["net/", "other/services.json"];
+        assert!(network_id_from_entries(entries.into_iter()).is_err());
+
+        let entries = ["net/", "net/../escape"];
+        assert!(network_id_from_entries(entries.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_services_file() {
+        let tempdir = TempDir::new("test_services_file").unwrap();
+        let directory_manager = DirectoryManager::_new_with_base_path(tempdir.path().into());
+        directory_manager.create_network_directory("net").unwrap();
+        let services = vec![
+            ServiceConfig {
+                service_name: "coordinator".to_string(),
+                service_type: crate::service::ServiceType::SnarkCoordinator,
+                ..Default::default()
+            },
+            ServiceConfig {
+                service_name: "worker".to_string(),
+                service_type: crate::service::ServiceType::SnarkWorker,
+                snark_coordinator_host: Some("coordinator".to_string()),
+                ..Default::default()
+            },
+        ];
+        directory_manager
+            .save_services_info("net", &services)
+            .unwrap();
+        assert_eq!(directory_manager.get_services_info("net").unwrap().len(), 2);
+        let graph = directory_manager.get_service_graph("net").unwrap();
+        assert_eq!(graph.service_dependencies("worker"), ["coordinator"]);
+
+        // services.json of networks created before the graph was recorded
+        fs::write(
+            directory_manager.services_file_path("net"),
+            serde_json::to_string(&services).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(directory_manager.get_services_info("net").unwrap().len(), 2);
+        assert_eq!(directory_manager.get_service_graph("net").unwrap(), graph);
+    }
 }
@@ -0,0 +1,168 @@
+//! # Image Builder Module
+//!
+//! Builds the Mina daemon image of services which specify a `git_build` instead
+//! of a `docker_image` in the topology. The Mina repository is fetched at the
+//! commit or tag into `~/.minimina/.builds/<rev>`, the daemon image is built from
+//! it once per revision and tagged for every service using it.
+
+use crate::{
+    docker::runtime::Runtime, service::ServiceConfig, topology::GitBuild, utils::run_command,
+};
+use log::info;
+use std::{
+    fs,
+    io::{Error, Result},
+    path::{Path, PathBuf},
+    process::Output,
+};
+
+const MINA_REPOSITORY: &str = "https://github.com/MinaProtocol/mina.git";
+/// Dockerfile of the daemon image, relative to the repository root
+const DAEMON_DOCKERFILE: &str = "dockerfiles/Dockerfile-mina-daemon";
+const IMAGE_REPOSITORY: &str = "minimina/mina-daemon";
+
+pub struct ImageBuilder {
+    runtime: Runtime,
+    /// Directory the repository is checked out to, one subdirectory per revision
+    builds_path: PathBuf,
+}
+
+impl ImageBuilder {
+    pub fn new(runtime: Runtime, builds_path: &Path) -> Self {
+        ImageBuilder {
+            runtime,
+            builds_path: builds_path.to_path_buf(),
+        }
+    }
+
+    /// Sets the docker image of every service with a `git_build` but no `docker_image`
+    /// to one built from source and tagged for the service in `network_id`
+    pub fn build_services(&self, services: &mut [ServiceConfig], network_id: &str) -> Result<()> {
+        for service in services.iter_mut() {
+            let git_build = match (&service.docker_image, &service.git_build) {
+                (None, Some(git_build)) => git_build,
+                _ => continue,
+            };
+            let image = self.build(git_build)?;
+            let tag = service_image(&service.service_name, network_id, git_build);
+            check_output(run_command(self.binary(), &["tag", &image, &tag])?)?;
+            service.docker_image = Some(tag);
+        }
+        Ok(())
+    }
+
+    /// Builds the daemon image of `git_build` unless it was built before
+    fn build(&self, git_build: &GitBuild) -> Result<String> {
+        let image = revision_image(git_build);
+        if run_command(self.binary(), &["image", "inspect", &image])?
+            .status
+            .success()
+        {
+            return Ok(image);
+        }
+
+        let source = self.checkout(git_build)?;
+        info!("Building image '{image}' from '{}'", source.display());
+        let source = source.to_str().unwrap();
+        let dockerfile = format!("{source}/{DAEMON_DOCKERFILE}");
+        check_output(run_command(
+            self.binary(),
+            &["build", "-t", &image, "-f", &dockerfile, source],
+        )?)?;
+        Ok(image)
+    }
+
+    /// Fetches the Mina repository at the revision of `git_build`
+    fn checkout(&self, git_build: &GitBuild) -> Result<PathBuf> {
+        let rev = git_build.rev();
+        let source = self.builds_path.join(rev);
+        if source.join(DAEMON_DOCKERFILE).exists() {
+            return Ok(source);
+        }
+
+        info!("Fetching '{rev}' of '{MINA_REPOSITORY}'");
+        fs::create_dir_all(&source)?;
+        let dir = source.to_str().unwrap();
+        for args in [
+            vec!["-C", dir, "init", "--quiet"],
+            vec!["-C", dir, "fetch", "--depth", "1", MINA_REPOSITORY, rev],
+            vec!["-C", dir, "checkout", "--quiet", "FETCH_HEAD"],
+            vec![
+                "-C",
+                dir,
+                "submodule",
+                "update",
+                "--init",
+                "--recursive",
+                "--depth",
+                "1",
+            ],
+        ] {
+            if let Err(e) = run_command("git", &args).and_then(check_output) {
+                // do not leave a partial checkout behind for the next build
+                let _ = fs::remove_dir_all(&source);
+                return Err(e);
+            }
+        }
+        Ok(source)
+    }
+
+    fn binary(&self) -> &'static str {
+        self.runtime.engine().binary()
+    }
+}
+
+/// Image built from the revision of `git_build`, shared by all networks
+fn revision_image(git_build: &GitBuild) -> String {
+    format!("{IMAGE_REPOSITORY}:{}", git_build.rev())
+}
+
+/// Image tag of a service of `network_id` built from `git_build`
+fn service_image(service_name: &str, network_id: &str, git_build: &GitBuild) -> String {
+    // repository names must be lowercase, tags need not be
+    let repository = format!("{IMAGE_REPOSITORY}-{service_name}-{network_id}").to_lowercase();
+    format!("{repository}:{}", git_build.rev())
+}
+
+fn check_output(out: Output) -> Result<()> {
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(Error::other(
+            String::from_utf8_lossy(&out.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_names() {
+        let tag = GitBuild::Tag("3.0.0".to_string());
+        assert_eq!(revision_image(&tag), "minimina/mina-daemon:3.0.0");
+        assert_eq!(
+            service_image("mina-BP-1", "net", &tag),
+            "minimina/mina-daemon-mina-bp-1-net:3.0.0"
+        );
+
+        let commit = GitBuild::Commit("a1b2c3d".to_string());
+        assert_eq!(commit.rev(), "a1b2c3d");
+        assert_eq!(revision_image(&commit), "minimina/mina-daemon:a1b2c3d");
+    }
+
+    #[test]
+    fn test_services_with_images_are_not_built() {
+        let mut services = vec![ServiceConfig {
+            service_name: "seed".to_string(),
+            docker_image: Some("seed-image".to_string()),
+            git_build: Some(GitBuild::Tag("3.0.0".to_string())),
+            ..Default::default()
+        }];
+        ImageBuilder::new(Runtime::Docker, Path::new("/not-a-real-path"))
+            .build_services(&mut services, "net")
+            .unwrap();
+        assert_eq!(services[0].docker_image.as_deref(), Some("seed-image"));
+    }
+}
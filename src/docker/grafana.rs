@@ -0,0 +1,166 @@
+//! # Grafana Module
+//!
+//! Pre-built Grafana dashboards for Mina daemon metrics, embedded in the binary and
+//! provisioned into the network directory for the monitoring stack's Grafana to load:
+//! - `grafana/provisioning/dashboards/minimina.yaml`: the dashboard provider.
+//! - `grafana/provisioning/datasources/minimina.yaml`: the Prometheus datasource.
+//! - `grafana/dashboards/*.json`: block production, transaction pool, snark pool
+//!   and libp2p peers dashboards.
+//!
+//! The dashboards query the Prometheus datasource with uid [`PROMETHEUS_DATASOURCE_UID`]
+//! and group series by the `node` label of the scraped targets.
+
+use std::{
+    fs,
+    io::Result,
+    path::{Path, PathBuf},
+};
+
+/// Uid of the Prometheus datasource the dashboards query
+pub const PROMETHEUS_DATASOURCE_UID: &str = "prometheus";
+
+/// Grafana directory in the network directory
+pub const GRAFANA_DIR: &str = "grafana";
+
+/// Path the provisioned dashboards are mounted at in the Grafana container
+pub const GRAFANA_DASHBOARDS_PATH: &str = "/var/lib/grafana/dashboards";
+
+/// Embedded dashboards by file name
+const DASHBOARDS: &[(&str, &str)] = &[
+    (
+        "block_production.json",
+        include_str!("dashboards/block_production.json"),
+    ),
+    (
+        "transaction_pool.json",
+        include_str!("dashboards/transaction_pool.json"),
+    ),
+    (
+        "snark_pool.json",
+        include_str!("dashboards/snark_pool.json"),
+    ),
+    (
+        "libp2p_peers.json",
+        include_str!("dashboards/libp2p_peers.json"),
+    ),
+];
+
+/// Writes the dashboards and their provider config to the network's Grafana directory
+/// and returns the directory
+pub fn provision_dashboards(network_path: &Path) -> Result<PathBuf> {
+    let grafana_path = network_path.join(GRAFANA_DIR);
+    let provisioning_path = grafana_path.join("provisioning").join("dashboards");
+    let dashboards_path = grafana_path.join("dashboards");
+    fs::create_dir_all(&provisioning_path)?;
+    fs::create_dir_all(&dashboards_path)?;
+
+    fs::write(
+        provisioning_path.join("minimina.yaml"),
+        dashboard_provider(),
+    )?;
+    for (file_name, dashboard) in DASHBOARDS {
+        fs::write(dashboards_path.join(file_name), dashboard)?;
+    }
+
+    Ok(grafana_path)
+}
+
+/// Writes the config of the Prometheus datasource served at `prometheus_url` to the
+/// network's Grafana directory
+pub fn provision_datasource(network_path: &Path, prometheus_url: &str) -> Result<()> {
+    let provisioning_path = network_path
+        .join(GRAFANA_DIR)
+        .join("provisioning")
+        .join("datasources");
+    fs::create_dir_all(&provisioning_path)?;
+    fs::write(
+        provisioning_path.join("minimina.yaml"),
+        datasource(prometheus_url),
+    )
+}
+
+fn datasource(prometheus_url: &str) -> String {
+    format!(
+        "apiVersion: 1\n\
+        datasources:\n  \
+          - name: Prometheus\n    \
+            uid: {PROMETHEUS_DATASOURCE_UID}\n    \
+            type: prometheus\n    \
+            access: proxy\n    \
+            url: {prometheus_url}\n    \
+            isDefault: true\n"
+    )
+}
+
+fn dashboard_provider() -> String {
+    format!(
+        "apiVersion: 1\n\
+        providers:\n  \
+          - name: minimina\n    \
+            folder: Mina\n    \
+            type: file\n    \
+            disableDeletion: true\n    \
+            options:\n      \
+              path: {GRAFANA_DASHBOARDS_PATH}\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_embedded_dashboards() {
+        for (file_name, dashboard) in DASHBOARDS {
+            let dashboard: serde_json::Value = serde_json::from_str(dashboard)
+                .unwrap_or_else(|e| panic!("{file_name} is not valid JSON: {e}"));
+            assert!(dashboard["uid"].as_str().unwrap().starts_with("minimina-"));
+            for panel in dashboard["panels"].as_array().unwrap() {
+                assert_eq!(panel["datasource"]["uid"], PROMETHEUS_DATASOURCE_UID);
+            }
+        }
+    }
+
+    #[test]
+    fn test_provision_dashboards() {
+        let tempdir =
+            TempDir::new("test_provision_dashboards").expect("Cannot create temporary directory");
+        let grafana_path = provision_dashboards(tempdir.path()).unwrap();
+
+        let provider =
+            fs::read_to_string(grafana_path.join("provisioning/dashboards/minimina.yaml")).unwrap();
+        assert!(provider.contains(&format!("path: {GRAFANA_DASHBOARDS_PATH}")));
+        assert_eq!(
+            fs::read_dir(grafana_path.join("dashboards"))
+                .unwrap()
+                .count(),
+            DASHBOARDS.len()
+        );
+    }
+
+    #[test]
+    fn test_provision_datasource() {
+        let tempdir =
+            TempDir::new("test_provision_datasource").expect("Cannot create temporary directory");
+        provision_datasource(tempdir.path(), "http://prometheus-net:9090").unwrap();
+
+        let datasource: serde_yaml::Value = serde_yaml::from_str(
+            &fs::read_to_string(
+                tempdir
+                    .path()
+                    .join("grafana/provisioning/datasources/minimina.yaml"),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            datasource["datasources"][0]["uid"],
+            PROMETHEUS_DATASOURCE_UID
+        );
+        assert_eq!(
+            datasource["datasources"][0]["url"],
+            "http://prometheus-net:9090"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! # Health Module
+//!
+//! Health probe sidecars added next to the daemons of a network created with
+//! `--with-health-probes`.
+//!
+//! Each sidecar serves `GET /healthz` on a published host port, answering `200` when
+//! its daemon's GraphQL `syncStatus` is `SYNCED` and `503` otherwise, so load
+//! balancers, k8s-style probes and CI smoke tests can check a node without speaking
+//! GraphQL. The sidecars run a small Python script written to the network's `health`
+//! directory; the host port of each daemon's sidecar is recorded in its service
+//! config.
+
+use crate::service::ServiceConfig;
+use std::{fs, io::Result, path::Path};
+
+pub const HEALTH_IMAGE: &str = "python:3-alpine";
+/// Port the sidecars listen on in their container
+pub const HEALTH_PORT: u16 = 8080;
+/// First host port tried for the sidecars, clear of the uptime service backend's 8080
+pub const DEFAULT_HEALTH_HOST_PORT: u16 = 18080;
+/// Health directory in the network directory, mounted as `/health`
+pub const HEALTH_DIR: &str = "health";
+const HEALTH_SCRIPT: &str = "healthz.py";
+
+const SCRIPT: &str = r#"import json
+import os
+import urllib.request
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+GRAPHQL_URL = os.environ["GRAPHQL_URL"]
+PORT = int(os.environ.get("HEALTH_PORT", "8080"))
+QUERY = json.dumps({"query": "{ syncStatus }"}).encode()
+
+
+def sync_status():
+    request = urllib.request.Request(
+        GRAPHQL_URL, data=QUERY, headers={"Content-Type": "application/json"}
+    )
+    with urllib.request.urlopen(request, timeout=5) as response:
+        return json.load(response)["data"]["syncStatus"]
+
+
+class Handler(BaseHTTPRequestHandler):
+    def do_GET(self):
+        if self.path.split("?")[0] != "/healthz":
+            self.send_error(404)
+            return
+        body = {"sync_status": None}
+        try:
+            body["sync_status"] = sync_status()
+        except Exception as e:
+            body["error"] = str(e)
+        body["healthy"] = body["sync_status"] == "SYNCED"
+        payload = json.dumps(body).encode()
+        self.send_response(200 if body["healthy"] else 503)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(payload)))
+        self.end_headers()
+        self.wfile.write(payload)
+
+    def log_message(self, format, *args):
+        pass
+
+
+HTTPServer(("0.0.0.0", PORT), Handler).serve_forever()
+"#;
+
+/// Name of the health sidecar of the daemon `service_name`
+pub fn health_service(service_name: &str, network_name: &str) -> String {
+    format!("{service_name}-health-{network_name}")
+}
+
+/// Whether any service of `configs` has a health sidecar
+pub fn enabled(configs: &[ServiceConfig]) -> bool {
+    configs
+        .iter()
+        .any(|config| config.published_health_port().is_some())
+}
+
+/// Path of the probe script in the sidecar containers
+pub fn script_path() -> String {
+    format!("/{HEALTH_DIR}/{HEALTH_SCRIPT}")
+}
+
+/// Writes the probe script run by the sidecars to the network's health directory
+pub fn write_script(network_path: &Path) -> Result<()> {
+    let health_path = network_path.join(HEALTH_DIR);
+    fs::create_dir_all(&health_path)?;
+    fs::write(health_path.join(HEALTH_SCRIPT), SCRIPT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::ServiceType;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_write_script() {
+        let tempdir = TempDir::new("test_write_health_script").unwrap();
+        let network_path = tempdir.path().join("net");
+        write_script(&network_path).unwrap();
+
+        let script = fs::read_to_string(network_path.join("health/healthz.py")).unwrap();
+        assert!(script.contains("/healthz"));
+        assert!(script.contains("syncStatus"));
+    }
+
+    #[test]
+    fn test_enabled() {
+        let mut seed = ServiceConfig {
+            service_name: "seed".to_string(),
+            service_type: ServiceType::Seed,
+            client_port: Some(3100),
+            ..Default::default()
+        };
+        assert!(!enabled(&[seed.clone()]));
+        seed.health_host_port = Some(18080);
+        assert!(enabled(&[seed]));
+    }
+}
@@ -1,2 +1,8 @@
+pub mod builder;
 pub mod compose;
+pub mod grafana;
+pub mod health;
 pub mod manager;
+pub mod monitoring;
+pub mod postgres;
+pub mod runtime;
@@ -0,0 +1,245 @@
+//! # Monitoring Module
+//!
+//! Prometheus and Grafana services added to the compose file of a network created
+//! with `--with-monitoring`.
+//!
+//! Prometheus scrapes the metrics and libp2p metrics ports of every daemon, each
+//! target labelled with the `node` it belongs to. Its config is generated into the
+//! network's `prometheus/prometheus.yml` whenever the compose file is rendered, so it
+//! follows the nodes of the network. Grafana is provisioned with the Prometheus
+//! datasource and the dashboards of the [`grafana`](super::grafana) module.
+//!
+//! The host ports both are published on are recorded in the network's
+//! `monitoring.json`, whose presence enables the stack.
+
+use crate::{docker::grafana, service::ServiceConfig};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::Result,
+    path::{Path, PathBuf},
+};
+
+pub const PROMETHEUS_IMAGE: &str = "prom/prometheus";
+pub const GRAFANA_IMAGE: &str = "grafana/grafana";
+pub const PROMETHEUS_PORT: u16 = 9090;
+pub const GRAFANA_PORT: u16 = 3000;
+/// Prometheus directory in the network directory, mounted as `/etc/prometheus`
+pub const PROMETHEUS_DIR: &str = "prometheus";
+const MONITORING_FILE: &str = "monitoring.json";
+const SCRAPE_INTERVAL: &str = "5s";
+
+/// Monitoring stack of a network, as recorded in its `monitoring.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Monitoring {
+    pub prometheus_host_port: u16,
+    pub grafana_host_port: u16,
+}
+
+impl Monitoring {
+    fn path(network_path: &Path) -> PathBuf {
+        network_path.join(MONITORING_FILE)
+    }
+
+    /// Monitoring stack of the network at `network_path`, `None` if it has none
+    pub fn load(network_path: &Path) -> Result<Option<Self>> {
+        match fs::read_to_string(Self::path(network_path)) {
+            Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Records the stack and provisions Grafana's datasource and dashboards
+    pub fn save(&self, network_path: &Path) -> Result<()> {
+        let network_name = network_name(network_path);
+        grafana::provision_dashboards(network_path)?;
+        grafana::provision_datasource(
+            network_path,
+            &format!(
+                "http://{}:{PROMETHEUS_PORT}",
+                prometheus_service(network_name)
+            ),
+        )?;
+        fs::write(
+            Self::path(network_path),
+            serde_json::to_string_pretty(self)?,
+        )
+    }
+
+    pub fn grafana_url(&self) -> String {
+        format!("http://localhost:{}", self.grafana_host_port)
+    }
+
+    pub fn prometheus_url(&self) -> String {
+        format!("http://localhost:{}", self.prometheus_host_port)
+    }
+}
+
+pub fn prometheus_service(network_name: &str) -> String {
+    format!("prometheus-{network_name}")
+}
+
+pub fn grafana_service(network_name: &str) -> String {
+    format!("grafana-{network_name}")
+}
+
+fn network_name(network_path: &Path) -> &str {
+    network_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .expect("Failed to get network name from network path")
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct PrometheusConfig {
+    global: Global,
+    scrape_configs: Vec<ScrapeConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Global {
+    scrape_interval: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct ScrapeConfig {
+    job_name: String,
+    static_configs: Vec<StaticConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct StaticConfig {
+    targets: Vec<String>,
+    labels: Labels,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Labels {
+    node: String,
+}
+
+/// Metrics and libp2p metrics ports of the daemons of `configs`, by node
+fn metrics_ports(configs: &[ServiceConfig]) -> Vec<(&str, u16, u16)> {
+    configs
+        .iter()
+        .filter_map(|config| {
+            let client_port = config.daemon_port()?;
+            Some((
+                config.service_name.as_str(),
+                client_port + 3,
+                client_port + 4,
+            ))
+        })
+        .collect()
+}
+
+fn prometheus_config(configs: &[ServiceConfig], network_name: &str) -> PrometheusConfig {
+    let nodes = metrics_ports(configs);
+    let job = |job_name: &str, port: fn(&(&str, u16, u16)) -> u16| ScrapeConfig {
+        job_name: job_name.to_string(),
+        static_configs: nodes
+            .iter()
+            .map(|node| StaticConfig {
+                targets: vec![format!("{}-{network_name}:{}", node.0, port(node))],
+                labels: Labels {
+                    node: node.0.to_string(),
+                },
+            })
+            .collect(),
+    };
+    PrometheusConfig {
+        global: Global {
+            scrape_interval: SCRAPE_INTERVAL.to_string(),
+        },
+        scrape_configs: vec![job("mina", |node| node.1), job("libp2p", |node| node.2)],
+    }
+}
+
+/// Writes the Prometheus config scraping the daemons of `configs` to the network's
+/// Prometheus directory
+pub fn write_prometheus_config(network_path: &Path, configs: &[ServiceConfig]) -> Result<()> {
+    let prometheus_path = network_path.join(PROMETHEUS_DIR);
+    fs::create_dir_all(&prometheus_path)?;
+    let config = prometheus_config(configs, network_name(network_path));
+    fs::write(
+        prometheus_path.join("prometheus.yml"),
+        serde_yaml::to_string(&config).map_err(std::io::Error::other)?,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::ServiceType;
+    use tempdir::TempDir;
+
+    fn configs() -> Vec<ServiceConfig> {
+        [
+            ("seed", ServiceType::Seed, 3100),
+            ("bp", ServiceType::BlockProducer, 4000),
+            ("worker", ServiceType::SnarkWorker, 5000),
+            ("archive-service", ServiceType::ArchiveService, 6000),
+        ]
+        .into_iter()
+        .map(|(name, service_type, client_port)| ServiceConfig {
+            service_name: name.to_string(),
+            service_type,
+            client_port: Some(client_port),
+            ..Default::default()
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_write_prometheus_config() {
+        let tempdir = TempDir::new("test_write_prometheus_config").unwrap();
+        let network_path = tempdir.path().join("net");
+        write_prometheus_config(&network_path, &configs()).unwrap();
+
+        let config: PrometheusConfig = serde_yaml::from_str(
+            &fs::read_to_string(network_path.join("prometheus/prometheus.yml")).unwrap(),
+        )
+        .unwrap();
+        let targets = |job: usize| -> Vec<(String, String)> {
+            config.scrape_configs[job]
+                .static_configs
+                .iter()
+                .map(|c| (c.targets[0].clone(), c.labels.node.clone()))
+                .collect()
+        };
+        assert_eq!(config.scrape_configs.len(), 2);
+        assert_eq!(
+            targets(0),
+            vec![
+                ("seed-net:3103".to_string(), "seed".to_string()),
+                ("bp-net:4003".to_string(), "bp".to_string()),
+            ]
+        );
+        assert_eq!(
+            targets(1),
+            vec![
+                ("seed-net:3104".to_string(), "seed".to_string()),
+                ("bp-net:4004".to_string(), "bp".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monitoring_file() {
+        let tempdir = TempDir::new("test_monitoring_file").unwrap();
+        let network_path = tempdir.path().join("net");
+        fs::create_dir_all(&network_path).unwrap();
+        assert_eq!(Monitoring::load(&network_path).unwrap(), None);
+
+        let monitoring = Monitoring {
+            prometheus_host_port: 9090,
+            grafana_host_port: 3000,
+        };
+        monitoring.save(&network_path).unwrap();
+        assert_eq!(Monitoring::load(&network_path).unwrap(), Some(monitoring));
+        assert!(network_path
+            .join("grafana/provisioning/datasources/minimina.yaml")
+            .exists());
+    }
+}
@@ -0,0 +1,470 @@
+//! # Container Runtime Module
+//!
+//! The container engine a network runs on. Networks run on docker (or a docker
+//! compatible CLI, see [`crate::config`]) by default; `network create --runtime podman`
+//! runs them on podman with `podman compose` instead. The runtime is persisted in
+//! `network.json` and used by all later commands on the network.
+//!
+//! Both runtimes share the container CLI (`run`, `exec`, `cp`, `inspect`, `logs`);
+//! the compose implementations differ in the subcommands they support, which
+//! [`ContainerRuntime`] papers over.
+
+use crate::config::docker_binary;
+use crate::docker::compose::ComposeProfile;
+use crate::docker::manager::{ComposeInfo, ContainerInfo, ContainerState, Publisher};
+use crate::utils::run_command;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::Result,
+    path::Path,
+    process::{Child, Command, Output, Stdio},
+};
+
+const COMPOSE_PROJECT_LABEL: &str = "com.docker.compose.project";
+const COMPOSE_SERVICE_LABEL: &str = "com.docker.compose.service";
+const COMPOSE_CONFIG_FILES_LABEL: &str = "com.docker.compose.project.config_files";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Runtime {
+    #[default]
+    Docker,
+    Podman,
+}
+
+impl Runtime {
+    pub fn is_default(&self) -> bool {
+        *self == Runtime::default()
+    }
+
+    /// Runtime recorded in the network's `network.json`, docker for networks
+    /// not created yet or created before the runtime was recorded
+    pub fn of_network(network_path: &Path) -> Self {
+        #[derive(Deserialize)]
+        struct NetworkRuntime {
+            #[serde(default)]
+            runtime: Runtime,
+        }
+
+        fs::read_to_string(network_path.join("network.json"))
+            .ok()
+            .and_then(|contents| serde_json::from_str::<NetworkRuntime>(&contents).ok())
+            .map(|network| network.runtime)
+            .unwrap_or_default()
+    }
+
+    pub fn engine(&self) -> &'static dyn ContainerRuntime {
+        match self {
+            Runtime::Docker => &Docker,
+            Runtime::Podman => &Podman,
+        }
+    }
+}
+
+impl std::fmt::Display for Runtime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Runtime::Docker => write!(f, "docker"),
+            Runtime::Podman => write!(f, "podman"),
+        }
+    }
+}
+
+pub trait ContainerRuntime {
+    /// CLI running single containers
+    fn binary(&self) -> &str;
+
+    /// Arguments of the runtime's compose for docker compose `args`
+    fn compose_args<'a>(&self, args: &[&'a str]) -> Vec<&'a str> {
+        args.to_vec()
+    }
+
+    /// Compose command running `args` on the `project` defined in `compose_file`, with
+    /// the services of `profiles` enabled
+    fn compose_command<'a>(
+        &self,
+        compose_file: &'a Path,
+        project: &'a str,
+        profiles: &[ComposeProfile],
+        args: &[&'a str],
+    ) -> Vec<&'a str> {
+        let mut cmd = vec![
+            "compose",
+            "-f",
+            compose_file.to_str().unwrap(),
+            "-p",
+            project,
+        ];
+        for profile in profiles {
+            cmd.extend(["--profile", profile.name()]);
+        }
+        cmd.extend(self.compose_args(args));
+        cmd
+    }
+
+    /// Run compose `args` on the `project` defined in `compose_file`
+    fn compose(
+        &self,
+        compose_file: &Path,
+        project: &str,
+        profiles: &[ComposeProfile],
+        args: &[&str],
+    ) -> Result<Output> {
+        let cmd = self.compose_command(compose_file, project, profiles, args);
+        run_command(self.binary(), &cmd)
+    }
+
+    /// Spawn compose `args` on the `project` with its stdout piped, for
+    /// commands whose output is read while they run
+    fn compose_spawn(
+        &self,
+        compose_file: &Path,
+        project: &str,
+        profiles: &[ComposeProfile],
+        args: &[&str],
+    ) -> Result<Child> {
+        let cmd = self.compose_command(compose_file, project, profiles, args);
+        Command::new(self.binary())
+            .args(&cmd)
+            .stdout(Stdio::piped())
+            .spawn()
+    }
+
+    /// Containers of the `project`, only those in `state` and of `service` if given
+    fn project_containers(
+        &self,
+        compose_file: &Path,
+        project: &str,
+        state: Option<ContainerState>,
+        service: Option<&str>,
+    ) -> Result<Vec<ContainerInfo>>;
+
+    /// All compose projects known to the runtime
+    fn projects(&self) -> Result<Vec<ComposeInfo>>;
+
+    /// Version of the runtime's compose, if it can be compared to docker compose's
+    fn compose_version(&self) -> Option<String>;
+
+    /// Whether the runtime runs `compose` at all
+    fn supports_compose(&self) -> bool {
+        run_command(self.binary(), &["compose", "version"])
+            .is_ok_and(|output| output.status.success())
+    }
+}
+
+pub struct Docker;
+
+impl ContainerRuntime for Docker {
+    fn binary(&self) -> &str {
+        docker_binary()
+    }
+
+    fn project_containers(
+        &self,
+        compose_file: &Path,
+        project: &str,
+        state: Option<ContainerState>,
+        service: Option<&str>,
+    ) -> Result<Vec<ContainerInfo>> {
+        let filter = state.map(|state| format!("status={state}"));
+        let mut args = vec!["ps", "-a", "--format", "json"];
+        if let Some(filter) = &filter {
+            args.extend(["--filter", filter.as_str()]);
+        }
+        args.extend(service);
+
+        // containers of all profiles, whichever were started
+        let output = self.compose(compose_file, project, &ComposeProfile::ALL, &args)?;
+        let stdout_str = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout_str
+            .trim()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<ContainerInfo>(line).ok())
+            .collect())
+    }
+
+    fn projects(&self) -> Result<Vec<ComposeInfo>> {
+        let output = run_command(self.binary(), &["compose", "ls", "--format", "json"])?;
+        let stdout_str = String::from_utf8_lossy(&output.stdout);
+        Ok(serde_json::from_str(&stdout_str)?)
+    }
+
+    fn compose_version(&self) -> Option<String> {
+        let output = run_command(self.binary(), &["compose", "version", "--short"]).ok()?;
+        if output.status.success() {
+            let stdout_str = String::from_utf8_lossy(&output.stdout);
+            Some(stdout_str.trim().to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Podman with `podman compose`, which has no `create` and `ls` and
+/// lists containers in podman's own format
+pub struct Podman;
+
+/// Container as listed by `podman ps --format json`
+#[derive(Debug, Deserialize)]
+struct PodmanContainer {
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "Names")]
+    names: Vec<String>,
+    #[serde(rename = "Image")]
+    image: String,
+    #[serde(rename = "Command", default)]
+    command: Option<Vec<String>>,
+    #[serde(rename = "CreatedAt", default)]
+    created_at: String,
+    #[serde(rename = "State")]
+    state: ContainerState,
+    #[serde(rename = "Status", default)]
+    status: String,
+    #[serde(rename = "ExitCode", default)]
+    exit_code: i32,
+    #[serde(rename = "Labels", default)]
+    labels: Option<HashMap<String, String>>,
+    #[serde(rename = "Ports", default)]
+    ports: Option<Vec<PodmanPort>>,
+}
+
+/// Published port range as listed by `podman ps --format json`
+#[derive(Debug, Deserialize)]
+struct PodmanPort {
+    #[serde(default)]
+    host_ip: String,
+    container_port: u16,
+    host_port: u16,
+    #[serde(default = "default_port_range")]
+    range: u16,
+    protocol: String,
+}
+
+fn default_port_range() -> u16 {
+    1
+}
+
+impl PodmanContainer {
+    fn label(&self, key: &str) -> Option<&str> {
+        self.labels.as_ref()?.get(key).map(String::as_str)
+    }
+
+    fn into_container_info(self) -> ContainerInfo {
+        let health = ["healthy", "unhealthy", "starting"]
+            .into_iter()
+            .find(|health| self.status.contains(&format!("({health})")))
+            .unwrap_or_default()
+            .to_string();
+        let mut labels: Vec<String> = self
+            .labels
+            .iter()
+            .flatten()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect();
+        labels.sort();
+        let publishers = self.ports.as_ref().map(|ports| {
+            ports
+                .iter()
+                .flat_map(|port| {
+                    (0..port.range).map(move |offset| Publisher {
+                        url: port.host_ip.clone(),
+                        target_port: port.container_port + offset,
+                        published_port: port.host_port + offset,
+                        protocol: port.protocol.clone(),
+                    })
+                })
+                .collect()
+        });
+        ContainerInfo {
+            service: self
+                .label(COMPOSE_SERVICE_LABEL)
+                .unwrap_or_default()
+                .to_string(),
+            id: self.id,
+            name: self.names.into_iter().next().unwrap_or_default(),
+            image: self.image,
+            command: self.command.unwrap_or_default().join(" "),
+            created_at: self.created_at,
+            state: self.state,
+            status: self.status,
+            health,
+            exit_code: self.exit_code,
+            labels: labels.join(","),
+            publishers,
+        }
+    }
+}
+
+impl Podman {
+    fn ps(&self, filters: &[String]) -> Result<Vec<PodmanContainer>> {
+        let mut args = vec!["ps", "-a", "--format", "json"];
+        for filter in filters {
+            args.extend(["--filter", filter.as_str()]);
+        }
+        let output = run_command(self.binary(), &args)?;
+        let stdout_str = String::from_utf8_lossy(&output.stdout);
+        if stdout_str.trim().is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(serde_json::from_str(&stdout_str)?)
+    }
+}
+
+impl ContainerRuntime for Podman {
+    fn binary(&self) -> &str {
+        "podman"
+    }
+
+    fn compose_args<'a>(&self, args: &[&'a str]) -> Vec<&'a str> {
+        match args {
+            ["create", services @ ..] => [&["up", "--no-start"][..], services].concat(),
+            ["pull", ..] => vec!["pull"],
+            _ => args.to_vec(),
+        }
+    }
+
+    fn project_containers(
+        &self,
+        _compose_file: &Path,
+        project: &str,
+        state: Option<ContainerState>,
+        service: Option<&str>,
+    ) -> Result<Vec<ContainerInfo>> {
+        let mut filters = vec![format!("label={COMPOSE_PROJECT_LABEL}={project}")];
+        if let Some(state) = state {
+            filters.push(format!("status={state}"));
+        }
+        if let Some(service) = service {
+            filters.push(format!("label={COMPOSE_SERVICE_LABEL}={service}"));
+        }
+        Ok(self
+            .ps(&filters)?
+            .into_iter()
+            .map(PodmanContainer::into_container_info)
+            .collect())
+    }
+
+    fn projects(&self) -> Result<Vec<ComposeInfo>> {
+        Ok(projects_of(self.ps(&[])?))
+    }
+
+    fn compose_version(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Compose projects of the containers, with the status of each project in
+/// the format of `docker compose ls`, e.g. `exited(1), running(2)`
+fn projects_of(containers: Vec<PodmanContainer>) -> Vec<ComposeInfo> {
+    let mut projects: BTreeMap<String, (BTreeMap<String, u32>, String)> = BTreeMap::new();
+    for container in &containers {
+        let Some(project) = container.label(COMPOSE_PROJECT_LABEL) else {
+            continue;
+        };
+        let (states, config_files) = projects.entry(project.to_string()).or_default();
+        *states.entry(container.state.to_string()).or_default() += 1;
+        if let Some(files) = container.label(COMPOSE_CONFIG_FILES_LABEL) {
+            *config_files = files.to_string();
+        }
+    }
+
+    projects
+        .into_iter()
+        .map(|(name, (states, config_files))| ComposeInfo {
+            name,
+            status: states
+                .iter()
+                .map(|(state, count)| format!("{state}({count})"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            config_files,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PODMAN_PS: &str = r#"[
+        {"Id": "a1", "Names": ["mina-bp-1-net"], "Image": "daemon-image",
+         "Command": ["daemon"], "CreatedAt": "2 minutes ago", "State": "running",
+         "Status": "Up 2 minutes", "ExitCode": 0,
+         "Ports": [{"host_ip": "", "container_port": 7006, "host_port": 20001, "range": 2, "protocol": "tcp"}],
+         "Labels": {"com.docker.compose.project": "net",
+                    "com.docker.compose.service": "mina-bp-1-net",
+                    "com.docker.compose.project.config_files": "/home/u/.minimina/net/docker-compose.yaml"}},
+        {"Id": "b2", "Names": ["postgres-net"], "Image": "postgres", "State": "exited",
+         "Status": "Exited (0) 1 minute ago (healthy)", "ExitCode": 0,
+         "Labels": {"com.docker.compose.project": "net",
+                    "com.docker.compose.service": "postgres-net"}},
+        {"Id": "c3", "Names": ["unrelated"], "Image": "alpine", "State": "running",
+         "Status": "Up", "Labels": null}
+    ]"#;
+
+    #[test]
+    fn test_podman_containers() {
+        let containers: Vec<PodmanContainer> = serde_json::from_str(PODMAN_PS).unwrap();
+        let info: Vec<ContainerInfo> = containers
+            .into_iter()
+            .map(PodmanContainer::into_container_info)
+            .collect();
+
+        assert_eq!(info[0].name, "mina-bp-1-net");
+        assert_eq!(info[0].service, "mina-bp-1-net");
+        assert_eq!(info[0].state, ContainerState::Running);
+        assert_eq!(info[0].command, "daemon");
+        let publishers = info[0].publishers.as_ref().unwrap();
+        assert_eq!(publishers.len(), 2);
+        assert_eq!(publishers[1].target_port, 7007);
+        assert_eq!(publishers[1].published_port, 20002);
+        assert_eq!(info[1].publishers, None);
+        assert_eq!(info[1].health, "healthy");
+        assert_eq!(info[1].state, ContainerState::Exited);
+        assert_eq!(info[2].service, "");
+    }
+
+    #[test]
+    fn test_podman_projects() {
+        let containers: Vec<PodmanContainer> = serde_json::from_str(PODMAN_PS).unwrap();
+        let projects = projects_of(containers);
+
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "net");
+        assert_eq!(projects[0].status, "exited(1), running(1)");
+        assert_eq!(
+            projects[0].config_files,
+            "/home/u/.minimina/net/docker-compose.yaml"
+        );
+    }
+
+    #[test]
+    fn test_podman_compose_args() {
+        assert_eq!(
+            Podman.compose_args(&["create", "postgres-net"]),
+            vec!["up", "--no-start", "postgres-net"]
+        );
+        assert_eq!(
+            Podman.compose_args(&["pull", "--ignore-buildable", "--quiet"]),
+            vec!["pull"]
+        );
+        assert_eq!(Podman.compose_args(&["start"]), vec!["start"]);
+        assert_eq!(Docker.compose_args(&["create"]), vec!["create"]);
+    }
+
+    #[test]
+    fn test_runtime_of_network() {
+        let tempdir = tempdir::TempDir::new("test_runtime_of_network").unwrap();
+        assert_eq!(Runtime::of_network(tempdir.path()), Runtime::Docker);
+
+        fs::write(
+            tempdir.path().join("network.json"),
+            r#"{"network_id": "net", "nodes": {}, "runtime": "podman"}"#,
+        )
+        .unwrap();
+        assert_eq!(Runtime::of_network(tempdir.path()), Runtime::Podman);
+    }
+}
@@ -0,0 +1,81 @@
+//! # Error Module
+//!
+//! Errors of the `minimina` commands and of the [`crate::api`] they are built on.
+//! Both return them instead of exiting the process, `main` reports them and turns
+//! them into the exit code.
+
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MiniminaError {
+    /// A command failed, the message is reported to the user as is
+    #[error("{0}")]
+    Command(String),
+    /// The directory of the network doesn't exist
+    #[error(
+        "Network directory '{}' does not exist, therefore network '{network_id}' does not exist too.",
+        path.display()
+    )]
+    NetworkNotFound { network_id: String, path: PathBuf },
+    /// The node is not a service of the network
+    #[error("Node '{node_id}' does not exist in network '{network_id}'.")]
+    NodeNotFound { node_id: String, network_id: String },
+    /// `network watch-height` saw the chain height stop advancing
+    #[error("Chain height of network '{network_id}' has not advanced for {seconds}s.")]
+    ChainStalled { network_id: String, seconds: u64 },
+    /// `node exec -r` ran a command that failed, `topology validate` found problems or
+    /// the archive didn't catch up after `network archive-fault`, the output has been
+    /// printed as is
+    #[error("Command exited with code {0}.")]
+    Exited(i32),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, MiniminaError>;
+
+impl MiniminaError {
+    /// Exit code of the `minimina` process failing with this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            MiniminaError::Exited(code) => *code,
+            _ => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_messages() {
+        let error = MiniminaError::ChainStalled {
+            network_id: "test".to_string(),
+            seconds: 120,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Chain height of network 'test' has not advanced for 120s."
+        );
+
+        let error: MiniminaError = std::io::Error::other("compose failed").into();
+        assert_eq!(error.to_string(), "compose failed");
+        assert_eq!(error.exit_code(), 1);
+
+        assert_eq!(MiniminaError::Exited(3).exit_code(), 3);
+
+        let error = MiniminaError::NetworkNotFound {
+            network_id: "test".to_string(),
+            path: PathBuf::from("/home/minimina/test"),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Network directory '/home/minimina/test' does not exist, therefore network 'test' does not exist too."
+        );
+        assert_eq!(error.exit_code(), 1);
+    }
+}
@@ -18,6 +18,136 @@ use crate::keys::NodeKey;
 
 pub(crate) const GENESIS_LEDGER_JSON: &str = "genesis_ledger.json";
 pub(crate) const REPLAYER_INPUT_JSON: &str = "replayer_input.json";
+/// Balance of the test accounts of the default genesis ledger, small enough to leave
+/// the stake with the block producers
+pub const TEST_ACCOUNT_BALANCE: &str = "1000.000000000";
+
+// Daemon defaults used when the runtime config does not override them
+const DEFAULT_SLOTS_PER_EPOCH: u64 = 7140;
+const DEFAULT_BLOCK_WINDOW_DURATION_MS: u64 = 180_000;
+
+/// Genesis constants overriding the daemon defaults, selected with `network create --preset`
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Preset {
+    /// 48 slots of 20 seconds per epoch and k = 10, so epoch transitions (staking
+    /// ledger promotion, VRF evaluation) come around every 16 minutes
+    FastEpochs,
+    /// Slots of 20 seconds, k = 3 and no proofs, so a single daemon produces blocks
+    /// right after genesis, see `minimina quickstart`
+    Quickstart,
+}
+
+/// Runtime config fields set by a [`Preset`]
+#[derive(Debug, PartialEq)]
+struct PresetConstants {
+    k: u64,
+    delta: u64,
+    slots_per_epoch: u64,
+    slots_per_sub_window: u64,
+    grace_period_slots: u64,
+    block_window_duration_ms: u64,
+    /// Proof level of the runtime config, left as is if `None`
+    proof_level: Option<&'static str>,
+}
+
+impl Preset {
+    fn constants(&self) -> PresetConstants {
+        match self {
+            Preset::FastEpochs => PresetConstants {
+                k: 10,
+                delta: 0,
+                slots_per_epoch: 48,
+                slots_per_sub_window: 2,
+                grace_period_slots: 3,
+                block_window_duration_ms: 20_000,
+                proof_level: None,
+            },
+            Preset::Quickstart => PresetConstants {
+                k: 3,
+                delta: 0,
+                slots_per_epoch: 48,
+                slots_per_sub_window: 2,
+                grace_period_slots: 3,
+                block_window_duration_ms: 20_000,
+                proof_level: Some("none"),
+            },
+        }
+    }
+}
+
+/// Sets the genesis and proof constants of `preset` in the genesis ledger/runtime
+/// config at `path`, keeping its other fields
+pub fn apply_preset(path: &Path, preset: Preset) -> std::io::Result<()> {
+    let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string());
+    let mut config: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let config_fields = config
+        .as_object_mut()
+        .ok_or_else(|| invalid("genesis ledger should be a JSON object"))?;
+    let constants = preset.constants();
+
+    let genesis = config_fields
+        .entry("genesis")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or_else(|| invalid("'genesis' field of the genesis ledger should be an object"))?;
+    genesis.insert("k".into(), constants.k.into());
+    genesis.insert("delta".into(), constants.delta.into());
+    genesis.insert("slots_per_epoch".into(), constants.slots_per_epoch.into());
+    genesis.insert(
+        "slots_per_sub_window".into(),
+        constants.slots_per_sub_window.into(),
+    );
+    genesis.insert(
+        "grace_period_slots".into(),
+        constants.grace_period_slots.into(),
+    );
+
+    let proof = config_fields
+        .entry("proof")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or_else(|| invalid("'proof' field of the genesis ledger should be an object"))?;
+    proof.insert(
+        "block_window_duration_ms".into(),
+        constants.block_window_duration_ms.into(),
+    );
+    if let Some(proof_level) = constants.proof_level {
+        proof.insert("level".into(), proof_level.into());
+    }
+
+    std::fs::write(path, serde_json::to_string_pretty(&config)?)
+}
+
+/// Adds `accounts` to the genesis ledger/runtime config at `path` in a single write,
+/// failing without changing it if one of their public keys is already in it
+pub fn add_ledger_accounts(path: &Path, accounts: &[LedgerAccount]) -> std::io::Result<()> {
+    let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+    let mut config: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let ledger_accounts = config
+        .pointer_mut("/ledger/accounts")
+        .and_then(serde_json::Value::as_array_mut)
+        .ok_or_else(|| invalid("genesis ledger should have a 'ledger.accounts' array".into()))?;
+    for account in accounts {
+        if ledger_accounts
+            .iter()
+            .any(|existing| existing["pk"] == account.pk.as_str())
+        {
+            return Err(invalid(format!(
+                "genesis ledger already has an account '{}'",
+                account.pk
+            )));
+        }
+        ledger_accounts.push(serde_json::to_value(Account {
+            pk: account.pk.clone(),
+            sk: account.sk.clone(),
+            balance: account.balance.clone(),
+            delegate: account.delegate.clone(),
+            timing: None,
+        })?);
+    }
+
+    std::fs::write(path, serde_json::to_string_pretty(&config)?)
+}
 
 /// Genesis ledger format
 #[derive(Serialize, Deserialize)]
@@ -56,6 +186,134 @@ struct Timing {
     vesting_increment: String,
 }
 
+/// Subset of the runtime config needed to compute slots and epochs
+#[derive(Deserialize)]
+struct RuntimeConstants {
+    genesis: GenesisConstants,
+    proof: Option<ProofConstants>,
+}
+
+#[derive(Deserialize)]
+struct GenesisConstants {
+    genesis_state_timestamp: String,
+    slots_per_epoch: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ProofConstants {
+    block_window_duration_ms: Option<u64>,
+}
+
+/// Subset of the genesis ledger needed to list its accounts
+#[derive(Deserialize)]
+struct LedgerAccounts {
+    ledger: LedgerAccountList,
+}
+
+#[derive(Deserialize)]
+struct LedgerAccountList {
+    accounts: Vec<LedgerAccount>,
+}
+
+/// Genesis account as listed by `network accounts`
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct LedgerAccount {
+    pub pk: String,
+    pub balance: String,
+    pub delegate: Option<String>,
+    /// Private key of the test accounts of the default genesis ledger
+    pub sk: Option<String>,
+}
+
+/// Reads the accounts of the genesis ledger/runtime config at `path`
+pub fn ledger_accounts(path: &Path) -> std::io::Result<Vec<LedgerAccount>> {
+    let ledger: LedgerAccounts = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    Ok(ledger.ledger.accounts)
+}
+
+/// Position of a point in time within the network's slot/epoch schedule
+#[derive(Debug, PartialEq)]
+pub struct SlotPosition {
+    pub started: bool,
+    pub global_slot: u64,
+    pub epoch: u64,
+    pub slot_in_epoch: u64,
+    pub ms_until_next_slot: u64,
+    pub ms_until_next_epoch: u64,
+}
+
+/// Slot schedule derived from the genesis timestamp and consensus constants
+#[derive(Debug)]
+pub struct SlotCalendar {
+    pub genesis_timestamp: DateTime<FixedOffset>,
+    pub slots_per_epoch: u64,
+    pub slot_duration_ms: u64,
+}
+
+impl SlotCalendar {
+    /// Reads the slot schedule from the genesis ledger/runtime config at `path`
+    pub fn from_genesis_ledger(path: &Path) -> std::io::Result<Self> {
+        let constants: RuntimeConstants = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let genesis_timestamp =
+            parse_genesis_timestamp(&constants.genesis.genesis_state_timestamp)?;
+
+        let calendar = SlotCalendar {
+            genesis_timestamp,
+            slots_per_epoch: constants
+                .genesis
+                .slots_per_epoch
+                .unwrap_or(DEFAULT_SLOTS_PER_EPOCH),
+            slot_duration_ms: constants
+                .proof
+                .and_then(|proof| proof.block_window_duration_ms)
+                .unwrap_or(DEFAULT_BLOCK_WINDOW_DURATION_MS),
+        };
+        // both divide the time since genesis in `position_at`
+        if calendar.slots_per_epoch == 0 || calendar.slot_duration_ms == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "Invalid genesis ledger '{}': slots_per_epoch and block_window_duration_ms must be positive",
+                    path.display()
+                ),
+            ));
+        }
+        Ok(calendar)
+    }
+
+    /// Computes the slot position at `now`
+    pub fn position_at<Tz: TimeZone>(&self, now: DateTime<Tz>) -> SlotPosition {
+        let elapsed_ms = now
+            .signed_duration_since(self.genesis_timestamp)
+            .num_milliseconds();
+
+        if elapsed_ms < 0 {
+            let until_genesis = elapsed_ms.unsigned_abs();
+            return SlotPosition {
+                started: false,
+                global_slot: 0,
+                epoch: 0,
+                slot_in_epoch: 0,
+                ms_until_next_slot: until_genesis,
+                ms_until_next_epoch: until_genesis,
+            };
+        }
+
+        let elapsed_ms = elapsed_ms as u64;
+        let epoch_duration_ms = self.slot_duration_ms * self.slots_per_epoch;
+        let global_slot = elapsed_ms / self.slot_duration_ms;
+
+        SlotPosition {
+            started: true,
+            global_slot,
+            epoch: global_slot / self.slots_per_epoch,
+            slot_in_epoch: global_slot % self.slots_per_epoch,
+            ms_until_next_slot: self.slot_duration_ms - elapsed_ms % self.slot_duration_ms,
+            ms_until_next_epoch: epoch_duration_ms - elapsed_ms % epoch_duration_ms,
+        }
+    }
+}
+
 /// Replayer input format
 #[derive(Serialize, Deserialize)]
 struct ReplayerInput {
@@ -75,13 +333,23 @@ pub mod default {
 
     use super::*;
 
+    /// Funded account of the default genesis ledger which belongs to no node, e.g. to
+    /// pay for and deploy zkApps from
+    pub struct TestAccount {
+        pub public_key: String,
+        /// Base58 private key, recorded in the ledger so tools like o1js can sign with it
+        pub private_key: String,
+    }
+
     pub struct LedgerGenerator;
 
     impl LedgerGenerator {
-        /// Generate default genesis ledger
+        /// Generate default genesis ledger funding the nodes of `bp_keys` and
+        /// `test_accounts`
         pub fn generate(
             network_path: &Path,
             bp_keys: &HashMap<String, NodeKey>,
+            test_accounts: &[TestAccount],
         ) -> std::io::Result<()> {
             info!("Generating default genesis ledger.");
             let accounts: Vec<Account> = bp_keys
@@ -93,6 +361,13 @@ pub mod default {
                     delegate: None,
                     timing: None,
                 })
+                .chain(test_accounts.iter().map(|account| Account {
+                    pk: account.public_key.clone(),
+                    sk: Some(account.private_key.clone()),
+                    balance: TEST_ACCOUNT_BALANCE.into(),
+                    delegate: None,
+                    timing: None,
+                }))
                 .collect();
 
             let ledger = Ledger {
@@ -149,10 +424,78 @@ pub mod default {
 }
 
 pub fn current_timestamp() -> String {
-    let datetime = Local::now();
+    format_timestamp(&Local::now())
+}
+
+/// Parses a genesis timestamp, accepting RFC 3339 and the common variants other tools
+/// emit: offsets without a colon (`+0200`) or minutes (`+02`) and missing offsets (UTC)
+pub fn parse_genesis_timestamp(timestamp: &str) -> std::io::Result<DateTime<FixedOffset>> {
+    let timestamp = timestamp.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(timestamp) {
+        return Ok(datetime);
+    }
+
+    for separator in ["T", " "] {
+        let zoned = format!("%Y-%m-%d{separator}%H:%M:%S%.f%#z");
+        if let Ok(datetime) = DateTime::parse_from_str(timestamp, &zoned) {
+            return Ok(datetime);
+        }
+
+        let naive = format!("%Y-%m-%d{separator}%H:%M:%S%.f");
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(timestamp, &naive) {
+            return Ok(datetime.and_utc().fixed_offset());
+        }
+    }
+
+    Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!(
+            "Invalid genesis timestamp '{timestamp}', expected RFC 3339 like '2024-01-01T00:00:00Z'"
+        ),
+    ))
+}
+
+/// Formats `datetime` the way genesis timestamps are written to the ledger
+pub fn format_timestamp<Tz: TimeZone>(datetime: &DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
     datetime.format("%Y-%m-%dT%H:%M:%S%.6f%Z").to_string()
 }
 
+/// Parses a delay like `90s`, `10m`, `1h30m` or `2d` (plain numbers are seconds)
+pub fn parse_delay(delay: &str) -> Result<chrono::Duration, String> {
+    let invalid = || format!("invalid delay '{delay}', expected e.g. '90s', '10m' or '1h30m'");
+    if let Ok(secs) = delay.parse::<i64>() {
+        return Ok(chrono::Duration::seconds(secs));
+    }
+
+    let mut total = chrono::Duration::zero();
+    let mut number = String::new();
+    for c in delay.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let value: i64 = number.parse().map_err(|_| invalid())?;
+        let unit = match c {
+            's' => chrono::Duration::seconds(value),
+            'm' => chrono::Duration::minutes(value),
+            'h' => chrono::Duration::hours(value),
+            'd' => chrono::Duration::days(value),
+            _ => return Err(invalid()),
+        };
+        total = total + unit;
+        number.clear();
+    }
+
+    if number.is_empty() && !delay.is_empty() {
+        Ok(total)
+    } else {
+        Err(invalid())
+    }
+}
+
 pub fn set_slot_since_genesis(network_path: &Path, slot_since_genesis: u64) -> std::io::Result<()> {
     let replayer_input_file = network_path.join(REPLAYER_INPUT_JSON);
     let mut replayer_input =
@@ -169,6 +512,24 @@ pub fn set_slot_since_genesis(network_path: &Path, slot_since_genesis: u64) -> s
     Ok(())
 }
 
+/// Sets the state hash the replayer replays up to, and whose ledgers it outputs, or
+/// removes it from the replayer input if `None`
+pub fn set_replayer_target_state_hash(
+    network_path: &Path,
+    target_state_hash: Option<&str>,
+) -> std::io::Result<()> {
+    let replayer_input_file = network_path.join(REPLAYER_INPUT_JSON);
+    let mut replayer_input =
+        serde_json::from_str::<ReplayerInput>(&std::fs::read_to_string(&replayer_input_file)?)?;
+
+    replayer_input.target_epoch_ledgers_state_hash = target_state_hash.map(String::from);
+
+    std::fs::write(
+        replayer_input_file,
+        serde_json::to_string_pretty(&replayer_input)?,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use tempdir::TempDir;
@@ -187,7 +548,7 @@ mod tests {
             key_path_docker: "test_key_path".to_string(),
         };
         bp_keys_map.insert("node0".to_string(), service_key);
-        let result = default::LedgerGenerator::generate(network_path, &bp_keys_map);
+        let result = default::LedgerGenerator::generate(network_path, &bp_keys_map, &[]);
         println!("{:?}", result);
         assert!(result.is_ok());
 
@@ -211,7 +572,7 @@ mod tests {
             key_path_docker: "test_key_path".to_string(),
         };
         bp_keys_map.insert("node0".to_string(), service_key);
-        let result = default::LedgerGenerator::generate(network_path, &bp_keys_map);
+        let result = default::LedgerGenerator::generate(network_path, &bp_keys_map, &[]);
         println!("{:?}", result);
         assert!(result.is_ok());
 
@@ -228,6 +589,44 @@ mod tests {
         assert!(content.contains("start_slot_since_genesis"));
     }
 
+    #[test]
+    fn test_ledger_accounts() {
+        let tempdir =
+            TempDir::new("test_ledger_accounts").expect("Cannot create temporary directory");
+        let path = tempdir.path().join(GENESIS_LEDGER_JSON);
+        std::fs::write(
+            &path,
+            r#"{
+                "genesis": { "genesis_state_timestamp": "2024-01-01T00:00:00Z" },
+                "ledger": {
+                    "accounts": [
+                        { "pk": "B62qbp", "sk": null, "balance": "1000", "delegate": null },
+                        { "pk": "B62qalice", "balance": "5.5", "delegate": "B62qbp", "token": "1" }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ledger_accounts(&path).unwrap(),
+            vec![
+                LedgerAccount {
+                    pk: "B62qbp".into(),
+                    balance: "1000".into(),
+                    delegate: None,
+                    sk: None,
+                },
+                LedgerAccount {
+                    pk: "B62qalice".into(),
+                    balance: "5.5".into(),
+                    delegate: Some("B62qbp".into()),
+                    sk: None,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_deserialize_genesis_ledger() {
         let genesis_ledger = r#"{
@@ -353,7 +752,7 @@ mod tests {
             key_path_docker: "test_key_path".to_string(),
         };
         bp_keys_map.insert("node0".to_string(), service_key);
-        let result = default::LedgerGenerator::generate(network_path, &bp_keys_map);
+        let result = default::LedgerGenerator::generate(network_path, &bp_keys_map, &[]);
         println!("{:?}", result);
         assert!(result.is_ok());
 
@@ -373,4 +772,261 @@ mod tests {
 
         assert_eq!(replayer_input.start_slot_since_genesis, 100);
     }
+
+    #[test]
+    fn test_set_replayer_target_state_hash() {
+        let tempdir = TempDir::new("test_set_replayer_target_state_hash")
+            .expect("Cannot create temporary directory");
+        let network_path = tempdir.path();
+        default::LedgerGenerator::generate(network_path, &HashMap::new(), &[]).unwrap();
+        default::LedgerGenerator::generate_replayer_input(network_path).unwrap();
+        let replayer_input = || -> ReplayerInput {
+            serde_json::from_str(
+                &std::fs::read_to_string(network_path.join(REPLAYER_INPUT_JSON)).unwrap(),
+            )
+            .unwrap()
+        };
+
+        set_replayer_target_state_hash(network_path, Some("3NKtarget")).unwrap();
+        assert_eq!(
+            replayer_input().target_epoch_ledgers_state_hash.as_deref(),
+            Some("3NKtarget")
+        );
+
+        set_slot_since_genesis(network_path, 100).unwrap();
+        assert_eq!(
+            replayer_input().target_epoch_ledgers_state_hash.as_deref(),
+            Some("3NKtarget")
+        );
+
+        set_replayer_target_state_hash(network_path, None).unwrap();
+        assert_eq!(replayer_input().target_epoch_ledgers_state_hash, None);
+        assert_eq!(replayer_input().start_slot_since_genesis, 100);
+    }
+
+    #[test]
+    fn test_slot_calendar() {
+        let tempdir =
+            TempDir::new("test_slot_calendar").expect("Cannot create temporary directory");
+        let path = tempdir.path().join(GENESIS_LEDGER_JSON);
+        std::fs::write(
+            &path,
+            r#"{
+                "genesis": {
+                    "slots_per_epoch": 48,
+                    "genesis_state_timestamp": "2023-09-20T17:00:00.000000+02:00"
+                },
+                "proof": { "block_window_duration_ms": 20000 },
+                "ledger": { "accounts": [] }
+            }"#,
+        )
+        .unwrap();
+
+        let calendar = SlotCalendar::from_genesis_ledger(&path).unwrap();
+        assert_eq!(calendar.slots_per_epoch, 48);
+        assert_eq!(calendar.slot_duration_ms, 20000);
+
+        // 50 slots and 5 seconds after genesis
+        let now = DateTime::parse_from_rfc3339("2023-09-20T17:16:45+02:00").unwrap();
+        let position = calendar.position_at(now);
+        assert_eq!(
+            position,
+            SlotPosition {
+                started: true,
+                global_slot: 50,
+                epoch: 1,
+                slot_in_epoch: 2,
+                ms_until_next_slot: 15000,
+                ms_until_next_epoch: 46 * 20000 - 5000,
+            }
+        );
+
+        // one minute before genesis
+        let now = DateTime::parse_from_rfc3339("2023-09-20T16:59:00+02:00").unwrap();
+        let position = calendar.position_at(now);
+        assert!(!position.started);
+        assert_eq!(position.global_slot, 0);
+        assert_eq!(position.ms_until_next_slot, 60000);
+    }
+
+    #[test]
+    fn test_slot_calendar_rejects_zero_constants() {
+        let tempdir = TempDir::new("test_slot_calendar_rejects_zero_constants")
+            .expect("Cannot create temporary directory");
+        let path = tempdir.path().join(GENESIS_LEDGER_JSON);
+        for (slots_per_epoch, block_window_duration_ms) in [(0, 20000), (48, 0)] {
+            std::fs::write(
+                &path,
+                format!(
+                    r#"{{
+                        "genesis": {{
+                            "slots_per_epoch": {slots_per_epoch},
+                            "genesis_state_timestamp": "2023-09-20T17:00:00Z"
+                        }},
+                        "proof": {{ "block_window_duration_ms": {block_window_duration_ms} }},
+                        "ledger": {{ "accounts": [] }}
+                    }}"#
+                ),
+            )
+            .unwrap();
+
+            let err = SlotCalendar::from_genesis_ledger(&path).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn test_apply_preset() {
+        let tempdir = TempDir::new("test_apply_preset").expect("Cannot create temporary directory");
+        let path = tempdir.path().join(GENESIS_LEDGER_JSON);
+        std::fs::write(
+            &path,
+            r#"{
+                "genesis": { "genesis_state_timestamp": "2023-09-20T17:00:00Z" },
+                "proof": { "level": "none" },
+                "ledger": { "accounts": [] }
+            }"#,
+        )
+        .unwrap();
+
+        apply_preset(&path, Preset::FastEpochs).unwrap();
+        let calendar = SlotCalendar::from_genesis_ledger(&path).unwrap();
+        assert_eq!(calendar.slots_per_epoch, 48);
+        assert_eq!(calendar.slot_duration_ms, 20_000);
+
+        let config: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(config["genesis"]["k"], 10);
+        assert_eq!(config["proof"]["level"], "none");
+        assert!(config["ledger"]["accounts"].is_array());
+
+        apply_preset(&path, Preset::Quickstart).unwrap();
+        let config: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(config["genesis"]["k"], 3);
+        assert_eq!(config["proof"]["level"], "none");
+    }
+
+    #[test]
+    fn test_add_ledger_accounts() {
+        let tempdir =
+            TempDir::new("test_add_ledger_accounts").expect("Cannot create temporary directory");
+        let path = tempdir.path().join(GENESIS_LEDGER_JSON);
+        std::fs::write(
+            &path,
+            r#"{
+                "genesis": { "genesis_state_timestamp": "2024-01-01T00:00:00Z" },
+                "ledger": { "accounts": [{ "pk": "B62qbp", "balance": "1000" }] }
+            }"#,
+        )
+        .unwrap();
+
+        let account = |pk: &str, sk: &str, balance: &str| LedgerAccount {
+            pk: pk.into(),
+            balance: balance.into(),
+            delegate: None,
+            sk: Some(sk.into()),
+        };
+        add_ledger_accounts(
+            &path,
+            &[
+                account("B62qalice", "EKalice", "5.000000000"),
+                account("B62qbob", "EKbob", "1.000000000"),
+            ],
+        )
+        .unwrap();
+        let accounts = ledger_accounts(&path).unwrap();
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(accounts[1], account("B62qalice", "EKalice", "5.000000000"));
+        assert_eq!(accounts[2].pk, "B62qbob");
+
+        // nothing is written if any of the accounts is already in the ledger
+        assert!(add_ledger_accounts(
+            &path,
+            &[
+                account("B62qcarol", "EKcarol", "1"),
+                account("B62qbp", "EKbp", "1")
+            ]
+        )
+        .is_err());
+        assert_eq!(ledger_accounts(&path).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_slot_calendar_defaults() {
+        let tempdir =
+            TempDir::new("test_slot_calendar_defaults").expect("Cannot create temporary directory");
+        let path = tempdir.path().join(GENESIS_LEDGER_JSON);
+        std::fs::write(
+            &path,
+            r#"{ "genesis": { "genesis_state_timestamp": "2023-09-20T17:00:00Z" } }"#,
+        )
+        .unwrap();
+
+        let calendar = SlotCalendar::from_genesis_ledger(&path).unwrap();
+        assert_eq!(calendar.slots_per_epoch, DEFAULT_SLOTS_PER_EPOCH);
+        assert_eq!(calendar.slot_duration_ms, DEFAULT_BLOCK_WINDOW_DURATION_MS);
+    }
+
+    #[test]
+    fn test_parse_delay() {
+        assert_eq!(parse_delay("90"), Ok(chrono::Duration::seconds(90)));
+        assert_eq!(parse_delay("10m"), Ok(chrono::Duration::minutes(10)));
+        assert_eq!(parse_delay("1h30m"), Ok(chrono::Duration::minutes(90)));
+        assert_eq!(parse_delay("2d"), Ok(chrono::Duration::days(2)));
+        assert!(parse_delay("").is_err());
+        assert!(parse_delay("10").is_ok());
+        assert!(parse_delay("m").is_err());
+        assert!(parse_delay("10x").is_err());
+        assert!(parse_delay("1h30").is_err());
+    }
+
+    #[test]
+    fn test_parse_genesis_timestamp() {
+        let expected = DateTime::parse_from_rfc3339("2023-09-20T17:20:57+02:00").unwrap();
+        for timestamp in [
+            "2023-09-20T17:20:57+02:00",
+            "2023-09-20T15:20:57Z",
+            "2023-09-20T15:20:57.000000z",
+            "2023-09-20 17:20:57+02:00",
+            "2023-09-20T17:20:57+0200",
+            "2023-09-20T17:20:57.000+02",
+            "2023-09-20T15:20:57",
+            " 2023-09-20 15:20:57 ",
+        ] {
+            assert_eq!(parse_genesis_timestamp(timestamp).unwrap(), expected);
+        }
+
+        let err = parse_genesis_timestamp("20/09/2023").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("20/09/2023"));
+    }
+
+    #[test]
+    fn test_generate_default_ledger_with_test_accounts() {
+        let tempdir = TempDir::new("test_generate_default_ledger_with_test_accounts").unwrap();
+        let network_path = tempdir.path();
+        let bp_keys_map = HashMap::from([(
+            "node0".to_string(),
+            NodeKey {
+                key_string: "bp_key".to_string(),
+                key_path_docker: "bp_key_path".to_string(),
+            },
+        )]);
+        let test_accounts = [default::TestAccount {
+            public_key: "test_key".to_string(),
+            private_key: "test_secret".to_string(),
+        }];
+        default::LedgerGenerator::generate(network_path, &bp_keys_map, &test_accounts).unwrap();
+
+        let ledger: GenesisLedger = serde_json::from_str(
+            &std::fs::read_to_string(network_path.join(GENESIS_LEDGER_JSON)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(ledger.ledger.accounts.len(), 2);
+        let account = &ledger.ledger.accounts[1];
+        assert_eq!(account.pk, "test_key");
+        assert_eq!(account.sk.as_deref(), Some("test_secret"));
+        assert_eq!(account.balance, TEST_ACCOUNT_BALANCE);
+    }
 }
@@ -1,7 +1,30 @@
-use log::info;
+use log::{info, warn};
 
-use crate::{directory_manager::DirectoryManager, exit_with, output::network, TIMEOUT_IN_SECS};
-use std::{self, io::Result};
+use crate::{api::TIMEOUT_IN_SECS, directory_manager::DirectoryManager, output::network};
+use std::{
+    self,
+    collections::{BTreeMap, HashMap},
+    io::Result,
+    time::{Duration, Instant},
+};
+
+/// Query for the length of the best chain known to the daemon
+const BLOCK_HEIGHT_QUERY: &str = "query { daemonStatus { blockchainLength } }";
+
+/// Query for the sync status of the daemon
+const SYNC_STATUS_QUERY: &str = "query { syncStatus }";
+
+/// Sync status of a daemon caught up with the network
+pub const SYNCED_STATUS: &str = "SYNCED";
+
+/// Interval between sync status checks while waiting for the nodes to sync
+const SYNC_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Status of a user command which made it into the best chain
+pub const INCLUDED_STATUS: &str = "INCLUDED";
+
+/// Interval between transaction status checks while waiting for inclusion
+const INCLUSION_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 pub struct GraphQl {
     directory_manager: DirectoryManager,
@@ -13,15 +36,239 @@ impl GraphQl {
     }
 
     pub fn get_endpoint(&self, node_id: &str, network_id: &str) -> Option<String> {
-        let nodes = self.directory_manager.get_network_info(network_id);
-        match nodes {
-            Ok(nodes) => {
-                let info = serde_json::from_str::<network::Create>(&nodes).unwrap();
-                let node = info.nodes.get(node_id)?;
-                let graphql_endpoint = node.graphql_uri.as_ref()?;
-                Some(graphql_endpoint.to_string())
+        let info = self.network_info(network_id)?;
+        let node = info.nodes.get(node_id)?;
+        let graphql_endpoint = node.graphql_uri.as_ref()?;
+        Some(graphql_endpoint.to_string())
+    }
+
+    /// Returns graphql endpoints of all nodes in the network which expose one
+    pub fn get_endpoints(&self, network_id: &str) -> HashMap<String, String> {
+        match self.network_info(network_id) {
+            Some(info) => info
+                .nodes
+                .into_iter()
+                .filter_map(|(node_id, node)| node.graphql_uri.map(|uri| (node_id, uri)))
+                .collect(),
+            None => HashMap::new(),
+        }
+    }
+
+    /// Info of the network recorded on creation, None if it's missing or unreadable
+    fn network_info(&self, network_id: &str) -> Option<network::Create> {
+        let info = self.directory_manager.get_network_info(network_id).ok()?;
+        match serde_json::from_str(&info) {
+            Ok(info) => Some(info),
+            Err(e) => {
+                warn!("Invalid info of network '{network_id}': {e}");
+                None
+            }
+        }
+    }
+
+    /// Sends `query` to the graphql endpoint and returns the JSON response
+    pub fn query(&self, gql_ep: &str, query: &str) -> Result<serde_json::Value> {
+        let payload = serde_json::json!({ "query": query }).to_string();
+        let client = crate::proxy::http_client();
+        let response = client
+            .post(gql_ep)
+            .header("Content-Type", "application/json")
+            .body(payload)
+            .send()
+            .and_then(|response| response.text())
+            .map_err(std::io::Error::other)?;
+
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// Sends `query` and returns the value at `pointer` in its data, failing on GraphQL errors
+    fn query_data(&self, gql_ep: &str, query: &str, pointer: &str) -> Result<serde_json::Value> {
+        let response = self.query(gql_ep, query)?;
+        if let Some(errors) = response.get("errors") {
+            return Err(std::io::Error::other(format!(
+                "GraphQL request to '{gql_ep}' failed: {errors}"
+            )));
+        }
+        response
+            .pointer(&format!("/data{pointer}"))
+            .cloned()
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Unexpected GraphQL response from '{gql_ep}': {response}"),
+                )
+            })
+    }
+
+    /// Imports the key file at `path` (inside the node's container) into the node's wallet
+    /// and returns its public key
+    pub fn import_account(&self, gql_ep: &str, path: &str, password: &str) -> Result<String> {
+        let query = format!(
+            "mutation {{ importAccount(path: {}, password: {}) {{ publicKey }} }}",
+            serde_json::json!(path),
+            serde_json::json!(password)
+        );
+        let public_key = self.query_data(gql_ep, &query, "/importAccount/publicKey")?;
+        Ok(public_key.as_str().unwrap_or_default().to_string())
+    }
+
+    /// Returns the public keys of the accounts in the node's wallet
+    pub fn owned_wallets(&self, gql_ep: &str) -> Result<Vec<String>> {
+        let wallets = self.query_data(
+            gql_ep,
+            "query { ownedWallets { publicKey } }",
+            "/ownedWallets",
+        )?;
+        Ok(wallets
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|wallet| wallet["publicKey"].as_str().map(String::from))
+            .collect())
+    }
+
+    /// Unlocks an account of the node's wallet so it can send payments
+    pub fn unlock_account(&self, gql_ep: &str, public_key: &str, password: &str) -> Result<()> {
+        let query = format!(
+            "mutation {{ unlockAccount(input: {{ publicKey: {}, password: {} }}) {{ publicKey }} }}",
+            serde_json::json!(public_key),
+            serde_json::json!(password)
+        );
+        self.query_data(gql_ep, &query, "/unlockAccount/publicKey")?;
+        Ok(())
+    }
+
+    /// Imports the key file at `path` and unlocks it, returning its public key
+    pub fn import_and_unlock_account(
+        &self,
+        gql_ep: &str,
+        path: &str,
+        password: &str,
+    ) -> Result<String> {
+        let public_key = self.import_account(gql_ep, path, password)?;
+        self.unlock_account(gql_ep, &public_key, password)?;
+        Ok(public_key)
+    }
+
+    /// Delegates the stake of an unlocked account to `to` and returns the
+    /// delegation's id and hash
+    pub fn send_delegation(
+        &self,
+        gql_ep: &str,
+        from: &str,
+        to: &str,
+        fee: u64,
+    ) -> Result<(String, String)> {
+        let query = format!(
+            "mutation {{ sendDelegation(input: {{ from: {}, to: {}, fee: \"{fee}\" }}) \
+            {{ delegation {{ id hash }} }} }}",
+            serde_json::json!(from),
+            serde_json::json!(to)
+        );
+        let delegation = self.query_data(gql_ep, &query, "/sendDelegation/delegation")?;
+        let field = |name| delegation[name].as_str().unwrap_or_default().to_string();
+        Ok((field("id"), field("hash")))
+    }
+
+    /// Returns the status (`PENDING`, `INCLUDED` or `UNKNOWN`) of a user command
+    pub fn transaction_status(&self, gql_ep: &str, id: &str) -> Result<String> {
+        let query = format!(
+            "query {{ transactionStatus(payment: {}) }}",
+            serde_json::json!(id)
+        );
+        let status = self.query_data(gql_ep, &query, "/transactionStatus")?;
+        Ok(status.as_str().unwrap_or_default().to_string())
+    }
+
+    /// Waits for a user command to be included in the node's best chain
+    pub fn wait_for_inclusion(&self, gql_ep: &str, id: &str, timeout: Duration) -> Result<()> {
+        let start = Instant::now();
+        info!("Waiting for transaction '{id}' to be included");
+        loop {
+            if self.transaction_status(gql_ep, id)? == INCLUDED_STATUS {
+                return Ok(());
+            }
+            if start.elapsed() >= timeout {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!(
+                        "Transaction '{id}' not included within {}s",
+                        timeout.as_secs()
+                    ),
+                ));
             }
-            Err(_) => None,
+            std::thread::sleep(INCLUSION_POLL_INTERVAL);
+        }
+    }
+
+    /// Sends a payment of `amount` nanomina from an unlocked account and returns its hash
+    pub fn send_payment(
+        &self,
+        gql_ep: &str,
+        from: &str,
+        to: &str,
+        amount: u64,
+        fee: u64,
+    ) -> Result<String> {
+        let query = format!(
+            "mutation {{ sendPayment(input: {{ from: {}, to: {}, amount: \"{amount}\", fee: \"{fee}\" }}) \
+            {{ payment {{ hash }} }} }}",
+            serde_json::json!(from),
+            serde_json::json!(to)
+        );
+        let hash = self.query_data(gql_ep, &query, "/sendPayment/payment/hash")?;
+        Ok(hash.as_str().unwrap_or_default().to_string())
+    }
+
+    /// Returns the best chain height of the node, if it can be queried
+    pub fn block_height(&self, gql_ep: &str) -> Option<u64> {
+        let response = self.query(gql_ep, BLOCK_HEIGHT_QUERY).ok()?;
+        response
+            .pointer("/data/daemonStatus/blockchainLength")?
+            .as_u64()
+    }
+
+    /// Returns the sync status of the node, if it can be queried
+    pub fn sync_status(&self, gql_ep: &str) -> Option<String> {
+        let response = self.query(gql_ep, SYNC_STATUS_QUERY).ok()?;
+        Some(response.pointer("/data/syncStatus")?.as_str()?.to_string())
+    }
+
+    /// Waits for the nodes of `endpoints` to report `SYNCED` and returns their last
+    /// sync status, `None` for nodes which did not respond
+    pub fn wait_for_sync(
+        &self,
+        endpoints: &HashMap<String, String>,
+        timeout: Duration,
+    ) -> Result<BTreeMap<String, Option<String>>> {
+        let start = Instant::now();
+        info!("Waiting for {} nodes to sync", endpoints.len());
+        loop {
+            let statuses: BTreeMap<String, Option<String>> = endpoints
+                .iter()
+                .map(|(node_id, gql_ep)| (node_id.clone(), self.sync_status(gql_ep)))
+                .collect();
+            let unsynced: Vec<String> = statuses
+                .iter()
+                .filter(|(_, status)| status.as_deref() != Some(SYNCED_STATUS))
+                .map(|(node_id, status)| {
+                    format!("{node_id}: {}", status.as_deref().unwrap_or("unreachable"))
+                })
+                .collect();
+            if unsynced.is_empty() {
+                return Ok(statuses);
+            }
+            if start.elapsed() >= timeout {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!(
+                        "Nodes not synced within {}s ({})",
+                        timeout.as_secs(),
+                        unsynced.join(", ")
+                    ),
+                ));
+            }
+            std::thread::sleep(SYNC_POLL_INTERVAL);
         }
     }
 
@@ -30,7 +277,7 @@ impl GraphQl {
         let mut retries = 0;
         let mut graphql_running = false;
         info!("Waiting for graphql to start '{gql_ep}'");
-        let client = reqwest::blocking::Client::new();
+        let client = crate::proxy::http_client();
 
         while !graphql_running && retries < TIMEOUT_IN_SECS {
             let response = client
@@ -46,9 +293,9 @@ impl GraphQl {
             }
         }
         if !graphql_running {
-            return exit_with(format!(
+            return Err(std::io::Error::other(format!(
                 "Failed to start graphql '{gql_ep}' within {TIMEOUT_IN_SECS}s",
-            ));
+            )));
         }
         Ok(())
     }
@@ -73,7 +320,7 @@ impl GraphQl {
                                                 \"30fe76cee159ea215fc05549e861501e\"]) }"
     }"#;
 
-        let client = reqwest::blocking::Client::new();
+        let client = crate::proxy::http_client();
         info!("Sending request to: {gql_ep}");
         let response = client
             .post(gql_ep)
@@ -81,17 +328,17 @@ impl GraphQl {
             .body(query)
             .send();
         if let Err(e) = response {
-            return exit_with(format!(
+            return Err(std::io::Error::other(format!(
                 "Failed to send request to graphql endpoint '{gql_ep}': {e}",
-            ));
+            )));
         }
 
         // Read the response body
         let response_body = response.unwrap().text();
         if let Err(e) = response_body {
-            return exit_with(format!(
+            return Err(std::io::Error::other(format!(
                 "Failed to read response body from graphql endpoint '{gql_ep}': {e}",
-            ));
+            )));
         }
         info!("Response body: {}", response_body.unwrap());
 
@@ -166,5 +413,10 @@ mod test {
 
         let endpoint = graphql.get_endpoint("mina-snark-worker-1", network_id);
         assert_eq!(endpoint, None);
+
+        let endpoints = graphql.get_endpoints(network_id);
+        assert_eq!(endpoints.len(), 4);
+        assert!(!endpoints.contains_key("mina-archive"));
+        assert_eq!(endpoints["mina-seed-1"], "http://localhost:3101/graphql");
     }
 }
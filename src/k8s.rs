@@ -0,0 +1,1051 @@
+//! # Kubernetes Module
+//!
+//! This module renders the Kubernetes manifests of a network, the `--target k8s`
+//! counterpart of `docker-compose.yaml`, from the same list of [`ServiceConfig`]s.
+//! All objects live in a namespace named after the network:
+//! - A `ConfigMap` with the genesis ledger (and peer list file) of the network and
+//!   one with the network and libp2p key files of its services.
+//! - A `StatefulSet` and `Service` per daemon, named like its compose service, so the
+//!   `/dns4/<service>-<network>/...` peer addresses resolve in the cluster. An init
+//!   container copies the ledger and the daemon's keys, with the permissions the daemon
+//!   insists on, to `/local-network`; its config directory is a persistent volume.
+//! - A postgres `StatefulSet` and `Service` per archive, whose database is created and
+//!   initialized from the archive's schema scripts on first start, and a `StatefulSet`
+//!   and `Service` for its archive service. The credentials are kept in a `Secret`.
+//!
+//! Host ports, cpusets, ulimits, CPU and memory limits, GPUs, extra hosts and DNS servers
+//! of the services are docker specific and not carried over. Uptime service backends and Rosetta services are
+//! not supported.
+
+use crate::{
+    directory_manager::{LIBP2P_KEYPAIRS, NETWORK_KEYPAIRS},
+    docker::{
+        compose::{CONFIG_DIRECTORY, RAYON_NUM_THREADS},
+        postgres::{
+            PostgresConfig, POSTGRES_DB_ENV, POSTGRES_PASSWORD_ENV, POSTGRES_PORT,
+            POSTGRES_USER_ENV,
+        },
+    },
+    genesis_ledger::GENESIS_LEDGER_JSON,
+    keys::{MINA_LIBP2P_PASS, MINA_PRIVKEY_PASS},
+    service::{command::CommandContext, ServiceConfig, ServiceType},
+};
+use log::{debug, warn};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{Error, Result},
+    path::{Path, PathBuf},
+};
+
+/// Directory of the manifests in the network directory
+pub const K8S_DIR: &str = "k8s";
+const MANIFESTS_FILE: &str = "manifests.yaml";
+
+/// Size requested for the config directory of daemons and the data of postgres
+const STORAGE_SIZE: &str = "10Gi";
+/// Kubernetes refuses ConfigMaps larger than 1MiB
+const CONFIG_MAP_MAX_SIZE: usize = 1024 * 1024;
+const PEER_LIST_FILE: &str = "peer_list_file.txt";
+const POSTGRES_IMAGE: &str = "postgres";
+const POSTGRES_DATA_PATH: &str = "/var/lib/postgresql/data";
+const INITDB_PATH: &str = "/docker-entrypoint-initdb.d";
+/// Mount points of the ConfigMaps the init container copies to `/local-network`
+const NETWORK_CONFIG_PATH: &str = "/minimina/network";
+const KEYS_CONFIG_PATH: &str = "/minimina/keys";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest<T> {
+    api_version: &'static str,
+    kind: &'static str,
+    metadata: Metadata,
+    #[serde(flatten)]
+    body: T,
+}
+
+#[derive(Default, Serialize)]
+struct Metadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    labels: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Serialize)]
+struct Empty {}
+
+#[derive(Serialize)]
+struct ConfigMap {
+    data: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Secret {
+    #[serde(rename = "type")]
+    secret_type: &'static str,
+    string_data: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct Service {
+    spec: ServiceSpec,
+}
+
+#[derive(Serialize)]
+struct ServiceSpec {
+    selector: BTreeMap<String, String>,
+    ports: Vec<ServicePort>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ServicePort {
+    name: String,
+    port: u16,
+    target_port: u16,
+}
+
+#[derive(Serialize)]
+struct StatefulSet {
+    spec: StatefulSetSpec,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StatefulSetSpec {
+    service_name: String,
+    replicas: u32,
+    selector: LabelSelector,
+    template: PodTemplate,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    volume_claim_templates: Vec<PersistentVolumeClaim>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LabelSelector {
+    match_labels: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct PodTemplate {
+    metadata: Metadata,
+    spec: PodSpec,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PodSpec {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    init_containers: Vec<Container>,
+    containers: Vec<Container>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    volumes: Vec<Volume>,
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Container {
+    name: String,
+    image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    env: Vec<EnvVar>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    ports: Vec<ContainerPort>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    volume_mounts: Vec<VolumeMount>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EnvVar {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value_from: Option<EnvVarSource>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EnvVarSource {
+    secret_key_ref: KeySelector,
+}
+
+#[derive(Serialize)]
+struct KeySelector {
+    name: String,
+    key: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ContainerPort {
+    name: String,
+    container_port: u16,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VolumeMount {
+    name: String,
+    mount_path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Volume {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config_map: Option<ConfigMapVolume>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    empty_dir: Option<Empty>,
+}
+
+#[derive(Serialize)]
+struct ConfigMapVolume {
+    name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    items: Vec<KeyToPath>,
+}
+
+#[derive(Serialize)]
+struct KeyToPath {
+    key: String,
+    path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistentVolumeClaim {
+    metadata: Metadata,
+    spec: PersistentVolumeClaimSpec,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistentVolumeClaimSpec {
+    access_modes: Vec<String>,
+    resources: ResourceRequirements,
+}
+
+#[derive(Serialize)]
+struct ResourceRequirements {
+    requests: BTreeMap<String, String>,
+}
+
+/// Path of the manifests of the network at `network_path`
+pub fn manifests_path(network_path: &Path) -> PathBuf {
+    network_path.join(K8S_DIR).join(MANIFESTS_FILE)
+}
+
+/// Renders the manifests of the network and writes them to [`manifests_path`]
+pub fn write_manifests(
+    configs: &[ServiceConfig],
+    network_path: &Path,
+    postgres: &PostgresConfig,
+    schemas: &HashMap<String, Vec<PathBuf>>,
+) -> Result<PathBuf> {
+    let manifests = K8sManifests::new(network_path, postgres).generate(configs, schemas)?;
+    let path = manifests_path(network_path);
+    fs::create_dir_all(network_path.join(K8S_DIR))?;
+    fs::write(&path, manifests)?;
+    Ok(path)
+}
+
+/// Renders the manifests of a network from the files in its directory
+pub struct K8sManifests<'a> {
+    network_path: &'a Path,
+    network_name: String,
+    postgres: &'a PostgresConfig,
+    documents: Vec<String>,
+}
+
+impl<'a> K8sManifests<'a> {
+    pub fn new(network_path: &'a Path, postgres: &'a PostgresConfig) -> Self {
+        let network_name = network_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .expect("Failed to get network name from network path")
+            .to_string();
+        K8sManifests {
+            network_path,
+            network_name,
+            postgres,
+            documents: vec![],
+        }
+    }
+
+    /// Multi-document YAML of the manifests of the services, initializing the database
+    /// of each archive with the schema scripts in `schemas`, by archive service name
+    pub fn generate(
+        mut self,
+        configs: &[ServiceConfig],
+        schemas: &HashMap<String, Vec<PathBuf>>,
+    ) -> Result<String> {
+        if ServiceConfig::get_uptime_service_backend(configs).is_some() {
+            return Err(Error::other(
+                "Uptime service backends are not supported by the k8s target",
+            ));
+        }
+        if configs
+            .iter()
+            .any(|config| config.service_type == ServiceType::Rosetta)
+        {
+            return Err(Error::other(
+                "Rosetta services are not supported by the k8s target",
+            ));
+        }
+        check_name(&self.network_name)?;
+
+        let context = CommandContext {
+            network_name: self.network_name.clone(),
+            uptime_service_host: None,
+            archive_service_host: None,
+        };
+
+        self.push("v1", "Namespace", self.metadata(None), Empty {})?;
+        self.push_network_config_map()?;
+        self.push_keys_config_map(configs)?;
+
+        for config in configs {
+            match config.service_type {
+                // handled with their postgres and archive service below
+                ServiceType::ArchiveNode | ServiceType::ArchiveService => (),
+                ServiceType::UptimeServiceBackend | ServiceType::Rosetta => unreachable!(),
+                _ => self.push_daemon(config, &context)?,
+            }
+        }
+
+        let archives = ServiceConfig::get_archives(configs);
+        if !archives.is_empty() {
+            self.push(
+                "v1",
+                "Secret",
+                self.metadata(Some(self.postgres_secret())),
+                Secret {
+                    secret_type: "Opaque",
+                    string_data: BTreeMap::from([
+                        (POSTGRES_USER_ENV.to_string(), self.postgres.user.clone()),
+                        (
+                            POSTGRES_PASSWORD_ENV.to_string(),
+                            self.postgres.password.clone(),
+                        ),
+                    ]),
+                },
+            )?;
+        }
+        for archive in archives {
+            let schema = schemas
+                .get(&archive.service_name)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            self.push_archive(archive, configs, schema, &context)?;
+        }
+
+        let manifests = self.documents.join("---\n");
+        debug!("Generated k8s manifests: {manifests}");
+        Ok(manifests)
+    }
+
+    fn push<T: Serialize>(
+        &mut self,
+        api_version: &'static str,
+        kind: &'static str,
+        metadata: Metadata,
+        body: T,
+    ) -> Result<()> {
+        if let Some(name) = &metadata.name {
+            check_name(name)?;
+        }
+        let manifest = Manifest {
+            api_version,
+            kind,
+            metadata,
+            body,
+        };
+        let document = serde_yaml::to_string(&manifest).map_err(Error::other)?;
+        self.documents.push(document);
+        Ok(())
+    }
+
+    /// Metadata of an object of the network, the namespace itself if `name` is `None`
+    fn metadata(&self, name: Option<String>) -> Metadata {
+        match name {
+            Some(name) => Metadata {
+                labels: Some(self.labels(&name)),
+                name: Some(name),
+                namespace: Some(self.network_name.clone()),
+            },
+            None => Metadata {
+                name: Some(self.network_name.clone()),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn labels(&self, name: &str) -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("app.kubernetes.io/name".to_string(), name.to_string()),
+            (
+                "app.kubernetes.io/instance".to_string(),
+                self.network_name.clone(),
+            ),
+            (
+                "app.kubernetes.io/managed-by".to_string(),
+                "minimina".to_string(),
+            ),
+        ])
+    }
+
+    fn selector(name: &str) -> BTreeMap<String, String> {
+        BTreeMap::from([("app.kubernetes.io/name".to_string(), name.to_string())])
+    }
+
+    fn network_config_map(&self) -> String {
+        format!("network-{}", self.network_name)
+    }
+
+    fn keys_config_map(&self) -> String {
+        format!("keys-{}", self.network_name)
+    }
+
+    fn postgres_secret(&self) -> String {
+        format!("postgres-credentials-{}", self.network_name)
+    }
+
+    /// Genesis ledger and peer list file, mounted by every daemon
+    fn push_network_config_map(&mut self) -> Result<()> {
+        let mut data = BTreeMap::new();
+        for file in [GENESIS_LEDGER_JSON, PEER_LIST_FILE] {
+            let path = self.network_path.join(file);
+            if path.exists() {
+                data.insert(file.to_string(), fs::read_to_string(path)?);
+            }
+        }
+        let name = self.network_config_map();
+        check_config_map_size(&name, &data);
+        self.push(
+            "v1",
+            "ConfigMap",
+            self.metadata(Some(name)),
+            ConfigMap { data },
+        )
+    }
+
+    /// Key files of all services, each mounted by its own service only
+    fn push_keys_config_map(&mut self, configs: &[ServiceConfig]) -> Result<()> {
+        let mut data = BTreeMap::new();
+        for config in configs {
+            for (directory, key_path) in Self::key_files(config) {
+                let path = self.network_path.join(&key_path);
+                data.insert(config_map_key(directory, config), fs::read_to_string(path)?);
+            }
+        }
+        let name = self.keys_config_map();
+        check_config_map_size(&name, &data);
+        self.push(
+            "v1",
+            "ConfigMap",
+            self.metadata(Some(name)),
+            ConfigMap { data },
+        )
+    }
+
+    /// Key directories of the service's key files and their paths in the network directory
+    fn key_files(config: &ServiceConfig) -> Vec<(&'static str, String)> {
+        let mut files = vec![];
+        if config.private_key_path.is_some() {
+            files.push((
+                NETWORK_KEYPAIRS,
+                format!("{NETWORK_KEYPAIRS}/{}.json", config.service_name),
+            ));
+        }
+        if config.libp2p_keypair_path.is_some() {
+            files.push((
+                LIBP2P_KEYPAIRS,
+                format!("{LIBP2P_KEYPAIRS}/{}.json", config.service_name),
+            ));
+        }
+        files
+    }
+
+    /// StatefulSet and Service of a daemon, `name` its compose service name
+    fn push_daemon(&mut self, config: &ServiceConfig, context: &CommandContext) -> Result<()> {
+        let name = format!("{}-{}", config.service_name, self.network_name);
+        let image = config
+            .docker_image
+            .clone()
+            .expect("Failed to get mina daemon docker image");
+        let args = config
+            .service_type
+            .command_builder()
+            .map(|builder| builder.build(config, context));
+        let ports = Self::daemon_ports(config);
+
+        let key_files = Self::key_files(config);
+        let mut install = vec![format!("cp -L {NETWORK_CONFIG_PATH}/* /local-network/")];
+        for (directory, _) in &key_files {
+            install.push(format!(
+                "cp -rL {KEYS_CONFIG_PATH}/{directory} /local-network/ && \
+                chmod 700 /local-network/{directory} && chmod 600 /local-network/{directory}/*"
+            ));
+        }
+
+        let mut volumes = vec![
+            Volume {
+                name: "local-network".to_string(),
+                config_map: None,
+                empty_dir: Some(Empty {}),
+            },
+            Volume {
+                name: "network".to_string(),
+                config_map: Some(ConfigMapVolume {
+                    name: self.network_config_map(),
+                    items: vec![],
+                }),
+                empty_dir: None,
+            },
+        ];
+        let mut init_mounts = vec![
+            volume_mount("local-network", "/local-network"),
+            volume_mount("network", NETWORK_CONFIG_PATH),
+        ];
+        if !key_files.is_empty() {
+            volumes.push(Volume {
+                name: "keys".to_string(),
+                config_map: Some(ConfigMapVolume {
+                    name: self.keys_config_map(),
+                    items: key_files
+                        .iter()
+                        .map(|(directory, path)| KeyToPath {
+                            key: config_map_key(directory, config),
+                            path: path.clone(),
+                        })
+                        .collect(),
+                }),
+                empty_dir: None,
+            });
+            init_mounts.push(volume_mount("keys", KEYS_CONFIG_PATH));
+        }
+
+        let mut env = vec![
+            env_var("MINA_PRIVKEY_PASS", MINA_PRIVKEY_PASS),
+            env_var("MINA_LIBP2P_PASS", MINA_LIBP2P_PASS),
+            env_var("MINA_CLIENT_TRUSTLIST", "0.0.0.0/0"),
+            env_var("RAYON_NUM_THREADS", &RAYON_NUM_THREADS.to_string()),
+        ];
+        // the topology's env overrides the defaults
+        if let Some(overrides) = &config.env {
+            env.retain(|var| !overrides.contains_key(&var.name));
+            env.extend(overrides.iter().map(|(name, value)| env_var(name, value)));
+        }
+
+        let pod = PodSpec {
+            init_containers: vec![Container {
+                name: "install-keys".to_string(),
+                image: image.clone(),
+                command: Some(vec![
+                    "sh".to_string(),
+                    "-c".to_string(),
+                    install.join(" && "),
+                ]),
+                volume_mounts: init_mounts,
+                ..Default::default()
+            }],
+            containers: vec![Container {
+                name: "mina".to_string(),
+                image,
+                command: Some(vec!["mina".to_string()]),
+                args,
+                env,
+                ports: container_ports(&ports),
+                volume_mounts: vec![
+                    volume_mount("local-network", "/local-network"),
+                    volume_mount(CONFIG_DIRECTORY, &format!("/{CONFIG_DIRECTORY}")),
+                ],
+            }],
+            volumes,
+        };
+        self.push_stateful_set(&name, pod, Some(CONFIG_DIRECTORY))?;
+        self.push_service(&name, &ports)
+    }
+
+    /// Named ports of a daemon: client, GraphQL, external and limited GraphQL if open
+    fn daemon_ports(config: &ServiceConfig) -> Vec<(&'static str, u16)> {
+        let Some(port) = config.client_port else {
+            return vec![];
+        };
+        let mut ports = vec![
+            ("client", port),
+            ("graphql", port + 1),
+            ("external", port + 2),
+        ];
+        if config.published_limited_graphql_port().is_some() {
+            ports.push(("limited-graphql", port + 5));
+        }
+        ports
+    }
+
+    /// Postgres, archive service and (unless standalone) archive node of an archive
+    fn push_archive(
+        &mut self,
+        archive: &ServiceConfig,
+        configs: &[ServiceConfig],
+        schema: &[PathBuf],
+        context: &CommandContext,
+    ) -> Result<()> {
+        let standalone = archive.service_type == ServiceType::ArchiveService;
+        let archive_db = archive.archive_db(configs);
+        let postgres = self.postgres.for_archive(&archive_db);
+        let postgres_name = archive_db.service_name(&self.network_name);
+
+        // postgres runs the scripts of its init directory in order when it creates the database
+        let mut volumes = vec![];
+        let mut postgres_mounts = vec![volume_mount("data", POSTGRES_DATA_PATH)];
+        if !schema.is_empty() {
+            let mut data = BTreeMap::new();
+            for (index, path) in schema.iter().enumerate() {
+                let file_name = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .expect("Failed to get schema script file name");
+                data.insert(format!("{index:02}-{file_name}"), fs::read_to_string(path)?);
+            }
+            let schema_name = format!("{postgres_name}-schema");
+            check_config_map_size(&schema_name, &data);
+            self.push(
+                "v1",
+                "ConfigMap",
+                self.metadata(Some(schema_name.clone())),
+                ConfigMap { data },
+            )?;
+            volumes.push(Volume {
+                name: "schema".to_string(),
+                config_map: Some(ConfigMapVolume {
+                    name: schema_name,
+                    items: vec![],
+                }),
+                empty_dir: None,
+            });
+            postgres_mounts.push(volume_mount("schema", INITDB_PATH));
+        } else {
+            warn!(
+                "No schema scripts for archive '{}', its database is created empty.",
+                archive.service_name
+            );
+        }
+
+        let postgres_ports = vec![("postgres", POSTGRES_PORT)];
+        let pod = PodSpec {
+            init_containers: vec![],
+            containers: vec![Container {
+                name: "postgres".to_string(),
+                image: POSTGRES_IMAGE.to_string(),
+                env: vec![
+                    self.secret_env_var("POSTGRES_USER", POSTGRES_USER_ENV),
+                    self.secret_env_var("POSTGRES_PASSWORD", POSTGRES_PASSWORD_ENV),
+                    env_var("POSTGRES_DB", &postgres.database),
+                    // the mount point of a volume is not empty, e.g. `lost+found`
+                    env_var("PGDATA", &format!("{POSTGRES_DATA_PATH}/pgdata")),
+                ],
+                ports: container_ports(&postgres_ports),
+                volume_mounts: postgres_mounts,
+                ..Default::default()
+            }],
+            volumes,
+        };
+        self.push_stateful_set(&postgres_name, pod, Some("data"))?;
+        self.push_service(&postgres_name, &postgres_ports)?;
+
+        let archive_node_name = format!("{}-{}", archive.service_name, self.network_name);
+        let archive_service_name = if standalone {
+            archive_node_name
+        } else {
+            format!("{}-service-{}", archive.service_name, self.network_name)
+        };
+        let archive_port = archive.archive_port.unwrap_or(3086);
+        let archive_ports = vec![("archive", archive_port)];
+        let pod = PodSpec {
+            init_containers: vec![],
+            containers: vec![Container {
+                name: "archive".to_string(),
+                image: archive
+                    .archive_docker_image
+                    .clone()
+                    .expect("Failed to get mina archive docker image"),
+                command: Some(vec!["mina-archive".to_string()]),
+                args: Some(vec![
+                    "run".to_string(),
+                    "--postgres-uri".to_string(),
+                    // expanded by kubernetes from the container's environment
+                    format!(
+                        "postgres://$({POSTGRES_USER_ENV}):$({POSTGRES_PASSWORD_ENV})@{postgres_name}:{POSTGRES_PORT}/{}",
+                        postgres.database
+                    ),
+                    "--server-port".to_string(),
+                    archive_port.to_string(),
+                ]),
+                env: vec![
+                    self.secret_env_var(POSTGRES_USER_ENV, POSTGRES_USER_ENV),
+                    self.secret_env_var(POSTGRES_PASSWORD_ENV, POSTGRES_PASSWORD_ENV),
+                    env_var(POSTGRES_DB_ENV, &postgres.database),
+                ],
+                ports: container_ports(&archive_ports),
+                ..Default::default()
+            }],
+            volumes: vec![],
+        };
+        self.push_stateful_set(&archive_service_name, pod, None)?;
+        self.push_service(&archive_service_name, &archive_ports)?;
+
+        if standalone {
+            return Ok(());
+        }
+        self.push_daemon(
+            archive,
+            &CommandContext {
+                archive_service_host: Some(archive_service_name),
+                ..context.clone()
+            },
+        )
+    }
+
+    /// Single replica StatefulSet `name` running `pod`, with a persistent volume `claim`
+    fn push_stateful_set(&mut self, name: &str, pod: PodSpec, claim: Option<&str>) -> Result<()> {
+        let volume_claim_templates = claim
+            .map(|claim| PersistentVolumeClaim {
+                metadata: Metadata {
+                    name: Some(claim.to_string()),
+                    ..Default::default()
+                },
+                spec: PersistentVolumeClaimSpec {
+                    access_modes: vec!["ReadWriteOnce".to_string()],
+                    resources: ResourceRequirements {
+                        requests: BTreeMap::from([(
+                            "storage".to_string(),
+                            STORAGE_SIZE.to_string(),
+                        )]),
+                    },
+                },
+            })
+            .into_iter()
+            .collect();
+        self.push(
+            "apps/v1",
+            "StatefulSet",
+            self.metadata(Some(name.to_string())),
+            StatefulSet {
+                spec: StatefulSetSpec {
+                    service_name: name.to_string(),
+                    replicas: 1,
+                    selector: LabelSelector {
+                        match_labels: Self::selector(name),
+                    },
+                    template: PodTemplate {
+                        metadata: Metadata {
+                            labels: Some(self.labels(name)),
+                            ..Default::default()
+                        },
+                        spec: pod,
+                    },
+                    volume_claim_templates,
+                },
+            },
+        )
+    }
+
+    /// Service `name` in front of the pods of the StatefulSet `name`
+    fn push_service(&mut self, name: &str, ports: &[(&str, u16)]) -> Result<()> {
+        self.push(
+            "v1",
+            "Service",
+            self.metadata(Some(name.to_string())),
+            Service {
+                spec: ServiceSpec {
+                    selector: Self::selector(name),
+                    ports: ports
+                        .iter()
+                        .map(|(port_name, port)| ServicePort {
+                            name: port_name.to_string(),
+                            port: *port,
+                            target_port: *port,
+                        })
+                        .collect(),
+                },
+            },
+        )
+    }
+
+    fn secret_env_var(&self, name: &str, key: &str) -> EnvVar {
+        EnvVar {
+            name: name.to_string(),
+            value: None,
+            value_from: Some(EnvVarSource {
+                secret_key_ref: KeySelector {
+                    name: self.postgres_secret(),
+                    key: key.to_string(),
+                },
+            }),
+        }
+    }
+}
+
+fn env_var(name: &str, value: &str) -> EnvVar {
+    EnvVar {
+        name: name.to_string(),
+        value: Some(value.to_string()),
+        value_from: None,
+    }
+}
+
+fn volume_mount(name: &str, mount_path: &str) -> VolumeMount {
+    VolumeMount {
+        name: name.to_string(),
+        mount_path: mount_path.to_string(),
+    }
+}
+
+fn container_ports(ports: &[(&str, u16)]) -> Vec<ContainerPort> {
+    ports
+        .iter()
+        .map(|(name, port)| ContainerPort {
+            name: name.to_string(),
+            container_port: *port,
+        })
+        .collect()
+}
+
+/// ConfigMap keys can't contain `/`, the key file of a service in `directory`
+fn config_map_key(directory: &str, config: &ServiceConfig) -> String {
+    format!("{directory}.{}.json", config.service_name)
+}
+
+fn check_config_map_size(name: &str, data: &BTreeMap<String, String>) {
+    let size: usize = data
+        .iter()
+        .map(|(key, value)| key.len() + value.len())
+        .sum();
+    if size > CONFIG_MAP_MAX_SIZE {
+        warn!("ConfigMap '{name}' is {size} bytes, above the 1MiB kubernetes accepts.");
+    }
+}
+
+/// Object names have to be DNS labels: at most 63 lowercase alphanumeric characters
+/// or `-`, starting and ending with an alphanumeric one
+fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 63
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !name.starts_with('-')
+        && !name.ends_with('-');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::other(format!(
+            "'{name}' is not a valid kubernetes object name, service and network names \
+            have to be lowercase alphanumeric or '-'"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docker::postgres::ArchiveDb;
+    use tempdir::TempDir;
+
+    fn write_network(network_path: &Path) {
+        fs::create_dir_all(network_path.join(NETWORK_KEYPAIRS)).unwrap();
+        fs::create_dir_all(network_path.join(LIBP2P_KEYPAIRS)).unwrap();
+        fs::write(network_path.join(GENESIS_LEDGER_JSON), "{\"ledger\":{}}").unwrap();
+        fs::write(
+            network_path.join(NETWORK_KEYPAIRS).join("bp.json"),
+            "bp-key",
+        )
+        .unwrap();
+        for service in ["seed", "bp", "archive"] {
+            fs::write(
+                network_path
+                    .join(LIBP2P_KEYPAIRS)
+                    .join(format!("{service}.json")),
+                format!("{service}-libp2p-key"),
+            )
+            .unwrap();
+        }
+    }
+
+    fn configs() -> Vec<ServiceConfig> {
+        vec![
+            ServiceConfig {
+                service_name: "seed".to_string(),
+                service_type: ServiceType::Seed,
+                docker_image: Some("daemon-image".into()),
+                client_port: Some(3100),
+                libp2p_keypair_path: Some("seed.json".into()),
+                ..Default::default()
+            },
+            ServiceConfig {
+                service_name: "bp".to_string(),
+                service_type: ServiceType::BlockProducer,
+                docker_image: Some("daemon-image".into()),
+                client_port: Some(4000),
+                private_key_path: Some("bp.json".into()),
+                libp2p_keypair_path: Some("bp.json".into()),
+                peers: Some(vec!["/dns4/seed-k8s/tcp/3102/p2p/peer".into()]),
+                ..Default::default()
+            },
+            ServiceConfig {
+                service_name: "archive".to_string(),
+                service_type: ServiceType::ArchiveNode,
+                docker_image: Some("daemon-image".into()),
+                archive_docker_image: Some("archive-image".into()),
+                client_port: Some(5000),
+                archive_port: Some(3086),
+                libp2p_keypair_path: Some("archive.json".into()),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn documents(manifests: &str) -> Vec<serde_yaml::Value> {
+        manifests
+            .split("---\n")
+            .map(|document| serde_yaml::from_str(document).unwrap())
+            .collect()
+    }
+
+    fn find<'a>(
+        documents: &'a [serde_yaml::Value],
+        kind: &str,
+        name: &str,
+    ) -> &'a serde_yaml::Value {
+        documents
+            .iter()
+            .find(|document| document["kind"] == kind && document["metadata"]["name"] == name)
+            .unwrap_or_else(|| panic!("No {kind} '{name}'"))
+    }
+
+    #[test]
+    fn test_generate() {
+        let tempdir = TempDir::new("test_k8s_generate").unwrap();
+        let network_path = tempdir.path().join("k8s");
+        write_network(&network_path);
+        let schema_path = tempdir.path().join("create_schema.sql");
+        fs::write(&schema_path, "CREATE TABLE blocks ();").unwrap();
+        let schemas = HashMap::from([("archive".to_string(), vec![schema_path])]);
+        let postgres = PostgresConfig::default();
+
+        let manifests = K8sManifests::new(&network_path, &postgres)
+            .generate(&configs(), &schemas)
+            .unwrap();
+        let documents = documents(&manifests);
+
+        let namespace = find(&documents, "Namespace", "k8s");
+        assert!(namespace["metadata"]["namespace"].is_null());
+
+        let network = find(&documents, "ConfigMap", "network-k8s");
+        assert_eq!(network["metadata"]["namespace"], "k8s");
+        assert_eq!(network["data"][GENESIS_LEDGER_JSON], "{\"ledger\":{}}");
+
+        let keys = find(&documents, "ConfigMap", "keys-k8s");
+        assert_eq!(keys["data"]["network-keypairs.bp.json"], "bp-key");
+        assert_eq!(keys["data"]["libp2p-keypairs.seed.json"], "seed-libp2p-key");
+        assert!(keys["data"]["network-keypairs.seed.json"].is_null());
+
+        // daemons keep their compose names so peer addresses resolve
+        let bp = find(&documents, "StatefulSet", "bp-k8s");
+        let pod = &bp["spec"]["template"]["spec"];
+        let args = pod["containers"][0]["args"].as_sequence().unwrap();
+        assert!(args.contains(&"/local-network/network-keypairs/bp.json".into()));
+        assert!(args.contains(&"/dns4/seed-k8s/tcp/3102/p2p/peer".into()));
+        let install = pod["initContainers"][0]["command"][2].as_str().unwrap();
+        assert!(install.contains("chmod 600 /local-network/network-keypairs/*"));
+        assert!(install.contains("chmod 600 /local-network/libp2p-keypairs/*"));
+        assert_eq!(
+            pod["volumes"][2]["configMap"]["items"][0]["path"],
+            "network-keypairs/bp.json"
+        );
+        assert_eq!(
+            bp["spec"]["volumeClaimTemplates"][0]["metadata"]["name"],
+            CONFIG_DIRECTORY
+        );
+
+        let seed = find(&documents, "Service", "seed-k8s");
+        let ports = seed["spec"]["ports"].as_sequence().unwrap();
+        assert_eq!(ports.len(), 3);
+        assert_eq!(ports[2]["name"], "external");
+        assert_eq!(ports[2]["port"], 3102);
+
+        // archive node, its archive service and postgres initialized from the schema
+        let postgres_name = ArchiveDb::primary().service_name("k8s");
+        find(&documents, "Service", &postgres_name);
+        let schema = find(&documents, "ConfigMap", &format!("{postgres_name}-schema"));
+        assert_eq!(
+            schema["data"]["00-create_schema.sql"],
+            "CREATE TABLE blocks ();"
+        );
+        let secret = find(&documents, "Secret", "postgres-credentials-k8s");
+        assert_eq!(secret["stringData"][POSTGRES_USER_ENV], "postgres");
+
+        let archive_service = find(&documents, "StatefulSet", "archive-service-k8s");
+        let args = &archive_service["spec"]["template"]["spec"]["containers"][0]["args"];
+        assert_eq!(
+            args[2],
+            format!(
+                "postgres://$({POSTGRES_USER_ENV}):$({POSTGRES_PASSWORD_ENV})@{postgres_name}:5432/archive"
+            )
+        );
+        let archive_node = find(&documents, "StatefulSet", "archive-k8s");
+        let args = archive_node["spec"]["template"]["spec"]["containers"][0]["args"]
+            .as_sequence()
+            .unwrap();
+        assert!(args.contains(&"archive-service-k8s:3086".into()));
+    }
+
+    #[test]
+    fn test_generate_rejects_invalid_names() {
+        let tempdir = TempDir::new("test_k8s_names").unwrap();
+        let network_path = tempdir.path().join("k8s");
+        write_network(&network_path);
+        let postgres = PostgresConfig::default();
+        let mut configs = configs();
+        configs[0].service_name = "Seed_1".to_string();
+
+        let result =
+            K8sManifests::new(&network_path, &postgres).generate(&configs, &HashMap::new());
+        assert!(result.is_err());
+
+        assert!(check_name("mina-bp-1-default").is_ok());
+        assert!(check_name("-bp").is_err());
+        assert!(check_name(&"a".repeat(64)).is_err());
+    }
+
+    #[test]
+    fn test_generate_rejects_uptime_service_backend() {
+        let tempdir = TempDir::new("test_k8s_uptime").unwrap();
+        let network_path = tempdir.path().join("k8s");
+        write_network(&network_path);
+        let postgres = PostgresConfig::default();
+        let configs = vec![ServiceConfig {
+            service_name: "uptime".to_string(),
+            service_type: ServiceType::UptimeServiceBackend,
+            ..Default::default()
+        }];
+
+        let result =
+            K8sManifests::new(&network_path, &postgres).generate(&configs, &HashMap::new());
+        assert!(result.is_err());
+    }
+}
@@ -6,22 +6,66 @@
 //! - A manager (`KeysManager`) that provides methods for generating:
 //!   - Block producer key pairs.
 //!   - libp2p key pairs.
+//!   - Account key pairs created on a running network.
+//! - The public key of a private key, for exporting keys to browser wallets.
+//! - Private keys of new key pairs, for the named accounts registry.
 //!
 //! The `KeysManager` relies on Docker and a specific Docker image to generate these key pairs,
 //! and uses the filesystem to store and manage these keys. It is designed to produce keys for multiple services
 //! and ensure the necessary environment settings are present during the key generation process.
+//! With native keys enabled it generates them in-process instead, writing the same
+//! password protected files as `mina advanced generate-keypair` and `mina libp2p generate-keypair`.
 //!
 //! Typical use involves creating a `KeysManager` instance with the desired configurations,
 //! then invoking the key generation methods as needed.
 
 use std::{
     collections::HashMap,
+    fs,
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
     path::{Path, PathBuf},
 };
 
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use crypto_secretbox::{aead::Aead, KeyInit, XSalsa20Poly1305};
+use ed25519_dalek::SigningKey;
 use log::{debug, info};
+use pasta_curves::{
+    arithmetic::CurveAffine,
+    group::{
+        ff::{Field, PrimeField},
+        Curve,
+    },
+    pallas,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    directory_manager::{ACCOUNTS, NETWORK_KEYPAIRS},
+    docker::runtime::Runtime,
+    proxy::ProxyConfig,
+    utils::{get_current_user_uid_gid, run_command},
+};
+
+/// Password of the private key files generated for the network
+pub const MINA_PRIVKEY_PASS: &str = "naughty blue worm";
 
-use crate::utils::{get_current_user_uid_gid, run_command};
+/// Password of the libp2p key files generated for the network
+pub const MINA_LIBP2P_PASS: &str = "naughty blue worm";
+
+const LIBP2P_KEYPAIRS: &str = "libp2p-keypairs";
+
+/// Format of a key exported by `keys export`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletFormat {
+    /// Base58 private key and GraphQL endpoint, as imported by the Auro and Clorio
+    /// browser wallets
+    Auro,
+}
 
 #[derive(Debug)]
 pub struct NodeKey {
@@ -32,76 +76,117 @@ pub struct NodeKey {
 pub struct KeysManager {
     pub network_path: PathBuf,
     pub docker_image: String,
+    pub runtime: Runtime,
+    /// Proxy variables passed into the key generation containers
+    proxy_env: Vec<String>,
+    /// Generate the key pairs in-process instead of in docker containers
+    native: bool,
 }
 
 impl KeysManager {
-    pub fn new(network_path: &Path, docker_image: &str) -> Self {
+    pub fn new(network_path: &Path, docker_image: &str, runtime: Runtime) -> Self {
         KeysManager {
             network_path: network_path.to_path_buf(),
             docker_image: docker_image.to_string(),
+            runtime,
+            proxy_env: ProxyConfig::load().docker_env(),
+            native: false,
+        }
+    }
+
+    /// Generate the key pairs without running the daemon image
+    pub fn with_native_keys(mut self, native: bool) -> Self {
+        self.native = native;
+        self
+    }
+
+    // path on the host of a key file mounted at `/local-network` in docker
+    fn host_path(&self, key_path_docker: &str) -> PathBuf {
+        let relative = key_path_docker.trim_start_matches("/local-network/");
+        self.network_path.join(relative)
+    }
+
+    // `docker run --rm` with the proxy environment of the container
+    fn docker_run_args(&self) -> Vec<&str> {
+        let mut args = vec!["run", "--rm"];
+        for env in &self.proxy_env {
+            args.extend(["--env", env.as_str()]);
         }
+        args
     }
+
     // generate bp key pair for single service
     pub fn generate_bp_key_pair(&self, service_name: &str) -> std::io::Result<NodeKey> {
         info!("Creating block producer keys for: {}", service_name);
+        self.generate_key_pair(NETWORK_KEYPAIRS, service_name)
+    }
+
+    // generate key pair for an account created on a running network
+    pub fn generate_account_key_pair(&self, account_name: &str) -> std::io::Result<NodeKey> {
+        info!("Creating account keys for: {}", account_name);
+        std::fs::create_dir_all(self.network_path.join(ACCOUNTS))?;
+        self.generate_key_pair(ACCOUNTS, account_name)
+    }
+
+    fn generate_key_pair(&self, key_subdir: &str, name: &str) -> std::io::Result<NodeKey> {
+        let pkey_path = format!("/local-network/{}/{}", key_subdir, name);
+        let key_string = if self.native {
+            write_mina_keypair(&self.host_path(&pkey_path), MINA_PRIVKEY_PASS)?
+        } else {
+            let output =
+                self.run_mina_advanced(&["generate-keypair", "-privkey-path", &pkey_path])?;
+            find_key(&output.stdout, "Public key: ")?
+        };
+
+        let keys = NodeKey {
+            key_string,
+            key_path_docker: pkey_path,
+        };
+        debug!("Generated keypair: {:?}", keys);
+        Ok(keys)
+    }
+
+    // decrypt the private key of a key pair generated by this manager
+    pub fn dump_private_key(&self, key_path_docker: &str) -> std::io::Result<String> {
+        if self.native {
+            let secret = fs::read_to_string(self.host_path(key_path_docker))?;
+            let plaintext = open_secret_box(&secret, MINA_PRIVKEY_PASS)?;
+            return Ok(bs58::encode(plaintext)
+                .with_check_version(PRIVATE_KEY_VERSION)
+                .into_string());
+        }
+        let output = self.run_mina_advanced(&["dump-keypair", "-privkey-path", key_path_docker])?;
+        find_key(&output.stdout, "Private key: ")
+    }
+
+    fn run_mina_advanced(&self, command: &[&str]) -> std::io::Result<std::process::Output> {
         let uid_gid = match get_current_user_uid_gid() {
             Some(uid_gid) => uid_gid,
             None => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
+                return Err(std::io::Error::other(
                     "Unable to retrieve UID and GID of current user",
                 ))
             }
         };
 
-        let key_subdir = "network-keypairs";
         let volume_path = format!("{}:/local-network", self.network_path.to_str().unwrap());
-        let pkey_path = format!("/local-network/{}/{}", key_subdir, service_name);
-        let args = vec![
-            "run",
-            "--rm",
+        let privkey_pass = format!("MINA_PRIVKEY_PASS={MINA_PRIVKEY_PASS}");
+        let mut args = self.docker_run_args();
+        args.extend([
             "--user",
             uid_gid.as_str(),
             "--env",
-            "MINA_PRIVKEY_PASS=naughty blue worm",
+            privkey_pass.as_str(),
             "--entrypoint",
             "mina",
             "-v",
             &volume_path,
             self.docker_image.as_str(),
             "advanced",
-            "generate-keypair",
-            "-privkey-path",
-            &pkey_path,
-        ];
+        ]);
+        args.extend(command);
 
-        let output = run_command("docker", &args)?;
-
-        let stdout_str = String::from_utf8_lossy(&output.stdout);
-        let public_key_line = stdout_str
-            .lines()
-            .find(|line| line.contains("Public key: "))
-            .ok_or_else(|| {
-                std::io::Error::new(std::io::ErrorKind::NotFound, "Public key not found")
-            })?;
-
-        let public_key = public_key_line
-            .split(": ")
-            .nth(1)
-            .ok_or_else(|| {
-                std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Public key format is incorrect",
-                )
-            })?
-            .to_string();
-
-        let keys = NodeKey {
-            key_string: public_key,
-            key_path_docker: pkey_path,
-        };
-        debug!("Generated keypair: {:?}", keys);
-        Ok(keys)
+        run_command(self.runtime.engine().binary(), &args)
     }
 
     // generate bp key pairs for multiple services
@@ -121,17 +206,25 @@ impl KeysManager {
     pub fn generate_libp2p_key_pair(&self, service_name: &str) -> std::io::Result<NodeKey> {
         info!("Creating libp2p keys for: {}", service_name);
 
-        let key_subdir = "libp2p-keypairs";
         let volume_path = format!("{}:/local-network", self.network_path.to_str().unwrap());
-        let pkey_path = format!("/local-network/{}/{}", key_subdir, service_name);
+        let pkey_path = format!("/local-network/{}/{}", LIBP2P_KEYPAIRS, service_name);
+
+        if self.native {
+            let keys = NodeKey {
+                key_string: write_libp2p_keypair(&self.host_path(&pkey_path), MINA_LIBP2P_PASS)?,
+                key_path_docker: pkey_path,
+            };
+            debug!("Generated keypair: {:?}", keys);
+            return Ok(keys);
+        }
 
-        let args = vec![
-            "run",
-            "--rm",
+        let libp2p_pass = format!("MINA_LIBP2P_PASS={MINA_LIBP2P_PASS}");
+        let mut args = self.docker_run_args();
+        args.extend([
             // "--user",
             // "1000:1000",
             "--env",
-            "MINA_LIBP2P_PASS=naughty blue worm",
+            libp2p_pass.as_str(),
             "--entrypoint",
             "mina",
             "-v",
@@ -141,9 +234,9 @@ impl KeysManager {
             "generate-keypair",
             "-privkey-path",
             &pkey_path,
-        ];
+        ]);
 
-        let output = run_command("docker", &args)?;
+        let output = run_command(self.runtime.engine().binary(), &args)?;
 
         // Extract the full keypair
         let stdout_str = String::from_utf8_lossy(&output.stdout);
@@ -169,3 +262,307 @@ impl KeysManager {
         Ok(keypairs)
     }
 }
+
+// find the key following `label` in the output of `mina advanced`
+fn find_key(stdout: &[u8], label: &str) -> std::io::Result<String> {
+    let stdout_str = String::from_utf8_lossy(stdout);
+    let key_line = stdout_str
+        .lines()
+        .find(|line| line.contains(label))
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} not found", label.trim_end_matches(": ")),
+            )
+        })?;
+
+    key_line
+        .split(": ")
+        .nth(1)
+        .map(|key| key.trim().to_string())
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{} format is incorrect", label.trim_end_matches(": ")),
+            )
+        })
+}
+
+// Native key generation, compatible with the files written by the daemon:
+// private keys are stored in a secret box (argon2i + xsalsa20poly1305) with
+// base58check encoded fields, next to a `.pub` or `.peerid` file.
+
+const PUBLIC_KEY_VERSION: u8 = 0xcb;
+const PRIVATE_KEY_VERSION: u8 = 0x5a;
+const SECRET_BOX_VERSION: u8 = 0x02;
+// version tag of the binary serialization of keys
+const STABLE_VERSION: u8 = 0x01;
+
+// argon2i limits of the daemon's secret boxes: 128 MiB and 6 passes
+const PW_MEM_LIMIT: u32 = 134_217_728;
+const PW_OPS_LIMIT: u32 = 6;
+
+// y coordinate of the Pallas generator (1, y) used by Mina
+const MINA_GENERATOR_Y: [u8; 32] = [
+    0xbb, 0x2a, 0xed, 0xca, 0x23, 0x7a, 0xcf, 0x19, 0x71, 0x47, 0x3d, 0x33, 0xd4, 0x5b, 0x65, 0x8f,
+    0x54, 0xee, 0x78, 0x63, 0xf0, 0xa9, 0xdf, 0x53, 0x7c, 0x93, 0x12, 0x0a, 0xa3, 0xb5, 0x74, 0x1b,
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SecretBox {
+    box_primitive: String,
+    pw_primitive: String,
+    nonce: String,
+    pwsalt: String,
+    pwdiff: (u32, u32),
+    ciphertext: String,
+}
+
+fn invalid_data(message: impl std::fmt::Display) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn password_key(password: &str, salt: &[u8]) -> std::io::Result<[u8; 32]> {
+    let params =
+        Params::new(PW_MEM_LIMIT / 1024, PW_OPS_LIMIT, 1, Some(32)).map_err(invalid_data)?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2i, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(invalid_data)?;
+    Ok(key)
+}
+
+fn b58_secret(data: &[u8]) -> String {
+    bs58::encode(data)
+        .with_check_version(SECRET_BOX_VERSION)
+        .into_string()
+}
+
+fn b58_secret_decode(data: &str) -> std::io::Result<Vec<u8>> {
+    let decoded = bs58::decode(data)
+        .with_check(Some(SECRET_BOX_VERSION))
+        .into_vec()
+        .map_err(invalid_data)?;
+    Ok(decoded[1..].to_vec())
+}
+
+/// Encrypts `plaintext` into the JSON secret box of the daemon's key files
+fn seal_secret_box(plaintext: &[u8], password: &str) -> std::io::Result<String> {
+    let mut nonce = [0u8; 24];
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut nonce);
+    OsRng.fill_bytes(&mut salt);
+
+    let key = password_key(password, &salt)?;
+    let ciphertext = XSalsa20Poly1305::new(&key.into())
+        .encrypt(&nonce.into(), plaintext)
+        .map_err(invalid_data)?;
+
+    let secret_box = SecretBox {
+        box_primitive: "xsalsa20poly1305".to_string(),
+        pw_primitive: "argon2i".to_string(),
+        nonce: b58_secret(&nonce),
+        pwsalt: b58_secret(&salt),
+        pwdiff: (PW_MEM_LIMIT, PW_OPS_LIMIT),
+        ciphertext: b58_secret(&ciphertext),
+    };
+    serde_json::to_string(&secret_box).map_err(invalid_data)
+}
+
+/// Decrypts a secret box written by [`seal_secret_box`] or the daemon
+fn open_secret_box(secret: &str, password: &str) -> std::io::Result<Vec<u8>> {
+    let secret_box: SecretBox = serde_json::from_str(secret).map_err(invalid_data)?;
+    let nonce: [u8; 24] = b58_secret_decode(&secret_box.nonce)?
+        .try_into()
+        .map_err(|_| invalid_data("Secret box nonce must be 24 bytes"))?;
+    let salt = b58_secret_decode(&secret_box.pwsalt)?;
+    let ciphertext = b58_secret_decode(&secret_box.ciphertext)?;
+
+    let key = password_key(password, &salt)?;
+    XSalsa20Poly1305::new(&key.into())
+        .decrypt(&nonce.into(), ciphertext.as_slice())
+        .map_err(|_| invalid_data("Unable to decrypt secret box, wrong password?"))
+}
+
+// write `contents` to `path` readable by the owner only, as the daemon requires
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(contents.as_bytes())
+}
+
+// `path` with `suffix` appended to its file name
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    path.into()
+}
+
+/// Base58check public key (`B62...`) of the Mina private key `scalar`
+fn mina_public_key(scalar: &pallas::Scalar) -> String {
+    let y = pallas::Base::from_repr(MINA_GENERATOR_Y).unwrap();
+    let generator = pallas::Affine::from_xy(pallas::Base::ONE, y).unwrap();
+    let public = (generator * scalar).to_affine().coordinates().unwrap();
+
+    let mut payload = vec![STABLE_VERSION, STABLE_VERSION];
+    payload.extend(public.x().to_repr());
+    payload.push(public.y().is_odd().unwrap_u8());
+    bs58::encode(payload)
+        .with_check_version(PUBLIC_KEY_VERSION)
+        .into_string()
+}
+
+/// Base58check public key (`B62...`) of the base58check private key (`EK...`)
+pub fn public_key_of(private_key: &str) -> std::io::Result<String> {
+    let decoded = bs58::decode(private_key.trim())
+        .with_check(Some(PRIVATE_KEY_VERSION))
+        .into_vec()
+        .map_err(invalid_data)?;
+    let repr: [u8; 32] = match decoded.as_slice() {
+        [_, STABLE_VERSION, repr @ ..] => repr
+            .try_into()
+            .map_err(|_| invalid_data("Private key must be 32 bytes"))?,
+        _ => return Err(invalid_data("Unknown private key version")),
+    };
+    let scalar = Option::<pallas::Scalar>::from(pallas::Scalar::from_repr(repr))
+        .ok_or_else(|| invalid_data("Private key is not a Pallas scalar"))?;
+    Ok(mina_public_key(&scalar))
+}
+
+/// Base58check private key (`EK...`) of a new random Mina key pair
+pub fn generate_private_key() -> String {
+    let mut plaintext = vec![STABLE_VERSION];
+    plaintext.extend(pallas::Scalar::random(OsRng).to_repr());
+    bs58::encode(plaintext)
+        .with_check_version(PRIVATE_KEY_VERSION)
+        .into_string()
+}
+
+/// Writes a new Mina key pair to `path` and `path.pub`, returning the public key
+fn write_mina_keypair(path: &Path, password: &str) -> std::io::Result<String> {
+    let scalar = pallas::Scalar::random(OsRng);
+    let public_key = mina_public_key(&scalar);
+
+    let mut plaintext = vec![STABLE_VERSION];
+    plaintext.extend(scalar.to_repr());
+    write_private(path, &seal_secret_box(&plaintext, password)?)?;
+    fs::write(with_suffix(path, ".pub"), &public_key)?;
+    Ok(public_key)
+}
+
+/// `privkey,pubkey,peerid` of an ed25519 libp2p key, keys being base64 encoded
+/// libp2p protobufs and the peer id the base58 identity multihash of the public key
+fn libp2p_keypair_string(signing_key: &SigningKey) -> String {
+    // protobuf header: key type ed25519 and length of the key data
+    let mut private = vec![0x08, 0x01, 0x12, 0x40];
+    private.extend(signing_key.to_keypair_bytes());
+    let mut public = vec![0x08, 0x01, 0x12, 0x20];
+    public.extend(signing_key.verifying_key().to_bytes());
+    let mut peer_id = vec![0x00, public.len() as u8];
+    peer_id.extend(&public);
+
+    let b64 = base64::engine::general_purpose::STANDARD;
+    format!(
+        "{},{},{}",
+        b64.encode(private),
+        b64.encode(public),
+        bs58::encode(peer_id).into_string()
+    )
+}
+
+/// Writes a new libp2p key pair to `path` and `path.peerid`, returning the key pair string
+fn write_libp2p_keypair(path: &Path, password: &str) -> std::io::Result<String> {
+    let keypair = libp2p_keypair_string(&SigningKey::generate(&mut OsRng));
+    let peer_id = keypair.split(',').next_back().unwrap_or_default();
+
+    write_private(path, &seal_secret_box(keypair.as_bytes(), password)?)?;
+    fs::write(with_suffix(path, ".peerid"), peer_id)?;
+    Ok(keypair)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_mina_public_key() {
+        let private_key = bs58::decode("EKFKgDtU3rcuFTVSEpmpXSkukjmX4cKefYREi6Sdsk7E7wsT7KRw")
+            .with_check(Some(PRIVATE_KEY_VERSION))
+            .into_vec()
+            .unwrap();
+        assert_eq!(private_key[1], STABLE_VERSION);
+        let scalar = pallas::Scalar::from_repr(private_key[2..].try_into().unwrap()).unwrap();
+
+        assert_eq!(
+            mina_public_key(&scalar),
+            "B62qiy32p8kAKnny8ZFwoMhYpBppM1DWVCqAPBYNcXnsAHhnfAAuXgg"
+        );
+    }
+
+    #[test]
+    fn test_public_key_of() {
+        assert_eq!(
+            public_key_of("EKFKgDtU3rcuFTVSEpmpXSkukjmX4cKefYREi6Sdsk7E7wsT7KRw").unwrap(),
+            "B62qiy32p8kAKnny8ZFwoMhYpBppM1DWVCqAPBYNcXnsAHhnfAAuXgg"
+        );
+        assert!(public_key_of("B62qiy32p8kAKnny8ZFwoMhYpBppM1DWVCqAPBYNcXnsAHhnfAAuXgg").is_err());
+        assert!(public_key_of("not a key").is_err());
+
+        let private_key = generate_private_key();
+        assert!(private_key.starts_with("EK"), "{private_key}");
+        assert!(public_key_of(&private_key).unwrap().starts_with("B62q"));
+    }
+
+    #[test]
+    fn test_native_mina_keypair_files() {
+        let tempdir = tempdir::TempDir::new("test_native_mina_keypair_files").unwrap();
+        let keys_manager =
+            KeysManager::new(tempdir.path(), "unused", Runtime::default()).with_native_keys(true);
+        fs::create_dir(tempdir.path().join(NETWORK_KEYPAIRS)).unwrap();
+
+        let key = keys_manager.generate_bp_key_pair("mina-bp-1").unwrap();
+        let key_path = tempdir.path().join(NETWORK_KEYPAIRS).join("mina-bp-1");
+        assert!(key.key_string.starts_with("B62q"));
+        assert_eq!(
+            fs::read_to_string(with_suffix(&key_path, ".pub")).unwrap(),
+            key.key_string
+        );
+        assert_eq!(
+            fs::metadata(&key_path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        // the dumped private key derives the public key
+        let private_key = keys_manager.dump_private_key(&key.key_path_docker).unwrap();
+        assert!(private_key.starts_with("EK"));
+        let private_key = bs58::decode(private_key)
+            .with_check(Some(PRIVATE_KEY_VERSION))
+            .into_vec()
+            .unwrap();
+        let scalar = pallas::Scalar::from_repr(private_key[2..].try_into().unwrap()).unwrap();
+        assert_eq!(mina_public_key(&scalar), key.key_string);
+    }
+
+    #[test]
+    fn test_secret_box_wrong_password() {
+        let secret = seal_secret_box(b"secret", MINA_PRIVKEY_PASS).unwrap();
+        let secret_box: SecretBox = serde_json::from_str(&secret).unwrap();
+        assert_eq!(secret_box.pwdiff, (134217728, 6));
+        assert!(open_secret_box(&secret, "wrong password").is_err());
+    }
+
+    #[test]
+    fn test_libp2p_keypair_string() {
+        let keypair = libp2p_keypair_string(&SigningKey::from_bytes(&[7; 32]));
+        let parts: Vec<&str> = keypair.split(',').collect();
+
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].starts_with("CAESQ"));
+        assert!(parts[1].starts_with("CAESI"));
+        assert!(parts[2].starts_with("12D3KooW"));
+    }
+}
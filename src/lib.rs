@@ -0,0 +1,32 @@
+//! # MiniMina
+//!
+//! Spins up Mina networks locally with docker compose. The `minimina` binary is
+//! a thin CLI over this crate; [`Network`] and [`Node`] expose the same
+//! operations to Rust programs.
+
+pub mod alert;
+pub mod api;
+pub mod chaos;
+pub mod cli;
+pub mod config;
+pub mod crash;
+pub mod directory_manager;
+pub mod docker;
+pub mod error;
+pub mod genesis_ledger;
+pub mod graphql;
+pub mod k8s;
+pub mod keys;
+pub mod log_filter;
+pub mod named_accounts;
+pub mod net_report;
+pub mod output;
+pub mod ports;
+pub mod proxy;
+pub mod service;
+pub mod session;
+pub mod sizing;
+pub mod topology;
+pub mod utils;
+
+pub use api::{Network, Node};
@@ -0,0 +1,189 @@
+//! # Log Filter Module
+//!
+//! Parses the JSON log lines the daemons write with `-log-json` into records and
+//! filters them by field, for `node logs --filter level=Error --json-lines`.
+//!
+//! A filter `FIELD=VALUE` matches a record whose field equals the value (`level`) or
+//! contains it (any other field), ignoring case. Filters on the same field match if
+//! any of them does, filters on different fields all have to match. Fields are
+//! `level`, `module`, `location`, `message`, `timestamp` and the keys of a record's
+//! `metadata`.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// A `FIELD=VALUE` filter of `node logs --filter`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    pub field: String,
+    pub value: String,
+}
+
+/// Parses a `FIELD=VALUE` filter, e.g. `level=Error`
+pub fn parse_log_filter(filter: &str) -> Result<LogFilter, String> {
+    match filter.split_once('=') {
+        Some((field, value)) if !field.trim().is_empty() => Ok(LogFilter {
+            field: field.trim().to_string(),
+            value: value.to_string(),
+        }),
+        _ => Err(format!(
+            "invalid filter '{filter}', expected FIELD=VALUE, e.g. level=Error"
+        )),
+    }
+}
+
+impl LogFilter {
+    fn matches(&self, record: &LogRecord) -> bool {
+        let Some(value) = record.field(&self.field) else {
+            return false;
+        };
+        let (value, wanted) = (value.to_lowercase(), self.value.to_lowercase());
+        if self.field == "level" {
+            value == wanted
+        } else {
+            value.contains(&wanted)
+        }
+    }
+}
+
+/// Whether `record` matches all `filters`, see the module documentation
+pub fn matches_all(filters: &[LogFilter], record: &LogRecord) -> bool {
+    filters.iter().all(|filter| {
+        filters
+            .iter()
+            .filter(|other| other.field == filter.field)
+            .any(|other| other.matches(record))
+    })
+}
+
+/// A line of a daemon's log, the fields of a JSON log line if it is one
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LogRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    /// `source.module` of the log line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    /// `source.location` of the log line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Message of a JSON log line, the line itself otherwise
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Map<String, Value>>,
+    /// The line as logged
+    #[serde(skip)]
+    pub line: String,
+}
+
+impl LogRecord {
+    pub fn parse(line: &str) -> Self {
+        let json = match serde_json::from_str::<Value>(line) {
+            Ok(json @ Value::Object(_)) => json,
+            _ => {
+                return LogRecord {
+                    message: line.to_string(),
+                    line: line.to_string(),
+                    ..Default::default()
+                }
+            }
+        };
+        let text = |value: Option<&Value>| value.and_then(Value::as_str).map(String::from);
+        LogRecord {
+            timestamp: text(json.get("timestamp")),
+            level: text(json.get("level")),
+            module: text(json.pointer("/source/module")),
+            location: text(json.pointer("/source/location")),
+            message: text(json.get("message")).unwrap_or_default(),
+            metadata: json.get("metadata").and_then(Value::as_object).cloned(),
+            line: line.to_string(),
+        }
+    }
+
+    /// Value of `field`, metadata values which aren't strings as JSON
+    fn field(&self, field: &str) -> Option<String> {
+        match field {
+            "timestamp" => self.timestamp.clone(),
+            "level" => self.level.clone(),
+            "module" => self.module.clone(),
+            "location" => self.location.clone(),
+            "message" => Some(self.message.clone()),
+            key => self.metadata.as_ref()?.get(key).map(|value| match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ERROR_LINE: &str = r#"{"timestamp":"2024-01-02 13:23:37.123456Z","level":"Error","source":{"module":"Mina_networking__Gossip_net","location":"File \"src/lib/gossip_net/libp2p.ml\", line 42"},"message":"Failed to connect to $peer","metadata":{"peer":"12D3KooW","attempt":3}}"#;
+    const INFO_LINE: &str = r#"{"timestamp":"2024-01-02 13:23:38.000000Z","level":"Info","source":{"module":"Mina_lib","location":"File \"src/lib/mina_lib/mina_lib.ml\", line 7"},"message":"Block produced","metadata":{}}"#;
+
+    fn filters(filters: &[&str]) -> Vec<LogFilter> {
+        filters
+            .iter()
+            .map(|filter| parse_log_filter(filter).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_log_filter() {
+        assert_eq!(
+            parse_log_filter("level=Error").unwrap(),
+            LogFilter {
+                field: "level".to_string(),
+                value: "Error".to_string(),
+            }
+        );
+        assert_eq!(parse_log_filter("message=a=b").unwrap().value, "a=b");
+        assert!(parse_log_filter("level").is_err());
+        assert!(parse_log_filter("=Error").is_err());
+    }
+
+    #[test]
+    fn test_parse_log_record() {
+        let record = LogRecord::parse(ERROR_LINE);
+        assert_eq!(record.level.as_deref(), Some("Error"));
+        assert_eq!(
+            record.module.as_deref(),
+            Some("Mina_networking__Gossip_net")
+        );
+        assert_eq!(record.message, "Failed to connect to $peer");
+        assert_eq!(record.field("attempt").as_deref(), Some("3"));
+        assert_eq!(record.line, ERROR_LINE);
+
+        let record = LogRecord::parse("Starting the daemon");
+        assert_eq!(record.level, None);
+        assert_eq!(record.message, "Starting the daemon");
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"message":"Starting the daemon"}"#
+        );
+    }
+
+    #[test]
+    fn test_matches_all() {
+        let error = LogRecord::parse(ERROR_LINE);
+        let info = LogRecord::parse(INFO_LINE);
+        let plain = LogRecord::parse("Starting the daemon");
+
+        let gossip_errors = filters(&["level=error", "module=Gossip"]);
+        assert!(matches_all(&gossip_errors, &error));
+        assert!(!matches_all(&gossip_errors, &info));
+        assert!(!matches_all(&gossip_errors, &plain));
+
+        let errors_or_infos = filters(&["level=Error", "level=Info"]);
+        assert!(matches_all(&errors_or_infos, &error));
+        assert!(matches_all(&errors_or_infos, &info));
+
+        assert!(matches_all(&filters(&["peer=12D3"]), &error));
+        assert!(!matches_all(&filters(&["level=Err"]), &error));
+        assert!(matches_all(&[], &plain));
+    }
+}
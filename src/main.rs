@@ -1,1179 +1,733 @@
-mod cli;
-mod directory_manager;
-mod docker;
-mod genesis_ledger;
-mod graphql;
-mod keys;
-mod output;
-mod service;
-mod topology;
-mod utils;
-
-use crate::{
-    genesis_ledger::*,
-    keys::{KeysManager, NodeKey},
-    output::{network, node},
-    service::{ServiceConfig, ServiceType},
-    utils::fetch_schema,
-};
-use clap::Parser;
-use cli::{
-    Cli, Command, CommandWithNetworkId, CommandWithNodeId, DefaultLogLevel, NetworkCommand,
-    NodeCommand,
-};
-use directory_manager::DirectoryManager;
-use docker::manager::{ContainerState, DockerManager};
+use clap::{parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches};
 use env_logger::{Builder, Env};
-use graphql::GraphQl;
-use log::{error, info, warn};
-use std::{
-    collections::HashMap,
-    io::{Error, ErrorKind, Result},
-    path::Path,
-    process::exit,
+use log::{error, info};
+use minimina::{
+    api::{self, Network},
+    cli::{
+        self, Cli, Command, CommandWithNetworkId, CommandWithNodeId, DebugCommand, DefaultLogLevel,
+        KeysCommand, NetworkCommand, NodeCommand, TopologyCommand,
+    },
+    config,
+    directory_manager::DirectoryManager,
+    docker::runtime::Runtime,
+    error::{MiniminaError, Result},
+    output::{self, network},
+    session, topology, utils,
 };
+use std::{process::exit, time::Duration};
 
 // The least supported version of docker compose
 const LEAST_COMPOSE_VERSION: &str = "2.21.0";
 
-// Hardcoded daemon image for default network
-const DEFAULT_DAEMON_DOCKER_IMAGE: &str =
-    "gcr.io/o1labs-192920/mina-daemon:2.0.0berkeley-rc1-1551e2f-bullseye-berkeley";
-
-// Hardcoded archive image for default network
-const DEFAULT_ARCHIVE_DOCKER_IMAGE: &str =
-    "gcr.io/o1labs-192920/mina-archive:2.0.0berkeley-rc1-1551e2f-bullseye";
-
-const IMAGE_COMMIT_HASH: &str = "1551e2f";
-
-// Timeout in seconds for waiting operations
-const TIMEOUT_IN_SECS: u16 = 180;
+fn main() {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let log_level = match &config::settings().log_level {
+        Some(log_level) if !log_level_given(&matches) => log_level.as_str(),
+        _ => cli.command.log_level(),
+    };
+    Builder::from_env(Env::default().default_filter_or(log_level)).init();
+
+    if let Err(e) = run(cli) {
+        error!("{e}");
+        // a stalled chain has already been reported by `network watch-height`,
+        // the output of a failed `node exec -r` command, `topology validate` or
+        // `network archive-fault` printed as is
+        if !matches!(
+            e,
+            MiniminaError::ChainStalled { .. } | MiniminaError::Exited(_)
+        ) {
+            println!(
+                "{}",
+                output::Error {
+                    error_message: e.to_string()
+                }
+            );
+        }
+        exit(e.exit_code());
+    }
+}
 
-fn main() -> Result<()> {
-    let cli: Cli = Cli::parse();
-    Builder::from_env(Env::default().default_filter_or(cli.command.log_level())).init();
+/// Whether `--log-level` was given to the subcommand run
+fn log_level_given(matches: &ArgMatches) -> bool {
+    let mut matches = matches;
+    while let Some((_, subcommand)) = matches.subcommand() {
+        matches = subcommand;
+    }
+    matches.ids().any(|id| id == "log_level")
+        && matches.value_source("log_level") == Some(ValueSource::CommandLine)
+}
 
+fn run(cli: Cli) -> Result<()> {
     let directory_manager = DirectoryManager::new();
-    check_compose_version()?;
+    let runtime = match &cli.command {
+        Command::Network(NetworkCommand::Create(cmd)) => cmd.runtime,
+        command => command
+            .network_id()
+            .map(|network_id| Runtime::of_network(&directory_manager.network_path(network_id)))
+            .unwrap_or_default(),
+    };
+    // topology files are checked and keys exported without a container engine,
+    // recorded commands check it themselves
+    if !matches!(
+        cli.command,
+        Command::Topology(_)
+            | Command::Record(_)
+            | Command::Replay(_)
+            | Command::Debug(_)
+            | Command::Keys(_)
+    ) {
+        check_compose_version(runtime)?;
+    }
 
     match cli.command {
         Command::Network(net_cmd) => match net_cmd {
             NetworkCommand::Create(cmd) => {
-                let network_id = cmd.network_id().to_string();
-                let network_path = directory_manager.network_path(&network_id);
-                let docker = DockerManager::new(&network_path);
-
-                check_setup_network(&docker, &directory_manager, &network_id)?;
-
-                // key-pairs for block producers and libp2p keys for all services
-                // for default network (not topology based)
-                let mut bp_keys_opt: Option<HashMap<String, NodeKey>> = None;
-                let mut libp2p_keys_opt: Option<HashMap<String, NodeKey>> = None;
-
-                // consume the genesis ledger
-                handle_genesis_ledger(
-                    &cmd,
-                    &directory_manager,
-                    &network_id,
-                    &mut bp_keys_opt,
-                    &mut libp2p_keys_opt,
-                )?;
-
-                // build services from topology file
-                let services = handle_topology(
-                    &cmd,
-                    &directory_manager,
-                    &network_id,
-                    bp_keys_opt,
-                    libp2p_keys_opt,
-                )?;
-
-                // copy libp2p + network keys
-                if let Err(e) = directory_manager.copy_all_network_keys(&network_id, &services) {
-                    return exit_with(format!("Failed to copy keys with error: {e}"));
-                }
-
-                // generate docker compose
-                if let Err(e) = docker.compose_generate_file(&services) {
-                    return exit_with(format!(
-                        "Failed to generate docker-compose.yaml with error: {e}"
-                    ));
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                if network.exists() && !cmd.no_overwrite && !cmd.yes && !cmd.if_not_exists {
+                    let plan = network.deletion_plan(false, false)?;
+                    eprintln!(
+                        "Network '{}' already exists, overwriting it destroys:\n{}",
+                        plan.network_id,
+                        plan.describe()
+                    );
+                    if !utils::confirm("Overwrite it?")? {
+                        return fail(format!(
+                            "Network '{}' already exists, not overwriting it (pass --yes to confirm).",
+                            plan.network_id
+                        ));
+                    }
                 }
-
-                create_network(&docker, &directory_manager, &network_id, &services)
+                let info = network.create(&api::CreateOptions::from(&cmd))?;
+                println!("{info}");
+                Ok(())
             }
 
             NetworkCommand::Info(cmd) => {
-                let network_id = cmd.network_id;
-                check_network_exists(&network_id)?;
-
-                match directory_manager.get_network_info(&network_id) {
-                    Ok(json_data) => {
-                        println!("{json_data}");
-                        Ok(())
-                    }
-                    Err(e) => {
-                        let error_message = format!(
-                            "Failed to get info for network '{network_id}' with error: {e}"
-                        );
-                        exit_with(error_message)
-                    }
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                if cmd.graph {
+                    println!("{}", network.graph()?);
+                } else if cmd.live {
+                    println!("{}", network.live_info()?);
+                } else {
+                    println!("{}", network.info()?);
                 }
+                Ok(())
             }
 
             NetworkCommand::Status(cmd) => {
-                let network_id = cmd.network_id;
-                let network_path = directory_manager.network_path(&network_id);
-                check_network_exists(&network_id)?;
-
-                let docker = DockerManager::new(&network_path);
-                let ls_out = match docker.compose_ls() {
-                    Ok(out) => out,
-                    Err(e) => {
-                        let error_message = format!(
-                            "Failed to get status from docker compose ls for network '{network_id}': {e}."
-                        );
-                        return exit_with(error_message);
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                match cmd.watch {
+                    Some(interval) => {
+                        network.watch_status(Duration::from_secs(interval), |status| {
+                            match cmd.format {
+                                cli::WatchFormat::Json => {
+                                    println!("{}", serde_json::to_string(status)?)
+                                }
+                                cli::WatchFormat::Table => {
+                                    // clear the screen and move the cursor home before redrawing
+                                    print!("\x1b[2J\x1b[H");
+                                    println!("{}", status.to_table());
+                                    println!(
+                                        "\nRefreshed at {}",
+                                        chrono::Local::now().format("%H:%M:%S")
+                                    );
+                                }
+                            }
+                            Ok(())
+                        })
                     }
-                };
-
-                let ps_out = match docker.compose_ps(None) {
-                    Ok(out) => out,
-                    Err(e) => {
-                        let error_message = format!(
-                            "Failed to get status from docker compose ps for network '{network_id}': {e}."
-                        );
-                        return exit_with(error_message);
+                    None => {
+                        println!("{}", network.status()?);
+                        Ok(())
                     }
-                };
-
-                let compose_file_path = docker.compose_path.to_str().unwrap();
-                let mut status = network::Status::new(&network_id);
-                status.update_from_compose_ls(ls_out, compose_file_path);
-                status.update_from_compose_ps(ps_out);
-                status.network_dir = network_path.into_os_string().into_string().unwrap();
-
-                println!("{status}");
-                Ok(())
+                }
             }
 
             NetworkCommand::Delete(cmd) => {
-                let network_id = cmd.network_id;
-                check_network_exists(&network_id)?;
-
-                let docker = DockerManager::new(&directory_manager.network_path(&network_id));
-                match docker.compose_down(None, true, true) {
-                    Ok(_) => match directory_manager.delete_network_directory(&network_id) {
-                        Ok(_) => {
-                            println!("{}", network::Delete { network_id });
-                            Ok(())
-                        }
-                        Err(e) => {
-                            let error_message = format!(
-                                "Failed to delete network directory for '{network_id}': {e}"
-                            );
-                            exit_with(error_message)
-                        }
-                    },
-                    Err(e) => {
-                        let error_message = format!("Failed to delete network '{network_id}': {e}");
-                        exit_with(error_message)
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                let plan = network.deletion_plan(true, cmd.remove_images)?;
+                if cmd.dry_run {
+                    println!("{plan}");
+                    return Ok(());
+                }
+                if !cmd.yes {
+                    eprintln!(
+                        "Deleting network '{}' destroys:\n{}",
+                        plan.network_id,
+                        plan.describe()
+                    );
+                    if !utils::confirm("Delete it?")? {
+                        return fail(format!(
+                            "Not deleting network '{}' (pass --yes to confirm).",
+                            plan.network_id
+                        ));
                     }
                 }
+                let deleted = network.delete(cmd.remove_images)?;
+                println!("{deleted}");
+                Ok(())
             }
 
             NetworkCommand::List => {
-                let networks = directory_manager
-                    .list_network_directories()
-                    .expect("Failed to list networks");
-                let mut list = network::List::new();
-
-                if networks.is_empty() {
-                    println!("{list}");
-                } else {
-                    list.update(
-                        networks,
-                        directory_manager.base_path.as_path().to_str().unwrap(),
-                    );
-                    println!("{list}");
-                }
-
+                println!("{}", Network::list(&directory_manager)?);
                 Ok(())
             }
 
             NetworkCommand::Start(cmd) => {
-                let network_id = cmd.network_id().to_string();
-                let network_path = directory_manager.network_path(&network_id);
-                let docker = DockerManager::new(&network_path);
-
-                check_network_exists(&network_id)?;
-                if let Err(e) = directory_manager.check_genesis_timestamp(&network_id) {
-                    warn!("{e} In case network is unstable consider updating by running 'network create' again.");
-                }
-
-                match docker.compose_start_all() {
-                    Ok(output) => {
-                        if cmd.verbose {
-                            println!("Status: {}", output.status);
-                            println!("Stdout: {}", String::from_utf8_lossy(&output.stdout));
-                            println!("Stderr: {}", String::from_utf8_lossy(&output.stderr));
-                        }
-
-                        println!("{}", network::Start { network_id });
-                        Ok(())
-                    }
-                    Err(e) => {
-                        let error_message = format!("Failed to start network '{network_id}': {e}");
-                        exit_with(error_message)
-                    }
-                }
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                let started = network.start(&api::StartOptions::from(&cmd))?;
+                println!("{started}");
+                Ok(())
             }
 
             NetworkCommand::Stop(cmd) => {
-                let network_id = cmd.network_id;
-                check_network_exists(&network_id)?;
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                let stopped = network.stop(&api::StopOptions::from(&cmd))?;
+                println!("{stopped}");
+                Ok(())
+            }
 
-                let network_path = directory_manager.network_path(&network_id);
-                let docker = DockerManager::new(&network_path);
+            NetworkCommand::Slots(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                println!("{}", network.slots()?);
+                Ok(())
+            }
 
-                match docker.compose_stop_all() {
-                    Ok(_) => {
-                        println!("{}", network::Stop { network_id });
-                        Ok(())
-                    }
-                    Err(e) => {
-                        let error_message = format!("Failed to stop network '{network_id}': {e}");
-                        exit_with(error_message)
-                    }
+            NetworkCommand::WatchHeight(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                let report = network.watch_height(&api::WatchHeightOptions::from(&cmd))?;
+                println!("{report}");
+                if report.stalled {
+                    return Err(MiniminaError::ChainStalled {
+                        network_id: report.network_id,
+                        seconds: report.seconds_since_advance,
+                    });
                 }
+                Ok(())
             }
-        },
 
-        Command::Node(node_cmd) => match node_cmd {
-            NodeCommand::Start(cmd) => {
-                let node_id = cmd.node_args.node_id().to_string();
-                let network_id = cmd.node_args.network_id().to_string();
-                let container = format!("{node_id}-{network_id}");
-                let network_path = directory_manager.network_path(&network_id);
-                let docker = DockerManager::new(&network_path);
-                let nodes = docker.compose_ps(None)?;
-
-                let mut _fresh_state;
-
-                _fresh_state = match docker.filter_container_by_name(nodes, &container) {
-                    Some(node) => match node.state {
-                        ContainerState::Running => {
-                            warn!("Node '{node_id}' is already running in network '{network_id}'.");
-                            false
-                        }
-                        ContainerState::Created => {
-                            info!("Starting node '{node_id}' in network '{network_id}' for the first time.");
-                            true
-                        }
-                        container_state => {
-                            info!(
-                                "Node '{node_id}' is {} in network '{network_id}'.",
-                                container_state.to_string()
-                            );
-                            false
-                        }
-                    },
-                    None => {
-                        let error =
-                            format!("Node '{node_id}' does not exist in network '{network_id}'.");
-                        return handle_start_error(&node_id, error.as_str());
-                    }
-                };
+            NetworkCommand::Export(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                let exported = network.export(&cmd.output, cmd.archive_data)?;
+                println!("{exported}");
+                Ok(())
+            }
 
-                if cmd.fresh_state {
-                    info!("Starting node '{node_id}' in network '{network_id}' with fresh state.");
-                    docker.compose_down(Some(container.clone()), true, false)?;
-                    docker.compose_create(Some(container.clone()))?;
-                    _fresh_state = true;
-                }
+            NetworkCommand::Import(cmd) => {
+                let imported = Network::import(directory_manager, &cmd.input)?;
+                println!("{imported}");
+                Ok(())
+            }
 
-                if cmd.import_accounts {
-                    warn!("Importing accounts for node '{node_id}' in network '{network_id}'. This can take a moment...");
-                    import_all_accounts(&docker, &directory_manager, &node_id, &network_id)?;
-                }
+            NetworkCommand::Snapshot(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                let snapshot = network.snapshot(&cmd.output)?;
+                println!("{snapshot}");
+                Ok(())
+            }
 
-                match docker.compose_start(vec![&container]) {
-                    Ok(out) => {
-                        if out.status.success() {
-                            if cmd.graphql_filtered_logs {
-                                warn!("Waiting for graphql server to be operational so I can request filtered logs. This can take a moment...");
-                                let gql = GraphQl::new(directory_manager.clone());
-                                if let Some(gql_ep) = gql.get_endpoint(&node_id, &network_id) {
-                                    gql.wait_for_server(&gql_ep)?;
-                                    gql.request_filtered_logs(&gql_ep)?;
-                                }
-                            }
+            NetworkCommand::Restore(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                let restored = network.restore(&cmd.input)?;
+                println!("{restored}");
+                Ok(())
+            }
 
-                            if cmd.node_args.raw_output {
-                                println!(
-                                    "Node '{node_id}' on network '{network_id}' \
-                                          has been started. {}",
-                                    String::from_utf8_lossy(&out.stdout)
-                                );
-                            } else {
-                                println!(
-                                    "{}",
-                                    node::Start {
-                                        // fresh_state,
-                                        node_id,
-                                        network_id,
-                                    }
-                                )
-                            }
+            NetworkCommand::Accounts(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                println!("{}", network.accounts()?);
+                Ok(())
+            }
 
-                            Ok(())
-                        } else {
-                            handle_start_error(&node_id, String::from_utf8_lossy(&out.stderr))
-                        }
-                    }
-                    Err(e) => handle_start_error(&node_id, e),
-                }
+            NetworkCommand::Nodes(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                println!("{}", network.nodes()?);
+                Ok(())
             }
 
-            NodeCommand::Stop(cmd) => {
-                let node_id = cmd.node_id().to_string();
-                let network_id = cmd.network_id().to_string();
-                let container = format!("{node_id}-{network_id}");
-                let network_path = directory_manager.network_path(&network_id);
-                let docker = DockerManager::new(&network_path);
+            NetworkCommand::NetReport(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                println!("{}", network.net_report(Duration::from_secs(cmd.interval))?);
+                Ok(())
+            }
 
-                match docker.compose_stop(vec![&container]) {
-                    Ok(out) => {
-                        if out.status.success() {
-                            if cmd.raw_output {
-                                println!(
-                                    "Node '{node_id}' on network '{network_id}' \
-                                          has been stopped. {}",
-                                    String::from_utf8_lossy(&out.stdout)
-                                );
-                            } else {
-                                println!(
-                                    "{}",
-                                    node::Stop {
-                                        node_id,
-                                        network_id,
-                                    }
-                                )
-                            }
-                            Ok(())
+            NetworkCommand::Logs(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                let since = cmd.since.as_deref();
+                if cmd.follow {
+                    network.follow_logs(since, |line| {
+                        if cmd.raw_output {
+                            println!("{line}");
                         } else {
-                            handle_stop_error(&node_id, String::from_utf8_lossy(&out.stderr))
+                            println!("{}", serde_json::to_string(&network::LogLine::parse(line))?);
                         }
-                    }
-                    Err(e) => handle_stop_error(&node_id, e),
-                }
-            }
-
-            NodeCommand::Logs(cmd) => {
-                let node_id = cmd.node_id();
-                let network_id = cmd.network_id();
-                let network_path = directory_manager.network_path(network_id);
-                let docker = DockerManager::new(&network_path);
-                let services = directory_manager
-                    .get_services_info(network_id)
-                    .expect("Failed to get services info");
-                match docker.run_docker_logs(node_id, network_id) {
-                    Ok(output) => {
-                        if output.status.success() {
-                            info!("Successfully got logs for '{node_id}' on '{network_id}'");
-                            // uptime service logs to stderr
-                            let out = if is_node_uptime_service(services, node_id) {
-                                &output.stderr
-                            } else {
-                                &output.stdout
-                            };
-                            if cmd.raw_output {
-                                println!("{}", String::from_utf8_lossy(out));
-                            } else {
-                                println!(
-                                    "{}",
-                                    output::node::Logs {
-                                        logs: String::from_utf8_lossy(out).into(),
-                                        network_id: network_id.into(),
-                                        node_id: node_id.into(),
-                                    }
-                                )
+                        Ok(())
+                    })
+                } else {
+                    network.logs(since).map(|logs| {
+                        if cmd.raw_output {
+                            for line in logs.logs {
+                                match line.service.as_str() {
+                                    "" => println!("{}", line.message),
+                                    service => println!("{service} | {}", line.message),
+                                }
                             }
                         } else {
-                            let error_message = format!(
-                                "Failed to get logs for '{node_id}' on '{network_id}': {}",
-                                String::from_utf8_lossy(&output.stderr)
-                            );
-                            return exit_with(error_message);
+                            println!("{logs}");
                         }
-                    }
-                    Err(e) => error!("Error while running 'docker logs {node_id}'{e}"),
+                    })
                 }
+            }
 
+            NetworkCommand::Repair(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                println!("{}", network.repair()?);
                 Ok(())
             }
 
-            NodeCommand::DumpArchiveData(cmd) => {
-                let network_id = cmd.network_id();
-                let node_id = cmd.node_id();
-                let network_path = directory_manager.network_path(cmd.network_id());
-                let docker = DockerManager::new(&network_path);
-                let services = directory_manager
-                    .get_services_info(network_id)
-                    .expect("Failed to get services info");
+            NetworkCommand::Recreate(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                println!("{}", network.recreate()?);
+                Ok(())
+            }
 
-                check_network_exists(network_id)?;
+            NetworkCommand::Partition(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                println!("{}", network.partition(&cmd.groups)?);
+                Ok(())
+            }
 
-                if !is_node_archive(services, node_id) {
-                    let error_message = format!(
-                        "Node '{node_id}' is not an archive node in '{network_id}' network."
-                    );
-                    return exit_with(error_message);
+            NetworkCommand::ArchiveFault(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                let fault = network.archive_fault(&api::ArchiveFaultOptions::from(&cmd))?;
+                println!("{fault}");
+                if fault.caught_up {
+                    Ok(())
+                } else {
+                    Err(MiniminaError::Exited(1))
                 }
+            }
 
-                match docker.compose_dump_archive_data(network_id) {
-                    Ok(output) => {
-                        if output.status.success() {
-                            info!("Successfully dumped archive data for node '{node_id}', network '{network_id}'");
-                            if cmd.raw_output {
-                                println!("{}", String::from_utf8_lossy(&output.stdout));
-                            } else {
-                                println!(
-                                    "{}",
-                                    output::node::ArchiveData {
-                                        data: String::from_utf8_lossy(&output.stdout).into(),
-                                        network_id: network_id.into(),
-                                        node_id: node_id.into(),
-                                    }
-                                )
-                            }
-                        } else {
-                            let error_message = format!(
-                                "Failed to dump archive data for node '{node_id}', network '{network_id}': {}",
-                                String::from_utf8_lossy(&output.stderr)
-                            );
-                            return exit_with(error_message);
-                        }
-                    }
-                    Err(e) => {
-                        return exit_with(format!(
-                            "Error while dumping archive data for node '{node_id}', network_id '{network_id}': {e}"
-                        ))
-                    }
+            NetworkCommand::LagReport(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                let report = network.lag_report(cmd.max_lag)?;
+                println!("{report}");
+                if report.behind.is_empty() {
+                    Ok(())
+                } else {
+                    Err(MiniminaError::Exited(1))
                 }
+            }
 
+            NetworkCommand::Heal(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                println!("{}", network.heal()?);
                 Ok(())
             }
 
-            NodeCommand::DumpPrecomputedBlocks(cmd) => {
-                let node_id = cmd.node_id();
-                let network_id = cmd.network_id();
-                let network_path = directory_manager.network_path(cmd.network_id());
-                let docker = DockerManager::new(&network_path);
+            NetworkCommand::Env(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, &cmd.network_id);
+                print!("{}", network.env(cmd.node.as_deref())?);
+                Ok(())
+            }
 
-                check_network_exists(network_id)?;
+            NetworkCommand::InitArchive(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                let initialized =
+                    network.init_archive(cmd.archive.as_deref(), cmd.reset, cmd.verify_archive)?;
+                println!("{initialized}");
+                Ok(())
+            }
 
-                match docker.compose_dump_precomputed_blocks(node_id, network_id) {
-                    Ok(output) => {
-                        if output.status.success() {
-                            info!("Successfully dumped precomputed blocks for '{node_id}' on '{network_id}'");
-                            if cmd.raw_output {
-                                println!("{}", String::from_utf8_lossy(&output.stdout));
-                            } else {
-                                println!(
-                                    "{}",
-                                    output::node::PrecomputedBlocks {
-                                        blocks: String::from_utf8_lossy(&output.stdout).into(),
-                                        network_id: network_id.into(),
-                                        node_id: node_id.into(),
-                                    }
-                                )
-                            }
-                        } else {
-                            let error_message = format!(
-                                "Failed to dump precomputed blocks for '{node_id}' on '{network_id}': {}", String::from_utf8_lossy(&output.stderr)
-                            );
-                            return exit_with(error_message);
-                        }
-                    }
-                    Err(e) => {
-                        let error_message = format!(
-                            "Failed to dump precomputed blocks for '{node_id}' on '{network_id}': {e}"
-                        );
-                        return exit_with(error_message);
-                    }
+            NetworkCommand::O1jsConfig(cmd) => {
+                let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+                let config =
+                    network.o1js_config(cmd.fee_payer.as_deref(), cmd.archive_endpoint.clone())?;
+                if cmd.raw_output {
+                    println!("{}", config.to_js_snippet());
+                } else {
+                    println!("{config}");
                 }
+                Ok(())
+            }
+        },
 
+        Command::Topology(TopologyCommand::Validate(cmd)) => {
+            let diagnostics = topology::validate(&cmd.topology);
+            let valid = diagnostics.is_empty();
+            println!(
+                "{}",
+                output::topology::Validate {
+                    topology: cmd.topology.display().to_string(),
+                    valid,
+                    diagnostics,
+                }
+            );
+            if valid {
                 Ok(())
+            } else {
+                Err(MiniminaError::Exited(1))
             }
+        }
 
-            NodeCommand::RunReplayer(cmd) => {
-                let start_slot = cmd.start_slot_since_genesis;
-                let node_id = cmd.node_args.node_id();
-                let network_id = cmd.node_args.network_id();
-                let network_path = directory_manager.network_path(cmd.node_args.network_id());
-                let docker = DockerManager::new(&network_path);
-                let services = directory_manager
-                    .get_services_info(network_id)
-                    .expect("Failed to get services info");
-                check_network_exists(network_id)?;
+        Command::Debug(DebugCommand::Render(cmd)) => {
+            let network =
+                Network::with_directory_manager(directory_manager, &cmd.network_id.network_id);
+            print!("{}", network.render_compose(&cmd.topology)?);
+            Ok(())
+        }
 
-                if !is_node_archive(services, node_id) {
-                    let error_message = format!(
-                        "Node '{node_id}' is not an archive node in '{network_id}' network."
-                    );
-                    return exit_with(error_message);
+        Command::Keys(KeysCommand::Export(cmd)) => {
+            let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+            println!(
+                "{}",
+                network.export_key(cmd.node_id.as_deref(), cmd.format)?
+            );
+            Ok(())
+        }
+
+        Command::Quickstart(cmd) => {
+            let network = Network::with_directory_manager(directory_manager, cmd.network_id());
+            if network.exists() && !cmd.yes {
+                let plan = network.deletion_plan(false, false)?;
+                eprintln!(
+                    "Network '{}' already exists, overwriting it destroys:\n{}",
+                    plan.network_id,
+                    plan.describe()
+                );
+                if !utils::confirm("Overwrite it?")? {
+                    return fail(format!(
+                        "Network '{}' already exists, not overwriting it (pass --yes to confirm).",
+                        plan.network_id
+                    ));
                 }
+            }
+            let quickstart = network.quickstart(Duration::from_secs(cmd.wait_timeout))?;
+            println!("{quickstart}");
+            Ok(())
+        }
 
-                if let Err(e) = genesis_ledger::set_slot_since_genesis(&network_path, start_slot) {
-                    let error_message = format!(
-                        "Failed to set slot since genesis to '{start_slot}' for node '{node_id}' on network '{network_id}': {e}"
-                    );
-                    return exit_with(error_message);
+        Command::Record(cmd) => {
+            if matches!(cmd.args[0].as_str(), "record" | "replay") {
+                return fail(format!("Cannot record a '{}' command.", cmd.args[0]));
+            }
+            match session::record(&cmd.out, &cmd.args) {
+                // the command's own output, its exit code becoming minimina's
+                Ok(command) => {
+                    print!("{}", command.stdout);
+                    match command.exit_code {
+                        0 => Ok(()),
+                        code => Err(MiniminaError::Exited(code)),
+                    }
                 }
+                Err(e) => fail(format!(
+                    "Failed to record command in '{}': {e}",
+                    cmd.out.display()
+                )),
+            }
+        }
 
-                let archive_service_id = format!("{node_id}-service");
-                match docker.compose_run_replayer(&archive_service_id, network_id) {
-                    Ok(output) => {
-                        if output.status.success() {
-                            info!("Successfully ran replayer for node '{node_id}' on network '{network_id}' \
-                                    and start_slot_since_genesis '{start_slot}'");
-                            if cmd.node_args.raw_output {
-                                println!("{}", String::from_utf8_lossy(&output.stdout));
-                            } else {
-                                println!(
-                                    "{}",
-                                    output::node::ReplayerLogs {
-                                        logs: String::from_utf8_lossy(&output.stdout).into(),
-                                        network_id: network_id.into(),
-                                        node_id: node_id.into(),
-                                    }
-                                )
-                            }
-                        } else {
-                            let error_message = format!(
-                                "Failed to run replayer for node '{node_id}' on network '{network_id}' \
-                                  and start_slot_since_genesis '{start_slot}': {}",
-                                String::from_utf8_lossy(&output.stderr)
-                            );
-                            return exit_with(error_message);
+        Command::Replay(cmd) => {
+            let replay = session::Session::load(&cmd.session).and_then(|recorded| {
+                session::replay(&recorded, cmd.network_id.as_deref(), cmd.keep_timing)
+            });
+            match replay {
+                Ok(steps) => {
+                    println!(
+                        "{}",
+                        output::session::Replay {
+                            session: cmd.session.display().to_string(),
+                            network_id: cmd.network_id,
+                            diverged: steps.iter().any(|step| step.diverged()),
+                            steps,
                         }
-                    }
-                    Err(e) => {
-                        return exit_with(format!(
-                            "Error while running replayer for node '{node_id}' on network '{network_id}' \
-                              and start_slot_since_genesis '{start_slot}': {e}"
-                        ));
-                    }
+                    );
+                    Ok(())
                 }
+                Err(e) => fail(format!(
+                    "Failed to replay session '{}': {e}",
+                    cmd.session.display()
+                )),
+            }
+        }
 
+        Command::Node(node_cmd) => match node_cmd {
+            NodeCommand::Start(cmd) => {
+                let node =
+                    Network::with_directory_manager(directory_manager, cmd.node_args.network_id())
+                        .node(cmd.node_args.node_id());
+                let started = node.start(&api::StartNodeOptions::from(&cmd))?;
+                if cmd.node_args.raw_output {
+                    println!(
+                        "Node '{}' on network '{}' has been started.",
+                        started.node_id, started.network_id
+                    );
+                } else {
+                    println!("{started}");
+                }
                 Ok(())
             }
-        },
-    }
-}
 
-fn create_network(
-    docker: &DockerManager,
-    directory_manager: &DirectoryManager,
-    network_id: &str,
-    services: &[ServiceConfig],
-) -> Result<()> {
-    match docker.compose_create(None) {
-        Ok(output) => {
-            if !output.status.success() {
-                let error_message = format!(
-                    "Failed to create network '{network_id}' with 'docker compose create': {}",
-                    String::from_utf8_lossy(&output.stderr)
-                );
-                return exit_with(error_message);
+            NodeCommand::Stop(cmd) => {
+                let node =
+                    Network::with_directory_manager(directory_manager, cmd.node_args.network_id())
+                        .node(cmd.node_args.node_id());
+                let stopped = node.stop(cmd.flush)?;
+                if cmd.node_args.raw_output {
+                    println!(
+                        "Node '{}' on network '{}' has been stopped.",
+                        stopped.node_id, stopped.network_id
+                    );
+                } else {
+                    println!("{stopped}");
+                }
+                Ok(())
             }
-            info!("Successfully created docker-compose for network '{network_id}'!");
 
-            // if we have archive node we need to:
-            //  - create input file for replayer (for run-replayer command)
-            //  - create database and apply schema scripts
-            if let Some(archive_node) = ServiceConfig::get_archive_node(services) {
-                // generate input file for mina-replayer
-                default::LedgerGenerator::generate_replayer_input(
-                    &directory_manager.network_path(network_id),
-                )?;
-
-                // create archive database and apply schema scripts
-                // start postgres container
-                let postgres_name = format!("postgres-{network_id}");
-                let error_message =
-                    format!("Failed to start postgres container in network '{network_id}'.");
+            NodeCommand::Restart(cmd) => {
+                let node =
+                    Network::with_directory_manager(directory_manager, cmd.node_args.network_id())
+                        .node(cmd.node_args.node_id());
+                let restarted = node.restart(&api::RestartNodeOptions::from(&cmd))?;
+                if cmd.node_args.raw_output {
+                    println!(
+                        "Node '{}' on network '{}' has been restarted.",
+                        restarted.node_id, restarted.network_id
+                    );
+                } else {
+                    println!("{restarted}");
+                }
+                Ok(())
+            }
 
-                match docker.compose_start(vec![&postgres_name]) {
-                    Ok(out) => {
-                        if out.status.success() {
-                            info!("Successfully started postgres container in network '{network_id}'!");
+            NodeCommand::Logs(args) => {
+                let cmd = args.node_args;
+                let node = Network::with_directory_manager(directory_manager, cmd.network_id())
+                    .node(cmd.node_id());
+                if args.internal {
+                    let logs = node.internal_logs()?;
+                    if cmd.raw_output {
+                        println!("{}", logs.logs_file);
+                    } else {
+                        println!("{logs}");
+                    }
+                    return Ok(());
+                }
+                if args.json_lines || args.follow || !args.filters.is_empty() {
+                    return node.log_records(&args.filters, args.follow, |record| {
+                        if args.json_lines {
+                            println!("{}", serde_json::to_string(record)?);
                         } else {
-                            return exit_with(format!(
-                                "{}: {}",
-                                error_message,
-                                String::from_utf8_lossy(&out.stderr)
-                            ));
+                            println!("{}", record.line);
                         }
-                    }
-                    Err(e) => return exit_with(format!("{error_message}: {e}")),
-                };
-
-                // make sure postgres is running
-                container_is_running(docker, &postgres_name)?;
-
-                // create database
-                let cmd = ["createdb", "-U", "postgres", "archive"];
-                docker.exec(&postgres_name, &cmd)?;
+                        Ok(())
+                    });
+                }
+                let logs = node.logs()?;
+                if cmd.raw_output {
+                    println!("{}", logs.logs);
+                } else {
+                    println!("{logs}");
+                }
+                Ok(())
+            }
 
-                // apply schema scripts
-                let scripts = archive_node.archive_schema_files.as_ref().unwrap();
-                apply_schema_scripts(
-                    docker.clone(),
-                    &postgres_name,
-                    scripts,
-                    &directory_manager.network_path(network_id),
-                )?;
+            NodeCommand::DumpArchiveData(args) => {
+                let cmd = &args.node_args;
+                let node = Network::with_directory_manager(directory_manager, cmd.network_id())
+                    .node(cmd.node_id());
+                if let (Some(interval), Some(output_dir)) = (args.interval, &args.output_dir) {
+                    let interval = match interval.to_std() {
+                        Ok(interval) if !interval.is_zero() => interval,
+                        _ => return fail("--interval must be positive.".to_string()),
+                    };
+                    let retain = args.retain.map(|retain| retain as usize);
+                    return node.dump_archive_data_every(output_dir, interval, retain, |dump| {
+                        println!("{dump}");
+                        Ok(())
+                    });
+                }
 
-                // stop postgres
-                docker.compose_stop(vec![&postgres_name])?;
+                if let Some(path) = &args.output {
+                    println!("{}", node.dump_archive_data_to(path)?);
+                    return Ok(());
+                }
+                let dump = node.dump_archive_data()?;
+                if cmd.raw_output {
+                    println!("{}", dump.data);
+                } else {
+                    println!("{dump}");
+                }
+                Ok(())
             }
 
-            // generate network.json and services.json
-            if let Err(e) = directory_manager.save_network_info(network_id, services) {
-                error!("Error generating network.json: {e}")
+            NodeCommand::RestoreArchiveData(args) => {
+                let cmd = args.node_args;
+                let node = Network::with_directory_manager(directory_manager, cmd.network_id())
+                    .node(cmd.node_id());
+                println!("{}", node.restore_archive_data(&args.input)?);
+                Ok(())
             }
 
-            if let Err(e) = directory_manager.save_services_info(network_id, services) {
-                error!("Error generating services.json: {e}")
+            NodeCommand::DumpPrecomputedBlocks(cmd) => {
+                let node = Network::with_directory_manager(directory_manager, cmd.network_id())
+                    .node(cmd.node_id());
+                let blocks = node.dump_precomputed_blocks()?;
+                if cmd.raw_output {
+                    println!("{}", blocks.blocks);
+                } else {
+                    println!("{blocks}");
+                }
+                Ok(())
             }
 
-            println!("{}", output::generate_network_info(services, network_id));
-            Ok(())
-        }
-        Err(e) => {
-            let error_message = format!(
-                "Failed to register network '{network_id}' with 'docker compose create': {e}"
-            );
-            exit_with(error_message)
-        }
-    }
-}
-
-fn container_is_running(docker: &DockerManager, container_name: &str) -> Result<()> {
-    let mut container_running = false;
-    let mut retries = 0;
-
-    while !container_running && retries < TIMEOUT_IN_SECS {
-        let containers = docker.compose_ps(None)?;
-        let container = docker.filter_container_by_name(containers, container_name);
-
-        if let Some(container) = container {
-            if container.state == ContainerState::Running {
-                container_running = true;
+            NodeCommand::CollectCrash(cmd) => {
+                let node = Network::with_directory_manager(directory_manager, cmd.network_id())
+                    .node(cmd.node_id());
+                println!("{}", node.collect_crash()?);
+                Ok(())
             }
-        }
-
-        retries += 1;
-        std::thread::sleep(std::time::Duration::from_secs(1));
-    }
-
-    if !container_running {
-        return exit_with(format!(
-            "Failed to start container '{container_name}' within {TIMEOUT_IN_SECS}s",
-        ));
-    }
-
-    Ok(())
-}
-
-#[allow(dead_code)]
-fn wait_for_daemon(
-    docker: &DockerManager,
-    node_id: &str,
-    network_id: &str,
-    client_port: u16,
-) -> Result<()> {
-    let mut retries = 0;
-    let mut daemon_running = false;
-    info!("Waiting for daemon to start for node '{node_id}' on network '{network_id}'...");
-    while !daemon_running && retries < TIMEOUT_IN_SECS {
-        let out = docker.compose_client_status(node_id, network_id, client_port)?;
-        if out.status.success() {
-            daemon_running = true;
-        } else {
-            retries += 1;
-            std::thread::sleep(std::time::Duration::from_secs(1));
-        }
-    }
-    if !daemon_running {
-        return exit_with(format!(
-            "Failed to start daemon for node '{node_id}' on network '{network_id}' within {TIMEOUT_IN_SECS}s",
-        ));
-    }
-    Ok(())
-}
-
-/// Applies provided schema `scripts` to the postgres db, `postgres_name`
-fn apply_schema_scripts(
-    docker: DockerManager,
-    postgres_name: &str,
-    scripts: &Vec<String>,
-    network_path: &Path,
-) -> Result<()> {
-    // copy scripts first
-    for script in scripts {
-        let file_path = fetch_schema(script, network_path.to_path_buf()).unwrap();
-        let file_name = file_path.file_name().unwrap().to_str().unwrap();
-        let docker_file_path = Path::new("/tmp").join(file_path.file_name().unwrap());
-
-        info!("Copying schema script: {}", file_name);
-        docker.cp(postgres_name, &file_path, &docker_file_path)?;
-    }
-
-    // then apply scripts 1 by 1
-    for script in scripts {
-        let file_path = fetch_schema(script, network_path.to_path_buf()).unwrap();
-        let file_name = file_path.file_name().unwrap().to_str().unwrap();
-        let docker_file_path = Path::new("/tmp").join(file_path.file_name().unwrap());
-        let cmd = [
-            "psql",
-            "-U",
-            "postgres",
-            "-d",
-            "archive",
-            "-f",
-            docker_file_path.to_str().unwrap(),
-        ];
-
-        info!("Applying schema script: {}", file_name);
-        docker.exec(postgres_name, &cmd)?;
-    }
-
-    Ok(())
-}
-
-/// Generates a genesis ledger for the default network:
-/// 1 seed, 2 bps, and a snark coordinator with one woker
-fn generate_default_genesis_ledger(
-    bp_keys_opt: &mut Option<HashMap<String, NodeKey>>,
-    libp2p_keys_opt: &mut Option<HashMap<String, NodeKey>>,
-    network_path: &Path,
-    docker_image: &str,
-) -> Result<()> {
-    info!("Genesis ledger not provided. Generating default genesis ledger.");
-
-    // set default services to generate keys for
-    let seeds = vec!["mina-seed-1"];
-    let block_producers = vec!["mina-bp-1", "mina-bp-2"];
-    let snark_coordinators = vec!["mina-snark-coordinator"];
-    let snark_workers = vec!["mina-snark-worker-1"];
-    let archive = vec!["mina-archive"];
-    let all_services = [
-        seeds,
-        block_producers,
-        snark_coordinators,
-        snark_workers,
-        archive,
-    ]
-    .concat();
-
-    // generate key-pairs for default services
-    let keys_manager = KeysManager::new(network_path, docker_image);
-    *bp_keys_opt = Some(
-        keys_manager
-            .generate_bp_key_pairs(&all_services)
-            .expect("Failed to generate key pairs for mina services."),
-    );
-    *libp2p_keys_opt = Some(
-        keys_manager
-            .generate_libp2p_key_pairs(&all_services)
-            .expect("Failed to generate libp2p key pairs for mina services."),
-    );
-
-    // generate default genesis ledger
-    if let Err(e) = default::LedgerGenerator::generate(network_path, bp_keys_opt.as_ref().unwrap())
-    {
-        error!("Error generating default ledger: {e}");
-    }
-
-    Ok(())
-}
-
-/// Generates a topology file for the default network:
-/// 1 seed, 2 bps, and a snark coordinator with one woker
-fn generate_default_topology(
-    bp_keys: &HashMap<String, NodeKey>,
-    libp2p_keys: &HashMap<String, NodeKey>,
-    docker_image: &str,
-    docker_image_archive: &str,
-    network_id: &str,
-) -> Vec<service::ServiceConfig> {
-    let seed_name = "mina-seed-1";
-    let libp2p_peerid = libp2p_keys[seed_name].key_string.split(',').last().unwrap();
-    let peer = ServiceConfig::generate_peer(
-        seed_name,
-        network_id,
-        libp2p_peerid,
-        3102, //external port on my mina_seed_1 will be 3102
-    );
-    let seed = ServiceConfig {
-        service_type: ServiceType::Seed,
-        service_name: seed_name.to_string(),
-        docker_image: Some(docker_image.into()),
-        client_port: Some(3100),
-        libp2p_keypair: Some(libp2p_keys[seed_name].key_string.clone()),
-        libp2p_peerid: Some(libp2p_peerid.to_string()),
-        ..Default::default()
-    };
-
-    let bp_1_name = "mina-bp-1";
-    let bp_1 = ServiceConfig {
-        service_type: ServiceType::BlockProducer,
-        service_name: bp_1_name.to_string(),
-        docker_image: Some(docker_image.into()),
-        client_port: Some(4000),
-        public_key: Some(bp_keys[bp_1_name].key_string.clone()),
-        public_key_path: Some(bp_keys[bp_1_name].key_path_docker.clone()),
-        libp2p_keypair: Some(libp2p_keys[bp_1_name].key_string.clone()),
-        peers: Some(vec![peer.clone()]),
-        ..Default::default()
-    };
-
-    let bp_2_name = "mina-bp-2";
-    let bp_2 = ServiceConfig {
-        service_type: ServiceType::BlockProducer,
-        service_name: bp_2_name.to_string(),
-        docker_image: Some(docker_image.into()),
-        client_port: Some(4005),
-        public_key: Some(bp_keys[bp_2_name].key_string.clone()),
-        public_key_path: Some(bp_keys[bp_2_name].key_path_docker.clone()),
-        libp2p_keypair: Some(libp2p_keys[bp_2_name].key_string.clone()),
-        peers: Some(vec![peer.clone()]),
-        ..Default::default()
-    };
-
-    let snark_coordinator_name = "mina-snark-coordinator";
-    let snark_coordinator = ServiceConfig {
-        service_type: ServiceType::SnarkCoordinator,
-        service_name: snark_coordinator_name.to_string(),
-        docker_image: Some(docker_image.into()),
-        client_port: Some(7000),
-        public_key: Some(bp_keys[snark_coordinator_name].key_string.clone()),
-        libp2p_keypair: Some(libp2p_keys[snark_coordinator_name].key_string.clone()),
-        peers: Some(vec![peer.clone()]),
-        snark_coordinator_fees: Some("0.001".into()),
-        worker_nodes: Some(1),
-        ..Default::default()
-    };
-
-    let snark_worker_1_name = "mina-snark-worker-1";
-    let snark_worker_1 = ServiceConfig {
-        service_type: ServiceType::SnarkWorker,
-        service_name: snark_worker_1_name.to_string(),
-        docker_image: Some(docker_image.into()),
-        snark_coordinator_port: Some(7000),
-        snark_worker_proof_level: Some("full".into()),
-        snark_coordinator_host: Some(snark_coordinator.service_name.clone()),
-        ..Default::default()
-    };
 
-    let archive_node_name = "mina-archive";
-    let archive_node = ServiceConfig {
-        service_type: ServiceType::ArchiveNode,
-        service_name: archive_node_name.to_string(),
-        docker_image: Some(docker_image.into()),
-        client_port: Some(5005),
-        public_key: Some(bp_keys[archive_node_name].key_string.clone()),
-        public_key_path: Some(bp_keys[archive_node_name].key_path_docker.clone()),
-        libp2p_keypair: Some(libp2p_keys[archive_node_name].key_string.clone()),
-        peers: Some(vec![peer]),
-        archive_docker_image: Some(docker_image_archive.into()),
-        archive_schema_files: Some(vec![
-            format!("https://raw.githubusercontent.com/MinaProtocol/mina/{IMAGE_COMMIT_HASH}/src/app/archive/zkapp_tables.sql"),
-            format!("https://raw.githubusercontent.com/MinaProtocol/mina/{IMAGE_COMMIT_HASH}/src/app/archive/create_schema.sql"),
-        ]),
-        archive_port: Some(3086),
-        ..Default::default()
-    };
-    vec![
-        seed,
-        bp_1,
-        bp_2,
-        snark_coordinator,
-        snark_worker_1,
-        archive_node,
-    ]
-}
-
-/// If the network exists, its directory is deleted, corresponding docker
-/// images are removed, and it is created anew.
-/// If the network doesn't exist, the directory structure is created.
-fn check_setup_network(
-    docker: &DockerManager,
-    directory_manager: &DirectoryManager,
-    network_id: &str,
-) -> Result<()> {
-    if directory_manager.network_path_exists(network_id) {
-        warn!("Network '{network_id}' already exists. Overwriting!");
-        docker.compose_down(None, false, false)?;
-        directory_manager.delete_network_directory(network_id)?;
-    }
-
-    // create directory structure for network
-    info!("Creating network '{network_id}'.");
-    if let Err(e) = directory_manager.generate_dir_structure(network_id) {
-        return exit_with(format!(
-            "Failed to set up network directory structure for '{network_id}' with error: {e}"
-        ));
-    }
-
-    Ok(())
-}
-
-/// Check that the network exists and overwrites genesis ledger if needed
-fn check_network_exists(network_id: &str) -> Result<()> {
-    let directory_manager = DirectoryManager::new();
-    if directory_manager.network_path_exists(network_id) {
-        Ok(())
-    } else {
-        let error_message = format!(
-            "Network directory '{}' does not exist, therefore network '{network_id}' does not exist too.",
-            directory_manager.network_path(network_id).display()
-        );
-        exit_with(error_message)
-    }
-}
-
-/// Handles `network_id`'s genesis ledger
-///
-/// If no genesis ledger is provided, a default ledger will be generated
-fn handle_genesis_ledger(
-    cmd: &cli::CreateNetworkArgs,
-    directory_manager: &DirectoryManager,
-    network_id: &str,
-    bp_keys_opt: &mut Option<HashMap<String, NodeKey>>,
-    libp2p_keys_opt: &mut Option<HashMap<String, NodeKey>>,
-) -> Result<()> {
-    let network_path = directory_manager.network_path(network_id);
-
-    match &cmd.genesis_ledger {
-        Some(genesis_ledger_path) => {
-            if cmd.topology.is_none() {
-                directory_manager.delete_network_directory(network_id)?;
-                return exit_with(
-                    "Must provide a topology file with a genesis ledger, keys will be incompatible otherwise.".to_string(),
-                );
+            NodeCommand::CreateAccount(cmd) => {
+                let node =
+                    Network::with_directory_manager(directory_manager, cmd.node_args.network_id())
+                        .node(cmd.node_args.node_id());
+                let account = node.create_account(&api::CreateAccountOptions::from(&cmd))?;
+                if cmd.node_args.raw_output {
+                    println!("{} {}", account.public_key, account.private_key);
+                } else {
+                    println!("{account}");
+                }
+                Ok(())
             }
 
-            info!(
-                "Copying genesis ledger from '{}' to network directory.",
-                genesis_ledger_path.display()
-            );
-
-            directory_manager.copy_genesis_ledger(network_id, genesis_ledger_path)?;
-            directory_manager.overwrite_genesis_timestamp(network_id, genesis_ledger_path)
-        }
-        None => generate_default_genesis_ledger(
-            bp_keys_opt,
-            libp2p_keys_opt,
-            &network_path,
-            DEFAULT_DAEMON_DOCKER_IMAGE,
-        ),
-    }
-}
+            NodeCommand::Delegate(cmd) => {
+                let node =
+                    Network::with_directory_manager(directory_manager, cmd.node_args.network_id())
+                        .node(cmd.node_args.node_id());
+                let delegation = node.delegate(&api::DelegateOptions::from(&cmd))?;
+                if cmd.node_args.raw_output {
+                    println!("{}", delegation.delegation_hash);
+                } else {
+                    println!("{delegation}");
+                }
+                Ok(())
+            }
 
-/// Creates the list of docker service configs from the topology file at `topology_path`
-/// using the seed nodes as the list of network peers (at least 1 seed node must be declared)
-///
-/// Logs and error and exits with code 1 if the topology file can't be parsed
-fn create_services(
-    directory_manager: &DirectoryManager,
-    topology_path: &Path,
-    network_id: &str,
-) -> Result<Vec<ServiceConfig>> {
-    match topology::Topology::new(topology_path) {
-        Ok(topology) => {
-            let peer_list_file = directory_manager.peer_list_file(network_id);
-            let services = topology.services(&peer_list_file);
-            let peers: Vec<&ServiceConfig> = ServiceConfig::get_seeds(&services);
-            directory_manager.create_peer_list_file(network_id, &peers)?;
+            NodeCommand::Query(cmd) => {
+                let node =
+                    Network::with_directory_manager(directory_manager, cmd.node_args.network_id())
+                        .node(cmd.node_args.node_id());
+                let query = node.query(&cmd.query)?;
+                if cmd.node_args.raw_output {
+                    println!("{}", query.response);
+                } else {
+                    println!("{query}");
+                }
+                Ok(())
+            }
 
-            if let Some(uptime_service_backend) =
-                ServiceConfig::get_uptime_service_backend(&services)
-            {
-                match directory_manager
-                    .copy_uptime_service_config(network_id, uptime_service_backend)
-                {
-                    Ok(_) => info!("Successfully copied uptime service config."),
-                    Err(e) => {
-                        let error_message = format!("Failed to copy uptime service config: {e}");
-                        exit_with(error_message)?;
+            NodeCommand::ImportAccounts(cmd) => {
+                let node = Network::with_directory_manager(directory_manager, cmd.network_id())
+                    .node(cmd.node_id());
+                let accounts = node.import_accounts()?;
+                if cmd.raw_output {
+                    for file in accounts.imported {
+                        println!("{file}");
                     }
+                } else {
+                    println!("{accounts}");
                 }
+                Ok(())
             }
 
-            if peers.is_empty() {
-                error!("There are no seed nodes declared in this network. You must include seed nodes.");
-                exit(1);
+            NodeCommand::Ps(cmd) => {
+                let node = Network::with_directory_manager(directory_manager, cmd.network_id())
+                    .node(cmd.node_id());
+                let container = node.ps()?;
+                if cmd.raw_output {
+                    println!("{}", container.state);
+                } else {
+                    println!("{container}");
+                }
+                Ok(())
             }
 
-            Ok(services)
-        }
-        Err(err) => {
-            error!(
-                "Error occured while parsing the topology file:\n\
-                 path: {}\n\
-                 error: {err}",
-                topology_path.display()
-            );
-            exit(1)
-        }
-    }
-}
-
-/// Creates service configs for the nodes specified in the topology file of the given `cmd`
-fn handle_topology(
-    cmd: &cli::CreateNetworkArgs,
-    directory_manager: &DirectoryManager,
-    network_id: &str,
-    bp_keys: Option<HashMap<String, NodeKey>>,
-    libp2p_keys: Option<HashMap<String, NodeKey>>,
-) -> Result<Vec<ServiceConfig>> {
-    match &cmd.topology {
-        Some(topology_path) => {
-            if cmd.genesis_ledger.is_none() {
-                error!(
-                    "Must provide a genesis ledger with a topology file, \
-                     keys will be incompatible otherwise."
-                );
-
-                directory_manager.delete_network_directory(network_id)?;
-                exit(1);
+            NodeCommand::Exec(cmd) => {
+                let node =
+                    Network::with_directory_manager(directory_manager, cmd.node_args.network_id())
+                        .node(cmd.node_args.node_id());
+                print_exec(node.exec(&cmd.command), cmd.node_args.raw_output)
             }
 
-            info!(
-                "Generating docker-compose based on provided topology '{}'.",
-                topology_path.display()
-            );
-
-            let network_topology_path = directory_manager.topology_file_path(network_id);
-            std::fs::copy(topology_path, network_topology_path)?;
-            create_services(directory_manager, topology_path, network_id)
-        }
-        None => {
-            info!("Topology not provided. Generating docker-compose based on default topology.");
+            NodeCommand::Client(cmd) => {
+                let node =
+                    Network::with_directory_manager(directory_manager, cmd.node_args.network_id())
+                        .node(cmd.node_args.node_id());
+                print_exec(node.client(&cmd.args), cmd.node_args.raw_output)
+            }
 
-            if let (Some(bp_keys), Some(libp2p_keys)) = (&bp_keys.as_ref(), &libp2p_keys.as_ref()) {
-                Ok(generate_default_topology(
-                    bp_keys,
-                    libp2p_keys,
-                    DEFAULT_DAEMON_DOCKER_IMAGE,
-                    DEFAULT_ARCHIVE_DOCKER_IMAGE,
-                    network_id,
-                ))
-            } else {
-                let err = "Failed to generate docker-compose.yaml. Keys not generated.";
-                error!("{err}");
-                Err(Error::new(ErrorKind::InvalidData, err))
+            NodeCommand::RunReplayer(cmd) => {
+                let node =
+                    Network::with_directory_manager(directory_manager, cmd.node_args.network_id())
+                        .node(cmd.node_args.node_id());
+                let replayed = node.run_replayer(&api::ReplayerOptions::from(&cmd))?;
+                if cmd.node_args.raw_output {
+                    println!("{}", replayed.logs);
+                } else {
+                    println!("{replayed}");
+                }
+                Ok(())
             }
-        }
+        },
+    }
+}
+
+/// Prints the result of a command run in a node's container; raw output is the
+/// command's own output, its exit code becoming minimina's
+fn print_exec(exec: Result<output::node::Exec>, raw_output: bool) -> Result<()> {
+    let exec = exec?;
+    if !raw_output {
+        println!("{exec}");
+        return Ok(());
+    }
+    print!("{}", exec.stdout);
+    eprint!("{}", exec.stderr);
+    match exec.exit_code {
+        Some(0) => Ok(()),
+        code => Err(MiniminaError::Exited(code.unwrap_or(1))),
     }
 }
 
-fn check_compose_version() -> Result<()> {
-    let compose_version = DockerManager::compose_version();
-    match compose_version {
+fn check_compose_version(runtime: Runtime) -> Result<()> {
+    let engine = runtime.engine();
+    match engine.compose_version() {
         Some(version) => {
             if version.as_str() < LEAST_COMPOSE_VERSION {
-                error!(
+                return fail(format!(
                     "Docker compose version '{version}' is less than \
                         the least supported version '{LEAST_COMPOSE_VERSION}'."
-                );
-
-                return Err(Error::new(
-                    ErrorKind::InvalidInput,
-                    "docker compose needs to be updated",
                 ));
             }
 
             Ok(())
         }
-        None => {
-            error!("It seems that docker not installed! Please install docker and try again.");
-            Err(Error::new(ErrorKind::NotFound, "docker is missing"))
-        }
-    }
-}
-
-fn exit_with(error_message: String) -> Result<()> {
-    error!("{error_message}");
-    println!("{}", output::Error { error_message });
-    exit(1);
-}
-
-fn handle_stop_error(node_id: &str, error: impl ToString) -> Result<()> {
-    let error_message = format!("Failed to stop node '{node_id}': {}", error.to_string());
-    exit_with(error_message)
-}
-
-fn handle_start_error(node_id: &str, error: impl ToString) -> Result<()> {
-    let error_message = format!("Failed to start node '{node_id}': {}", error.to_string());
-    exit_with(error_message)
-}
-
-fn is_node_uptime_service(services: Vec<ServiceConfig>, node_id: &str) -> bool {
-    if let Some(uptime) = ServiceConfig::get_uptime_service_backend(&services) {
-        if uptime.service_name == node_id {
-            return true;
-        }
-    }
-    false
-}
-
-fn is_node_archive(services: Vec<ServiceConfig>, node_id: &str) -> bool {
-    if let Some(archive) = ServiceConfig::get_archive_node(&services) {
-        if archive.service_name == node_id {
-            return true;
+        None if engine.supports_compose() => {
+            info!(
+                "Could not determine the compose version of '{}', assuming it is supported.",
+                engine.binary()
+            );
+            Ok(())
         }
+        None => fail(format!(
+            "'{}' is not installed or does not support the 'compose' subcommand! \
+                Please install {runtime} (or set MINIMINA_DOCKER) and try again.",
+            engine.binary()
+        )),
     }
-    false
 }
 
-fn import_all_accounts(
-    docker: &DockerManager,
-    directory_manager: &DirectoryManager,
-    node_id: &str,
-    network_id: &str,
-) -> Result<()> {
-    let account_files = directory_manager.get_network_keypair_files(network_id)?;
-    for account_file in account_files {
-        let out = docker.compose_import_account(node_id, network_id, &account_file);
-        match out {
-            Ok(output) => {
-                if output.status.success() {
-                    info!(
-                        "Successfully imported account from file '{account_file}' \
-                        for node '{node_id}' on network '{network_id}'",
-                    );
-                } else {
-                    let error_message = format!(
-                        "Failed to import account from file '{account_file}' \
-                        for node '{node_id}' on network '{network_id}': {}",
-                        String::from_utf8_lossy(&output.stderr),
-                    );
-                    return exit_with(error_message);
-                }
-            }
-            Err(e) => {
-                let error_message = format!(
-                    "Failed to import account from file '{account_file}' \
-                    for node '{node_id}' on network '{network_id}': {e}",
-                );
-                return exit_with(error_message);
-            }
-        }
-    }
-    Ok(())
+fn fail<T>(error_message: String) -> Result<T> {
+    Err(MiniminaError::Command(error_message))
 }
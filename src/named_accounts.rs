@@ -0,0 +1,279 @@
+//! # Named Accounts Module
+//!
+//! User-level registry of named key pairs, shared by every network and kept in
+//! `~/.minimina/accounts.toml`:
+//!
+//! ```toml
+//! [alice]
+//! public_key = "B62q..."
+//! private_key = "EK..."
+//! ```
+//!
+//! `network create --fund alice=1000` adds the account `alice` with 1000 MINA to the
+//! genesis ledger of the new network, generating its key pair and adding it to the
+//! registry the first time the name is used. Its address is then the same on every
+//! network, so test suites can hardcode it.
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{ErrorKind, Result, Write},
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    genesis_ledger::{add_ledger_accounts, LedgerAccount},
+    keys::{generate_private_key, public_key_of},
+    utils::{parse_account_name, parse_mina_amount},
+};
+
+const ACCOUNTS_FILE: &str = "accounts.toml";
+
+/// Key pair of a named account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamedAccount {
+    pub public_key: String,
+    /// Base58 private key, recorded in the genesis ledgers the account is funded in
+    pub private_key: String,
+}
+
+/// Named accounts by name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AccountRegistry {
+    pub accounts: BTreeMap<String, NamedAccount>,
+}
+
+impl AccountRegistry {
+    /// Registry file in the minimina home `base_path`
+    pub fn path(base_path: &Path) -> PathBuf {
+        base_path.join(ACCOUNTS_FILE)
+    }
+
+    /// Registry in `base_path`, empty if the file doesn't exist yet
+    pub fn load(base_path: &Path) -> Result<Self> {
+        match fs::read_to_string(Self::path(base_path)) {
+            Ok(contents) => toml::from_str(&contents).map_err(|e| {
+                std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid {ACCOUNTS_FILE}: {e}"),
+                )
+            }),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes the registry to `base_path`, readable by the owner only as it holds
+    /// private keys
+    pub fn save(&self, base_path: &Path) -> Result<()> {
+        let contents = toml::to_string(self).map_err(std::io::Error::other)?;
+        let path = Self::path(base_path);
+        fs::create_dir_all(base_path)?;
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&path)?;
+        // `mode` only applies to a new file, an existing one may be readable by others
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+        file.write_all(contents.as_bytes())
+    }
+
+    /// Account `name`, generating a new key pair for it if it isn't registered yet
+    pub fn get_or_generate(&mut self, name: &str) -> Result<NamedAccount> {
+        if let Some(account) = self.accounts.get(name) {
+            return Ok(account.clone());
+        }
+        info!("Generating a key pair for the new named account '{name}'");
+        let private_key = generate_private_key();
+        let account = NamedAccount {
+            public_key: public_key_of(&private_key)?,
+            private_key,
+        };
+        self.accounts.insert(name.to_string(), account.clone());
+        Ok(account)
+    }
+}
+
+/// Named account funded in the genesis ledger of a new network, see `network create --fund`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Funding {
+    pub name: String,
+    /// Balance in nanomina
+    pub amount: u64,
+}
+
+impl Funding {
+    /// Balance in MINA with nine decimals, as written in genesis ledgers
+    fn balance(&self) -> String {
+        format!(
+            "{}.{:09}",
+            self.amount / 1_000_000_000,
+            self.amount % 1_000_000_000
+        )
+    }
+}
+
+/// Parses a `NAME=AMOUNT` funding, e.g. `alice=1000`, see `parse_account_name`
+pub fn parse_funding(funding: &str) -> std::result::Result<Funding, String> {
+    let (name, amount) = funding
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid funding '{funding}', expected NAME=AMOUNT"))?;
+    Ok(Funding {
+        name: parse_account_name(name)?,
+        amount: parse_mina_amount(amount)?,
+    })
+}
+
+/// Fails if `fundings` fund the same named account more than once
+pub fn check_fundings(fundings: &[Funding]) -> Result<()> {
+    let mut names = BTreeSet::new();
+    match fundings.iter().find(|f| !names.insert(f.name.as_str())) {
+        Some(duplicate) => Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Named account '{}' is funded more than once",
+                duplicate.name
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Adds the named accounts of `fundings` to the genesis ledger at `genesis_ledger`,
+/// registering those unknown to the registry in `base_path`, and returns their keys.
+/// New key pairs are saved to the registry before the ledger is written, so a name
+/// keeps its key even if the ledger can't take the accounts
+pub fn fund_named_accounts(
+    base_path: &Path,
+    genesis_ledger: &Path,
+    fundings: &[Funding],
+) -> Result<Vec<NamedAccount>> {
+    check_fundings(fundings)?;
+
+    let mut registry = AccountRegistry::load(base_path)?;
+    let known = registry.clone();
+    let funded = fundings
+        .iter()
+        .map(|funding| registry.get_or_generate(&funding.name))
+        .collect::<Result<Vec<_>>>()?;
+    if registry != known {
+        registry.save(base_path)?;
+    }
+
+    let ledger_accounts: Vec<LedgerAccount> = fundings
+        .iter()
+        .zip(&funded)
+        .map(|(funding, account)| LedgerAccount {
+            pk: account.public_key.clone(),
+            balance: funding.balance(),
+            delegate: None,
+            sk: Some(account.private_key.clone()),
+        })
+        .collect();
+    add_ledger_accounts(genesis_ledger, &ledger_accounts)?;
+    Ok(funded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genesis_ledger::ledger_accounts;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_parse_funding() {
+        assert_eq!(
+            parse_funding("alice=1000.5"),
+            Ok(Funding {
+                name: "alice".to_string(),
+                amount: 1_000_500_000_000,
+            })
+        );
+        assert!(parse_funding("alice").is_err());
+        assert!(parse_funding("=1000").is_err());
+        assert!(parse_funding("al ice=1000").is_err());
+        assert!(parse_funding("alice=lots").is_err());
+    }
+
+    #[test]
+    fn test_fund_named_accounts() {
+        let tempdir =
+            TempDir::new("test_fund_named_accounts").expect("Cannot create temporary directory");
+        let base_path = tempdir.path().join(".minimina");
+        let genesis_ledger = |network: &str| -> PathBuf {
+            let path = tempdir.path().join(format!("{network}.json"));
+            fs::write(&path, r#"{ "genesis": {}, "ledger": { "accounts": [] } }"#).unwrap();
+            path
+        };
+        let fundings = [
+            parse_funding("alice=1000").unwrap(),
+            parse_funding("faucet=0.5").unwrap(),
+        ];
+
+        let first = genesis_ledger("first");
+        let funded = fund_named_accounts(&base_path, &first, &fundings).unwrap();
+        let accounts = ledger_accounts(&first).unwrap();
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].pk, funded[0].public_key);
+        assert_eq!(accounts[0].balance, "1000.000000000");
+        assert_eq!(accounts[1].balance, "0.500000000");
+        assert_eq!(
+            fs::metadata(AccountRegistry::path(&base_path))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o600
+        );
+
+        // an existing registry readable by others is restricted when saved
+        let registry_path = AccountRegistry::path(&base_path);
+        fs::set_permissions(&registry_path, fs::Permissions::from_mode(0o644)).unwrap();
+        AccountRegistry::load(&base_path)
+            .unwrap()
+            .save(&base_path)
+            .unwrap();
+        assert_eq!(
+            fs::metadata(&registry_path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        // the same keys on the next network
+        let second = genesis_ledger("second");
+        let funded_again = fund_named_accounts(&base_path, &second, &fundings[..1]).unwrap();
+        assert_eq!(funded_again[0], funded[0]);
+        let registry = AccountRegistry::load(&base_path).unwrap();
+        assert_eq!(registry.accounts.len(), 2);
+        assert_eq!(registry.accounts["faucet"], funded[1]);
+
+        // a new key is registered even if the ledger rejects the accounts
+        let third = genesis_ledger("third");
+        let rejected = [
+            parse_funding("bob=1").unwrap(),
+            parse_funding("alice=1").unwrap(),
+        ];
+        fund_named_accounts(&base_path, &third, &rejected[1..]).unwrap();
+        assert!(fund_named_accounts(&base_path, &third, &rejected).is_err());
+        assert_eq!(ledger_accounts(&third).unwrap().len(), 1);
+        assert!(AccountRegistry::load(&base_path)
+            .unwrap()
+            .accounts
+            .contains_key("bob"));
+
+        let duplicated = [
+            parse_funding("carol=1").unwrap(),
+            parse_funding("carol=2").unwrap(),
+        ];
+        assert!(fund_named_accounts(&base_path, &genesis_ledger("fourth"), &duplicated).is_err());
+        assert!(!AccountRegistry::load(&base_path)
+            .unwrap()
+            .accounts
+            .contains_key("carol"));
+    }
+}
@@ -0,0 +1,140 @@
+//! # Net Report Module
+//!
+//! Measures the network traffic of a running network's containers by sampling
+//! `/proc/net/dev` inside each container's network namespace twice, `interval` apart,
+//! and reporting the received/transmitted bytes in between per node.
+
+use crate::{
+    docker::manager::{ContainerState, DockerManager},
+    output::network::{NetReport, NodeTraffic},
+};
+use log::warn;
+use std::{collections::HashMap, io::Result, time::Duration};
+
+/// Interface counters which are not network traffic
+const IGNORED_INTERFACES: &[&str] = &["lo"];
+
+/// Received and transmitted bytes of a container
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Counters {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+/// Samples the traffic of all running containers of the network over `interval`
+pub fn sample(docker: &DockerManager, network_id: &str, interval: Duration) -> Result<NetReport> {
+    let containers: Vec<String> = docker
+        .compose_ps(Some(ContainerState::Running))?
+        .into_iter()
+        .map(|container| container.name)
+        .collect();
+
+    let before = read_all(docker, &containers);
+    std::thread::sleep(interval);
+    let after = read_all(docker, &containers);
+
+    let seconds = interval.as_secs_f64().max(f64::EPSILON);
+    let mut nodes: Vec<NodeTraffic> = after
+        .into_iter()
+        .filter_map(|(container, after)| {
+            let before = before.get(&container)?;
+            // counters reset if the container restarted in between
+            let rx_bytes = after.rx_bytes.saturating_sub(before.rx_bytes);
+            let tx_bytes = after.tx_bytes.saturating_sub(before.tx_bytes);
+            let node_id = container
+                .strip_suffix(&format!("-{network_id}"))
+                .unwrap_or(&container)
+                .to_string();
+            Some(NodeTraffic {
+                node_id,
+                rx_bytes,
+                tx_bytes,
+                rx_bytes_per_sec: (rx_bytes as f64 / seconds) as u64,
+                tx_bytes_per_sec: (tx_bytes as f64 / seconds) as u64,
+            })
+        })
+        .collect();
+    nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+
+    Ok(NetReport {
+        network_id: network_id.to_string(),
+        interval_secs: interval.as_secs(),
+        total_rx_bytes: nodes.iter().map(|node| node.rx_bytes).sum(),
+        total_tx_bytes: nodes.iter().map(|node| node.tx_bytes).sum(),
+        nodes,
+    })
+}
+
+/// Reads the counters of each container, skipping the ones which cannot be read
+fn read_all(docker: &DockerManager, containers: &[String]) -> HashMap<String, Counters> {
+    containers
+        .iter()
+        .filter_map(|container| match read(docker, container) {
+            Ok(counters) => Some((container.clone(), counters)),
+            Err(e) => {
+                warn!("Failed to read network counters of '{container}': {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+fn read(docker: &DockerManager, container: &str) -> Result<Counters> {
+    let out = docker.exec(container, &["cat", "/proc/net/dev"])?;
+    if !out.status.success() {
+        return Err(std::io::Error::other(
+            String::from_utf8_lossy(&out.stderr).trim().to_string(),
+        ));
+    }
+    Ok(parse_proc_net_dev(&String::from_utf8_lossy(&out.stdout)))
+}
+
+/// Sums the received/transmitted bytes of all interfaces in `/proc/net/dev`
+/// except the ignored ones
+fn parse_proc_net_dev(contents: &str) -> Counters {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (interface, counters) = line.split_once(':')?;
+            if IGNORED_INTERFACES.contains(&interface.trim()) {
+                return None;
+            }
+            // rx bytes is the 1st counter, tx bytes the 9th
+            let counters: Vec<u64> = counters
+                .split_whitespace()
+                .map(|counter| counter.parse().ok())
+                .collect::<Option<_>>()?;
+            Some(Counters {
+                rx_bytes: *counters.first()?,
+                tx_bytes: *counters.get(8)?,
+            })
+        })
+        .fold(Counters::default(), |total, counters| Counters {
+            rx_bytes: total.rx_bytes + counters.rx_bytes,
+            tx_bytes: total.tx_bytes + counters.tx_bytes,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_net_dev() {
+        let contents = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:  123456     100    0    0    0     0          0         0   123456     100    0    0    0     0       0          0
+  eth0: 5000000    4000    0    0    0     0          0         0  2500000    3000    0    0    0     0       0          0
+  eth1:    1000      10    0    0    0     0          0         0      500       5    0    0    0     0       0          0
+";
+
+        assert_eq!(
+            parse_proc_net_dev(contents),
+            Counters {
+                rx_bytes: 5_001_000,
+                tx_bytes: 2_500_500,
+            }
+        );
+    }
+}
@@ -5,13 +5,15 @@
 //! It primarily focuses on operations related to networks and nodes:
 //!
 //! - `network`: Structures and implementations for serializing output related to various network operations like
-//!    creation, start, listing, stopping, and more.
+//!   creation, start, listing, stopping, and more.
 //! - `node`: Structures and implementations for serializing output concerning node information and various node-related actions.
 //! - `Error`: Represents an error structure to be serialized into JSON format with an accompanying error message.
 //!
 //! This module also offers utility functions such as `generate_network_info` and implements display
 //! formatting for a number of types to further facilitate serialization.
 
+use crate::docker::manager::ContainerInfo;
+use crate::docker::runtime::Runtime;
 use crate::service::{ServiceConfig, ServiceType};
 use std::collections::HashMap;
 
@@ -19,16 +21,181 @@ pub mod network {
     use serde::{Deserialize, Serialize};
 
     use crate::docker::manager::{ComposeInfo, ContainerInfo};
+    use crate::docker::runtime::Runtime;
 
     #[derive(Debug, Serialize, Deserialize, PartialEq)]
     pub struct Create {
         pub network_id: String,
         pub nodes: std::collections::HashMap<String, super::node::Info>,
+        /// Duration of each setup phase, only reported by `network create`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub timings: Option<Timings>,
+        /// Container runtime of the network, omitted for docker
+        #[serde(default, skip_serializing_if = "Runtime::is_default")]
+        pub runtime: Runtime,
+        /// Monitoring stack, only reported by `network create --with-monitoring`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub monitoring: Option<Monitoring>,
+    }
+
+    impl Create {
+        /// Merges the containers listed by `docker compose ps` and their IP addresses
+        /// into the nodes of `services`, pointing `graphql_uri` at the host port the
+        /// daemon's GraphQL port is actually published on
+        pub fn merge_containers(
+            &mut self,
+            services: &[crate::service::ServiceConfig],
+            containers: &[ContainerInfo],
+            ip_addresses: &std::collections::HashMap<String, Vec<String>>,
+        ) {
+            for (node_id, node) in self.nodes.iter_mut() {
+                let service_name = format!("{node_id}-{}", self.network_id);
+                let Some(container) = containers.iter().find(|c| c.service == service_name) else {
+                    continue;
+                };
+                if let Some(graphql_uri) = services
+                    .iter()
+                    .find(|service| &service.service_name == node_id)
+                    .and_then(|service| service.live_graphql_uri(container))
+                {
+                    node.graphql_uri = Some(graphql_uri);
+                }
+                node.container = Some(super::node::Container {
+                    name: container.name.clone(),
+                    state: container.state.clone(),
+                    ip_addresses: ip_addresses
+                        .get(&container.name)
+                        .cloned()
+                        .unwrap_or_default(),
+                    ports: container.publishers.clone().unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    pub struct Monitoring {
+        pub grafana_url: String,
+        pub prometheus_url: String,
+    }
+
+    /// `network info --live`: the network info with live statistics
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct LiveInfo {
+        #[serde(flatten)]
+        pub info: Create,
+        /// Best chain height of each daemon, `null` if it did not respond
+        pub heights: std::collections::HashMap<String, Option<u64>>,
+        /// Block statistics of each archive database which could be queried
+        pub archives: std::collections::HashMap<String, ArchiveStats>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct ArchiveStats {
+        /// Height of the highest block in the database, `null` if it is empty
+        pub max_block_height: Option<u64>,
+        pub canonical_blocks: u64,
+        pub pending_blocks: u64,
+    }
+
+    impl ArchiveStats {
+        /// Parses the unaligned `psql` output of the query
+        /// `max(height), canonical count, pending count`, e.g. `42,30,12`
+        pub fn parse(row: &str) -> Option<Self> {
+            let mut columns = row.trim().split(',');
+            let max_block_height = match columns.next()? {
+                "" => None,
+                height => Some(height.parse().ok()?),
+            };
+            let canonical_blocks = columns.next()?.parse().ok()?;
+            let pending_blocks = columns.next()?.parse().ok()?;
+            Some(ArchiveStats {
+                max_block_height,
+                canonical_blocks,
+                pending_blocks,
+            })
+        }
+    }
+
+    /// Milliseconds spent in each phase of `network create`
+    #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct Timings {
+        pub key_generation_ms: u64,
+        pub genesis_ledger_ms: u64,
+        pub compose_render_ms: u64,
+        pub image_pull_ms: u64,
+        pub container_create_ms: u64,
+        pub schema_apply_ms: u64,
+        pub total_ms: u64,
+    }
+
+    impl Timings {
+        /// Runs `phase` and adds its duration to `ms`
+        pub fn measure<T>(ms: &mut u64, phase: impl FnOnce() -> T) -> T {
+            let start = std::time::Instant::now();
+            let result = phase();
+            *ms += start.elapsed().as_millis() as u64;
+            result
+        }
+    }
+
+    /// `network info --graph`: the dependency graph of the network's services
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Graph {
+        pub network_id: String,
+        pub services: crate::service::graph::Graph,
     }
 
     #[derive(Debug, Serialize, PartialEq)]
     pub struct Start {
         pub network_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub genesis_staleness: Option<GenesisStaleness>,
+        /// Genesis timestamp a stale network was restarted with by
+        /// `network start --refresh-genesis`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub refreshed_genesis_timestamp: Option<String>,
+        /// Sync status of each node, only reported by `network start --wait`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub sync_status: Option<std::collections::BTreeMap<String, Option<String>>>,
+        /// Output of `docker compose start`, only reported by `network start --verbose`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub compose_output: Option<ComposeOutput>,
+    }
+
+    /// Exit status and output of a docker compose command
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct ComposeOutput {
+        pub status: String,
+        pub stdout: String,
+        pub stderr: String,
+    }
+
+    /// `quickstart`: the single daemon of the network and its funded account
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Quickstart {
+        pub network_id: String,
+        pub node_id: String,
+        pub graphql_uri: Option<String>,
+        pub account: FundedAccount,
+        /// Time from creating the network until its daemon reported `SYNCED`
+        pub duration_ms: u64,
+    }
+
+    /// Test account of the genesis ledger, with the private key to sign with
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct FundedAccount {
+        pub public_key: String,
+        pub private_key: String,
+        pub balance: String,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct GenesisStaleness {
+        pub genesis_timestamp: String,
+        pub stale_after_minutes: u64,
+        pub minutes_since_genesis: i64,
+        pub stale: bool,
     }
 
     #[derive(Debug, Serialize, PartialEq)]
@@ -37,7 +204,7 @@ pub mod network {
         pub config_dir: String,
     }
 
-    #[derive(Debug, Serialize, PartialEq)]
+    #[derive(Debug, Default, Serialize, PartialEq)]
     pub struct List {
         pub networks: Vec<ListInfo>,
     }
@@ -65,6 +232,8 @@ pub mod network {
     #[derive(Debug, Serialize, PartialEq)]
     pub struct Stop {
         pub network_id: String,
+        /// Containers which did not stop within the timeout and were killed
+        pub killed: Vec<String>,
     }
 
     #[derive(Debug, Serialize, PartialEq)]
@@ -127,12 +296,372 @@ pub mod network {
                 });
             });
         }
+
+        /// The network's status and a table of its services, with aligned columns
+        pub fn to_table(&self) -> String {
+            let header = ["SERVICE", "STATE", "STATUS", "IMAGE"].map(String::from);
+            let rows: Vec<[String; 4]> = self
+                .services
+                .iter()
+                .map(|service| {
+                    [
+                        service.id.clone(),
+                        service.state.to_string(),
+                        service.status.clone(),
+                        service.docker_image.clone(),
+                    ]
+                })
+                .collect();
+
+            let mut widths = header.clone().map(|column| column.len());
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.len());
+                }
+            }
+
+            let mut lines = vec![format!("Network '{}': {}", self.network_id, self.status)];
+            for row in std::iter::once(&header).chain(&rows) {
+                let cells: Vec<String> = row
+                    .iter()
+                    .zip(widths)
+                    .map(|(cell, width)| format!("{cell:width$}"))
+                    .collect();
+                lines.push(cells.join("  ").trim_end().to_string());
+            }
+            lines.join("\n")
+        }
     }
 
     #[derive(Debug, Serialize, PartialEq)]
     pub struct Delete {
         pub network_id: String,
     }
+
+    /// What deleting (or overwriting) a network destroys
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct DeletionPlan {
+        pub network_id: String,
+        pub directory: String,
+        pub containers: Vec<String>,
+        pub volumes: Vec<String>,
+        pub images: Vec<String>,
+    }
+
+    impl DeletionPlan {
+        /// Human readable list of everything the plan destroys
+        pub fn describe(&self) -> String {
+            let mut lines = vec![format!("directory: {}", self.directory)];
+            for (kind, names) in [
+                ("containers", &self.containers),
+                ("volumes", &self.volumes),
+                ("images", &self.images),
+            ] {
+                if !names.is_empty() {
+                    lines.push(format!("{kind}: {}", names.join(", ")));
+                }
+            }
+            lines.join("\n")
+        }
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Partition {
+        pub network_id: String,
+        /// Node ids of each partition
+        pub groups: Vec<Vec<String>>,
+        /// Docker network of each partition
+        pub networks: Vec<String>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct ArchiveFault {
+        pub network_id: String,
+        pub archive: String,
+        pub fault: crate::chaos::ArchiveFault,
+        pub fault_duration_ms: u64,
+        /// Height of the highest archived block before the fault
+        pub max_block_height_before: Option<u64>,
+        /// Height of the highest archived block at the last audit
+        pub max_block_height_after: Option<u64>,
+        /// Heights without a block at the last audit
+        pub missing_heights: Vec<u64>,
+        /// Whether the archive wrote new blocks and filled all gaps in time
+        pub caught_up: bool,
+        /// Time from lifting the fault until the archive caught up
+        pub catch_up_ms: Option<u64>,
+    }
+
+    /// `network nodes`: the nodes of a network from its `services.json`, with the
+    /// state of their containers
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    pub struct Nodes {
+        pub network_id: String,
+        pub nodes: Vec<super::node::Summary>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct LagReport {
+        pub network_id: String,
+        /// Best chain height among the nodes
+        pub best_height: Option<u64>,
+        pub max_lag: u64,
+        pub nodes: std::collections::BTreeMap<String, NodeLag>,
+        /// Nodes more than `max_lag` blocks behind the best height or not responding
+        pub behind: Vec<String>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct NodeLag {
+        pub height: Option<u64>,
+        /// Blocks behind the best height, `None` if the node did not respond
+        pub lag: Option<u64>,
+        /// Resource limits of the node's container
+        pub limits: Option<crate::service::ContainerLimits>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Heal {
+        pub network_id: String,
+        /// Node ids of the partitions that were healed
+        pub groups: Vec<Vec<String>>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Repair {
+        pub network_id: String,
+        /// Whether the compose file was rewritten from `services.json`
+        pub compose_file_rendered: bool,
+        /// Stale duplicate containers removed
+        pub removed_duplicates: Vec<String>,
+        /// Containers of services the network doesn't define removed
+        pub removed_unknown: Vec<String>,
+        /// Services whose missing container was created
+        pub created: Vec<String>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Recreate {
+        pub network_id: String,
+        /// Archives whose database was created and had its schema applied
+        pub archives_initialized: Vec<String>,
+        /// Archives whose postgres volume survived, their database left as is
+        pub archives_kept: Vec<String>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Slots {
+        pub network_id: String,
+        pub genesis_timestamp: String,
+        pub slots_per_epoch: u64,
+        pub slot_duration_ms: u64,
+        pub started: bool,
+        pub global_slot: u64,
+        pub epoch: u64,
+        pub slot_in_epoch: u64,
+        pub ms_until_next_slot: u64,
+        pub ms_until_next_epoch: u64,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Export {
+        pub network_id: String,
+        pub output: String,
+        pub archive_data: bool,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Import {
+        pub network_id: String,
+        pub network_dir: String,
+        pub archive_data: bool,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Snapshot {
+        pub network_id: String,
+        pub output: String,
+        pub volumes: Vec<String>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Restore {
+        pub network_id: String,
+        pub snapshot_network_id: String,
+        pub network_dir: String,
+        pub volumes: Vec<String>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct NodeTraffic {
+        pub node_id: String,
+        pub rx_bytes: u64,
+        pub tx_bytes: u64,
+        pub rx_bytes_per_sec: u64,
+        pub tx_bytes_per_sec: u64,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct NetReport {
+        pub network_id: String,
+        pub interval_secs: u64,
+        pub total_rx_bytes: u64,
+        pub total_tx_bytes: u64,
+        pub nodes: Vec<NodeTraffic>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Account {
+        pub public_key: String,
+        pub balance: String,
+        pub delegate: Option<String>,
+        /// Key file relative to the network directory, if stored locally
+        pub key_file: Option<String>,
+        /// Node using the key as its block producer key
+        pub node: Option<String>,
+        /// Running nodes whose wallet holds the account
+        pub imported_into: Vec<String>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Accounts {
+        pub network_id: String,
+        pub accounts: Vec<Account>,
+    }
+
+    /// Endpoints in the shape of o1js' `Mina.Network({...})` argument
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct O1jsNetwork {
+        pub mina: String,
+        /// Archive node API endpoint, which minimina does not run itself
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub archive: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct O1jsConfig {
+        pub network_id: String,
+        pub network: O1jsNetwork,
+        pub fee_payer_node: String,
+        pub fee_payer_private_key: String,
+    }
+
+    impl O1jsConfig {
+        /// Uses the GraphQL endpoint of a seed (or any other) node and the private key
+        /// of `fee_payer`, or of the first block producer if not given
+        pub fn from_network_info(
+            info: &Create,
+            fee_payer: Option<&str>,
+            archive: Option<String>,
+        ) -> Result<Self, String> {
+            use crate::service::ServiceType;
+
+            let mut nodes: Vec<(&String, &super::node::Info)> = info.nodes.iter().collect();
+            nodes.sort_by_key(|(node_id, node)| (node.node_type != ServiceType::Seed, *node_id));
+
+            let mina = nodes
+                .iter()
+                .find_map(|(_, node)| node.graphql_uri.clone())
+                .ok_or("Network has no node with a GraphQL endpoint.")?;
+
+            let (fee_payer_node, fee_payer_private_key) = nodes
+                .iter()
+                .filter(|(node_id, node)| match fee_payer {
+                    Some(fee_payer) => *node_id == fee_payer,
+                    None => node.node_type == ServiceType::BlockProducer,
+                })
+                .find_map(|(node_id, node)| {
+                    node.private_key
+                        .clone()
+                        .map(|private_key| (node_id.to_string(), private_key))
+                })
+                .ok_or_else(|| match fee_payer {
+                    Some(fee_payer) => format!("Node '{fee_payer}' has no private key."),
+                    None => "Network has no block producer with a private key.".to_string(),
+                })?;
+
+            Ok(O1jsConfig {
+                network_id: info.network_id.clone(),
+                network: O1jsNetwork { mina, archive },
+                fee_payer_node,
+                fee_payer_private_key,
+            })
+        }
+
+        /// JavaScript snippet setting up the network as o1js' active instance
+        pub fn to_js_snippet(&self) -> String {
+            format!(
+                "const network = Mina.Network({});\n\
+                Mina.setActiveInstance(network);\n\
+                const feePayerKey = PrivateKey.fromBase58('{}');",
+                serde_json::to_string_pretty(&self.network).unwrap(),
+                self.fee_payer_private_key
+            )
+        }
+    }
+
+    /// `network env`: variables wiring external tools up to a network and one of its nodes
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Env {
+        pub network_id: String,
+        pub node_id: String,
+        pub variables: std::collections::BTreeMap<String, String>,
+    }
+
+    impl std::fmt::Display for Env {
+        /// `export` lines to `eval`, the values single-quoted for the shell
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            for (name, value) in &self.variables {
+                writeln!(f, "export {name}='{}'", value.replace('\'', r"'\''"))?;
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct InitArchive {
+        pub network_id: String,
+        pub archives: Vec<String>,
+    }
+
+    /// A line of `docker compose logs`, split into the service and its message
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct LogLine {
+        pub service: String,
+        pub message: String,
+    }
+
+    impl LogLine {
+        /// Parses a `service  | message` line; lines without a service prefix
+        /// (e.g. compose's own messages) keep an empty service
+        pub fn parse(line: &str) -> Self {
+            match line.split_once(" | ") {
+                Some((service, message)) => LogLine {
+                    service: service.trim().to_string(),
+                    message: message.to_string(),
+                },
+                None => LogLine {
+                    service: String::new(),
+                    message: line.to_string(),
+                },
+            }
+        }
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Logs {
+        pub network_id: String,
+        pub logs: Vec<LogLine>,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct WatchHeight {
+        pub network_id: String,
+        pub stalled: bool,
+        pub height: Option<u64>,
+        pub seconds_since_advance: u64,
+        pub heights: std::collections::HashMap<String, Option<u64>>,
+    }
 }
 
 pub mod node {
@@ -147,6 +676,61 @@ pub mod node {
         pub graphql_uri: Option<String>,
         pub private_key: Option<String>,
         pub node_type: ServiceType,
+        /// `localhost:port` the archive server is published on, for external archive consumers
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub archive_address: Option<String>,
+        /// `localhost:port` the archive database is published on
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub postgres_address: Option<String>,
+        /// URL the Rosetta API is published on
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub rosetta_uri: Option<String>,
+        /// URL of the `/healthz` endpoint of the daemon's health probe sidecar
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub health_uri: Option<String>,
+        /// URL the uptime service backend is published on
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub uptime_uri: Option<String>,
+        /// Container of the node as it runs, only reported by `network info`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub container: Option<Container>,
+    }
+
+    /// A node as listed by `network nodes`
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct Summary {
+        pub node_id: String,
+        pub role: ServiceType,
+        pub image: Option<String>,
+        pub client_port: Option<u16>,
+        /// GraphQL URL on the host port it is actually published on if the node runs
+        pub graphql_uri: Option<String>,
+        /// State of the node's container, `null` if it has none
+        pub state: Option<ContainerState>,
+    }
+
+    /// Container of a node as listed by `docker compose ps`
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct Container {
+        pub name: String,
+        pub state: ContainerState,
+        /// Addresses of the container on its networks
+        pub ip_addresses: Vec<String>,
+        /// Ports of the container published on the host
+        pub ports: Vec<crate::docker::manager::Publisher>,
+    }
+
+    /// `mina client` settings of a daemon, as written to the network's
+    /// `clients/<node>.json`, see `node client`
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct ClientConfig {
+        pub node_id: String,
+        pub container: String,
+        /// `-daemon-port` of `mina client` in the container
+        pub daemon_port: u16,
+        /// REST (GraphQL) port in the container
+        pub rest_port: u16,
+        pub graphql_uri: Option<String>,
     }
 
     #[derive(Debug, Serialize, PartialEq)]
@@ -171,6 +755,17 @@ pub mod node {
         pub node_id: String,
     }
 
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Restart {
+        pub network_id: String,
+        pub node_id: String,
+        pub fresh_state: bool,
+        /// Account files imported into the node's wallet
+        pub imported_accounts: Vec<String>,
+        /// Time until the node's GraphQL server answered, or it was started if it has none
+        pub duration_ms: u64,
+    }
+
     #[derive(Debug, Serialize, PartialEq)]
     pub struct ArchiveData {
         pub data: String,
@@ -178,6 +773,14 @@ pub mod node {
         pub node_id: String,
     }
 
+    /// Archive data dumped to or restored from a file
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct ArchiveDataFile {
+        pub dump_file: String,
+        pub network_id: String,
+        pub node_id: String,
+    }
+
     #[derive(Debug, Serialize, PartialEq)]
     pub struct Logs {
         pub logs: String,
@@ -185,6 +788,82 @@ pub mod node {
         pub node_id: String,
     }
 
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct InternalLogs {
+        pub logs_file: String,
+        pub network_id: String,
+        pub node_id: String,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct CreateAccount {
+        pub public_key: String,
+        pub private_key: String,
+        pub key_file: String,
+        pub funder: String,
+        /// Amount sent to the account in nanomina
+        pub amount: u64,
+        pub payment_hash: String,
+        pub network_id: String,
+        pub node_id: String,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Delegate {
+        pub from: String,
+        pub to: String,
+        pub delegation_hash: String,
+        /// Transaction status, only known when waiting for the inclusion
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub status: Option<String>,
+        pub network_id: String,
+        pub node_id: String,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct FailedImport {
+        pub file: String,
+        pub error: String,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct ImportAccounts {
+        pub imported: Vec<String>,
+        /// Account files imported before, as recorded in the node's config directory
+        pub skipped: Vec<String>,
+        pub failed: Vec<FailedImport>,
+        pub network_id: String,
+        pub node_id: String,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Query {
+        pub response: serde_json::Value,
+        pub network_id: String,
+        pub node_id: String,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Exec {
+        pub command: Vec<String>,
+        pub stdout: String,
+        pub stderr: String,
+        /// `None` if the command was killed by a signal
+        pub exit_code: Option<i32>,
+        pub network_id: String,
+        pub node_id: String,
+    }
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct CrashReport {
+        pub bundle: String,
+        pub status: String,
+        pub exit_code: i32,
+        pub oom_killed: bool,
+        pub network_id: String,
+        pub node_id: String,
+    }
+
     #[derive(Debug, Serialize, PartialEq)]
     pub struct PrecomputedBlocks {
         pub blocks: String,
@@ -197,6 +876,53 @@ pub mod node {
         pub logs: String,
         pub network_id: String,
         pub node_id: String,
+        /// Replayed ledger saved by `node run-replayer --output-file`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub output_file: Option<std::path::PathBuf>,
+    }
+}
+
+pub mod topology {
+    use crate::topology::Diagnostic;
+    use serde::Serialize;
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Validate {
+        pub topology: String,
+        pub valid: bool,
+        pub diagnostics: Vec<Diagnostic>,
+    }
+}
+
+pub mod session {
+    use crate::session::ReplayStep;
+    use serde::Serialize;
+
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Replay {
+        pub session: String,
+        pub network_id: Option<String>,
+        pub steps: Vec<ReplayStep>,
+        /// Some command exited with another code than when recorded
+        pub diverged: bool,
+    }
+}
+
+pub mod keys {
+    use crate::keys::WalletFormat;
+    use serde::Serialize;
+
+    /// `keys export`: a node's key pair and the endpoint a wallet connects to
+    #[derive(Debug, Serialize, PartialEq)]
+    pub struct Export {
+        pub network_id: String,
+        pub node_id: String,
+        pub format: WalletFormat,
+        pub public_key: String,
+        /// Base58 private key, pasted into the wallet's "Import private key"
+        pub private_key: String,
+        /// Endpoint of the wallet's custom network, `None` if the node has no GraphQL port
+        pub graphql_uri: Option<String>,
     }
 }
 
@@ -206,18 +932,78 @@ pub struct Error {
 }
 
 impl ServiceConfig {
+    /// The service as listed by `network nodes`, with the state of its container
+    /// from `containers`
+    pub fn to_node_summary(&self, network_id: &str, containers: &[ContainerInfo]) -> node::Summary {
+        let service_name = format!("{}-{network_id}", self.service_name);
+        let container = containers.iter().find(|c| c.service == service_name);
+        node::Summary {
+            node_id: self.service_name.clone(),
+            role: self.service_type.clone(),
+            image: self
+                .docker_image
+                .clone()
+                .or_else(|| self.archive_docker_image.clone()),
+            client_port: self.client_port,
+            graphql_uri: container
+                .and_then(|container| self.live_graphql_uri(container))
+                .or_else(|| self.to_node_info().graphql_uri),
+            state: container.map(|container| container.state.clone()),
+        }
+    }
+
     pub fn to_node_info(&self) -> node::Info {
         node::Info {
             graphql_uri: self
-                .client_port
-                .map(|port| format!("http://localhost:{}/graphql", port + 1)),
+                .published_graphql_port()
+                .map(|port| format!("http://localhost:{port}/graphql")),
             private_key: self.private_key.clone(),
             node_type: self.service_type.clone(),
+            archive_address: self
+                .published_archive_port()
+                .map(|port| format!("localhost:{port}")),
+            postgres_address: self
+                .postgres_host_port
+                .map(|port| format!("localhost:{port}")),
+            rosetta_uri: self
+                .published_rosetta_port()
+                .map(|port| format!("http://localhost:{port}")),
+            health_uri: self
+                .published_health_port()
+                .map(|port| format!("http://localhost:{port}/healthz")),
+            uptime_uri: self
+                .published_uptime_port()
+                .map(|port| format!("http://localhost:{port}")),
+            container: None,
         }
     }
+
+    /// GraphQL URL of the service's daemon on the host port `container` actually
+    /// publishes it on, `None` if it doesn't
+    pub fn live_graphql_uri(&self, container: &ContainerInfo) -> Option<String> {
+        let rest_port = self.daemon_port()? + 1;
+        let published_port = container.published_port(rest_port)?;
+        Some(format!("http://localhost:{published_port}/graphql"))
+    }
+
+    /// `mina client` settings of the service's daemon, `None` if it doesn't run one
+    pub fn to_client_config(&self, network_id: &str) -> Option<node::ClientConfig> {
+        let daemon_port = self.daemon_port()?;
+        Some(node::ClientConfig {
+            node_id: self.service_name.clone(),
+            container: format!("{}-{network_id}", self.service_name),
+            daemon_port,
+            rest_port: daemon_port + 1,
+            graphql_uri: self.to_node_info().graphql_uri,
+        })
+    }
 }
 
-pub fn generate_network_info(services: &[ServiceConfig], network_id: &str) -> network::Create {
+pub fn generate_network_info(
+    services: &[ServiceConfig],
+    network_id: &str,
+    runtime: Runtime,
+) -> network::Create {
     let mut nodes: HashMap<String, node::Info> = HashMap::new();
     for service in services.iter() {
         nodes.insert(service.service_name.clone(), service.to_node_info());
@@ -226,6 +1012,9 @@ pub fn generate_network_info(services: &[ServiceConfig], network_id: &str) -> ne
     network::Create {
         network_id: network_id.to_string(),
         nodes,
+        timings: None,
+        runtime,
+        monitoring: None,
     }
 }
 
@@ -241,24 +1030,103 @@ macro_rules! impl_display {
 }
 
 impl_display!(network::Create);
+impl_display!(network::LiveInfo);
+impl_display!(network::Graph);
 impl_display!(network::Start);
+impl_display!(network::Quickstart);
 impl_display!(network::Stop);
+impl_display!(network::Repair);
+impl_display!(network::Recreate);
+impl_display!(network::Partition);
+impl_display!(network::ArchiveFault);
+impl_display!(network::LagReport);
+impl_display!(network::Nodes);
+impl_display!(network::Heal);
+impl_display!(network::DeletionPlan);
 impl_display!(network::Status);
 impl_display!(network::ListInfo);
 impl_display!(network::List);
 impl_display!(network::Delete);
+impl_display!(network::Slots);
+impl_display!(network::WatchHeight);
+impl_display!(network::O1jsConfig);
+impl_display!(network::Accounts);
+impl_display!(network::NetReport);
+impl_display!(network::Logs);
+impl_display!(network::InitArchive);
+impl_display!(network::Export);
+impl_display!(network::Import);
+impl_display!(network::Snapshot);
+impl_display!(network::Restore);
+impl_display!(node::ClientConfig);
 impl_display!(node::Start);
 impl_display!(node::Stop);
+impl_display!(node::Restart);
 impl_display!(node::ArchiveData);
+impl_display!(node::ArchiveDataFile);
 impl_display!(node::Logs);
+impl_display!(node::InternalLogs);
+impl_display!(node::CrashReport);
+impl_display!(node::CreateAccount);
+impl_display!(node::Delegate);
+impl_display!(node::ImportAccounts);
+impl_display!(node::Query);
 impl_display!(node::PrecomputedBlocks);
 impl_display!(node::ReplayerLogs);
 impl_display!(node::Status);
+impl_display!(node::Exec);
+impl_display!(topology::Validate);
+impl_display!(session::Replay);
+impl_display!(keys::Export);
+impl_display!(crate::docker::manager::ContainerInfo);
 impl_display!(Error);
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::docker::manager::ContainerState;
+
+    #[test]
+    fn test_parse_log_line() {
+        assert_eq!(
+            network::LogLine::parse(
+                "mina-bp-1-net  | 2024-01-02 13:23:37 UTC [Info] Mina daemon is booting up"
+            ),
+            network::LogLine {
+                service: "mina-bp-1-net".to_string(),
+                message: "2024-01-02 13:23:37 UTC [Info] Mina daemon is booting up".to_string(),
+            }
+        );
+        assert_eq!(
+            network::LogLine::parse("postgres-net | a | b"),
+            network::LogLine {
+                service: "postgres-net".to_string(),
+                message: "a | b".to_string(),
+            }
+        );
+        assert_eq!(network::LogLine::parse("no prefix").service, "");
+    }
+
+    #[test]
+    fn test_parse_archive_stats() {
+        assert_eq!(
+            network::ArchiveStats::parse("42,30,12\n"),
+            Some(network::ArchiveStats {
+                max_block_height: Some(42),
+                canonical_blocks: 30,
+                pending_blocks: 12,
+            })
+        );
+        assert_eq!(
+            network::ArchiveStats::parse(",0,0"),
+            Some(network::ArchiveStats {
+                max_block_height: None,
+                canonical_blocks: 0,
+                pending_blocks: 0,
+            })
+        );
+        assert_eq!(network::ArchiveStats::parse("ERROR"), None);
+    }
 
     #[test]
     fn test_generate_network_info() {
@@ -297,10 +1165,19 @@ mod tests {
             )),
             private_key: bp_service.private_key,
             node_type: bp_service.service_type,
+            archive_address: None,
+            postgres_address: None,
+            rosetta_uri: None,
+            health_uri: None,
+            uptime_uri: None,
+            container: None,
         };
         let expect = network::Create {
             network_id: network_id.to_string(),
             nodes: HashMap::from([(bp_service.service_name.clone(), bp_info.clone())]),
+            timings: None,
+            runtime: Runtime::Docker,
+            monitoring: None,
         };
 
         assert_eq!(
@@ -310,6 +1187,267 @@ mod tests {
                 .unwrap(),
             &serde_json::to_value("Block_producer").unwrap()
         );
-        assert_eq!(expect, generate_network_info(&services, network_id));
+        assert_eq!(
+            expect,
+            generate_network_info(&services, network_id, Runtime::Docker)
+        );
+    }
+
+    #[test]
+    fn test_merge_containers() {
+        use crate::docker::manager::{ContainerInfo, Publisher};
+
+        let bp = ServiceConfig {
+            service_type: ServiceType::BlockProducer,
+            service_name: "mina-bp-1".to_string(),
+            client_port: Some(7070),
+            ..Default::default()
+        };
+        let seed = ServiceConfig {
+            service_type: ServiceType::Seed,
+            service_name: "mina-seed-1".to_string(),
+            client_port: Some(7075),
+            ..Default::default()
+        };
+        let services = vec![bp, seed];
+        let mut info = generate_network_info(&services, "net", Runtime::Docker);
+        let publisher = |target_port, published_port| Publisher {
+            url: "0.0.0.0".to_string(),
+            target_port,
+            published_port,
+            protocol: "tcp".to_string(),
+        };
+        let containers = vec![ContainerInfo {
+            id: "a1".to_string(),
+            name: "mina-bp-1-net".to_string(),
+            image: "daemon-image".to_string(),
+            command: "daemon".to_string(),
+            created_at: String::new(),
+            state: ContainerState::Running,
+            status: "Up".to_string(),
+            health: String::new(),
+            exit_code: 0,
+            labels: String::new(),
+            service: "mina-bp-1-net".to_string(),
+            publishers: Some(vec![publisher(7070, 0), publisher(7071, 20001)]),
+        }];
+        let ip_addresses =
+            HashMap::from([("mina-bp-1-net".to_string(), vec!["172.18.0.3".to_string()])]);
+        info.merge_containers(&services, &containers, &ip_addresses);
+
+        let bp = &info.nodes["mina-bp-1"];
+        assert_eq!(
+            bp.graphql_uri.as_deref(),
+            Some("http://localhost:20001/graphql")
+        );
+        let container = bp.container.as_ref().unwrap();
+        assert_eq!(container.state, ContainerState::Running);
+        assert_eq!(container.ip_addresses, vec!["172.18.0.3"]);
+        assert_eq!(container.ports.len(), 2);
+
+        let seed = &info.nodes["mina-seed-1"];
+        assert_eq!(
+            seed.graphql_uri.as_deref(),
+            Some("http://localhost:7076/graphql")
+        );
+        assert_eq!(seed.container, None);
+    }
+
+    #[test]
+    fn test_to_node_summary() {
+        use crate::docker::manager::{ContainerInfo, Publisher};
+
+        let bp = ServiceConfig {
+            service_type: ServiceType::BlockProducer,
+            service_name: "mina-bp-1".to_string(),
+            docker_image: Some("daemon-image".to_string()),
+            client_port: Some(7070),
+            ..Default::default()
+        };
+        let summary = bp.to_node_summary("net", &[]);
+        assert_eq!(summary.role, ServiceType::BlockProducer);
+        assert_eq!(summary.image.as_deref(), Some("daemon-image"));
+        assert_eq!(summary.client_port, Some(7070));
+        assert_eq!(
+            summary.graphql_uri.as_deref(),
+            Some("http://localhost:7071/graphql")
+        );
+        assert_eq!(summary.state, None);
+
+        let container = ContainerInfo {
+            id: "a1".to_string(),
+            name: "mina-bp-1-net".to_string(),
+            image: "daemon-image".to_string(),
+            command: "daemon".to_string(),
+            created_at: String::new(),
+            state: ContainerState::Exited,
+            status: "Exited (0)".to_string(),
+            health: String::new(),
+            exit_code: 0,
+            labels: String::new(),
+            service: "mina-bp-1-net".to_string(),
+            publishers: Some(vec![Publisher {
+                url: "0.0.0.0".to_string(),
+                target_port: 7071,
+                published_port: 20001,
+                protocol: "tcp".to_string(),
+            }]),
+        };
+        let summary = bp.to_node_summary("net", &[container]);
+        assert_eq!(
+            summary.graphql_uri.as_deref(),
+            Some("http://localhost:20001/graphql")
+        );
+        assert_eq!(summary.state, Some(ContainerState::Exited));
+    }
+
+    #[test]
+    fn test_o1js_config() {
+        let node = |node_type, port: Option<u16>, private_key: Option<&str>| node::Info {
+            graphql_uri: port.map(|port| format!("http://localhost:{port}/graphql")),
+            private_key: private_key.map(String::from),
+            node_type,
+            archive_address: None,
+            postgres_address: None,
+            rosetta_uri: None,
+            health_uri: None,
+            uptime_uri: None,
+            container: None,
+        };
+        let info = network::Create {
+            network_id: "o1js".to_string(),
+            nodes: HashMap::from([
+                (
+                    "bp-2".to_string(),
+                    node(ServiceType::BlockProducer, Some(7081), Some("EKbp2")),
+                ),
+                (
+                    "bp-1".to_string(),
+                    node(ServiceType::BlockProducer, Some(7076), Some("EKbp1")),
+                ),
+                (
+                    "seed".to_string(),
+                    node(ServiceType::Seed, Some(7071), None),
+                ),
+                (
+                    "worker".to_string(),
+                    node(ServiceType::SnarkWorker, None, None),
+                ),
+            ]),
+            timings: None,
+            runtime: Runtime::Docker,
+            monitoring: None,
+        };
+
+        let config = network::O1jsConfig::from_network_info(&info, None, None).unwrap();
+        assert_eq!(config.network.mina, "http://localhost:7071/graphql");
+        assert_eq!(config.fee_payer_node, "bp-1");
+        assert_eq!(config.fee_payer_private_key, "EKbp1");
+        assert!(config
+            .to_js_snippet()
+            .contains("const feePayerKey = PrivateKey.fromBase58('EKbp1');"));
+
+        let config = network::O1jsConfig::from_network_info(&info, Some("bp-2"), None).unwrap();
+        assert_eq!(config.fee_payer_private_key, "EKbp2");
+        assert!(network::O1jsConfig::from_network_info(&info, Some("seed"), None).is_err());
+    }
+
+    #[test]
+    fn test_create_timings() {
+        let mut timings = network::Timings::default();
+        let result = network::Timings::measure(&mut timings.image_pull_ms, || {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            42
+        });
+        assert_eq!(result, 42);
+        assert!(timings.image_pull_ms >= 5);
+
+        // network.json does not record timings
+        let info = network::Create {
+            network_id: "timed".to_string(),
+            nodes: HashMap::new(),
+            timings: None,
+            runtime: Runtime::Docker,
+            monitoring: None,
+        };
+        let json = info.to_string();
+        assert!(!json.contains("timings"));
+        assert!(!json.contains("runtime"));
+        assert_eq!(
+            serde_json::from_str::<network::Create>(&json).unwrap(),
+            info
+        );
+
+        let info = network::Create {
+            timings: Some(timings),
+            ..info
+        };
+        assert!(info.to_string().contains("\"image_pull_ms\""));
+    }
+
+    #[test]
+    fn test_deletion_plan_describe() {
+        let plan = network::DeletionPlan {
+            network_id: "test".to_string(),
+            directory: "/home/u/.minimina/test".to_string(),
+            containers: vec!["mina-seed-1-test".to_string(), "mina-bp-1-test".to_string()],
+            volumes: vec![],
+            images: vec!["daemon-image".to_string()],
+        };
+
+        assert_eq!(
+            plan.describe(),
+            "directory: /home/u/.minimina/test\n\
+             containers: mina-seed-1-test, mina-bp-1-test\n\
+             images: daemon-image"
+        );
+    }
+
+    #[test]
+    fn test_status_table() {
+        let mut status = network::Status::new("test");
+        status.status = "running(2)".to_string();
+        status.services = vec![
+            node::Status {
+                id: "mina-seed-1-test".to_string(),
+                state: ContainerState::Running,
+                status: "Up 2 minutes".to_string(),
+                docker_image: "daemon".to_string(),
+            },
+            node::Status {
+                id: "mina-bp-1-test".to_string(),
+                state: ContainerState::Exited,
+                status: "Exited (1)".to_string(),
+                docker_image: "daemon".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            status.to_table(),
+            "Network 'test': running(2)\n\
+             SERVICE           STATE    STATUS        IMAGE\n\
+             mina-seed-1-test  running  Up 2 minutes  daemon\n\
+             mina-bp-1-test    exited   Exited (1)    daemon"
+        );
+    }
+
+    #[test]
+    fn test_env_exports() {
+        let env = network::Env {
+            network_id: "net".to_string(),
+            node_id: "seed".to_string(),
+            variables: [
+                ("NODE_CONTAINER", "seed-net"),
+                ("NETWORK_DIR", "/home/o'neil/.minimina/net"),
+            ]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        };
+        assert_eq!(
+            env.to_string(),
+            "export NETWORK_DIR='/home/o'\\''neil/.minimina/net'\n\
+            export NODE_CONTAINER='seed-net'\n"
+        );
     }
 }
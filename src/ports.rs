@@ -0,0 +1,126 @@
+//! # Ports Module
+//!
+//! Allocates the host ports the services of a network are published on, so
+//! networks created side by side don't collide. Ports recorded by the other
+//! networks and ports in use on the host are skipped.
+
+use std::{
+    collections::HashSet,
+    io::{Error, ErrorKind, Result},
+    net::TcpListener,
+    ops::RangeInclusive,
+};
+
+pub struct PortAllocator {
+    /// Ports to allocate from, upwards from the preferred port if not set
+    range: Option<RangeInclusive<u16>>,
+    /// Ports of other networks and ports allocated so far
+    taken: HashSet<u16>,
+}
+
+impl PortAllocator {
+    pub fn new(range: Option<RangeInclusive<u16>>, taken: impl IntoIterator<Item = u16>) -> Self {
+        PortAllocator {
+            range,
+            taken: taken.into_iter().collect(),
+        }
+    }
+
+    /// Whether another network uses `port`
+    pub fn is_taken(&self, port: u16) -> bool {
+        self.taken.contains(&port)
+    }
+
+    /// Reserves `port`, e.g. one configured explicitly
+    pub fn reserve(&mut self, port: u16) {
+        self.taken.insert(port);
+    }
+
+    /// Allocates `preferred` if it is free, or else the next free port of the range
+    /// (above `preferred` without a range)
+    pub fn allocate(&mut self, preferred: u16) -> Result<u16> {
+        self.allocate_with(preferred, is_free_on_host)
+    }
+
+    fn allocate_with(&mut self, preferred: u16, is_free: impl Fn(u16) -> bool) -> Result<u16> {
+        let candidates = match &self.range {
+            Some(range) => range.clone(),
+            None => preferred..=u16::MAX,
+        };
+        let port = std::iter::once(preferred)
+            .filter(|port| candidates.contains(port))
+            .chain(candidates.clone())
+            .find(|port| !self.taken.contains(port) && is_free(*port))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::AddrInUse,
+                    format!(
+                        "No free host port left in {}-{}",
+                        candidates.start(),
+                        candidates.end()
+                    ),
+                )
+            })?;
+        self.taken.insert(port);
+        Ok(port)
+    }
+}
+
+/// Whether nothing listens on `port` on any interface of the host
+pub fn is_free_on_host(port: u16) -> bool {
+    TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
+
+/// Parses a `START-END` port range, e.g. `20000-20999`
+pub fn parse_port_range(range: &str) -> std::result::Result<RangeInclusive<u16>, String> {
+    let (start, end) = range
+        .split_once('-')
+        .ok_or_else(|| format!("'{range}' is not a START-END port range"))?;
+    let parse = |port: &str| {
+        port.trim()
+            .parse::<u16>()
+            .map_err(|e| format!("Invalid port '{port}': {e}"))
+    };
+    let (start, end) = (parse(start)?, parse(end)?);
+    if start == 0 || start > end {
+        return Err(format!("'{range}' is not a valid port range"));
+    }
+    Ok(start..=end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate_preferred_port() {
+        let mut allocator = PortAllocator::new(None, [3101]);
+
+        assert_eq!(allocator.allocate_with(4001, |_| true).unwrap(), 4001);
+        // taken by another network, then by this one
+        assert_eq!(allocator.allocate_with(3101, |_| true).unwrap(), 3102);
+        assert_eq!(allocator.allocate_with(4001, |_| true).unwrap(), 4002);
+        // busy on the host
+        assert_eq!(
+            allocator.allocate_with(5000, |port| port != 5000).unwrap(),
+            5001
+        );
+    }
+
+    #[test]
+    fn test_allocate_from_range() {
+        let mut allocator = PortAllocator::new(Some(20000..=20001), [20000]);
+
+        assert_eq!(allocator.allocate_with(3101, |_| true).unwrap(), 20001);
+        assert!(allocator.allocate_with(3101, |_| true).is_err());
+    }
+
+    #[test]
+    fn test_parse_port_range() {
+        assert_eq!(parse_port_range("20000-20999"), Ok(20000..=20999));
+        assert!(parse_port_range("20000").is_err());
+        assert!(parse_port_range("2000-1000").is_err());
+        assert!(parse_port_range("0-10").is_err());
+        assert!(parse_port_range("1-70000").is_err());
+    }
+}
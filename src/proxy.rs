@@ -0,0 +1,123 @@
+//! # Proxy Module
+//!
+//! HTTP(S) proxy settings for networks created behind a proxy.
+//!
+//! Settings are read from the `proxy` section of the config file (see [`crate::config`]).
+//! Values missing from the file fall back to the `HTTP_PROXY`, `HTTPS_PROXY` and
+//! `NO_PROXY` environment variables (or their lowercase forms). They are used by
+//! minimina's own HTTP requests (schema fetch, GraphQL, webhooks) and passed into the
+//! containers it runs for key generation. Image pulls are done by the docker daemon,
+//! which has to be configured with its own proxy settings.
+
+use crate::config::Config;
+use log::warn;
+use reqwest::{blocking::Client, NoProxy, Proxy};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ProxyConfig {
+    pub http_proxy: Option<String>,
+    pub https_proxy: Option<String>,
+    pub no_proxy: Option<String>,
+}
+
+impl ProxyConfig {
+    /// Settings of the config file in the minimina home, completed from the environment
+    pub fn load() -> Self {
+        Config::load()
+            .proxy
+            .with_fallback(|key| std::env::var(key).ok())
+    }
+
+    fn with_fallback(self, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let env = |name: &str| {
+            lookup(&name.to_uppercase())
+                .or_else(|| lookup(name))
+                .filter(|v| !v.is_empty())
+        };
+        ProxyConfig {
+            http_proxy: self.http_proxy.or_else(|| env("http_proxy")),
+            https_proxy: self.https_proxy.or_else(|| env("https_proxy")),
+            no_proxy: self.no_proxy.or_else(|| env("no_proxy")),
+        }
+    }
+
+    /// Blocking HTTP client going through the configured proxies
+    pub fn client(&self) -> Client {
+        let mut builder = Client::builder().no_proxy();
+        let no_proxy = || self.no_proxy.as_deref().and_then(NoProxy::from_string);
+        if let Some(url) = &self.http_proxy {
+            match Proxy::http(url) {
+                Ok(proxy) => builder = builder.proxy(proxy.no_proxy(no_proxy())),
+                Err(e) => warn!("Ignoring invalid http proxy {url}: {e}"),
+            }
+        }
+        if let Some(url) = &self.https_proxy {
+            match Proxy::https(url) {
+                Ok(proxy) => builder = builder.proxy(proxy.no_proxy(no_proxy())),
+                Err(e) => warn!("Ignoring invalid https proxy {url}: {e}"),
+            }
+        }
+        builder.build().expect("Failed to build HTTP client")
+    }
+
+    /// `KEY=value` pairs to pass into containers with `--env`
+    pub fn docker_env(&self) -> Vec<String> {
+        [
+            ("HTTP_PROXY", &self.http_proxy),
+            ("HTTPS_PROXY", &self.https_proxy),
+            ("NO_PROXY", &self.no_proxy),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.as_ref().map(|v| format!("{key}={v}")))
+        .collect()
+    }
+}
+
+/// Blocking HTTP client honouring the proxy settings
+pub fn http_client() -> Client {
+    ProxyConfig::load().client()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashMap, fs};
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_config_file_takes_precedence_over_env() {
+        let tmp = TempDir::new("proxy").unwrap();
+        fs::write(
+            tmp.path().join("config.json"),
+            r#"{"proxy": {"https_proxy": "http://file-proxy:3128"}}"#,
+        )
+        .unwrap();
+        let env = HashMap::from([
+            ("HTTPS_PROXY", "http://env-proxy:3128"),
+            ("http_proxy", "http://env-proxy:8080"),
+            ("NO_PROXY", "localhost"),
+        ]);
+
+        let config = Config::load_from(tmp.path())
+            .proxy
+            .with_fallback(|key| env.get(key).map(|v| v.to_string()));
+
+        assert_eq!(
+            config,
+            ProxyConfig {
+                http_proxy: Some("http://env-proxy:8080".to_string()),
+                https_proxy: Some("http://file-proxy:3128".to_string()),
+                no_proxy: Some("localhost".to_string()),
+            }
+        );
+        assert_eq!(
+            config.docker_env(),
+            vec![
+                "HTTP_PROXY=http://env-proxy:8080",
+                "HTTPS_PROXY=http://file-proxy:3128",
+                "NO_PROXY=localhost",
+            ]
+        );
+    }
+}
@@ -0,0 +1,653 @@
+//! # Service Command Module
+//!
+//! This module builds the container command of every Mina service role.
+//! Each role has its own [`CommandBuilder`], looked up via [`ServiceType::command_builder`],
+//! so that a new role only needs a new builder rather than changes to the existing ones.
+
+use log::warn;
+
+use super::{ServiceConfig, ServiceType, UPTIME_SERVICE_PORT};
+use crate::{docker::compose::CONFIG_DIRECTORY, genesis_ledger::GENESIS_LEDGER_JSON};
+
+/// Network-wide information needed to build a service command
+#[derive(Debug, Clone, Default)]
+pub struct CommandContext {
+    /// Network name, used as a suffix of all container hostnames
+    pub network_name: String,
+    /// Hostname of the uptime service backend, if the network has one
+    pub uptime_service_host: Option<String>,
+    /// Hostname of the archive service an archive node sends blocks to
+    pub archive_service_host: Option<String>,
+}
+
+/// Client port of daemons without one configured
+pub const DEFAULT_CLIENT_PORT: u16 = 3100;
+
+/// Builds the command arguments of a service with a particular role
+pub trait CommandBuilder {
+    fn build(&self, config: &ServiceConfig, context: &CommandContext) -> Vec<String>;
+}
+
+pub struct SeedCommand;
+pub struct BlockProducerCommand;
+pub struct SnarkCoordinatorCommand;
+pub struct SnarkWorkerCommand;
+pub struct ArchiveNodeCommand;
+
+impl ServiceType {
+    /// Returns the command builder of the role, or `None` if the role's container
+    /// runs its image's own entrypoint
+    pub fn command_builder(&self) -> Option<&'static dyn CommandBuilder> {
+        match self {
+            ServiceType::Seed => Some(&SeedCommand),
+            ServiceType::BlockProducer => Some(&BlockProducerCommand),
+            ServiceType::SnarkCoordinator => Some(&SnarkCoordinatorCommand),
+            ServiceType::SnarkWorker => Some(&SnarkWorkerCommand),
+            ServiceType::ArchiveNode => Some(&ArchiveNodeCommand),
+            ServiceType::ArchiveService
+            | ServiceType::UptimeServiceBackend
+            | ServiceType::Rosetta => None,
+        }
+    }
+}
+
+impl ServiceConfig {
+    /// Generate the container command of the service
+    pub fn generate_command(&self, context: &CommandContext) -> Option<String> {
+        self.service_type
+            .command_builder()
+            .map(|builder| builder.build(self, context).join(" "))
+    }
+
+    /// Client port of the service's daemon, `None` if the service doesn't run one
+    pub fn daemon_port(&self) -> Option<u16> {
+        match self.service_type {
+            ServiceType::Seed
+            | ServiceType::BlockProducer
+            | ServiceType::SnarkCoordinator
+            | ServiceType::ArchiveNode => Some(self.client_port.unwrap_or(DEFAULT_CLIENT_PORT)),
+            _ => None,
+        }
+    }
+
+    /// `mina client` command with `args` talking to the service's daemon
+    pub fn client_command(&self, args: &[String]) -> Option<Vec<String>> {
+        let daemon_port = self.daemon_port()?;
+        let mut command = vec!["mina".to_string(), "client".to_string()];
+        command.extend_from_slice(args);
+        if !args.iter().any(|arg| arg == "-daemon-port") {
+            command.push("-daemon-port".to_string());
+            command.push(daemon_port.to_string());
+        }
+        Some(command)
+    }
+}
+
+impl CommandBuilder for SeedCommand {
+    fn build(&self, config: &ServiceConfig, _context: &CommandContext) -> Vec<String> {
+        let mut command = base_daemon_command(config);
+        command.push("-seed".to_string());
+
+        add_libp2p_command(config, &mut command);
+        command
+    }
+}
+
+impl CommandBuilder for BlockProducerCommand {
+    fn build(&self, config: &ServiceConfig, context: &CommandContext) -> Vec<String> {
+        let mut command = base_daemon_command(config);
+
+        // Handling multiple peers
+        add_peers_command(config, &mut command);
+
+        if let Some(uptime_service_host) = &context.uptime_service_host {
+            command.push("-uptime-url".to_string());
+            command.push(format!(
+                "http://{uptime_service_host}:{UPTIME_SERVICE_PORT}/v1/submit"
+            ));
+        }
+
+        let key_path = if config.private_key_path.is_some() {
+            Some(format!(
+                "/local-network/network-keypairs/{}.json",
+                config.service_name
+            ))
+        } else {
+            config.public_key_path.clone()
+        };
+
+        if let Some(key_path) = key_path {
+            command.push("-block-producer-key".to_string());
+            command.push(key_path.clone());
+            if context.uptime_service_host.is_some() {
+                command.push("-uptime-submitter-key".to_string());
+                command.push(key_path);
+            }
+        } else {
+            warn!(
+                "No public or private key path provided for block producer node '{}'. This is not recommended.",
+                config.service_name
+            );
+        }
+
+        add_libp2p_command(config, &mut command);
+        command
+    }
+}
+
+impl CommandBuilder for SnarkCoordinatorCommand {
+    fn build(&self, config: &ServiceConfig, _context: &CommandContext) -> Vec<String> {
+        let mut command = base_daemon_command(config);
+
+        command.push("-work-selection".to_string());
+        command.push(
+            config
+                .work_selection
+                .clone()
+                .unwrap_or_default()
+                .to_string(),
+        );
+
+        if let Some(wait) = config.work_reassignment_wait {
+            command.push("-work-reassignment-wait".to_string());
+            command.push(wait.to_string());
+        }
+
+        add_peers_command(config, &mut command);
+
+        if let Some(snark_worker_fees) = &config.snark_coordinator_fees {
+            command.push("-snark-worker-fee".to_string());
+            command.push(snark_worker_fees.clone());
+        } else {
+            warn!(
+                "No snark worker fees provided for snark coordinator node '{}'. This is not recommended.",
+                config.service_name
+            );
+        }
+
+        if let Some(public_key) = &config.public_key {
+            command.push("-run-snark-coordinator".to_string());
+            command.push(public_key.clone());
+        } else {
+            warn!(
+                "No public key provided for snark coordinator node '{}'. This is not recommended.",
+                config.service_name
+            );
+        }
+
+        add_libp2p_command(config, &mut command);
+        command
+    }
+}
+
+impl CommandBuilder for SnarkWorkerCommand {
+    fn build(&self, config: &ServiceConfig, context: &CommandContext) -> Vec<String> {
+        let mut command = vec![
+            "internal".to_string(),
+            "snark-worker".to_string(),
+            "-shutdown-on-disconnect".to_string(),
+            "false".to_string(),
+            "-config-directory".to_string(),
+            format!("/{CONFIG_DIRECTORY}"),
+        ];
+
+        if let (Some(host), Some(port)) = (
+            &config.snark_coordinator_host,
+            config.snark_coordinator_port,
+        ) {
+            command.push("-daemon-address".to_string());
+            command.push(format!("{host}-{}:{port}", context.network_name));
+        } else {
+            warn!(
+                "No snark coordinator port or host provided for snark worker node '{}'. This is not recommended.",
+                config.service_name
+            );
+        }
+
+        if let Some(proof_level) = &config.snark_worker_proof_level {
+            command.push("-proof-level".to_string());
+            command.push(proof_level.clone());
+        } else {
+            warn!(
+                "No proof level provided for snark worker node '{}'. This is not recommended.",
+                config.service_name
+            );
+        }
+
+        command
+    }
+}
+
+impl CommandBuilder for ArchiveNodeCommand {
+    fn build(&self, config: &ServiceConfig, context: &CommandContext) -> Vec<String> {
+        let mut command = base_daemon_command(config);
+
+        // Handling multiple peers
+        add_peers_command(config, &mut command);
+
+        match (&context.archive_service_host, config.archive_port) {
+            (Some(archive_service_host), Some(archive_port)) => {
+                command.push("-archive-address".to_string());
+                command.push(format!("{}:{}", archive_service_host, archive_port));
+            }
+            _ => warn!(
+                "No archive port provided for archive node '{}'. This is not recommended.",
+                config.service_name
+            ),
+        }
+
+        add_libp2p_command(config, &mut command);
+        command
+    }
+}
+
+/// Daemon flag setting the origin allowed by the GraphQL server's CORS headers
+const CORS_ALLOW_ORIGIN_FLAG: &str = "-cors-allow-origin";
+/// Daemon flag setting the token required by the GraphQL server
+const GRAPHQL_AUTH_TOKEN_FLAG: &str = "-graphql-auth-token";
+
+/// Daemon flags overriding the peer connection defaults
+const MAX_CONNECTIONS_FLAG: &str = "-max-connections";
+const MIN_CONNECTIONS_FLAG: &str = "-min-connections";
+const ENABLE_PEER_EXCHANGE_FLAG: &str = "-enable-peer-exchange";
+
+/// Generate base daemon command common for most mina services
+fn base_daemon_command(config: &ServiceConfig) -> Vec<String> {
+    let client_port = config.client_port.unwrap_or(DEFAULT_CLIENT_PORT);
+    let rest_port = client_port + 1;
+    let external_port = rest_port + 1;
+    let metrics_port = external_port + 1;
+    let libp2p_metrics_port = metrics_port + 1;
+    let limited_graphql_port = libp2p_metrics_port + 1;
+
+    let mut command = vec![
+        "daemon".to_string(),
+        "-client-port".to_string(),
+        client_port.to_string(),
+        "-rest-port".to_string(),
+        rest_port.to_string(),
+        "-insecure-rest-server".to_string(),
+        "-external-port".to_string(),
+        external_port.to_string(),
+        "-metrics-port".to_string(),
+        metrics_port.to_string(),
+        "-libp2p-metrics-port".to_string(),
+        libp2p_metrics_port.to_string(),
+        "-config-file".to_string(),
+        format!("/local-network/{GENESIS_LEDGER_JSON}"),
+        "-log-json".to_string(),
+        "-log-level".to_string(),
+        "Trace".to_string(),
+        "-file-log-level".to_string(),
+        "Trace".to_string(),
+        "-config-directory".to_string(),
+        format!("/{CONFIG_DIRECTORY}"),
+        "-precomputed-blocks-file".to_string(),
+        format!("/{CONFIG_DIRECTORY}/precomputed_blocks.log"),
+        "-log-txn-pool-gossip".to_string(),
+        "true".to_string(),
+        "-log-snark-work-gossip".to_string(),
+        "true".to_string(),
+        "-log-precomputed-blocks".to_string(),
+        "true".to_string(),
+    ];
+
+    // full proofs unless the extra flags set another level, e.g. `minimina quickstart`
+    if !config
+        .extra_daemon_flags
+        .iter()
+        .flatten()
+        .any(|flag| flag == "-proof-level")
+    {
+        command.push("-proof-level".to_string());
+        command.push("full".to_string());
+    }
+
+    if let Some(graphql) = &config.graphql {
+        if let Some(origin) = &graphql.cors_allow_origin {
+            command.push(CORS_ALLOW_ORIGIN_FLAG.to_string());
+            command.push(origin.clone());
+        }
+        if let Some(token) = &graphql.auth_token {
+            command.push(GRAPHQL_AUTH_TOKEN_FLAG.to_string());
+            command.push(token.clone());
+        }
+        if graphql.open_limited_port {
+            command.push("-open-limited-graphql-port".to_string());
+            command.push("-limited-graphql-port".to_string());
+            command.push(limited_graphql_port.to_string());
+        }
+    }
+
+    if let Some(connections) = &config.connections {
+        if let Some(max_connections) = connections.max_connections {
+            command.push(MAX_CONNECTIONS_FLAG.to_string());
+            command.push(max_connections.to_string());
+        }
+        if let Some(min_connections) = connections.min_connections {
+            command.push(MIN_CONNECTIONS_FLAG.to_string());
+            command.push(min_connections.to_string());
+        }
+        if let Some(enable_peer_exchange) = connections.enable_peer_exchange {
+            command.push(ENABLE_PEER_EXCHANGE_FLAG.to_string());
+            command.push(enable_peer_exchange.to_string());
+        }
+    }
+
+    if let Some(flags) = &config.extra_daemon_flags {
+        command.extend(flags.iter().cloned());
+    }
+
+    command
+}
+
+fn add_peers_command(config: &ServiceConfig, command: &mut Vec<String>) {
+    if config.peer_list_file.is_some() {
+        command.push("-peer-list-file".to_string());
+        command.push("/local-network/peer_list_file.txt".into());
+    } else if let Some(ref peers) = config.peers {
+        for peer in peers.iter() {
+            command.push("-peer".to_string());
+            command.push(peer.clone());
+        }
+    } else {
+        warn!(
+            "No peers provided for block producer node '{}'. This is not recommended.",
+            config.service_name
+        );
+    }
+}
+
+fn add_libp2p_command(config: &ServiceConfig, command: &mut Vec<String>) {
+    if config.libp2p_keypair_path.is_some() {
+        command.push("-libp2p-keypair".to_string());
+        command.push(format!(
+            "/local-network/libp2p-keypairs/{}.json",
+            config.service_name
+        ));
+    } else if let Some(libp2p_keypair) = &config.libp2p_keypair {
+        command.push("-libp2p-keypair".to_string());
+        command.push(libp2p_keypair.clone());
+    } else {
+        warn!(
+            "No libp2p keypair provided for node '{}'. This is not recommended.",
+            config.service_name
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::{ConnectionOptions, GraphQlOptions, WorkSelection};
+    use std::path::PathBuf;
+
+    const GOLDEN_DIR: &str = "./tests/data/golden/commands";
+
+    fn context() -> CommandContext {
+        CommandContext {
+            network_name: "golden".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Every role/field combination with its golden file name
+    fn cases() -> Vec<(&'static str, ServiceConfig, CommandContext)> {
+        let seed = ServiceConfig {
+            service_type: ServiceType::Seed,
+            service_name: "seed".to_string(),
+            client_port: Some(3100),
+            libp2p_keypair: Some("seed-keypair".to_string()),
+            ..Default::default()
+        };
+        let block_producer = ServiceConfig {
+            service_type: ServiceType::BlockProducer,
+            service_name: "bp".to_string(),
+            client_port: Some(4000),
+            peers: Some(vec![
+                "/dns4/seed-golden/tcp/3102/p2p/peer".to_string(),
+                "/dns4/seed2-golden/tcp/3107/p2p/peer2".to_string(),
+            ]),
+            libp2p_keypair: Some("bp-keypair".to_string()),
+            ..Default::default()
+        };
+        let coordinator = ServiceConfig {
+            service_type: ServiceType::SnarkCoordinator,
+            service_name: "coordinator".to_string(),
+            client_port: Some(7000),
+            public_key: Some("B62-coordinator".to_string()),
+            snark_coordinator_fees: Some("0.001".to_string()),
+            peer_list_file: Some(PathBuf::from("/path/to/peers.txt")),
+            libp2p_keypair_path: Some(PathBuf::from("/path/to/libp2p.json")),
+            ..Default::default()
+        };
+        let worker = ServiceConfig {
+            service_type: ServiceType::SnarkWorker,
+            service_name: "worker".to_string(),
+            snark_coordinator_host: Some("coordinator".to_string()),
+            snark_coordinator_port: Some(7000),
+            snark_worker_proof_level: Some("none".to_string()),
+            ..Default::default()
+        };
+        let archive = ServiceConfig {
+            service_type: ServiceType::ArchiveNode,
+            service_name: "archive".to_string(),
+            client_port: Some(5005),
+            archive_port: Some(3086),
+            peer_list_file: Some(PathBuf::from("/path/to/peers.txt")),
+            libp2p_keypair_path: Some(PathBuf::from("/path/to/libp2p.json")),
+            ..Default::default()
+        };
+        let uptime_context = CommandContext {
+            uptime_service_host: Some("uptime-golden".to_string()),
+            ..context()
+        };
+        let archive_context = CommandContext {
+            archive_service_host: Some("archive-service-golden".to_string()),
+            ..context()
+        };
+
+        vec![
+            ("seed", seed.clone(), context()),
+            (
+                "seed_libp2p_keyfile",
+                ServiceConfig {
+                    libp2p_keypair: None,
+                    libp2p_keypair_path: Some(PathBuf::from("/path/to/libp2p.json")),
+                    ..seed.clone()
+                },
+                context(),
+            ),
+            (
+                "seed_without_keys",
+                ServiceConfig {
+                    libp2p_keypair: None,
+                    client_port: None,
+                    ..seed.clone()
+                },
+                context(),
+            ),
+            (
+                "seed_graphql_options",
+                ServiceConfig {
+                    graphql: Some(GraphQlOptions {
+                        cors_allow_origin: Some("*".to_string()),
+                        auth_token: Some("s3cret".to_string()),
+                        open_limited_port: true,
+                    }),
+                    ..seed
+                },
+                context(),
+            ),
+            (
+                "block_producer_connection_limits",
+                ServiceConfig {
+                    connections: Some(ConnectionOptions {
+                        max_connections: Some(2),
+                        min_connections: Some(1),
+                        enable_peer_exchange: Some(false),
+                    }),
+                    ..block_producer.clone()
+                },
+                context(),
+            ),
+            (
+                "block_producer_without_key",
+                block_producer.clone(),
+                context(),
+            ),
+            (
+                "block_producer_public_key_path",
+                ServiceConfig {
+                    public_key_path: Some("/local-network/network-keypairs/bp".to_string()),
+                    ..block_producer.clone()
+                },
+                context(),
+            ),
+            (
+                "block_producer_private_key_path_uptime",
+                ServiceConfig {
+                    private_key_path: Some(PathBuf::from("/path/to/bp.json")),
+                    peer_list_file: Some(PathBuf::from("/path/to/peers.txt")),
+                    ..block_producer.clone()
+                },
+                uptime_context.clone(),
+            ),
+            (
+                "block_producer_public_key_path_uptime",
+                ServiceConfig {
+                    public_key_path: Some("/local-network/network-keypairs/bp".to_string()),
+                    ..block_producer
+                },
+                uptime_context,
+            ),
+            ("snark_coordinator", coordinator.clone(), context()),
+            (
+                "snark_coordinator_tuned",
+                ServiceConfig {
+                    work_selection: Some(WorkSelection::Random),
+                    work_reassignment_wait: Some(30000),
+                    ..coordinator.clone()
+                },
+                context(),
+            ),
+            (
+                "snark_coordinator_without_fee_and_key",
+                ServiceConfig {
+                    public_key: None,
+                    snark_coordinator_fees: None,
+                    ..coordinator
+                },
+                context(),
+            ),
+            ("snark_worker", worker.clone(), context()),
+            (
+                "snark_worker_without_coordinator",
+                ServiceConfig {
+                    snark_coordinator_host: None,
+                    snark_worker_proof_level: None,
+                    ..worker
+                },
+                context(),
+            ),
+            ("archive_node", archive.clone(), archive_context),
+            ("archive_node_without_service", archive, context()),
+        ]
+    }
+
+    #[test]
+    fn test_commands_match_golden_files() {
+        let update = std::env::var("UPDATE_GOLDEN").is_ok();
+
+        for (name, config, context) in cases() {
+            let command = config
+                .generate_command(&context)
+                .expect("role should have a command");
+            let golden_file = PathBuf::from(GOLDEN_DIR).join(format!("{name}.txt"));
+
+            if update {
+                std::fs::create_dir_all(GOLDEN_DIR).unwrap();
+                std::fs::write(&golden_file, format!("{command}\n")).unwrap();
+                continue;
+            }
+
+            let expected = std::fs::read_to_string(&golden_file)
+                .unwrap_or_else(|_| panic!("Missing golden file {}", golden_file.display()));
+            assert_eq!(expected.trim_end(), command, "golden file '{name}' differs");
+        }
+    }
+
+    #[test]
+    fn test_uptime_service_has_no_command() {
+        let config = ServiceConfig {
+            service_type: ServiceType::UptimeServiceBackend,
+            service_name: "uptime".to_string(),
+            ..Default::default()
+        };
+
+        assert!(config.generate_command(&context()).is_none());
+    }
+
+    #[test]
+    fn test_client_command() {
+        let args = |args: &[&str]| -> Vec<String> { args.iter().map(|a| a.to_string()).collect() };
+        let seed = ServiceConfig {
+            service_type: ServiceType::Seed,
+            client_port: Some(4000),
+            ..Default::default()
+        };
+        assert_eq!(
+            seed.client_command(&args(&["status"])),
+            Some(args(&["mina", "client", "status", "-daemon-port", "4000"]))
+        );
+        // an explicit daemon port is kept
+        assert_eq!(
+            seed.client_command(&args(&["status", "-daemon-port", "5000"])),
+            Some(args(&["mina", "client", "status", "-daemon-port", "5000"]))
+        );
+
+        let worker = ServiceConfig {
+            service_type: ServiceType::SnarkWorker,
+            ..Default::default()
+        };
+        assert_eq!(worker.client_command(&args(&["status"])), None);
+    }
+
+    #[test]
+    fn test_extra_daemon_flags() {
+        let flags = vec!["-log-level".to_string(), "Info".to_string()];
+        let mut seed = ServiceConfig {
+            service_type: ServiceType::Seed,
+            service_name: "seed".to_string(),
+            client_port: Some(3100),
+            ..Default::default()
+        };
+        seed.extend_daemon_flags(&flags);
+        let command = seed.generate_command(&context()).unwrap();
+        assert!(command.ends_with("-log-level Info -seed"), "{command}");
+
+        // workers don't run a daemon
+        let mut worker = ServiceConfig {
+            service_type: ServiceType::SnarkWorker,
+            ..Default::default()
+        };
+        worker.extend_daemon_flags(&flags);
+        assert_eq!(worker.extra_daemon_flags, None);
+    }
+
+    #[test]
+    fn test_extra_proof_level_flag() {
+        let mut seed = ServiceConfig {
+            service_type: ServiceType::Seed,
+            service_name: "seed".to_string(),
+            ..Default::default()
+        };
+        let command = seed.generate_command(&context()).unwrap();
+        assert!(command.contains("-proof-level full"), "{command}");
+
+        seed.extend_daemon_flags(&["-proof-level".to_string(), "none".to_string()]);
+        let command = seed.generate_command(&context()).unwrap();
+        assert!(!command.contains("-proof-level full"), "{command}");
+        assert!(command.contains("-proof-level none"), "{command}");
+    }
+}
@@ -0,0 +1,188 @@
+//! # Graph Module
+//!
+//! Dependency graph of the services of a network: snark workers on their coordinator,
+//! archive nodes on their archive server and archive servers on their postgres, Rosetta
+//! on its archive database and daemon, health sidecars on their daemon, block producers
+//! on the uptime service backend they submit to and daemons on the seeds they peer with.
+//!
+//! It is the one source of the `depends_on` of the compose file, of the dependencies
+//! partitions must not split and of `network info --graph`, and is recorded in the
+//! network's `services.json`. Services are named as in the compose file without the
+//! network suffix, e.g. `mina-archive-service` or `postgres`.
+
+use super::{ServiceConfig, ServiceType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyKind {
+    /// Seed a daemon bootstraps from, only reached over libp2p once both run
+    Peer,
+    /// Service which has to run before the dependent one can work
+    Service,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dependency {
+    pub service: String,
+    pub kind: DependencyKind,
+}
+
+/// Dependencies of every service of a network, by service
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Graph(pub BTreeMap<String, Vec<Dependency>>);
+
+impl Graph {
+    /// Dependency graph of the services of `configs`
+    pub fn of(configs: &[ServiceConfig]) -> Self {
+        let mut graph = Graph::default();
+        let seeds = ServiceConfig::get_seeds(configs);
+        let uptime_service_backend = ServiceConfig::get_uptime_service_backend(configs);
+        for config in configs {
+            let name = config.service_name.as_str();
+            graph.0.entry(name.to_string()).or_default();
+            match config.service_type {
+                ServiceType::SnarkWorker => {
+                    if let Some(coordinator) = &config.snark_coordinator_host {
+                        graph.add(name, coordinator, DependencyKind::Service);
+                    }
+                }
+                ServiceType::ArchiveNode => {
+                    let archive_service = format!("{name}-service");
+                    graph.add(name, &archive_service, DependencyKind::Service);
+                    graph.add(
+                        &archive_service,
+                        &config.archive_db(configs).name(),
+                        DependencyKind::Service,
+                    );
+                }
+                ServiceType::ArchiveService => {
+                    graph.add(
+                        name,
+                        &config.archive_db(configs).name(),
+                        DependencyKind::Service,
+                    );
+                }
+                ServiceType::Rosetta => {
+                    if let Some(archive) = config.rosetta_archive(configs) {
+                        graph.add(
+                            name,
+                            &archive.archive_db(configs).name(),
+                            DependencyKind::Service,
+                        );
+                    }
+                    if let Some(daemon) = config.rosetta_daemon(configs) {
+                        graph.add(name, &daemon.service_name, DependencyKind::Service);
+                    }
+                }
+                ServiceType::BlockProducer => {
+                    if let Some(uptime) = uptime_service_backend {
+                        graph.add(name, &uptime.service_name, DependencyKind::Service);
+                    }
+                }
+                ServiceType::Seed
+                | ServiceType::SnarkCoordinator
+                | ServiceType::UptimeServiceBackend => (),
+            }
+            if matches!(
+                config.service_type,
+                ServiceType::Seed
+                    | ServiceType::BlockProducer
+                    | ServiceType::SnarkCoordinator
+                    | ServiceType::ArchiveNode
+            ) {
+                for seed in seeds.iter().filter(|seed| seed.service_name != name) {
+                    graph.add(name, &seed.service_name, DependencyKind::Peer);
+                }
+            }
+            if config.published_health_port().is_some() {
+                graph.add(&format!("{name}-health"), name, DependencyKind::Service);
+            }
+        }
+        graph
+    }
+
+    fn add(&mut self, service: &str, dependency: &str, kind: DependencyKind) {
+        self.0.entry(dependency.to_string()).or_default();
+        self.0
+            .entry(service.to_string())
+            .or_default()
+            .push(Dependency {
+                service: dependency.to_string(),
+                kind,
+            });
+    }
+
+    /// Services `service` can't work without, in the order they were added
+    pub fn service_dependencies(&self, service: &str) -> Vec<&str> {
+        self.0
+            .get(service)
+            .into_iter()
+            .flatten()
+            .filter(|dependency| dependency.kind == DependencyKind::Service)
+            .map(|dependency| dependency.service.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(name: &str, service_type: ServiceType) -> ServiceConfig {
+        ServiceConfig {
+            service_name: name.to_string(),
+            service_type,
+            client_port: Some(3100),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_graph() {
+        let mut seed = config("seed", ServiceType::Seed);
+        seed.health_host_port = Some(18080);
+        let mut worker = config("worker", ServiceType::SnarkWorker);
+        worker.snark_coordinator_host = Some("coordinator".to_string());
+        let configs = vec![
+            seed,
+            config("bp", ServiceType::BlockProducer),
+            config("coordinator", ServiceType::SnarkCoordinator),
+            worker,
+            config("archive", ServiceType::ArchiveNode),
+            config("rosetta", ServiceType::Rosetta),
+        ];
+        let graph = Graph::of(&configs);
+
+        assert_eq!(graph.service_dependencies("worker"), ["coordinator"]);
+        assert_eq!(graph.service_dependencies("archive"), ["archive-service"]);
+        assert_eq!(graph.service_dependencies("archive-service"), ["postgres"]);
+        assert_eq!(graph.service_dependencies("rosetta"), ["postgres", "seed"]);
+        assert_eq!(graph.service_dependencies("seed-health"), ["seed"]);
+        assert!(graph.service_dependencies("bp").is_empty());
+        assert_eq!(
+            graph.0["bp"],
+            [Dependency {
+                service: "seed".to_string(),
+                kind: DependencyKind::Peer,
+            }]
+        );
+        assert!(graph.0["seed"].is_empty());
+        assert!(graph.0["postgres"].is_empty());
+    }
+
+    #[test]
+    fn test_graph_uptime_service_backend() {
+        let configs = vec![
+            config("seed", ServiceType::Seed),
+            config("bp", ServiceType::BlockProducer),
+            config("uptime", ServiceType::UptimeServiceBackend),
+        ];
+        let graph = Graph::of(&configs);
+
+        assert_eq!(graph.service_dependencies("bp"), ["uptime"]);
+        assert!(graph.service_dependencies("seed").is_empty());
+        assert!(graph.0["uptime"].is_empty());
+    }
+}
@@ -0,0 +1,585 @@
+//! # Service Module
+//!
+//! This module provides structures and methods to hold and manage configurations for different Mina daemons.
+//! With these configurations, docker-compose files can be dynamically generated to deploy and manage nodes in the network.
+
+pub mod command;
+pub mod graph;
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use crate::{docker::postgres::ArchiveDb, topology::GitBuild};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Default)]
+pub enum ServiceType {
+    #[serde(rename = "Seed_node")]
+    Seed,
+    #[default]
+    #[serde(rename = "Block_producer")]
+    BlockProducer,
+    #[serde(rename = "Snark_worker")]
+    SnarkWorker,
+    #[serde(rename = "Snark_coordinator")]
+    SnarkCoordinator,
+    #[serde(rename = "Archive_node")]
+    ArchiveNode,
+    /// Archive service + postgres without a daemon, fed by a daemon outside the network
+    #[serde(rename = "Archive_service")]
+    ArchiveService,
+    #[serde(rename = "Uptime_service_backend")]
+    UptimeServiceBackend,
+    /// `mina-rosetta` reading an archive's database and a daemon's GraphQL endpoint
+    #[serde(rename = "Rosetta")]
+    Rosetta,
+}
+
+/// Port block producers submit their uptime to in the uptime service backend container
+pub const UPTIME_SERVICE_PORT: u16 = 8080;
+
+/// Proof level used by snark workers unless their coordinator sets one
+pub const DEFAULT_SNARK_WORKER_PROOF_LEVEL: &str = "full";
+
+/// Strategy used by a snark coordinator to pick work for its workers
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub enum WorkSelection {
+    #[default]
+    #[serde(rename = "seq")]
+    Sequence,
+    #[serde(rename = "rand")]
+    Random,
+}
+
+impl std::fmt::Display for WorkSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkSelection::Sequence => write!(f, "seq"),
+            WorkSelection::Random => write!(f, "rand"),
+        }
+    }
+}
+
+/// GraphQL endpoint options of a daemon, for browser-based tools talking to it directly
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct GraphQlOptions {
+    /// Origin allowed to make cross-origin requests, `*` for any
+    pub cors_allow_origin: Option<String>,
+    /// Token clients have to present to the GraphQL endpoint
+    pub auth_token: Option<String>,
+    /// Serve the limited (read-only) GraphQL endpoint on all interfaces
+    #[serde(default)]
+    pub open_limited_port: bool,
+}
+
+/// Peer connection limits and peer exchange of a daemon, overriding the daemon defaults
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct ConnectionOptions {
+    /// Connections above which the daemon starts pruning peers
+    pub max_connections: Option<u32>,
+    /// Connections below which the daemon looks for more peers
+    pub min_connections: Option<u32>,
+    /// Whether the daemon exchanges peers with the peers it connects to
+    pub enable_peer_exchange: Option<bool>,
+}
+
+/// Extra `/etc/hosts` entries and DNS servers of a container, for reaching
+/// infrastructure outside docker
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct HostOptions {
+    /// Entries in the compose `host:ip` form, e.g. `uptime.lab:10.0.0.5`
+    #[serde(default)]
+    pub extra_hosts: Vec<String>,
+    /// DNS servers, e.g. `10.0.0.1`
+    #[serde(default)]
+    pub dns: Vec<String>,
+}
+
+impl HostOptions {
+    /// Network-wide options followed by the node's own ones
+    pub fn merge(global: Option<&Self>, node: Option<&Self>) -> Option<Self> {
+        match (global, node) {
+            (None, None) => None,
+            (global, node) => {
+                let mut merged = global.cloned().unwrap_or_default();
+                if let Some(node) = node {
+                    merged.extra_hosts.extend(node.extra_hosts.iter().cloned());
+                    merged.dns.extend(node.dns.iter().cloned());
+                }
+                Some(merged)
+            }
+        }
+    }
+}
+
+/// Resource limits of a daemon container, overriding the compose defaults
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct ContainerLimits {
+    /// Open file descriptors limit (`ulimits.nofile`), defaults to 65536
+    pub nofile: Option<u64>,
+    /// Size of `/dev/shm`, e.g. `512mb`
+    pub shm_size: Option<String>,
+    /// CPUs the container may use (compose `cpus`), e.g. `0.5` for a weak node
+    pub cpu_limit: Option<f64>,
+    /// Memory the container may use (compose `mem_limit`), e.g. `2g`
+    pub memory_limit: Option<String>,
+    /// Relative block IO weight (compose `blkio_config.weight`, 10 to 1000), e.g. `100`
+    /// for a node on a slow disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub io_weight: Option<u16>,
+}
+
+/// Resource limits applied to every daemon of a network, simulating the hardware of
+/// its nodes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceProfile {
+    /// Low-end community hardware: one CPU, 4 GB of memory and a low IO weight
+    Constrained,
+}
+
+impl ResourceProfile {
+    /// Limits of the daemon containers of the profile
+    pub fn limits(self) -> ContainerLimits {
+        match self {
+            ResourceProfile::Constrained => ContainerLimits {
+                cpu_limit: Some(1.0),
+                memory_limit: Some("4g".to_string()),
+                io_weight: Some(100),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Compose `stop_grace_period` and `restart` policy of a role's containers,
+/// overriding the defaults of the role
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct LifecycleOptions {
+    /// Time given to stop gracefully before being killed, e.g. `90s`
+    pub stop_grace_period: Option<String>,
+    /// Compose restart policy, e.g. `on-failure` or `unless-stopped`
+    pub restart: Option<String>,
+}
+
+/// Path in the uptime service backend's container its network directory storage is
+/// mounted at
+pub const UPTIME_STORAGE_PATH: &str = "/uptime-storage";
+
+/// Storage the uptime service backend writes submissions to, the backend picking the
+/// one configured in its app config
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UptimeStorage {
+    /// `uptime-storage` in the network directory
+    Filesystem,
+    /// S3, with the app config's `aws` section
+    Aws,
+    /// AWS Keyspaces, with the app config's `aws_keyspaces` section
+    AwsKeyspaces,
+}
+
+impl UptimeStorage {
+    /// Key of the storage's section in the app config
+    fn key(self) -> &'static str {
+        match self {
+            UptimeStorage::Filesystem => "filesystem",
+            UptimeStorage::Aws => "aws",
+            UptimeStorage::AwsKeyspaces => "aws_keyspaces",
+        }
+    }
+}
+
+/// Settings of the uptime service backend overriding those of its app config file, so
+/// variations of a network don't each need a hand-written one
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct UptimeAppConfig {
+    /// `network_name` of the app config
+    pub network_name: Option<String>,
+    /// Submissions accepted from each block producer key per hour
+    /// (`REQUESTS_PER_PK_HOURLY`)
+    pub requests_per_pk_hourly: Option<u32>,
+    /// Accept submissions of block producers missing from the delegation whitelist
+    pub delegation_whitelist_disabled: Option<bool>,
+    /// Accept submissions without checking their signatures
+    pub verify_signature_disabled: Option<bool>,
+    /// Storage submissions are written to, the other storages of the app config are
+    /// dropped
+    pub storage: Option<UptimeStorage>,
+}
+
+impl UptimeAppConfig {
+    /// The app config `base` with these settings applied
+    pub fn apply(&self, mut base: serde_json::Value) -> Result<serde_json::Value, String> {
+        let config = base
+            .as_object_mut()
+            .ok_or("Uptime service app config should be a JSON object")?;
+        if let Some(network_name) = &self.network_name {
+            config.insert("network_name".into(), network_name.clone().into());
+        }
+        for (key, value) in [
+            (
+                "delegation_whitelist_disabled",
+                self.delegation_whitelist_disabled,
+            ),
+            ("verify_signature_disabled", self.verify_signature_disabled),
+        ] {
+            if let Some(value) = value {
+                config.insert(key.into(), value.into());
+            }
+        }
+        if let Some(storage) = self.storage {
+            if storage == UptimeStorage::Filesystem {
+                config.insert(
+                    storage.key().into(),
+                    serde_json::json!({ "path": UPTIME_STORAGE_PATH }),
+                );
+            } else if !config.contains_key(storage.key()) {
+                return Err(format!(
+                    "Uptime service storage '{}' needs an '{}' section in the app config",
+                    storage.key(),
+                    storage.key()
+                ));
+            }
+            for other in [
+                UptimeStorage::Filesystem,
+                UptimeStorage::Aws,
+                UptimeStorage::AwsKeyspaces,
+            ] {
+                if other != storage {
+                    config.remove(other.key());
+                }
+            }
+        }
+        Ok(base)
+    }
+}
+
+/// GPUs and devices passed through to a snark worker's container, for experimenting
+/// with accelerated (e.g. CUDA or OpenCL) provers
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct GpuOptions {
+    /// Driver of the reserved GPUs, defaults to `nvidia`
+    pub driver: Option<String>,
+    /// Number of GPUs to reserve, all of them unless this or `device_ids` is set
+    pub count: Option<u32>,
+    /// Ids of the GPUs to reserve, e.g. `["0", "1"]`
+    #[serde(default)]
+    pub device_ids: Vec<String>,
+    /// Host devices mapped into the container, e.g. `/dev/dri` for OpenCL; only
+    /// these are passed through unless a driver, count or device ids are set too
+    #[serde(default)]
+    pub devices: Vec<String>,
+    /// Extra environment of the container, e.g. `OCL_ICD_VENDORS`
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+impl GpuOptions {
+    /// Whether GPUs are reserved from a driver rather than only devices mapped
+    pub fn reserves_gpus(&self) -> bool {
+        self.driver.is_some()
+            || self.count.is_some()
+            || !self.device_ids.is_empty()
+            || self.devices.is_empty()
+    }
+
+    pub fn driver(&self) -> &str {
+        self.driver.as_deref().unwrap_or(DEFAULT_GPU_DRIVER)
+    }
+}
+
+/// Driver of the GPUs reserved for snark workers unless set
+pub const DEFAULT_GPU_DRIVER: &str = "nvidia";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServiceConfig {
+    pub service_type: ServiceType,
+    pub service_name: String,
+    pub docker_image: Option<String>,
+    /// Digest `docker_image` resolved to when the network was created, e.g. `repo@sha256:...`
+    pub docker_image_digest: Option<String>,
+    pub git_build: Option<GitBuild>,
+    pub client_port: Option<u16>,
+    /// Host port the GraphQL port is published on, the GraphQL port itself if not set
+    pub graphql_host_port: Option<u16>,
+    /// Host port the limited GraphQL port is published on, the port itself if not set
+    pub limited_graphql_host_port: Option<u16>,
+    pub public_key: Option<String>,
+    pub public_key_path: Option<String>,
+    pub private_key: Option<String>,
+    /// Path to the privkey file used by `mina daemon --block-producer-key KEYFILE ...`
+    pub private_key_path: Option<PathBuf>,
+    pub libp2p_keypair: Option<String>,
+    /// Path to the libp2p keyfile used by `mina daemon --libp2p-keypair KEYFILE ...`
+    pub libp2p_keypair_path: Option<PathBuf>,
+    pub libp2p_peerid: Option<String>,
+    pub peers: Option<Vec<String>>,
+    /// Path to the file used by `mina daemon --peer-list-file PATH ...`
+    pub peer_list_file: Option<PathBuf>,
+    /// GraphQL options of daemon roles, see [`GraphQlOptions`]
+    pub graphql: Option<GraphQlOptions>,
+    /// Connection options of daemon roles, see [`ConnectionOptions`]
+    pub connections: Option<ConnectionOptions>,
+    /// CPUs the container may run on, e.g. `0-3` or `4,5` (compose `cpuset`)
+    pub cpuset: Option<String>,
+    /// Resource limits of daemon roles, see [`ContainerLimits`]
+    pub limits: Option<ContainerLimits>,
+    /// Extra hosts and DNS servers of all the service's containers
+    pub hosts: Option<HostOptions>,
+    /// Stop grace period and restart policy of the service's role, see [`LifecycleOptions`]
+    pub lifecycle: Option<LifecycleOptions>,
+    /// Host port the daemon's health probe sidecar is published on, no sidecar if not set
+    pub health_host_port: Option<u16>,
+    /// Flags appended to the daemon command, e.g. the config file's `extra_daemon_flags`
+    pub extra_daemon_flags: Option<Vec<String>>,
+    /// Environment variables of the daemon, from the topology's `env`
+    pub env: Option<BTreeMap<String, String>>,
+
+    //snark coordinator specific
+    pub snark_coordinator_fees: Option<String>,
+    pub worker_nodes: Option<u16>,
+    /// Used by `mina daemon -work-selection seq|rand ...`, defaults to `seq`
+    pub work_selection: Option<WorkSelection>,
+    /// Used by `mina daemon -work-reassignment-wait MS ...`
+    pub work_reassignment_wait: Option<u64>,
+    /// `cpuset` of the coordinator's snark workers
+    pub snark_worker_cpuset: Option<String>,
+    /// GPUs of the coordinator's snark workers
+    pub snark_worker_gpus: Option<GpuOptions>,
+
+    //snark worker specific
+    /// GPUs and devices passed through to the worker, see [`GpuOptions`]
+    pub gpus: Option<GpuOptions>,
+    pub snark_worker_proof_level: Option<String>,
+    // on snark_worker -daemon-address <snark_coordinator_host>:<snark_coordinator_port>
+    pub snark_coordinator_host: Option<String>,
+    pub snark_coordinator_port: Option<u16>,
+
+    //archive node specific
+    pub archive_docker_image: Option<String>,
+    /// Digest `archive_docker_image` resolved to when the network was created
+    pub archive_docker_image_digest: Option<String>,
+    pub archive_schema_files: Option<Vec<String>>,
+    /// Expected sha256 of the pinned schema files, by file
+    pub archive_schema_sha256: Option<HashMap<String, String>>,
+    pub archive_port: Option<u16>,
+    /// Host port the archive server port is published on
+    pub archive_host_port: Option<u16>,
+    /// Host port the postgres port is published on, a random one if not set
+    pub postgres_host_port: Option<u16>,
+    /// Size of postgres' `/dev/shm`, defaults to 256mb
+    pub postgres_shm_size: Option<String>,
+    /// Stop grace period and restart policy of an archive node's archive server
+    pub archive_lifecycle: Option<LifecycleOptions>,
+
+    //uptime service backend specific
+    pub uptime_service_backend_app_config: Option<PathBuf>,
+    pub uptime_service_backend_minasheets: Option<PathBuf>,
+    pub uptime_service_other_config_files: Option<Vec<PathBuf>>,
+    /// Host port the uptime service backend is published on, 8080 if not set
+    pub uptime_host_port: Option<u16>,
+    /// Settings applied to the uptime service backend's app config
+    pub uptime_app_config: Option<UptimeAppConfig>,
+
+    //rosetta specific
+    pub rosetta_port: Option<u16>,
+    /// Host port the Rosetta API is published on, `rosetta_port` if not set
+    pub rosetta_host_port: Option<u16>,
+    /// Archive whose database Rosetta reads, the primary archive if not set
+    pub rosetta_archive: Option<String>,
+    /// Daemon whose GraphQL endpoint Rosetta queries, the first seed if not set
+    pub rosetta_daemon: Option<String>,
+}
+
+impl ServiceConfig {
+    pub fn generate_peer(
+        seed_name: &str,
+        network_name: &str,
+        libp2p_peerid: &str,
+        external_port: u16,
+    ) -> String {
+        let seed_host = format!("{}-{}", seed_name, network_name);
+        format!(
+            "/dns4/{}/tcp/{}/p2p/{}",
+            seed_host, external_port, libp2p_peerid
+        )
+    }
+
+    pub fn get_seeds(services: &[Self]) -> Vec<&Self> {
+        services
+            .iter()
+            .filter(|service| ServiceType::Seed == service.service_type)
+            .collect()
+    }
+
+    /// Returns the services owning an archive database: archive nodes
+    /// and standalone archive services
+    pub fn get_archives(services: &[Self]) -> Vec<&Self> {
+        services
+            .iter()
+            .filter(|s| {
+                s.service_type == ServiceType::ArchiveNode
+                    || s.service_type == ServiceType::ArchiveService
+            })
+            .collect()
+    }
+
+    /// Returns the archive named `service_name`
+    pub fn get_archive<'a>(services: &'a [Self], service_name: &str) -> Option<&'a Self> {
+        Self::get_archives(services)
+            .into_iter()
+            .find(|archive| archive.service_name == service_name)
+    }
+
+    /// Postgres of an archive; the archive with the smallest name is the primary one
+    pub fn archive_db(&self, services: &[Self]) -> ArchiveDb {
+        let is_primary = Self::get_archives(services)
+            .iter()
+            .all(|archive| archive.service_name >= self.service_name);
+        if is_primary {
+            ArchiveDb::primary()
+        } else {
+            ArchiveDb::of(&self.service_name)
+        }
+    }
+
+    /// Service running the archive server of an archive: the archive service itself,
+    /// or the `{name}-service` of an archive node
+    pub fn archive_server(&self) -> String {
+        match self.service_type {
+            ServiceType::ArchiveService => self.service_name.clone(),
+            _ => format!("{}-service", self.service_name),
+        }
+    }
+
+    /// Host port of the archive server: the configured one, or the archive port for
+    /// a standalone archive service which is always published for the external daemon
+    pub fn published_archive_port(&self) -> Option<u16> {
+        match self.service_type {
+            ServiceType::ArchiveNode => self.archive_host_port,
+            ServiceType::ArchiveService => self.archive_host_port.or(self.archive_port),
+            _ => None,
+        }
+    }
+
+    /// Host port of the Rosetta API, always published for exchange integrations
+    pub fn published_rosetta_port(&self) -> Option<u16> {
+        match self.service_type {
+            ServiceType::Rosetta => self.rosetta_host_port.or(self.rosetta_port),
+            _ => None,
+        }
+    }
+
+    /// Host port of the uptime service backend, where block producers' submissions can
+    /// be inspected
+    pub fn published_uptime_port(&self) -> Option<u16> {
+        match self.service_type {
+            ServiceType::UptimeServiceBackend => {
+                Some(self.uptime_host_port.unwrap_or(UPTIME_SERVICE_PORT))
+            }
+            _ => None,
+        }
+    }
+
+    /// Archive whose database a Rosetta service reads: the named one, or the primary
+    /// archive
+    pub fn rosetta_archive<'a>(&self, services: &'a [Self]) -> Option<&'a Self> {
+        match &self.rosetta_archive {
+            Some(archive) => Self::get_archive(services, archive),
+            None => Self::get_archives(services)
+                .into_iter()
+                .min_by(|a, b| a.service_name.cmp(&b.service_name)),
+        }
+    }
+
+    /// Daemon whose GraphQL endpoint a Rosetta service queries: the named one, or the
+    /// seed with the smallest name
+    pub fn rosetta_daemon<'a>(&self, services: &'a [Self]) -> Option<&'a Self> {
+        match &self.rosetta_daemon {
+            Some(daemon) => services
+                .iter()
+                .find(|service| &service.service_name == daemon && service.daemon_port().is_some()),
+            None => Self::get_seeds(services)
+                .into_iter()
+                .min_by(|a, b| a.service_name.cmp(&b.service_name)),
+        }
+    }
+
+    /// Sets the CPU, memory and IO limits of a daemon container its limits don't set
+    pub fn default_resource_limits(&mut self, defaults: &ContainerLimits) {
+        if self.service_type.command_builder().is_none() {
+            return;
+        }
+        let limits = self.limits.get_or_insert_with(ContainerLimits::default);
+        limits.cpu_limit = limits.cpu_limit.or(defaults.cpu_limit);
+        if limits.memory_limit.is_none() {
+            limits.memory_limit = defaults.memory_limit.clone();
+        }
+        limits.io_weight = limits.io_weight.or(defaults.io_weight);
+    }
+
+    /// Appends `flags` to the command of a daemon
+    pub fn extend_daemon_flags(&mut self, flags: &[String]) {
+        if flags.is_empty() || self.daemon_port().is_none() {
+            return;
+        }
+        self.extra_daemon_flags
+            .get_or_insert_with(Vec::new)
+            .extend_from_slice(flags);
+    }
+
+    /// Host port of the health probe sidecar of a daemon, if it has one
+    pub fn published_health_port(&self) -> Option<u16> {
+        self.daemon_port()?;
+        self.health_host_port
+    }
+
+    /// Host port of the GraphQL endpoint of a daemon
+    pub fn published_graphql_port(&self) -> Option<u16> {
+        match self.service_type {
+            ServiceType::ArchiveService
+            | ServiceType::UptimeServiceBackend
+            | ServiceType::Rosetta => None,
+            _ => self
+                .graphql_host_port
+                .or(self.client_port.map(|port| port + 1)),
+        }
+    }
+
+    /// Host port of the limited GraphQL endpoint of a daemon, if it is opened
+    pub fn published_limited_graphql_port(&self) -> Option<u16> {
+        self.published_graphql_port()?;
+        let port = self.client_port?;
+        self.graphql
+            .as_ref()
+            .is_some_and(|graphql| graphql.open_limited_port)
+            .then(|| self.limited_graphql_host_port.unwrap_or(port + 5))
+    }
+
+    /// Fixed host ports the service is published on
+    pub fn host_ports(&self) -> Vec<u16> {
+        [
+            self.published_graphql_port(),
+            self.published_limited_graphql_port(),
+            self.published_archive_port(),
+            self.postgres_host_port,
+            self.published_rosetta_port(),
+            self.published_health_port(),
+            self.published_uptime_port(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// The uptime service backend of the network, `topology::validate` rejecting
+    /// topologies with more than one
+    pub fn get_uptime_service_backend(services: &[Self]) -> Option<&Self> {
+        services
+            .iter()
+            .find(|s| s.service_type == ServiceType::UptimeServiceBackend)
+    }
+}
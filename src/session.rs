@@ -0,0 +1,220 @@
+//! # Session Module
+//!
+//! Records `minimina` commands to a session file and replays them, so an
+//! orchestration-level failure seen once can be reproduced step by step.
+//!
+//! `minimina record --out session.json -- <command>` runs the command as a child
+//! `minimina` process and appends its arguments, start time, duration, exit code and
+//! output to the session. `minimina replay session.json` runs the recorded commands
+//! again in order, typically starting with the `network create` of a fresh network,
+//! and compares their exit codes with the recorded ones.
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{ErrorKind, Result},
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Options taking the network a command operates on
+const NETWORK_ID_OPTIONS: [&str; 2] = ["-n", "--network-id"];
+
+/// Command of a session, as it ran when recorded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCommand {
+    pub args: Vec<String>,
+    /// RFC 3339 start time
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub exit_code: i32,
+    pub stdout: String,
+}
+
+/// Recorded commands, in the order they ran
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub commands: Vec<RecordedCommand>,
+}
+
+impl Session {
+    /// Session at `path`, empty if the file doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Time between the start of the first command and that of command `index`
+    pub fn offset(&self, index: usize) -> Duration {
+        let start = |index: usize| {
+            chrono::DateTime::parse_from_rfc3339(&self.commands[index].started_at).ok()
+        };
+        match (start(0), start(index)) {
+            (Some(first), Some(current)) => (current - first).to_std().unwrap_or_default(),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Runs `minimina` with `args` as a child process, its logs going to stderr
+pub fn run(args: &[String]) -> Result<RecordedCommand> {
+    let started_at = chrono::Local::now().to_rfc3339();
+    let started = Instant::now();
+    info!("Running 'minimina {}'", args.join(" "));
+    let output = Command::new(std::env::current_exe()?)
+        .args(args)
+        .stderr(Stdio::inherit())
+        .output()?;
+    Ok(RecordedCommand {
+        args: args.to_vec(),
+        started_at,
+        duration_ms: started.elapsed().as_millis() as u64,
+        exit_code: output.status.code().unwrap_or(1),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+    })
+}
+
+/// Runs `minimina` with `args` and appends the command to the session at `path`
+pub fn record(path: &Path, args: &[String]) -> Result<RecordedCommand> {
+    let mut session = Session::load(path)?;
+    let command = run(args)?;
+    session.commands.push(command.clone());
+    session.save(path)?;
+    Ok(command)
+}
+
+/// `args` operating on network `network_id` instead of the recorded one
+pub fn with_network_id(args: &[String], network_id: &str) -> Vec<String> {
+    let mut replaced = Vec::with_capacity(args.len());
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if NETWORK_ID_OPTIONS.contains(&arg.as_str()) {
+            replaced.push(arg.clone());
+            if args.next().is_some() {
+                replaced.push(network_id.to_string());
+            }
+        } else if arg.starts_with("--network-id=") {
+            replaced.push(format!("--network-id={network_id}"));
+        } else {
+            replaced.push(arg.clone());
+        }
+    }
+    replaced
+}
+
+/// Step of a replay, compared with the recorded command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReplayStep {
+    pub args: Vec<String>,
+    pub recorded_exit_code: i32,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+}
+
+impl ReplayStep {
+    pub fn diverged(&self) -> bool {
+        self.exit_code != self.recorded_exit_code
+    }
+}
+
+/// Replays the commands of `session` in order, on network `network_id` when given
+///
+/// With `keep_timing` each command starts at the same offset from the first one as
+/// when recorded, reproducing the pauses between them.
+pub fn replay(
+    session: &Session,
+    network_id: Option<&str>,
+    keep_timing: bool,
+) -> Result<Vec<ReplayStep>> {
+    let started = Instant::now();
+    let mut steps = vec![];
+    for (index, recorded) in session.commands.iter().enumerate() {
+        if keep_timing {
+            let offset = session.offset(index);
+            if let Some(wait) = offset.checked_sub(started.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+        let args = match network_id {
+            Some(network_id) => with_network_id(&recorded.args, network_id),
+            None => recorded.args.clone(),
+        };
+        let command = run(&args)?;
+        steps.push(ReplayStep {
+            args,
+            recorded_exit_code: recorded.exit_code,
+            exit_code: command.exit_code,
+            duration_ms: command.duration_ms,
+        });
+    }
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    fn recorded(args_str: &str, started_at: &str) -> RecordedCommand {
+        RecordedCommand {
+            args: args(args_str),
+            started_at: started_at.to_string(),
+            duration_ms: 1000,
+            exit_code: 0,
+            stdout: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_with_network_id() {
+        assert_eq!(
+            with_network_id(&args("network create -n net --seeds 2"), "replay"),
+            args("network create -n replay --seeds 2")
+        );
+        assert_eq!(
+            with_network_id(&args("node stop --network-id net -i mina-bp-1"), "replay"),
+            args("node stop --network-id replay -i mina-bp-1")
+        );
+        assert_eq!(
+            with_network_id(&args("network status --network-id=net"), "replay"),
+            args("network status --network-id=replay")
+        );
+        assert_eq!(
+            with_network_id(&args("network list"), "replay"),
+            args("network list")
+        );
+    }
+
+    #[test]
+    fn test_session_file() {
+        let tempdir = TempDir::new("test_session_file").unwrap();
+        let path = tempdir.path().join("session.json");
+        assert_eq!(Session::load(&path).unwrap(), Session::default());
+
+        let session = Session {
+            commands: vec![
+                recorded("network create -n net", "2026-10-17T10:00:00+00:00"),
+                recorded("network stop -n net", "2026-10-17T10:02:30+00:00"),
+            ],
+        };
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        assert_eq!(loaded, session);
+        assert_eq!(loaded.offset(0), Duration::ZERO);
+        assert_eq!(loaded.offset(1), Duration::from_secs(150));
+    }
+}
@@ -0,0 +1,233 @@
+//! # Sizing Module
+//!
+//! Estimates the memory, CPUs and disk a network needs from the roles of its
+//! services and compares them with the resources of the host before the network is
+//! created, so a topology too big for the machine is refused up front instead of
+//! leaving its daemons swapping until minimina seems to hang.
+
+use crate::{docker::runtime::Runtime, service::ServiceType, utils::run_command};
+use log::debug;
+use std::{fs, path::Path};
+
+/// Share of the host's memory above which creating a network is warned about
+const MEMORY_WARNING_RATIO: f64 = 0.8;
+
+/// Resources a network is estimated to need
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Requirements {
+    pub memory_mb: u64,
+    pub cpus: f64,
+    pub disk_mb: u64,
+}
+
+impl Requirements {
+    /// Rough needs of a single service of `service_type`, including the archive
+    /// server and postgres of an archive node
+    fn of_role(service_type: &ServiceType) -> Self {
+        let (memory_mb, cpus, disk_mb) = match service_type {
+            ServiceType::Seed | ServiceType::BlockProducer | ServiceType::SnarkCoordinator => {
+                (4096, 1.0, 2048)
+            }
+            ServiceType::SnarkWorker => (2048, 1.0, 256),
+            ServiceType::ArchiveNode => (5120, 1.5, 6144),
+            ServiceType::ArchiveService => (1024, 0.5, 4096),
+            ServiceType::UptimeServiceBackend => (256, 0.25, 256),
+            ServiceType::Rosetta => (512, 0.5, 256),
+        };
+        Requirements {
+            memory_mb,
+            cpus,
+            disk_mb,
+        }
+    }
+
+    /// Needs of a network whose services have the roles `service_types`
+    pub fn of<'a>(service_types: impl IntoIterator<Item = &'a ServiceType>) -> Self {
+        service_types
+            .into_iter()
+            .map(Self::of_role)
+            .fold(Self::default(), |total, role| Requirements {
+                memory_mb: total.memory_mb + role.memory_mb,
+                cpus: total.cpus + role.cpus,
+                disk_mb: total.disk_mb + role.disk_mb,
+            })
+    }
+}
+
+/// Resources available to the containers, `None` where they could not be queried
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HostResources {
+    pub memory_mb: Option<u64>,
+    pub cpus: Option<u64>,
+    pub free_disk_mb: Option<u64>,
+}
+
+impl HostResources {
+    /// Memory and CPUs of the container engine of `runtime` (the VM of Docker
+    /// Desktop rather than the machine running it), falling back to those of this
+    /// machine, and the disk space free where the network directory `path` is created
+    pub fn query(runtime: Runtime, path: &Path) -> Self {
+        let (cpus, memory_mb) = match engine_resources(runtime) {
+            Some((cpus, memory_mb)) => (Some(cpus), Some(memory_mb)),
+            None => (
+                std::thread::available_parallelism()
+                    .ok()
+                    .map(|cpus| cpus.get() as u64),
+                local_memory_mb(),
+            ),
+        };
+        HostResources {
+            memory_mb,
+            cpus,
+            free_disk_mb: free_disk_mb(path),
+        }
+    }
+}
+
+/// CPUs and memory of the container engine, as reported by `docker info`
+fn engine_resources(runtime: Runtime) -> Option<(u64, u64)> {
+    let format = match runtime {
+        Runtime::Docker => "{{.NCPU}} {{.MemTotal}}",
+        Runtime::Podman => "{{.Host.CPUs}} {{.Host.MemTotal}}",
+    };
+    let output = run_command(runtime.engine().binary(), &["info", "--format", format]).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut fields = stdout.split_whitespace();
+    let cpus = fields.next()?.parse().ok()?;
+    let memory_bytes: u64 = fields.next()?.parse().ok()?;
+    Some((cpus, memory_bytes / (1024 * 1024)))
+}
+
+/// Total memory of this machine from `/proc/meminfo`, `None` off Linux
+fn local_memory_mb() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    parse_mem_total_mb(&meminfo)
+}
+
+fn parse_mem_total_mb(meminfo: &str) -> Option<u64> {
+    let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb / 1024)
+}
+
+/// Disk space free on the file system of `path`, or of its closest existing
+/// ancestor, from `df`
+fn free_disk_mb(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|ancestor| ancestor.exists())?;
+    let output = run_command("df", &["-Pk", existing.to_str()?]).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_df_available_mb(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_df_available_mb(df: &str) -> Option<u64> {
+    let kb: u64 = df.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kb / 1024)
+}
+
+/// Outcome of comparing the needs of a network with the host
+#[derive(Debug, Default, PartialEq)]
+pub struct Assessment {
+    /// The network is likely slow on the host
+    pub warnings: Vec<String>,
+    /// The network doesn't fit on the host
+    pub shortfalls: Vec<String>,
+}
+
+/// Compares `required` with `host`: too little memory or disk is a shortfall, too
+/// few CPUs or most of the memory only a warning. Unknown host resources pass
+pub fn assess(required: &Requirements, host: &HostResources) -> Assessment {
+    debug!("Estimated needs {required:?}, host resources {host:?}");
+    let mut assessment = Assessment::default();
+    if let Some(memory_mb) = host.memory_mb {
+        if required.memory_mb > memory_mb {
+            assessment.shortfalls.push(format!(
+                "needs about {} MB of memory, the host has {memory_mb} MB",
+                required.memory_mb
+            ));
+        } else if required.memory_mb as f64 > memory_mb as f64 * MEMORY_WARNING_RATIO {
+            assessment.warnings.push(format!(
+                "needs about {} MB of the host's {memory_mb} MB of memory",
+                required.memory_mb
+            ));
+        }
+    }
+    if let Some(free_disk_mb) = host.free_disk_mb {
+        if required.disk_mb > free_disk_mb {
+            assessment.shortfalls.push(format!(
+                "needs about {} MB of disk, {free_disk_mb} MB are free",
+                required.disk_mb
+            ));
+        }
+    }
+    if let Some(cpus) = host.cpus {
+        if required.cpus > cpus as f64 {
+            assessment.warnings.push(format!(
+                "needs about {} CPUs, the host has {cpus}",
+                required.cpus
+            ));
+        }
+    }
+    assessment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requirements() {
+        let roles = [
+            ServiceType::Seed,
+            ServiceType::BlockProducer,
+            ServiceType::BlockProducer,
+            ServiceType::SnarkWorker,
+            ServiceType::ArchiveNode,
+        ];
+        let required = Requirements::of(&roles);
+        assert_eq!(required.memory_mb, 3 * 4096 + 2048 + 5120);
+        assert_eq!(required.cpus, 5.5);
+        assert_eq!(required.disk_mb, 3 * 2048 + 256 + 6144);
+        assert_eq!(Requirements::of(&[]), Requirements::default());
+    }
+
+    #[test]
+    fn test_assess() {
+        let required = Requirements::of(&vec![ServiceType::BlockProducer; 30]);
+        let laptop = HostResources {
+            memory_mb: Some(16384),
+            cpus: Some(8),
+            free_disk_mb: Some(512 * 1024),
+        };
+        let assessment = assess(&required, &laptop);
+        assert_eq!(assessment.shortfalls.len(), 1);
+        assert!(assessment.shortfalls[0].contains("memory"));
+        assert_eq!(assessment.warnings.len(), 1);
+        assert!(assessment.warnings[0].contains("CPUs"));
+
+        let required = Requirements::of(&vec![ServiceType::BlockProducer; 4]);
+        let assessment = assess(&required, &laptop);
+        assert!(assessment.shortfalls.is_empty());
+        assert_eq!(assessment.warnings.len(), 1);
+        assert!(assessment.warnings[0].contains("memory"));
+
+        assert_eq!(
+            assess(&required, &HostResources::default()),
+            Assessment::default()
+        );
+    }
+
+    #[test]
+    fn test_parse_host_resources() {
+        let meminfo = "MemTotal:       16318480 kB\nMemFree:         1024000 kB\n";
+        assert_eq!(parse_mem_total_mb(meminfo), Some(15936));
+        let df = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                  /dev/nvme0n1p2   490617784 123456789 342135636      27% /\n";
+        assert_eq!(parse_df_available_mb(df), Some(334116));
+        assert_eq!(parse_df_available_mb(""), None);
+    }
+}
@@ -1,10 +1,24 @@
-use crate::service::{ServiceConfig, ServiceType};
-use serde::{Deserialize, Serialize};
+use crate::service::{
+    ConnectionOptions, ContainerLimits, GpuOptions, GraphQlOptions, HostOptions, LifecycleOptions,
+    ServiceConfig, ServiceType, UptimeAppConfig, WorkSelection, DEFAULT_SNARK_WORKER_PROOF_LEVEL,
+};
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use serde_json::Value;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, fs,
+    ops::Range,
     path::{Path, PathBuf},
 };
 
+/// Archive port of the archive with the smallest name, the others get the following ports
+const DEFAULT_ARCHIVE_PORT: u16 = 3086;
+/// Port the Rosetta API listens on in its container
+const DEFAULT_ROSETTA_PORT: u16 = 3087;
+
 /// Type of git build
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum GitBuild {
@@ -14,6 +28,45 @@ pub enum GitBuild {
     Tag(String),
 }
 
+impl GitBuild {
+    /// The commit or tag
+    pub fn rev(&self) -> &str {
+        match self {
+            GitBuild::Commit(rev) | GitBuild::Tag(rev) => rev,
+        }
+    }
+}
+
+/// Archive schema script, a plain path/URL or one pinned to its sha256
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum SchemaFile {
+    Path(PathBuf),
+    Pinned { url: PathBuf, sha256: String },
+}
+
+impl SchemaFile {
+    pub fn location(&self) -> String {
+        match self {
+            SchemaFile::Path(path) | SchemaFile::Pinned { url: path, .. } => {
+                path.to_str().unwrap().to_string()
+            }
+        }
+    }
+
+    /// Expected sha256 of the schema files pinned to one, by location
+    fn checksums(schema_files: &[Self]) -> Option<HashMap<String, String>> {
+        let checksums: HashMap<String, String> = schema_files
+            .iter()
+            .filter_map(|file| match file {
+                SchemaFile::Pinned { sha256, .. } => Some((file.location(), sha256.to_lowercase())),
+                SchemaFile::Path(_) => None,
+            })
+            .collect();
+        (!checksums.is_empty()).then_some(checksums)
+    }
+}
+
 /// Topology info for an archive node
 #[derive(Debug, Clone, Deserialize, PartialEq)]
 pub struct ArchiveTopologyInfo {
@@ -24,10 +77,44 @@ pub struct ArchiveTopologyInfo {
     pub docker_image: Option<String>,
     pub archive_image: Option<String>,
     pub git_build: Option<GitBuild>,
-    pub schema_files: Vec<PathBuf>,
+    pub schema_files: Vec<SchemaFile>,
     pub libp2p_pass: String,
     pub libp2p_keyfile: PathBuf,
     pub libp2p_peerid: String,
+    pub graphql: Option<GraphQlOptions>,
+    pub connections: Option<ConnectionOptions>,
+    /// CPUs the node may run on, e.g. `0-3` or `4,5`
+    pub cpuset: Option<String>,
+    pub limits: Option<ContainerLimits>,
+    pub hosts: Option<HostOptions>,
+    /// Port of the archive server, defaults to 3086
+    pub archive_port: Option<u16>,
+    /// Host port the archive server is published on, not published if omitted
+    pub archive_host_port: Option<u16>,
+    /// Host port postgres is published on, a random one if omitted
+    pub postgres_host_port: Option<u16>,
+    /// Size of postgres' `/dev/shm`, defaults to 256mb
+    pub postgres_shm_size: Option<String>,
+    /// Environment variables of the archive node's daemon, e.g. feature flags
+    pub env: Option<BTreeMap<String, String>>,
+}
+
+/// Topology info for a standalone archive service, receiving blocks from
+/// a daemon running outside of the network
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ArchiveServiceTopologyInfo {
+    #[serde(rename(deserialize = "role"))]
+    pub service_type: ServiceType,
+    pub archive_image: Option<String>,
+    pub schema_files: Vec<SchemaFile>,
+    /// Port published on the host for the external daemon's `-archive-address`, defaults to 3086
+    pub archive_port: Option<u16>,
+    /// Host port the archive server is published on, defaults to `archive_port`
+    pub archive_host_port: Option<u16>,
+    /// Host port postgres is published on, a random one if omitted
+    pub postgres_host_port: Option<u16>,
+    /// Size of postgres' `/dev/shm`, defaults to 256mb
+    pub postgres_shm_size: Option<String>,
 }
 
 /// Topology info for a block producer or seed node
@@ -43,6 +130,14 @@ pub struct NodeTopologyInfo {
     pub libp2p_pass: String,
     pub libp2p_keyfile: PathBuf,
     pub libp2p_peerid: String,
+    pub graphql: Option<GraphQlOptions>,
+    pub connections: Option<ConnectionOptions>,
+    /// CPUs the node may run on, e.g. `0-3` or `4,5`
+    pub cpuset: Option<String>,
+    pub limits: Option<ContainerLimits>,
+    pub hosts: Option<HostOptions>,
+    /// Environment variables of the daemon, e.g. feature flags
+    pub env: Option<BTreeMap<String, String>>,
 }
 
 /// Topology info for a snark coordinator
@@ -56,9 +151,23 @@ pub struct SnarkCoordinatorTopologyInfo {
     pub git_build: Option<GitBuild>,
     pub worker_nodes: u16,
     pub snark_worker_fee: String,
+    pub work_selection: Option<WorkSelection>,
+    pub work_reassignment_wait: Option<u64>,
+    /// Proof level of the coordinator's workers, defaults to `full`
+    pub snark_worker_proof_level: Option<String>,
+    /// CPUs the coordinator's workers may run on
+    pub worker_cpuset: Option<String>,
+    /// GPUs and devices passed through to the coordinator's workers
+    pub worker_gpus: Option<GpuOptions>,
     pub libp2p_pass: String,
     pub libp2p_keyfile: PathBuf,
     pub libp2p_peerid: String,
+    pub graphql: Option<GraphQlOptions>,
+    pub connections: Option<ConnectionOptions>,
+    /// CPUs the node may run on, e.g. `0-3` or `4,5`
+    pub cpuset: Option<String>,
+    pub limits: Option<ContainerLimits>,
+    pub hosts: Option<HostOptions>,
 }
 
 /// Topology info for uptime service backend
@@ -70,6 +179,28 @@ pub struct UptimeServiceTopologyInfo {
     pub app_config_path: PathBuf,
     pub minasheets_path: PathBuf,
     pub other_config_files: Option<Vec<PathBuf>>,
+    /// Host port the uptime service backend is published on, defaults to 8080
+    pub uptime_host_port: Option<u16>,
+    /// Settings overriding those of `app_config_path`, e.g. disabling validation
+    pub app_config: Option<UptimeAppConfig>,
+}
+
+/// Topology info for a Rosetta API service
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct RosettaTopologyInfo {
+    #[serde(rename(deserialize = "role"))]
+    pub service_type: ServiceType,
+    /// Image providing `mina-rosetta`
+    pub docker_image: String,
+    /// Archive whose database Rosetta reads, the primary archive if omitted
+    pub archive: Option<String>,
+    /// Daemon whose GraphQL endpoint Rosetta queries, the first seed if omitted
+    pub daemon: Option<String>,
+    /// Port of the Rosetta API in the container, defaults to 3087
+    pub rosetta_port: Option<u16>,
+    /// Host port the Rosetta API is published on, defaults to `rosetta_port`
+    pub rosetta_host_port: Option<u16>,
+    pub hosts: Option<HostOptions>,
 }
 
 /// Each node variant's topology info
@@ -80,13 +211,25 @@ pub enum TopologyInfo {
     SnarkCoordinator(SnarkCoordinatorTopologyInfo),
     Node(NodeTopologyInfo),
     UptimeServiceBackend(UptimeServiceTopologyInfo),
+    // must stay after the daemons: its fields are a subset of the archive node's
+    ArchiveService(ArchiveServiceTopologyInfo),
+    // must stay last: it only requires a docker image
+    Rosetta(RosettaTopologyInfo),
 }
 
 /// Full network topology
 #[derive(Debug, Clone, Deserialize, PartialEq)]
 pub struct Topology {
+    /// Extra hosts and DNS servers added to every service of the network
+    #[serde(default)]
+    pub hosts: Option<HostOptions>,
+    /// Stop grace period and restart policy by role; `Archive_service` applies
+    /// to the archive servers of archive nodes too
+    #[serde(default)]
+    pub lifecycle: HashMap<ServiceType, LifecycleOptions>,
+    /// Services by name, in the order their client ports are assigned
     #[serde(flatten)]
-    pub topology: HashMap<String, TopologyInfo>,
+    pub topology: BTreeMap<String, TopologyInfo>,
 }
 
 impl TopologyInfo {
@@ -109,6 +252,8 @@ impl TopologyInfo {
                     uptime_service_info.minasheets_path.clone(),
                 ),
                 uptime_service_other_config_files: uptime_service_info.other_config_files.clone(),
+                uptime_host_port: uptime_service_info.uptime_host_port,
+                uptime_app_config: uptime_service_info.app_config.clone(),
 
                 ..Default::default()
             },
@@ -125,13 +270,52 @@ impl TopologyInfo {
                     archive_info
                         .schema_files
                         .iter()
-                        .map(|path| path.to_str().unwrap().to_string())
+                        .map(SchemaFile::location)
                         .collect(),
                 ),
-                archive_port: Some(archive_port),
+                archive_schema_sha256: SchemaFile::checksums(&archive_info.schema_files),
+                archive_port: Some(archive_info.archive_port.unwrap_or(archive_port)),
+                archive_host_port: archive_info.archive_host_port,
+                postgres_host_port: archive_info.postgres_host_port,
+                postgres_shm_size: archive_info.postgres_shm_size.clone(),
                 archive_docker_image: archive_info.archive_image.clone(),
                 libp2p_keypair_path: Some(archive_info.libp2p_keyfile.clone()),
                 libp2p_peerid: Some(archive_info.libp2p_peerid.clone()),
+                graphql: archive_info.graphql.clone(),
+                connections: archive_info.connections.clone(),
+                cpuset: archive_info.cpuset.clone(),
+                limits: archive_info.limits.clone(),
+                hosts: archive_info.hosts.clone(),
+                env: archive_info.env.clone(),
+                ..Default::default()
+            },
+            TopologyInfo::ArchiveService(archive_info) => ServiceConfig {
+                service_type: ServiceType::ArchiveService,
+                service_name,
+                archive_schema_files: Some(
+                    archive_info
+                        .schema_files
+                        .iter()
+                        .map(SchemaFile::location)
+                        .collect(),
+                ),
+                archive_schema_sha256: SchemaFile::checksums(&archive_info.schema_files),
+                archive_port: Some(archive_info.archive_port.unwrap_or(archive_port)),
+                archive_host_port: archive_info.archive_host_port,
+                postgres_host_port: archive_info.postgres_host_port,
+                postgres_shm_size: archive_info.postgres_shm_size.clone(),
+                archive_docker_image: archive_info.archive_image.clone(),
+                ..Default::default()
+            },
+            TopologyInfo::Rosetta(rosetta_info) => ServiceConfig {
+                service_type: ServiceType::Rosetta,
+                service_name,
+                docker_image: Some(rosetta_info.docker_image.clone()),
+                rosetta_port: Some(rosetta_info.rosetta_port.unwrap_or(DEFAULT_ROSETTA_PORT)),
+                rosetta_host_port: rosetta_info.rosetta_host_port,
+                rosetta_archive: rosetta_info.archive.clone(),
+                rosetta_daemon: rosetta_info.daemon.clone(),
+                hosts: rosetta_info.hosts.clone(),
                 ..Default::default()
             },
             TopologyInfo::Node(node_info) => ServiceConfig {
@@ -145,6 +329,12 @@ impl TopologyInfo {
                 private_key_path: node_info.privkey_path.clone(),
                 libp2p_keypair_path: Some(node_info.libp2p_keyfile.clone()),
                 libp2p_peerid: Some(node_info.libp2p_peerid.clone()),
+                graphql: node_info.graphql.clone(),
+                connections: node_info.connections.clone(),
+                cpuset: node_info.cpuset.clone(),
+                limits: node_info.limits.clone(),
+                hosts: node_info.hosts.clone(),
+                env: node_info.env.clone(),
                 peer_list_file: Some(peer_list_file.to_path_buf()),
                 ..Default::default()
             },
@@ -158,10 +348,24 @@ impl TopologyInfo {
                 private_key: Some(snark_info.sk.clone()),
                 libp2p_keypair_path: Some(snark_info.libp2p_keyfile.clone()),
                 libp2p_peerid: Some(snark_info.libp2p_peerid.clone()),
+                graphql: snark_info.graphql.clone(),
+                connections: snark_info.connections.clone(),
+                cpuset: snark_info.cpuset.clone(),
+                limits: snark_info.limits.clone(),
+                hosts: snark_info.hosts.clone(),
                 peer_list_file: Some(peer_list_file.to_path_buf()),
                 snark_coordinator_fees: Some(snark_info.snark_worker_fee.clone()),
-                snark_worker_proof_level: Some("full".to_string()),
+                snark_worker_proof_level: Some(
+                    snark_info
+                        .snark_worker_proof_level
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_SNARK_WORKER_PROOF_LEVEL.to_string()),
+                ),
                 worker_nodes: Some(snark_info.worker_nodes),
+                work_selection: snark_info.work_selection.clone(),
+                work_reassignment_wait: snark_info.work_reassignment_wait,
+                snark_worker_cpuset: snark_info.worker_cpuset.clone(),
+                snark_worker_gpus: snark_info.worker_gpus.clone(),
                 ..Default::default()
             },
         }
@@ -169,25 +373,41 @@ impl TopologyInfo {
 }
 
 impl Topology {
-    pub fn new(path: &Path) -> serde_json::Result<Self> {
-        let contents = std::fs::read_to_string(path).unwrap();
-        serde_json::from_str(&contents)
+    pub fn new(path: &Path) -> std::io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
     }
 
     pub fn services(&self, peer_list_file: &Path) -> Vec<ServiceConfig> {
         let mut client_port = 7070;
-        let archive_port = 3086;
+        // archives default to consecutive archive ports in the order of their names
+        let mut archive_names: Vec<&String> = self
+            .topology
+            .iter()
+            .filter(|(_, info)| {
+                matches!(
+                    info,
+                    TopologyInfo::Archive(_) | TopologyInfo::ArchiveService(_)
+                )
+            })
+            .map(|(service_name, _)| service_name)
+            .collect();
+        archive_names.sort();
 
         let mut services: Vec<ServiceConfig> = self
             .topology
             .iter()
             .map(|(service_name, service_info)| {
                 client_port += 5;
+                let archive_index = archive_names
+                    .iter()
+                    .position(|name| *name == service_name)
+                    .unwrap_or_default();
                 service_info.to_service_config(
                     service_name.clone(),
                     peer_list_file,
                     client_port,
-                    archive_port,
+                    DEFAULT_ARCHIVE_PORT + archive_index as u16,
                 )
             })
             .collect();
@@ -204,14 +424,28 @@ impl Topology {
                     service_type: ServiceType::SnarkWorker,
                     service_name: format!("{}-worker_{}", coordinator.service_name, i),
                     docker_image: coordinator.docker_image.clone(),
+                    git_build: coordinator.git_build.clone(),
                     snark_coordinator_port: coordinator.client_port,
                     snark_worker_proof_level: coordinator.snark_worker_proof_level.clone(),
                     snark_coordinator_host: Some(coordinator.service_name.clone()),
+                    cpuset: coordinator.snark_worker_cpuset.clone(),
+                    gpus: coordinator.snark_worker_gpus.clone(),
+                    limits: coordinator.limits.clone(),
+                    hosts: coordinator.hosts.clone(),
                     ..Default::default()
                 }),
             );
         }
 
+        for service in services.iter_mut() {
+            service.hosts = HostOptions::merge(self.hosts.as_ref(), service.hosts.as_ref());
+            service.lifecycle = self.lifecycle.get(&service.service_type).cloned();
+            if service.service_type == ServiceType::ArchiveNode {
+                service.archive_lifecycle =
+                    self.lifecycle.get(&ServiceType::ArchiveService).cloned();
+            }
+        }
+
         services
     }
 
@@ -270,9 +504,447 @@ impl Topology {
     }
 }
 
+/// Roles a topology entry can declare
+const ROLES: [&str; 7] = [
+    "Seed_node",
+    "Block_producer",
+    "Snark_coordinator",
+    "Archive_node",
+    "Archive_service",
+    "Uptime_service_backend",
+    "Rosetta",
+];
+
+/// Problem found in a topology file, located by the service, field and line it concerns
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Diagnostic {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}: ")?;
+        }
+        match (&self.service, &self.field) {
+            (Some(service), Some(field)) => write!(f, "{service}.{field}: ")?,
+            (Some(service), None) => write!(f, "{service}: ")?,
+            (None, Some(field)) => write!(f, "{field}: ")?,
+            (None, None) => (),
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// Top-level entries of a topology file in file order, duplicate names included
+struct Entries(Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for Entries {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+
+        impl<'de> Visitor<'de> for EntriesVisitor {
+            type Value = Entries;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an object of services by name")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Entries, A::Error> {
+                let mut entries = vec![];
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(Entries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor)
+    }
+}
+
+/// Lines (1-based) of the `"key":` occurrences of `contents` within `lines`
+fn key_lines(contents: &str, key: &str, lines: Range<usize>) -> Vec<usize> {
+    let quoted = format!("\"{key}\"");
+    contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(number, line)| {
+            lines.contains(number)
+                && line
+                    .match_indices(&quoted)
+                    .any(|(start, _)| line[start + quoted.len()..].trim_start().starts_with(':'))
+        })
+        .map(|(number, _)| number)
+        .collect()
+}
+
+/// Field named by a serde error such as "missing field `pk`"
+fn error_field(message: &str) -> Option<&str> {
+    let (_, rest) = message.split_once('`')?;
+    rest.split_once('`').map(|(field, _)| field)
+}
+
+/// Local schema files of an archive, URLs are fetched when the network is created
+fn local_schema_files(files: &[SchemaFile]) -> impl Iterator<Item = (&'static str, &Path)> {
+    files.iter().filter_map(|file| match file {
+        SchemaFile::Path(path) | SchemaFile::Pinned { url: path, .. } => {
+            let location = path.to_str().unwrap_or_default();
+            (!location.starts_with("http://") && !location.starts_with("https://"))
+                .then_some(("schema_files", path.as_path()))
+        }
+    })
+}
+
+/// Local files a service reads from the host, by field
+fn service_files(info: &TopologyInfo) -> Vec<(&'static str, &Path)> {
+    match info {
+        TopologyInfo::Node(node) => {
+            let mut files = vec![("libp2p_keyfile", node.libp2p_keyfile.as_path())];
+            files.extend(
+                node.privkey_path
+                    .as_deref()
+                    .map(|path| ("privkey_path", path)),
+            );
+            files
+        }
+        TopologyInfo::SnarkCoordinator(coordinator) => {
+            vec![("libp2p_keyfile", coordinator.libp2p_keyfile.as_path())]
+        }
+        TopologyInfo::Archive(archive) => {
+            let mut files = vec![("libp2p_keyfile", archive.libp2p_keyfile.as_path())];
+            files.extend(local_schema_files(&archive.schema_files));
+            files
+        }
+        TopologyInfo::ArchiveService(archive) => {
+            local_schema_files(&archive.schema_files).collect()
+        }
+        TopologyInfo::UptimeServiceBackend(uptime) => {
+            let mut files = vec![
+                ("app_config_path", uptime.app_config_path.as_path()),
+                ("minasheets_path", uptime.minasheets_path.as_path()),
+            ];
+            files.extend(
+                uptime
+                    .other_config_files
+                    .iter()
+                    .flatten()
+                    .map(|path| ("other_config_files", path.as_path())),
+            );
+            files
+        }
+        TopologyInfo::Rosetta(_) => vec![],
+    }
+}
+
+/// Host ports a service is published on, by field
+fn service_host_ports(info: &TopologyInfo) -> Vec<(&'static str, u16)> {
+    let ports = match info {
+        TopologyInfo::Archive(archive) => vec![
+            ("archive_host_port", archive.archive_host_port),
+            ("postgres_host_port", archive.postgres_host_port),
+        ],
+        TopologyInfo::ArchiveService(archive) => vec![
+            ("archive_host_port", archive.archive_host_port),
+            ("postgres_host_port", archive.postgres_host_port),
+        ],
+        TopologyInfo::Rosetta(rosetta) => vec![("rosetta_host_port", rosetta.rosetta_host_port)],
+        TopologyInfo::UptimeServiceBackend(uptime) => {
+            vec![("uptime_host_port", uptime.uptime_host_port)]
+        }
+        _ => vec![],
+    };
+    ports
+        .into_iter()
+        .filter_map(|(field, port)| Some((field, port?)))
+        .collect()
+}
+
+/// Libp2p peer id of a daemon
+fn service_peer_id(info: &TopologyInfo) -> Option<&str> {
+    match info {
+        TopologyInfo::Node(node) => Some(&node.libp2p_peerid),
+        TopologyInfo::SnarkCoordinator(coordinator) => Some(&coordinator.libp2p_peerid),
+        TopologyInfo::Archive(archive) => Some(&archive.libp2p_peerid),
+        TopologyInfo::ArchiveService(_)
+        | TopologyInfo::UptimeServiceBackend(_)
+        | TopologyInfo::Rosetta(_) => None,
+    }
+}
+
+/// Parses a topology entry as the topology info of its `role`
+fn parse_service(role: &str, value: &Value) -> Option<serde_json::Result<TopologyInfo>> {
+    let value = value.clone();
+    Some(match role {
+        "Seed_node" | "Block_producer" => serde_json::from_value(value).map(TopologyInfo::Node),
+        "Snark_coordinator" => serde_json::from_value(value).map(TopologyInfo::SnarkCoordinator),
+        "Archive_node" => serde_json::from_value(value).map(TopologyInfo::Archive),
+        "Archive_service" => serde_json::from_value(value).map(TopologyInfo::ArchiveService),
+        "Uptime_service_backend" => {
+            serde_json::from_value(value).map(TopologyInfo::UptimeServiceBackend)
+        }
+        "Rosetta" => serde_json::from_value(value).map(TopologyInfo::Rosetta),
+        _ => return None,
+    })
+}
+
+/// Validates the topology file at `path`, empty if the network can be created from it
+///
+/// Relative paths of the file are resolved against the current directory, like
+/// `network create` does.
+pub fn validate(path: &Path) -> Vec<Diagnostic> {
+    match fs::read_to_string(path) {
+        Ok(contents) => validate_contents(&contents),
+        Err(e) => vec![Diagnostic {
+            message: format!("Failed to read topology file '{}': {e}", path.display()),
+            ..Default::default()
+        }],
+    }
+}
+
+fn validate_contents(contents: &str) -> Vec<Diagnostic> {
+    let entries = match serde_json::from_str::<Entries>(contents) {
+        Ok(Entries(entries)) => entries,
+        Err(e) => {
+            return vec![Diagnostic {
+                line: Some(e.line()),
+                message: format!("Invalid JSON: {e}"),
+                ..Default::default()
+            }]
+        }
+    };
+
+    // each entry spans the lines up to the next one
+    let end = contents.lines().count() + 1;
+    let mut occurrences: HashMap<&str, usize> = HashMap::new();
+    let starts: Vec<Option<usize>> = entries
+        .iter()
+        .map(|(name, _)| {
+            let occurrence = occurrences.entry(name).or_default();
+            *occurrence += 1;
+            key_lines(contents, name, 1..end)
+                .get(*occurrence - 1)
+                .copied()
+        })
+        .collect();
+    let diagnostic = |index: usize, field: Option<&str>, message: String| {
+        let start = starts[index];
+        let next = starts[index + 1..].iter().flatten().next().copied();
+        let field_line = field.and_then(|field| {
+            key_lines(contents, field, start.unwrap_or(1)..next.unwrap_or(end))
+                .first()
+                .copied()
+        });
+        Diagnostic {
+            line: field_line.or(start),
+            service: Some(entries[index].0.clone()),
+            field: field.map(String::from),
+            message,
+        }
+    };
+
+    let mut diagnostics = vec![];
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut services: Vec<(usize, TopologyInfo)> = vec![];
+    let mut seeds = 0;
+
+    for (index, (name, value)) in entries.iter().enumerate() {
+        let mut report = |field: Option<&str>, message: String| {
+            diagnostics.push(diagnostic(index, field, message))
+        };
+
+        if !seen.insert(name) {
+            report(
+                None,
+                "Duplicate service name, only the last declaration would be used".to_string(),
+            );
+            continue;
+        }
+        match name.as_str() {
+            "hosts" => {
+                if let Err(e) = serde_json::from_value::<HostOptions>(value.clone()) {
+                    report(None, e.to_string());
+                }
+                continue;
+            }
+            "lifecycle" => {
+                if let Err(e) =
+                    serde_json::from_value::<HashMap<ServiceType, LifecycleOptions>>(value.clone())
+                {
+                    report(None, e.to_string());
+                }
+                continue;
+            }
+            _ => (),
+        }
+
+        let role = match value.get("role") {
+            Some(Value::String(role)) => role.as_str(),
+            Some(_) => {
+                report(Some("role"), "Role must be a string".to_string());
+                continue;
+            }
+            None => {
+                report(
+                    Some("role"),
+                    format!("Missing role, expected one of {}", ROLES.join(", ")),
+                );
+                continue;
+            }
+        };
+        let info = match parse_service(role, value) {
+            Some(Ok(info)) => info,
+            Some(Err(e)) => {
+                let message = e.to_string();
+                report(error_field(&message), message.clone());
+                continue;
+            }
+            None if role == "Snark_worker" => {
+                report(
+                    Some("role"),
+                    "Snark workers are not declared in the topology, set `worker_nodes` \
+                     on a Snark_coordinator instead"
+                        .to_string(),
+                );
+                continue;
+            }
+            None => {
+                report(
+                    Some("role"),
+                    format!(
+                        "Unknown role '{role}', expected one of {}",
+                        ROLES.join(", ")
+                    ),
+                );
+                continue;
+            }
+        };
+
+        if role == "Seed_node" {
+            seeds += 1;
+        }
+        for (field, path) in service_files(&info) {
+            if !path.exists() {
+                report(
+                    Some(field),
+                    format!("File '{}' does not exist", path.display()),
+                );
+            }
+        }
+        services.push((index, info));
+    }
+
+    let mut peer_ids: HashMap<&str, &str> = HashMap::new();
+    let mut host_ports: HashMap<u16, (&str, &str)> = HashMap::new();
+    for (index, info) in &services {
+        let name = entries[*index].0.as_str();
+        if let Some(peer_id) = service_peer_id(info) {
+            match peer_ids.get(peer_id) {
+                Some(other) => diagnostics.push(diagnostic(
+                    *index,
+                    Some("libp2p_peerid"),
+                    format!("Peer id '{peer_id}' is also used by '{other}'"),
+                )),
+                None => {
+                    peer_ids.insert(peer_id, name);
+                }
+            }
+        }
+        for (field, port) in service_host_ports(info) {
+            match host_ports.get(&port) {
+                Some((other, other_field)) => diagnostics.push(diagnostic(
+                    *index,
+                    Some(field),
+                    format!("Host port {port} is also used by '{other}' ({other_field})"),
+                )),
+                None => {
+                    host_ports.insert(port, (name, field));
+                }
+            }
+        }
+    }
+
+    // the daemons report to a single uptime service backend
+    let mut uptime_backends = services
+        .iter()
+        .filter(|(_, info)| matches!(info, TopologyInfo::UptimeServiceBackend(_)));
+    if let Some((first, _)) = uptime_backends.next() {
+        for (index, _) in uptime_backends {
+            diagnostics.push(diagnostic(
+                *index,
+                Some("role"),
+                format!(
+                    "There can only be one uptime service backend in topology, '{}' is already one",
+                    entries[*first].0
+                ),
+            ));
+        }
+    }
+
+    // Rosetta needs an archive database and a daemon of the network
+    let named = |matches: fn(&TopologyInfo) -> bool| -> Vec<&str> {
+        services
+            .iter()
+            .filter(|(_, info)| matches(info))
+            .map(|(index, _)| entries[*index].0.as_str())
+            .collect()
+    };
+    let archives = named(|info| {
+        matches!(
+            info,
+            TopologyInfo::Archive(_) | TopologyInfo::ArchiveService(_)
+        )
+    });
+    let daemons = named(|info| service_peer_id(info).is_some());
+    for (index, info) in &services {
+        let TopologyInfo::Rosetta(rosetta) = info else {
+            continue;
+        };
+        match &rosetta.archive {
+            Some(archive) if !archives.contains(&archive.as_str()) => diagnostics.push(diagnostic(
+                *index,
+                Some("archive"),
+                format!("'{archive}' is not an archive of the network"),
+            )),
+            None if archives.is_empty() => diagnostics.push(diagnostic(
+                *index,
+                None,
+                "Rosetta needs an archive, but the network has none".to_string(),
+            )),
+            _ => (),
+        }
+        if let Some(daemon) = &rosetta.daemon {
+            if !daemons.contains(&daemon.as_str()) {
+                diagnostics.push(diagnostic(
+                    *index,
+                    Some("daemon"),
+                    format!("'{daemon}' is not a daemon of the network"),
+                ));
+            }
+        }
+    }
+
+    if seeds == 0 {
+        diagnostics.push(Diagnostic {
+            message: "No seed node declared, at least one service must have role Seed_node"
+                .to_string(),
+            ..Default::default()
+        });
+    }
+    diagnostics
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::docker::postgres::ArchiveDb;
 
     #[test]
     fn test_deserialize_archive() {
@@ -299,7 +971,9 @@ mod tests {
                 ],
                 \"libp2p_pass\": \"naughty blue potato\",
                 \"libp2p_keyfile\": \"/path/to/keyfile\",
-                \"libp2p_peerid\": \"123\"
+                \"libp2p_peerid\": \"123\",
+                \"archive_host_port\": 13086,
+                \"postgres_host_port\": 15432
             }}"
         ))
         .unwrap();
@@ -312,11 +986,24 @@ mod tests {
                 docker_image: None,
                 git_build: Some(GitBuild::Commit(commit)),
                 service_type: ServiceType::ArchiveNode,
-                schema_files: vec![schema_file.into(), zkapp_table.into()],
+                schema_files: vec![
+                    SchemaFile::Path(schema_file.into()),
+                    SchemaFile::Path(zkapp_table.into()),
+                ],
                 archive_image: Some("archive-image".into()),
                 libp2p_pass: "naughty blue potato".into(),
                 libp2p_keyfile: "/path/to/keyfile".into(),
                 libp2p_peerid: "123".into(),
+                graphql: None,
+                connections: None,
+                cpuset: None,
+                limits: None,
+                hosts: None,
+                env: None,
+                archive_port: None,
+                archive_host_port: Some(13086),
+                postgres_host_port: Some(15432),
+                postgres_shm_size: None,
             }
         );
     }
@@ -360,6 +1047,12 @@ mod tests {
                 libp2p_pass,
                 libp2p_keyfile: libp2p_keyfile.into(),
                 libp2p_peerid,
+                graphql: None,
+                connections: None,
+                cpuset: None,
+                limits: None,
+                hosts: None,
+                env: None,
             }
         );
     }
@@ -403,6 +1096,12 @@ mod tests {
                 libp2p_pass,
                 libp2p_keyfile: libp2p_keyfile.into(),
                 libp2p_peerid,
+                graphql: None,
+                connections: None,
+                cpuset: None,
+                limits: None,
+                hosts: None,
+                env: None,
             }
         );
     }
@@ -430,6 +1129,8 @@ mod tests {
                 }},
                 \"worker_nodes\": {worker_nodes},
                 \"snark_worker_fee\": \"{snark_worker_fee}\",
+                \"work_selection\": \"rand\",
+                \"work_reassignment_wait\": 30000,
                 \"libp2p_pass\": \"{libp2p_pass}\",
                 \"libp2p_keyfile\": \"{libp2p_keyfile}\",
                 \"libp2p_keypair\": \"snark_keypair\",
@@ -448,9 +1149,19 @@ mod tests {
                 service_type: ServiceType::SnarkCoordinator,
                 worker_nodes,
                 snark_worker_fee,
+                work_selection: Some(WorkSelection::Random),
+                work_reassignment_wait: Some(30000),
+                snark_worker_proof_level: None,
+                worker_cpuset: None,
+                worker_gpus: None,
                 libp2p_pass,
                 libp2p_keyfile: libp2p_keyfile.into(),
                 libp2p_peerid,
+                graphql: None,
+                connections: None,
+                cpuset: None,
+                limits: None,
+                hosts: None,
             }
         );
     }
@@ -474,6 +1185,20 @@ mod tests {
             libp2p_pass,
             libp2p_keyfile,
             libp2p_peerid,
+            graphql: Some(GraphQlOptions {
+                cors_allow_origin: Some("*".to_string()),
+                auth_token: None,
+                open_limited_port: false,
+            }),
+            connections: Some(ConnectionOptions {
+                max_connections: Some(2),
+                min_connections: None,
+                enable_peer_exchange: Some(false),
+            }),
+            cpuset: Some("0-1".to_string()),
+            limits: None,
+            hosts: None,
+            env: None,
         };
 
         let seed_name = "seed".into();
@@ -494,6 +1219,12 @@ mod tests {
             libp2p_pass,
             libp2p_keyfile,
             libp2p_peerid,
+            graphql: None,
+            connections: None,
+            cpuset: None,
+            limits: None,
+            hosts: None,
+            env: None,
         };
 
         let snark_name = "snark".into();
@@ -514,9 +1245,19 @@ mod tests {
             git_build: None,
             worker_nodes,
             snark_worker_fee,
+            work_selection: None,
+            work_reassignment_wait: None,
+            snark_worker_proof_level: Some("none".to_string()),
+            worker_cpuset: None,
+            worker_gpus: None,
             libp2p_pass,
             libp2p_keyfile,
             libp2p_peerid,
+            graphql: None,
+            connections: None,
+            cpuset: None,
+            limits: None,
+            hosts: None,
         };
 
         let expect: Topology = serde_json::from_str(
@@ -533,7 +1274,15 @@ mod tests {
                     \"libp2p_pass\": \"pwd0\",
                     \"libp2p_keyfile\": \"path/to/bp_keyfile.json\",
                     \"libp2p_keypair\": \"bp_keypair\",
-                    \"libp2p_peerid\": \"bp_peerid\"
+                    \"libp2p_peerid\": \"bp_peerid\",
+                    \"graphql\": {
+                        \"cors_allow_origin\": \"*\"
+                    },
+                    \"connections\": {
+                        \"max_connections\": 2,
+                        \"enable_peer_exchange\": false
+                    },
+                    \"cpuset\": \"0-1\"
                 },
                 \"seed\": {
                     \"pk\": \"pk1\",
@@ -555,6 +1304,7 @@ mod tests {
                     \"git_build\": null,
                     \"worker_nodes\": 42,
                     \"snark_worker_fee\": \"0.01\",
+                    \"snark_worker_proof_level\": \"none\",
                     \"libp2p_pass\": \"snark_pwd\",
                     \"libp2p_keyfile\": \"path/to/snark_keyfile.json\",
                     \"libp2p_peerid\": \"snark_peerid\",
@@ -565,7 +1315,9 @@ mod tests {
         .unwrap();
 
         let topology = Topology {
-            topology: HashMap::from([
+            hosts: None,
+            lifecycle: HashMap::new(),
+            topology: BTreeMap::from([
                 (bp_name, TopologyInfo::Node(bp_node)),
                 (seed_name, TopologyInfo::Node(seed_node)),
                 (snark_name, TopologyInfo::SnarkCoordinator(snark_node)),
@@ -628,4 +1380,543 @@ mod tests {
         assert_eq!(num_scs, 1);
         assert_eq!(num_workers, 2);
     }
+
+    #[test]
+    fn test_worker_cpuset_follows_coordinator() {
+        let topology: Topology = serde_json::from_str(
+            "{
+                \"snark\": {
+                    \"pk\": \"pk\",
+                    \"sk\": \"sk\",
+                    \"role\": \"Snark_coordinator\",
+                    \"docker_image\": \"snark-image\",
+                    \"worker_nodes\": 2,
+                    \"snark_worker_fee\": \"0.01\",
+                    \"libp2p_pass\": \"pwd\",
+                    \"libp2p_keyfile\": \"path/to/keyfile.json\",
+                    \"libp2p_peerid\": \"peerid\",
+                    \"cpuset\": \"0\",
+                    \"worker_cpuset\": \"4-7\"
+                }
+            }",
+        )
+        .unwrap();
+
+        for service in topology.services(&PathBuf::from("peers.txt")) {
+            let expected = match service.service_type {
+                ServiceType::SnarkWorker => "4-7",
+                _ => "0",
+            };
+            assert_eq!(service.cpuset.as_deref(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_worker_gpus_follow_coordinator() {
+        let topology: Topology = serde_json::from_str(
+            "{
+                \"snark\": {
+                    \"pk\": \"pk\",
+                    \"sk\": \"sk\",
+                    \"role\": \"Snark_coordinator\",
+                    \"docker_image\": \"snark-image\",
+                    \"worker_nodes\": 2,
+                    \"snark_worker_fee\": \"0.01\",
+                    \"libp2p_pass\": \"pwd\",
+                    \"libp2p_keyfile\": \"path/to/keyfile.json\",
+                    \"libp2p_peerid\": \"peerid\",
+                    \"worker_gpus\": { \"count\": 1, \"env\": { \"CUDA_CACHE_DISABLE\": \"1\" } }
+                }
+            }",
+        )
+        .unwrap();
+
+        let expected = GpuOptions {
+            count: Some(1),
+            env: HashMap::from([("CUDA_CACHE_DISABLE".to_string(), "1".to_string())]),
+            ..Default::default()
+        };
+        for service in topology.services(&PathBuf::from("peers.txt")) {
+            match service.service_type {
+                ServiceType::SnarkWorker => assert_eq!(service.gpus.as_ref(), Some(&expected)),
+                _ => assert_eq!(service.gpus, None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_global_hosts_are_merged_into_services() {
+        let topology: Topology = serde_json::from_str(
+            "{
+                \"hosts\": {
+                    \"extra_hosts\": [\"uptime.lab:10.0.0.5\"],
+                    \"dns\": [\"10.0.0.1\"]
+                },
+                \"snark\": {
+                    \"pk\": \"pk\",
+                    \"sk\": \"sk\",
+                    \"role\": \"Snark_coordinator\",
+                    \"docker_image\": \"snark-image\",
+                    \"worker_nodes\": 1,
+                    \"snark_worker_fee\": \"0.01\",
+                    \"libp2p_pass\": \"pwd\",
+                    \"libp2p_keyfile\": \"path/to/keyfile.json\",
+                    \"libp2p_peerid\": \"peerid\",
+                    \"hosts\": {
+                        \"extra_hosts\": [\"archive.lab:10.0.0.6\"]
+                    }
+                }
+            }",
+        )
+        .unwrap();
+
+        let services = topology.services(&PathBuf::from("peers.txt"));
+        assert_eq!(services.len(), 2);
+        for service in services {
+            let hosts = service.hosts.unwrap();
+            assert_eq!(
+                hosts.extra_hosts,
+                vec!["uptime.lab:10.0.0.5", "archive.lab:10.0.0.6"]
+            );
+            assert_eq!(hosts.dns, vec!["10.0.0.1"]);
+        }
+    }
+
+    #[test]
+    fn test_node_env() {
+        let topology: Topology = serde_json::from_str(
+            "{
+                \"bp\": {
+                    \"pk\": \"pk\",
+                    \"sk\": \"sk\",
+                    \"role\": \"Block_producer\",
+                    \"docker_image\": \"bp-image\",
+                    \"libp2p_pass\": \"pwd\",
+                    \"libp2p_keyfile\": \"path/to/keyfile.json\",
+                    \"libp2p_peerid\": \"peerid\",
+                    \"env\": { \"MINA_FEATURE_FLAG\": \"1\" }
+                }
+            }",
+        )
+        .unwrap();
+
+        let services = topology.services(&PathBuf::from("peers.txt"));
+        assert_eq!(
+            services[0].env,
+            Some(BTreeMap::from([(
+                "MINA_FEATURE_FLAG".to_string(),
+                "1".to_string()
+            )]))
+        );
+    }
+
+    #[test]
+    fn test_lifecycle_by_role() {
+        let topology: Topology = serde_json::from_str(
+            "{
+                \"lifecycle\": {
+                    \"Snark_worker\": { \"stop_grace_period\": \"1s\" },
+                    \"Archive_service\": { \"restart\": \"always\" }
+                },
+                \"snark\": {
+                    \"pk\": \"pk\",
+                    \"sk\": \"sk\",
+                    \"role\": \"Snark_coordinator\",
+                    \"docker_image\": \"snark-image\",
+                    \"worker_nodes\": 1,
+                    \"snark_worker_fee\": \"0.01\",
+                    \"libp2p_pass\": \"pwd\",
+                    \"libp2p_keyfile\": \"path/to/keyfile.json\",
+                    \"libp2p_peerid\": \"peerid\"
+                },
+                \"archive\": {
+                    \"pk\": \"pk\",
+                    \"sk\": \"sk\",
+                    \"role\": \"Archive_node\",
+                    \"docker_image\": \"archive-image\",
+                    \"schema_files\": [],
+                    \"libp2p_pass\": \"pwd\",
+                    \"libp2p_keyfile\": \"path/to/keyfile.json\",
+                    \"libp2p_peerid\": \"peerid\"
+                }
+            }",
+        )
+        .unwrap();
+
+        for service in topology.services(&PathBuf::from("peers.txt")) {
+            match service.service_type {
+                ServiceType::SnarkWorker => assert_eq!(
+                    service.lifecycle.unwrap().stop_grace_period.as_deref(),
+                    Some("1s")
+                ),
+                ServiceType::ArchiveNode => {
+                    assert_eq!(service.lifecycle, None);
+                    assert_eq!(
+                        service.archive_lifecycle.unwrap().restart.as_deref(),
+                        Some("always")
+                    );
+                }
+                _ => assert_eq!(service.lifecycle, None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_worker_proof_level_follows_coordinator() {
+        let snark_info = |proof_level: Option<&str>| {
+            TopologyInfo::SnarkCoordinator(SnarkCoordinatorTopologyInfo {
+                pk: "pk".into(),
+                sk: "sk".into(),
+                service_type: ServiceType::SnarkCoordinator,
+                docker_image: Some("snark-image".into()),
+                git_build: None,
+                worker_nodes: 2,
+                snark_worker_fee: "0.01".into(),
+                work_selection: None,
+                work_reassignment_wait: None,
+                snark_worker_proof_level: proof_level.map(String::from),
+                worker_cpuset: None,
+                worker_gpus: None,
+                libp2p_pass: "pwd".into(),
+                libp2p_keyfile: "path/to/keyfile.json".into(),
+                libp2p_peerid: "peerid".into(),
+                graphql: None,
+                connections: None,
+                cpuset: None,
+                limits: None,
+                hosts: None,
+            })
+        };
+        let peer_list_file = PathBuf::from("peers.txt");
+
+        for (proof_level, expected) in [
+            (None, DEFAULT_SNARK_WORKER_PROOF_LEVEL),
+            (Some("none"), "none"),
+        ] {
+            let topology = Topology {
+                hosts: None,
+                lifecycle: HashMap::new(),
+                topology: BTreeMap::from([("snark".to_string(), snark_info(proof_level))]),
+            };
+            let services = topology.services(&peer_list_file);
+
+            assert_eq!(services.len(), 3);
+            for service in services {
+                assert_eq!(service.snark_worker_proof_level.as_deref(), Some(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_deserialize_archive_service() {
+        let topology: Topology = serde_json::from_str(
+            "{
+                \"archive\": {
+                    \"role\": \"Archive_service\",
+                    \"archive_image\": \"archive-image\",
+                    \"schema_files\": [\"path/to/create_schema.sql\"],
+                    \"archive_port\": 3087,
+                    \"postgres_host_port\": 5433
+                }
+            }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            topology.topology["archive"],
+            TopologyInfo::ArchiveService(ArchiveServiceTopologyInfo {
+                service_type: ServiceType::ArchiveService,
+                archive_image: Some("archive-image".into()),
+                schema_files: vec![SchemaFile::Path("path/to/create_schema.sql".into())],
+                archive_port: Some(3087),
+                archive_host_port: None,
+                postgres_host_port: Some(5433),
+                postgres_shm_size: None,
+            })
+        );
+
+        let services = topology.services(&PathBuf::from("peers.txt"));
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].service_type, ServiceType::ArchiveService);
+        assert_eq!(services[0].archive_port, Some(3087));
+        assert_eq!(services[0].published_archive_port(), Some(3087));
+        assert_eq!(services[0].postgres_host_port, Some(5433));
+        assert_eq!(services[0].client_port, None);
+    }
+
+    #[test]
+    fn test_multiple_archives() {
+        let topology: Topology = serde_json::from_str(
+            "{
+                \"archive-2\": {
+                    \"role\": \"Archive_service\",
+                    \"schema_files\": []
+                },
+                \"archive-1\": {
+                    \"role\": \"Archive_service\",
+                    \"schema_files\": []
+                }
+            }",
+        )
+        .unwrap();
+
+        let services = topology.services(&PathBuf::from("peers.txt"));
+        let archive = |name| ServiceConfig::get_archive(&services, name).unwrap();
+        assert_eq!(ServiceConfig::get_archives(&services).len(), 2);
+        assert_eq!(archive("archive-1").archive_port, Some(3086));
+        assert_eq!(archive("archive-2").archive_port, Some(3087));
+        assert_eq!(
+            archive("archive-1").archive_db(&services),
+            ArchiveDb::primary()
+        );
+        assert_eq!(
+            archive("archive-2").archive_db(&services),
+            ArchiveDb::of("archive-2")
+        );
+    }
+
+    #[test]
+    fn test_deserialize_pinned_schema_files() {
+        let topology: Topology = serde_json::from_str(
+            "{
+                \"archive\": {
+                    \"role\": \"Archive_service\",
+                    \"schema_files\": [
+                        {
+                            \"url\": \"https://example.com/create_schema.sql\",
+                            \"sha256\": \"ABC123\"
+                        },
+                        \"path/to/zkapp_tables.sql\"
+                    ]
+                }
+            }",
+        )
+        .unwrap();
+
+        let services = topology.services(&PathBuf::from("peers.txt"));
+        assert_eq!(
+            services[0].archive_schema_files,
+            Some(vec![
+                "https://example.com/create_schema.sql".to_string(),
+                "path/to/zkapp_tables.sql".to_string(),
+            ])
+        );
+        assert_eq!(
+            services[0].archive_schema_sha256,
+            Some(HashMap::from([(
+                "https://example.com/create_schema.sql".to_string(),
+                "abc123".to_string()
+            )]))
+        );
+    }
+
+    #[test]
+    fn test_validate_small_network() {
+        let path = Path::new("./tests/data/small_network/topology.json");
+        assert_eq!(validate(path), vec![]);
+    }
+
+    #[test]
+    fn test_validate_diagnostics() {
+        let keyfile = "./tests/data/small_network/libp2p_keys/seed-0.json";
+        let contents = format!(
+            r#"{{
+  "bp": {{
+    "pk": "pk", "sk": "sk", "libp2p_pass": "pass",
+    "role": "Block_producer",
+    "libp2p_keyfile": "./missing.json",
+    "libp2p_peerid": "peer"
+  }},
+  "bp": {{
+    "role": "Block_producer"
+  }},
+  "worker": {{
+    "role": "Snark_worker"
+  }},
+  "observer": {{
+    "role": "Observer"
+  }},
+  "coordinator": {{
+    "pk": "pk", "sk": "sk", "libp2p_pass": "pass",
+    "role": "Snark_coordinator",
+    "libp2p_keyfile": "{keyfile}",
+    "libp2p_peerid": "peer",
+    "worker_nodes": 1
+  }},
+  "archive-service-0": {{
+    "role": "Archive_service",
+    "schema_files": ["https://example.com/create_schema.sql"],
+    "postgres_host_port": 5432
+  }},
+  "archive-service-1": {{
+    "role": "Archive_service",
+    "schema_files": [],
+    "archive_host_port": 5432
+  }}
+}}"#
+        );
+        let diagnostic = |line, service: &str, field: Option<&str>, message: &str| Diagnostic {
+            line: Some(line),
+            service: Some(service.to_string()),
+            field: field.map(String::from),
+            message: message.to_string(),
+        };
+
+        let diagnostics = validate_contents(&contents);
+        assert_eq!(
+            diagnostics,
+            vec![
+                diagnostic(
+                    5,
+                    "bp",
+                    Some("libp2p_keyfile"),
+                    "File './missing.json' does not exist"
+                ),
+                diagnostic(
+                    8,
+                    "bp",
+                    None,
+                    "Duplicate service name, only the last declaration would be used"
+                ),
+                diagnostic(
+                    12,
+                    "worker",
+                    Some("role"),
+                    "Snark workers are not declared in the topology, set `worker_nodes` \
+                     on a Snark_coordinator instead"
+                ),
+                diagnostic(
+                    15,
+                    "observer",
+                    Some("role"),
+                    "Unknown role 'Observer', expected one of Seed_node, Block_producer, \
+                     Snark_coordinator, Archive_node, Archive_service, Uptime_service_backend, Rosetta"
+                ),
+                diagnostic(
+                    17,
+                    "coordinator",
+                    Some("snark_worker_fee"),
+                    "missing field `snark_worker_fee`"
+                ),
+                diagnostic(
+                    32,
+                    "archive-service-1",
+                    Some("archive_host_port"),
+                    "Host port 5432 is also used by 'archive-service-0' (postgres_host_port)"
+                ),
+                Diagnostic {
+                    message: "No seed node declared, at least one service must have role \
+                              Seed_node"
+                        .to_string(),
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "line 5: bp.libp2p_keyfile: File './missing.json' does not exist"
+        );
+    }
+
+    #[test]
+    fn test_validate_duplicate_peer_ids() {
+        let node = |role: &str| {
+            format!(
+                r#"{{"pk": "pk", "sk": "sk", "libp2p_pass": "pass", "role": "{role}",
+                "libp2p_keyfile": "./tests/data/small_network/libp2p_keys/seed-0.json",
+                "libp2p_peerid": "peer"}}"#
+            )
+        };
+        let contents = format!(
+            "{{\n\"seed\": {},\n\"bp\": {}\n}}",
+            node("Seed_node"),
+            node("Block_producer")
+        );
+
+        assert_eq!(
+            validate_contents(&contents),
+            vec![Diagnostic {
+                line: Some(7),
+                service: Some("bp".to_string()),
+                field: Some("libp2p_peerid".to_string()),
+                message: "Peer id 'peer' is also used by 'seed'".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_several_uptime_service_backends() {
+        let file = "./tests/data/uptime_service_network/topology.json";
+        let backend = format!(
+            r#"{{"role": "Uptime_service_backend", "app_config_path": "{file}", "minasheets_path": "{file}"}}"#
+        );
+        let contents = format!("{{\n\"uptime-0\": {backend},\n\"uptime-1\": {backend}\n}}");
+
+        let diagnostics = validate_contents(&contents);
+        assert!(diagnostics.contains(&Diagnostic {
+            line: Some(3),
+            service: Some("uptime-1".to_string()),
+            field: Some("role".to_string()),
+            message: "There can only be one uptime service backend in topology, \
+                      'uptime-0' is already one"
+                .to_string(),
+        }));
+        assert!(!diagnostics
+            .iter()
+            .any(|d| d.service.as_deref() == Some("uptime-0")));
+    }
+
+    #[test]
+    fn test_validate_invalid_json() {
+        let diagnostics = validate_contents("{\n  \"seed\": {\n}");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, Some(3));
+        assert!(diagnostics[0].message.starts_with("Invalid JSON"));
+    }
+
+    #[test]
+    fn test_rosetta_topology() {
+        let topology: Topology = serde_json::from_str(
+            r#"{
+                "rosetta": {
+                    "role": "Rosetta",
+                    "docker_image": "rosetta-image",
+                    "archive": "archive",
+                    "rosetta_host_port": 13087
+                }
+            }"#,
+        )
+        .unwrap();
+        let services = topology.services(Path::new("/peers.txt"));
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].service_type, ServiceType::Rosetta);
+        assert_eq!(services[0].docker_image.as_deref(), Some("rosetta-image"));
+        assert_eq!(services[0].rosetta_port, Some(DEFAULT_ROSETTA_PORT));
+        assert_eq!(services[0].rosetta_host_port, Some(13087));
+        assert_eq!(services[0].rosetta_archive.as_deref(), Some("archive"));
+        assert_eq!(services[0].client_port, None);
+    }
+
+    #[test]
+    fn test_validate_rosetta_references() {
+        let contents = r#"{
+  "rosetta": {
+    "role": "Rosetta",
+    "docker_image": "rosetta-image",
+    "daemon": "bp"
+  }
+}"#;
+        let diagnostics: Vec<String> = validate_contents(contents)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            diagnostics,
+            vec![
+                "line 2: rosetta: Rosetta needs an archive, but the network has none",
+                "line 5: rosetta.daemon: 'bp' is not a daemon of the network",
+                "No seed node declared, at least one service must have role Seed_node",
+            ]
+        );
+    }
 }
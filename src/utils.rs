@@ -1,13 +1,14 @@
 //! # Utils Module
 //!
-//! This module provides utility functions to run external commands
-//! and fetch the UID and GID of the current user.
+//! This module provides utility functions to run external commands,
+//! fetch the UID and GID of the current user and ask for confirmation.
 
 use log::{debug, error};
+use sha2::{Digest, Sha256};
 use std::{
     fs::File,
-    io::{self, ErrorKind},
-    path::PathBuf,
+    io::{self, BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
     process::{Command, Output},
 };
 use url::Url;
@@ -35,11 +36,42 @@ pub fn run_command(cmd: &str, args: &[&str]) -> io::Result<Output> {
         }
         Err(e) => {
             error!("Failed to run command: {e}");
-            Err(io::Error::new(ErrorKind::Other, e))
+            Err(io::Error::other(e))
         }
     }
 }
 
+/// Turns a command that ran but failed into an error with its stderr
+pub fn check_output(output: io::Result<Output>) -> io::Result<()> {
+    let output = output?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// Asks the user to confirm `prompt` on the terminal, true only for `y` or `yes`.
+/// Without a terminal (e.g. in scripts) there is nobody to ask and it's refused,
+/// callers pointing to `--yes` instead.
+pub fn confirm(prompt: &str) -> io::Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    eprint!("{prompt} [y/N] ");
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(is_yes(&answer))
+}
+
+fn is_yes(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
 /// Fetch the UID and GID of the current user.
 ///
 /// # Returns
@@ -52,34 +84,97 @@ pub fn get_current_user_uid_gid() -> Option<String> {
     Some(format!("{current_user}:{current_group}"))
 }
 
+/// Hex encoded sha256 of the file at `path`
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
 /// Fetch the schema from a given URL and save it to a file.
-/// The file is saved in the given network path.
-pub fn fetch_schema(url: &str, network_path: PathBuf) -> Result<PathBuf, reqwest::Error> {
+/// The file is saved in the given network path, failing on an invalid URL
+/// or a response other than 2xx.
+pub fn fetch_schema(url: &str, network_path: PathBuf) -> io::Result<PathBuf> {
     debug!("Fetching schema from: {url}");
 
-    let parsed_url = Url::parse(url).expect("Invalid URL");
+    let parsed_url = Url::parse(url).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid URL '{url}': {e}"),
+        )
+    })?;
     let filename = parsed_url
         .path_segments()
-        .and_then(|segments| segments.last())
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty())
         .unwrap_or("schema.sql");
     let mut file_path = network_path;
 
     file_path.push(filename);
-    let response = reqwest::blocking::get(parsed_url)?;
-    let mut file = File::create(&file_path).expect("Failed to create file");
-
-    std::io::copy(
-        &mut response
-            .bytes()
-            .expect("Failed to read bytes from response")
-            .as_ref(),
-        &mut file,
-    )
-    .expect("Failed to write to file");
+    let fetch_error = |e: reqwest::Error| io::Error::other(format!("Failed to fetch '{url}': {e}"));
+    let response = crate::proxy::http_client()
+        .get(parsed_url.clone())
+        .send()
+        .and_then(reqwest::blocking::Response::error_for_status)
+        .map_err(fetch_error)?;
+    let bytes = response.bytes().map_err(fetch_error)?;
+    File::create(&file_path)?.write_all(&bytes)?;
 
     Ok(file_path)
 }
 
+/// Parses an account name made of letters, digits, `-` and `_`, so it is a plain
+/// file name and a single word in key listings
+pub fn parse_account_name(name: &str) -> Result<String, String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid account name '{name}', expected letters, digits, '-' and '_'"
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Nanomina in one MINA
+const NANOMINA_PER_MINA: u64 = 1_000_000_000;
+
+/// Parse an amount of MINA with up to nine decimals, e.g. `1000` or `0.25`, into nanomina.
+pub fn parse_mina_amount(amount: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid MINA amount '{amount}'");
+    let (whole, fraction) = amount.trim().split_once('.').unwrap_or((amount.trim(), ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > 9
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| invalid())?
+    };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{fraction:0<9}").parse().map_err(|_| invalid())?
+    };
+    whole
+        .checked_mul(NANOMINA_PER_MINA)
+        .and_then(|nanomina| nanomina.checked_add(fraction))
+        .ok_or_else(invalid)
+}
+
 #[cfg(test)]
 mod tests {
     use tempdir::TempDir;
@@ -99,6 +194,38 @@ mod tests {
         assert!(uid_gid.contains(':'));
     }
 
+    #[test]
+    fn test_parse_mina_amount() {
+        assert_eq!(parse_mina_amount("1000"), Ok(1_000_000_000_000));
+        assert_eq!(parse_mina_amount("0.25"), Ok(250_000_000));
+        assert_eq!(parse_mina_amount(".000000001"), Ok(1));
+        assert!(parse_mina_amount("0.0000000001").is_err());
+        assert!(parse_mina_amount("-1").is_err());
+        assert!(parse_mina_amount(".").is_err());
+        assert!(parse_mina_amount("1e3").is_err());
+    }
+
+    #[test]
+    fn test_parse_account_name() {
+        assert_eq!(parse_account_name("bob_2-x"), Ok("bob_2-x".to_string()));
+        assert!(parse_account_name("").is_err());
+        assert!(parse_account_name("../bob").is_err());
+        assert!(parse_account_name("bob.pub").is_err());
+        assert!(parse_account_name("bob smith").is_err());
+    }
+
+    #[test]
+    fn test_sha256_file() {
+        let tempdir = TempDir::new("test_sha256_file").expect("Cannot create temporary directory");
+        let path = tempdir.path().join("schema.sql");
+        std::fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(sha256_file(&tempdir.path().join("missing.sql")).is_err());
+    }
+
     #[test]
     fn test_fetch_schema() {
         let url = "https://raw.githubusercontent.com/MinaProtocol/mina/master/src/app/archive/create_schema.sql";
@@ -108,4 +235,20 @@ mod tests {
         assert!(file_path.exists());
         assert_eq!(file_path.file_name().unwrap(), "create_schema.sql");
     }
+
+    #[test]
+    fn test_fetch_schema_invalid_url() {
+        let tempdir = TempDir::new("test_fetch_schema_invalid_url")
+            .expect("Cannot create temporary directory");
+        let error = fetch_schema("not a url", tempdir.path().to_path_buf()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_is_yes() {
+        assert!(is_yes("y\n"));
+        assert!(is_yes(" YES "));
+        assert!(!is_yes("\n"));
+        assert!(!is_yes("no"));
+    }
 }